@@ -6,7 +6,7 @@ use crate::genesis;
 use crate::p2p::P2pConfig;
 use crate::transaction_deny_config::TransactionDenyConfig;
 use crate::Config;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use narwhal_config::Parameters as ConsensusParameters;
 use once_cell::sync::OnceCell;
 use rand::rngs::OsRng;
@@ -29,6 +29,7 @@ use sui_types::crypto::NetworkKeyPair;
 use sui_types::crypto::SuiKeyPair;
 use sui_types::crypto::{get_key_pair_from_rng, AccountKeyPair, AuthorityKeyPair};
 use sui_types::multiaddr::Multiaddr;
+use sui_types::parse_sui_struct_tag;
 use tracing::info;
 
 // Default max number of concurrent requests served
@@ -77,6 +78,18 @@ pub struct NodeConfig {
     #[serde(default = "default_enable_index_processing")]
     pub enable_index_processing: bool,
 
+    /// Maintain an index of all objects by type, used to serve queries for the objects of a given
+    /// package, module or struct. Only takes effect on fullnodes with index processing enabled.
+    /// When enabled on a node that already has state, the index must be built with
+    /// `sui-tool db-tool rebuild-indexes --index objects-by-type` before it serves queries.
+    #[serde(default)]
+    pub enable_objects_by_type_index: bool,
+
+    /// Keep a log of the balance changes of every address in every coin type, used to serve the
+    /// balance history of an address. Only takes effect on fullnodes with index processing enabled.
+    #[serde(default)]
+    pub enable_balance_history_index: bool,
+
     #[serde(default)]
     pub grpc_load_shed: Option<bool>,
 
@@ -130,6 +143,12 @@ pub struct NodeConfig {
     #[serde(default)]
     pub transaction_deny_config: TransactionDenyConfig,
 
+    /// File persisting the updates of `transaction_deny_config` made through the admin API. When
+    /// it exists, it is loaded at startup in place of `transaction_deny_config`. Defaults to
+    /// `transaction_deny_config.yaml` in the db directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction_deny_config_path: Option<PathBuf>,
+
     #[serde(default)]
     pub certificate_deny_config: CertificateDenyConfig,
 
@@ -148,6 +167,15 @@ pub struct NodeConfig {
     #[serde(default)]
     pub indexer_max_subscriptions: Option<usize>,
 
+    /// Maximum number of calls in a JSON-RPC batch request. Defaults to
+    /// `sui_json_rpc::axum_router::DEFAULT_MAX_BATCH_SIZE`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_rpc_max_batch_size: Option<usize>,
+
+    /// Per client rate limits of the JSON-RPC server. Disabled when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_rpc_rate_limit_config: Option<JsonRpcRateLimitConfig>,
+
     #[serde(default = "default_transaction_kv_store_config")]
     pub transaction_kv_store_read_config: TransactionKeyValueStoreReadConfig,
 
@@ -289,6 +317,12 @@ impl NodeConfig {
         self.db_path.join("db_checkpoints")
     }
 
+    pub fn transaction_deny_config_path(&self) -> PathBuf {
+        self.transaction_deny_config_path
+            .clone()
+            .unwrap_or_else(|| self.db_path.join("transaction_deny_config.yaml"))
+    }
+
     pub fn archive_path(&self) -> PathBuf {
         self.db_path.join("archive")
     }
@@ -533,7 +567,7 @@ impl Default for CheckpointExecutorConfig {
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct AuthorityStorePruningConfig {
     /// number of the latest epoch dbs to retain
@@ -563,6 +597,9 @@ pub struct AuthorityStorePruningConfig {
     /// enables pruner to prune no longer needed object tombstones. We don't serialize it if it is the default value, false.
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub enable_pruning_tombstones: bool,
+    /// objects and transactions that are never pruned, regardless of the retention periods above
+    #[serde(default, skip_serializing_if = "RetainedHistoryConfig::is_empty")]
+    pub retained_history: RetainedHistoryConfig,
 }
 
 impl Default for AuthorityStorePruningConfig {
@@ -580,6 +617,7 @@ impl Default for AuthorityStorePruningConfig {
             periodic_compaction_threshold_days: None,
             num_epochs_to_retain_for_checkpoints: None,
             enable_pruning_tombstones: false,
+            retained_history: RetainedHistoryConfig::default(),
         }
     }
 }
@@ -600,6 +638,7 @@ impl AuthorityStorePruningConfig {
             periodic_compaction_threshold_days: None,
             num_epochs_to_retain_for_checkpoints,
             enable_pruning_tombstones: false,
+            retained_history: RetainedHistoryConfig::default(),
         }
     }
     pub fn fullnode_config() -> Self {
@@ -617,6 +656,7 @@ impl AuthorityStorePruningConfig {
             periodic_compaction_threshold_days: None,
             num_epochs_to_retain_for_checkpoints,
             enable_pruning_tombstones: false,
+            retained_history: RetainedHistoryConfig::default(),
         }
     }
 
@@ -642,6 +682,57 @@ impl AuthorityStorePruningConfig {
     }
 }
 
+/// Selects the objects whose every version, and the transactions whose effects and events, the
+/// pruner keeps forever, so that a node can serve the full history of a few protocols without
+/// becoming an archival node.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RetainedHistoryConfig {
+    /// keep objects owned by these addresses, and transactions sent by them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<SuiAddress>,
+    /// keep these packages, objects of types defined in them, and transactions calling into them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<ObjectID>,
+    /// keep objects of these struct types. A type given without type parameters,
+    /// e.g. `0x2::coin::Coin`, matches all of its instantiations
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_object_types"
+    )]
+    pub object_types: Vec<String>,
+}
+
+impl RetainedHistoryConfig {
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.packages.is_empty() && self.object_types.is_empty()
+    }
+
+    /// Check that all of the `object_types` are struct types.
+    pub fn validate(&self) -> Result<()> {
+        for object_type in &self.object_types {
+            parse_sui_struct_tag(object_type)
+                .with_context(|| format!("Invalid retained object type {object_type:?}"))?;
+        }
+        Ok(())
+    }
+}
+
+fn deserialize_object_types<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let config = RetainedHistoryConfig {
+        object_types: Vec::deserialize(deserializer)?,
+        ..Default::default()
+    };
+    config
+        .validate()
+        .map_err(|err| serde::de::Error::custom(format!("{err:#}")))?;
+    Ok(config.object_types)
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct MetricsConfig {
@@ -688,6 +779,10 @@ pub struct StateSnapshotConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub object_store_config: Option<ObjectStoreConfig>,
     pub concurrency: usize,
+    /// Serve the latest state snapshot to peers bootstrapping through state sync. Only supported
+    /// when `object-store-config` is a `File` store.
+    #[serde(default)]
+    pub serve_to_peers: bool,
 }
 
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
@@ -724,6 +819,69 @@ fn default_overload_threshold_config() -> OverloadThresholdConfig {
     OverloadThresholdConfig::default()
 }
 
+/// Rate limits applied by the JSON-RPC server to each of its clients. Requests carrying one of the
+/// configured API keys are limited per key, all other requests are limited per IP address.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct JsonRpcRateLimitConfig {
+    /// Limit of each client IP address. IP addresses are not limited when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_ip: Option<TokenBucketConfig>,
+    /// Header carrying the API key of a request.
+    #[serde(default = "default_api_key_header")]
+    pub api_key_header: String,
+    /// Limit of each API key.
+    #[serde(default)]
+    pub api_keys: BTreeMap<String, TokenBucketConfig>,
+    /// Number of tokens consumed by a call to each method. Other methods consume one token.
+    #[serde(default = "default_method_weights")]
+    pub method_weights: BTreeMap<String, u32>,
+    /// Number of trusted proxies in front of the node, which each append the address they
+    /// received a request from to its `X-Forwarded-For` header. Clients are identified by the
+    /// address appended by the first of them, the `trusted-proxy-hops`-th from the end of the
+    /// header, rather than by the address of the connection. The header is ignored when zero.
+    #[serde(default)]
+    pub trusted_proxy_hops: usize,
+}
+
+/// A token bucket holding up to `capacity` tokens, and refilled with `refill_per_second` tokens
+/// every second.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TokenBucketConfig {
+    pub capacity: u32,
+    pub refill_per_second: u32,
+}
+
+impl Default for JsonRpcRateLimitConfig {
+    fn default() -> Self {
+        Self {
+            per_ip: Some(TokenBucketConfig {
+                capacity: 200,
+                refill_per_second: 100,
+            }),
+            api_key_header: default_api_key_header(),
+            api_keys: BTreeMap::new(),
+            method_weights: default_method_weights(),
+            trusted_proxy_hops: 0,
+        }
+    }
+}
+
+fn default_api_key_header() -> String {
+    "x-api-key".to_string()
+}
+
+fn default_method_weights() -> BTreeMap<String, u32> {
+    BTreeMap::from([
+        ("suix_queryEvents".to_string(), 10),
+        ("suix_queryTransactionBlocks".to_string(), 10),
+        ("suix_getOwnedObjects".to_string(), 5),
+        ("sui_multiGetObjects".to_string(), 5),
+        ("sui_multiGetTransactionBlocks".to_string(), 5),
+    ])
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Eq)]
 pub struct Genesis {
     #[serde(flatten)]
@@ -920,7 +1078,7 @@ mod tests {
     use sui_keys::keypair_file::{write_authority_keypair_to_file, write_keypair_to_file};
     use sui_types::crypto::{get_key_pair_from_rng, AuthorityKeyPair, NetworkKeyPair, SuiKeyPair};
 
-    use super::Genesis;
+    use super::{Genesis, RetainedHistoryConfig};
     use crate::NodeConfig;
 
     #[test]
@@ -940,6 +1098,22 @@ mod tests {
         let _template: NodeConfig = serde_yaml::from_str(TEMPLATE).unwrap();
     }
 
+    #[test]
+    fn retained_history_object_types() {
+        let config: RetainedHistoryConfig = serde_yaml::from_str(
+            "object-types:\n  - 0x2::coin::Coin<0x2::sui::SUI>\n  - 0x2::kiosk::Kiosk\n",
+        )
+        .unwrap();
+        assert_eq!(config.object_types.len(), 2);
+
+        let err = serde_yaml::from_str::<RetainedHistoryConfig>("object-types:\n  - 0x2::coin\n")
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid retained object type"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn load_key_pairs_to_node_config() {
         let protocol_key_pair: AuthorityKeyPair =
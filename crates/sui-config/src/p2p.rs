@@ -179,6 +179,20 @@ pub struct StateSyncConfig {
     /// If unspecified, this will default to no limit.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub get_checkpoint_contents_per_checkpoint_limit: Option<usize>,
+
+    /// Per-peer inflight limit for the GetStateSnapshotChunk RPC.
+    ///
+    /// If unspecified, this will default to `2`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub get_state_snapshot_chunk_per_peer_limit: Option<usize>,
+
+    /// Set the amount of time a peer is excluded from sync requests once its score has dropped
+    /// too low, after repeatedly timing out, failing requests or serving invalid data. A value of
+    /// `0` disables banning, in which case such peers are only queried after all others.
+    ///
+    /// If unspecified, this will default to `300,000` milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_ban_duration_ms: Option<u64>,
 }
 
 impl StateSyncConfig {
@@ -237,6 +251,21 @@ impl StateSyncConfig {
             .map(Duration::from_millis)
             .unwrap_or(DEFAULT_TIMEOUT)
     }
+
+    pub fn get_state_snapshot_chunk_per_peer_limit(&self) -> usize {
+        const GET_STATE_SNAPSHOT_CHUNK_PER_PEER_LIMIT: usize = 2;
+
+        self.get_state_snapshot_chunk_per_peer_limit
+            .unwrap_or(GET_STATE_SNAPSHOT_CHUNK_PER_PEER_LIMIT)
+    }
+
+    pub fn peer_ban_duration(&self) -> Duration {
+        const DEFAULT_PEER_BAN_DURATION: Duration = Duration::from_secs(300);
+
+        self.peer_ban_duration_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_PEER_BAN_DURATION)
+    }
 }
 
 /// Access Type of a node.
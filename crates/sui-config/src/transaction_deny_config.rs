@@ -2,11 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::collections::HashSet;
+use std::hash::Hash;
 
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use sui_types::base_types::{ObjectID, SuiAddress};
 
+use crate::Config;
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct TransactionDenyConfig {
@@ -75,7 +78,56 @@ pub struct TransactionDenyConfig {
     // TODO: We could also consider disable more types of commands, such as transfer, split and etc.
 }
 
+impl Config for TransactionDenyConfig {}
+
+/// The deny lists of a [TransactionDenyConfig] that can be updated at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DenyList {
+    Object,
+    Package,
+    Address,
+}
+
 impl TransactionDenyConfig {
+    /// Add `id` to a deny list. Returns whether it was not denied yet.
+    pub fn add_to_deny_list(&mut self, list: DenyList, id: SuiAddress) -> bool {
+        match list {
+            DenyList::Object => add_to_list(
+                &mut self.object_deny_list,
+                &mut self.object_deny_set,
+                id.into(),
+            ),
+            DenyList::Package => add_to_list(
+                &mut self.package_deny_list,
+                &mut self.package_deny_set,
+                id.into(),
+            ),
+            DenyList::Address => {
+                add_to_list(&mut self.address_deny_list, &mut self.address_deny_set, id)
+            }
+        }
+    }
+
+    /// Remove `id` from a deny list. Returns whether it was denied.
+    pub fn remove_from_deny_list(&mut self, list: DenyList, id: SuiAddress) -> bool {
+        match list {
+            DenyList::Object => remove_from_list(
+                &mut self.object_deny_list,
+                &mut self.object_deny_set,
+                id.into(),
+            ),
+            DenyList::Package => remove_from_list(
+                &mut self.package_deny_list,
+                &mut self.package_deny_set,
+                id.into(),
+            ),
+            DenyList::Address => {
+                remove_from_list(&mut self.address_deny_list, &mut self.address_deny_set, id)
+            }
+        }
+    }
+
     pub fn get_object_deny_set(&self) -> &HashSet<ObjectID> {
         self.object_deny_set
             .get_or_init(|| self.object_deny_list.iter().cloned().collect())
@@ -120,6 +172,27 @@ impl TransactionDenyConfig {
     }
 }
 
+fn add_to_list<T: Eq + Hash>(list: &mut Vec<T>, set: &mut OnceCell<HashSet<T>>, id: T) -> bool {
+    if list.contains(&id) {
+        return false;
+    }
+    list.push(id);
+    // The lookup set is rebuilt on next use
+    set.take();
+    true
+}
+
+fn remove_from_list<T: Eq + Hash>(
+    list: &mut Vec<T>,
+    set: &mut OnceCell<HashSet<T>>,
+    id: T,
+) -> bool {
+    let len = list.len();
+    list.retain(|denied| denied != &id);
+    set.take();
+    list.len() != len
+}
+
 #[derive(Default)]
 pub struct TransactionDenyConfigBuilder {
     config: TransactionDenyConfig,
@@ -184,3 +257,28 @@ impl TransactionDenyConfigBuilder {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_deny_lists() {
+        let object = ObjectID::random();
+        let mut config = TransactionDenyConfigBuilder::new()
+            .add_denied_object(object)
+            .build();
+        assert!(config.get_object_deny_set().contains(&object));
+
+        let address = SuiAddress::random_for_testing_only();
+        assert!(config.add_to_deny_list(DenyList::Address, address));
+        assert!(!config.add_to_deny_list(DenyList::Address, address));
+        assert!(config.get_address_deny_set().contains(&address));
+        assert!(config.get_package_deny_set().is_empty());
+
+        assert!(config.remove_from_deny_list(DenyList::Object, object.into()));
+        assert!(!config.remove_from_deny_list(DenyList::Object, object.into()));
+        assert!(config.get_object_deny_set().is_empty());
+        assert!(config.get_address_deny_set().contains(&address));
+    }
+}
@@ -15,7 +15,7 @@ use futures::stream::FuturesUnordered;
 use itertools::Itertools;
 use move_binary_format::CompiledModule;
 use move_bytecode_utils::module_cache::GetModule;
-use move_core_types::language_storage::ModuleId;
+use move_core_types::language_storage::{ModuleId, StructTag};
 use move_core_types::value::MoveStructLayout;
 use mysten_metrics::{TX_TYPE_SHARED_OBJ_TX, TX_TYPE_SINGLE_WRITER_TX};
 use parking_lot::Mutex;
@@ -71,7 +71,9 @@ use sui_json_rpc_types::{
 };
 use sui_macros::{fail_point, fail_point_async};
 use sui_protocol_config::{ProtocolConfig, SupportedProtocolVersions};
-use sui_storage::indexes::{CoinInfo, ObjectIndexChanges};
+use sui_storage::indexes::{
+    object_type_key, BalanceChangeInfo, CoinInfo, ObjectIndexChanges, TotalBalance,
+};
 use sui_storage::key_value_store::{TransactionKeyValueStore, TransactionKeyValueStoreTrait};
 use sui_storage::key_value_store_metrics::KeyValueStoreMetrics;
 use sui_storage::IndexStore;
@@ -254,6 +256,10 @@ pub struct AuthorityMetrics {
 }
 
 // Override default Prom buckets for positive numbers in 0-50k range
+/// Number of objects of the types of a query looked at, at most, to fill a page of the objects
+/// matching its other filters.
+const MAX_OBJECTS_BY_TYPE_SCAN: usize = 10_000;
+
 const POSITIVE_INT_BUCKETS: &[f64] = &[
     1., 2., 5., 10., 20., 50., 100., 200., 500., 1000., 2000., 5000., 10000., 20000., 50000.,
 ];
@@ -630,16 +636,18 @@ pub struct AuthorityState {
     tx_execution_shutdown: Mutex<Option<oneshot::Sender<()>>>,
 
     pub metrics: Arc<AuthorityMetrics>,
-    _pruner: AuthorityStorePruner,
+    pruner: AuthorityStorePruner,
     _authority_per_epoch_pruner: AuthorityPerEpochStorePruner,
 
     /// Take db checkpoints of different dbs
     db_checkpoint_config: DBCheckpointConfig,
 
-    /// Config controlling what kind of expensive safety checks to perform.
-    expensive_safety_check_config: ExpensiveSafetyCheckConfig,
+    /// Config controlling what kind of expensive safety checks to perform. Can be updated at
+    /// runtime through the admin API.
+    expensive_safety_check_config: ArcSwap<ExpensiveSafetyCheckConfig>,
 
-    transaction_deny_config: TransactionDenyConfig,
+    /// Can be updated at runtime through the admin API.
+    transaction_deny_config: ArcSwap<TransactionDenyConfig>,
 
     certificate_deny_config: CertificateDenyConfig,
 
@@ -677,6 +685,39 @@ impl AuthorityState {
         self.overload_threshold_config.max_txn_age_in_queue
     }
 
+    pub fn transaction_deny_config(&self) -> Arc<TransactionDenyConfig> {
+        self.transaction_deny_config.load_full()
+    }
+
+    /// Replace the transaction deny config. Transactions signed from now on are checked against
+    /// the new config.
+    pub fn set_transaction_deny_config(&self, transaction_deny_config: TransactionDenyConfig) {
+        self.transaction_deny_config
+            .store(Arc::new(transaction_deny_config));
+    }
+
+    pub fn expensive_safety_check_config(&self) -> Arc<ExpensiveSafetyCheckConfig> {
+        self.expensive_safety_check_config.load_full()
+    }
+
+    /// Replace the expensive safety check config. Checks run per transaction are affected
+    /// immediately, and the ones set up for an epoch from the next epoch on.
+    pub fn set_expensive_safety_check_config(
+        &self,
+        expensive_safety_check_config: ExpensiveSafetyCheckConfig,
+    ) {
+        self.database.set_enable_epoch_sui_conservation_check(
+            expensive_safety_check_config.enable_epoch_sui_conservation_check(),
+        );
+        self.expensive_safety_check_config
+            .store(Arc::new(expensive_safety_check_config));
+    }
+
+    /// Replace the config of the object and checkpoint pruner.
+    pub fn set_pruning_config(&self, pruning_config: AuthorityStorePruningConfig) {
+        self.pruner.update_config(pruning_config);
+    }
+
     pub fn get_epoch_state_commitments(
         &self,
         epoch: EpochId,
@@ -717,7 +758,7 @@ impl AuthorityState {
             transaction.tx_signatures(),
             &input_object_kinds,
             &receiving_objects_refs,
-            &self.transaction_deny_config,
+            &self.transaction_deny_config.load(),
             &self.database,
         )?;
 
@@ -1366,6 +1407,7 @@ impl AuthorityState {
                 // TODO: would be nice to pass the whole NodeConfig here, but it creates a
                 // cyclic dependency w/ sui-adapter
                 self.expensive_safety_check_config
+                    .load()
                     .enable_deep_per_tx_sui_conservation_check(),
                 self.certificate_deny_config.certificate_deny_set(),
                 &epoch_store.epoch_start_config().epoch_data().epoch_id(),
@@ -1415,7 +1457,7 @@ impl AuthorityState {
             &[],
             &input_object_kinds,
             &receiving_object_refs,
-            &self.transaction_deny_config,
+            &self.transaction_deny_config.load(),
             &self.database,
         )?;
 
@@ -1635,6 +1677,7 @@ impl AuthorityState {
         let executor = sui_execution::executor(
             protocol_config,
             self.expensive_safety_check_config
+                .load()
                 .enable_move_vm_paranoid_checks(),
             silent,
         )
@@ -1742,10 +1785,23 @@ impl AuthorityState {
             .collect::<HashMap<_, _>>();
 
         let tx_digest = effects.transaction_digest();
+        let index_object_types = self
+            .indexes
+            .as_ref()
+            .is_some_and(|indexes| indexes.objects_by_type_index_enabled());
         let mut deleted_owners = vec![];
         let mut deleted_dynamic_fields = vec![];
+        let mut deleted_object_types = vec![];
         for (id, _, _) in effects.deleted().into_iter().chain(effects.wrapped()) {
             let old_version = modified_at_version.get(&id).unwrap();
+            if index_object_types {
+                let Some(old_object) = self.database.get_object_by_key(&id, *old_version)? else {
+                    panic!("tx_digest={:?}, error processing object type index, cannot find object {:?} at version {:?}", tx_digest, id, old_version);
+                };
+                if let Some(type_) = old_object.struct_tag() {
+                    deleted_object_types.push(object_type_key(&type_, id));
+                }
+            }
             // When we process the index, the latest object hasn't been written yet so
             // the old object must be present.
             match self.get_owner_at_version(&id, *old_version).unwrap_or_else(
@@ -1761,9 +1817,30 @@ impl AuthorityState {
 
         let mut new_owners = vec![];
         let mut new_dynamic_fields = vec![];
+        let mut new_object_types = vec![];
 
         for (oref, owner, kind) in effects.all_changed_objects() {
             let id = &oref.0;
+            // An object's type never changes, but its entry is rewritten to keep the version,
+            // digest and owner it carries up to date.
+            if index_object_types {
+                let new_object = written.get(id).unwrap_or_else(
+                    || panic!("tx_digest={:?}, error processing object type index, written does not contain object {:?}", tx_digest, id)
+                );
+                if let Some(type_) = new_object.type_() {
+                    new_object_types.push((
+                        object_type_key(&type_.clone().into(), *id),
+                        ObjectInfo {
+                            object_id: *id,
+                            version: oref.1,
+                            digest: oref.2,
+                            type_: ObjectType::Struct(type_.clone()),
+                            owner,
+                            previous_transaction: *effects.transaction_digest(),
+                        },
+                    ));
+                }
+            }
             // For mutated objects, retrieve old owner and delete old index if there is a owner change.
             if let WriteKind::Mutate = kind {
                 let Some(old_version) = modified_at_version.get(id) else {
@@ -1818,10 +1895,13 @@ impl AuthorityState {
                     );
                     assert_eq!(new_object.version(), oref.1, "tx_digest={:?} error processing object owner index, object {:?} from written has mismatched version. Actual: {}, expected: {}", tx_digest, id, new_object.version(), oref.1);
 
-                    let Some(df_info) = self
-                        .try_create_dynamic_field_info(new_object, written, module_resolver)
-                        .expect("try_create_dynamic_field_info should not fail.")
-                    else {
+                    let Some(df_info) = Self::try_create_dynamic_field_info(
+                        &self.database,
+                        new_object,
+                        written,
+                        module_resolver,
+                    )
+                    .expect("try_create_dynamic_field_info should not fail.") else {
                         // Skip indexing for non dynamic field objects.
                         continue;
                     };
@@ -1836,11 +1916,13 @@ impl AuthorityState {
             deleted_dynamic_fields,
             new_owners,
             new_dynamic_fields,
+            deleted_object_types,
+            new_object_types,
         })
     }
 
-    fn try_create_dynamic_field_info(
-        &self,
+    pub(crate) fn try_create_dynamic_field_info(
+        database: &AuthorityStore,
         o: &Object,
         written: &WrittenObjects,
         resolver: &impl GetModule,
@@ -1884,8 +1966,7 @@ impl AuthorityState {
                     (version, digest, object_type)
                 } else {
                     // If not found, try to find it in the database.
-                    let object = self
-                        .database
+                    let object = database
                         .get_object_by_key(&object_id, o.version())?
                         .ok_or_else(|| UserInputError::ObjectNotFound {
                             object_id,
@@ -2160,7 +2241,7 @@ impl AuthorityState {
 
         let _authority_per_epoch_pruner =
             AuthorityPerEpochStorePruner::new(epoch_store.get_parent_path(), &pruning_config);
-        let _pruner = AuthorityStorePruner::new(
+        let pruner = AuthorityStorePruner::new(
             store.perpetual_tables.clone(),
             checkpoint_store.clone(),
             store.objects_lock_table.clone(),
@@ -2184,11 +2265,11 @@ impl AuthorityState {
             transaction_manager,
             tx_execution_shutdown: Mutex::new(Some(tx_execution_shutdown)),
             metrics,
-            _pruner,
+            pruner,
             _authority_per_epoch_pruner,
             db_checkpoint_config: db_checkpoint_config.clone(),
-            expensive_safety_check_config,
-            transaction_deny_config,
+            expensive_safety_check_config: ArcSwap::from_pointee(expensive_safety_check_config),
+            transaction_deny_config: ArcSwap::from_pointee(transaction_deny_config),
             certificate_deny_config,
             debug_dump_config,
             overload_threshold_config,
@@ -2206,6 +2287,7 @@ impl AuthorityState {
         state
             .create_owner_index_if_empty(genesis_objects, &epoch_store)
             .expect("Error indexing genesis objects.");
+        state.warn_if_objects_by_type_index_not_built();
 
         state
     }
@@ -2283,7 +2365,8 @@ impl AuthorityState {
                 )),
                 Owner::ObjectOwner(object_id) => {
                     let id = o.id();
-                    let Some(info) = self.try_create_dynamic_field_info(
+                    let Some(info) = Self::try_create_dynamic_field_info(
+                        &self.database,
                         o,
                         &BTreeMap::new(),
                         epoch_store.module_cache(),
@@ -2297,14 +2380,44 @@ impl AuthorityState {
             }
         }
 
+        let new_object_types = genesis_objects
+            .iter()
+            .filter_map(|o| {
+                let type_ = o.struct_tag()?;
+                Some((
+                    object_type_key(&type_, o.id()),
+                    ObjectInfo::new(&o.compute_object_reference(), o),
+                ))
+            })
+            .collect();
+
         index_store.insert_genesis_objects(ObjectIndexChanges {
             deleted_owners: vec![],
             deleted_dynamic_fields: vec![],
             new_owners,
             new_dynamic_fields,
+            deleted_object_types: vec![],
+            new_object_types,
         })
     }
 
+    /// The objects_by_type index is built from the live object set offline, as scanning it can
+    /// take hours. Until then, queries by type fail.
+    fn warn_if_objects_by_type_index_not_built(&self) {
+        let Some(index_store) = &self.indexes else {
+            return;
+        };
+        match index_store.objects_by_type_needs_backfill() {
+            Ok(false) => {}
+            Ok(true) => warn!(
+                "The objects_by_type index is enabled but not built, queries by object type will \
+                 fail until it is. Build it with the node stopped, using \
+                 `sui-tool db-tool --db-path <db path> rebuild-indexes --genesis <genesis> --index objects-by-type`"
+            ),
+            Err(e) => error!("Failed to read the state of the objects_by_type index: {e}"),
+        }
+    }
+
     #[instrument(level = "error", skip_all)]
     pub async fn reconfigure(
         &self,
@@ -2558,7 +2671,7 @@ impl AuthorityState {
 
         if expensive_safety_check_config.enable_secondary_index_checks() {
             if let Some(indexes) = self.indexes.clone() {
-                verify_indexes(self.database.clone(), indexes)
+                verify_indexes(self.database.clone(), indexes.tables())
                     .expect("secondary indexes are inconsistent");
             }
         }
@@ -2892,6 +3005,113 @@ impl AuthorityState {
         }
     }
 
+    /// Objects of any owner matching `filter`, read from the objects_by_type index. `filter` must
+    /// be a `Package`, `MoveModule` or `StructType` filter, or a `MatchAll` including one of them,
+    /// in which case the other filters are applied to the objects found by type.
+    ///
+    /// At most `MAX_OBJECTS_BY_TYPE_SCAN` objects are looked at. When fewer than `limit` of them
+    /// match, the type and ID of the last one is returned too, to resume the query after it.
+    #[instrument(level = "trace", skip_all)]
+    pub fn get_objects_by_type(
+        &self,
+        filter: &SuiObjectDataFilter,
+        // If `Some`, the query will start from the next item after the specified cursor. Its type
+        // is read from the object when unknown.
+        cursor: Option<(Option<StructTag>, ObjectID)>,
+        limit: usize,
+    ) -> SuiResult<(Vec<ObjectInfo>, Option<(StructTag, ObjectID)>)> {
+        let Some(indexes) = &self.indexes else {
+            return Err(SuiError::IndexStoreNotAvailable);
+        };
+        let is_type_filter = |f: &SuiObjectDataFilter| {
+            matches!(
+                f,
+                SuiObjectDataFilter::Package(_)
+                    | SuiObjectDataFilter::MoveModule { .. }
+                    | SuiObjectDataFilter::StructType(_)
+            )
+        };
+        let type_filter = match filter {
+            SuiObjectDataFilter::MatchAll(filters) => filters.iter().find(|f| is_type_filter(f)),
+            f if is_type_filter(f) => Some(f),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            UserInputError::Unsupported(
+                "querying objects requires a package, module or struct type filter".to_string(),
+            )
+        })?;
+
+        // Objects are ordered by type first, so the cursor's type is needed to resume after it.
+        let cursor = cursor
+            .map(|(type_, id)| match type_ {
+                Some(type_) => Ok((type_, id)),
+                None => self
+                    .database
+                    .get_object(&id)?
+                    .and_then(|object| object.struct_tag())
+                    .map(|type_| (type_, id))
+                    .ok_or_else(|| {
+                        SuiError::from(UserInputError::ObjectNotFound {
+                            object_id: id,
+                            version: None,
+                        })
+                    }),
+            })
+            .transpose()?;
+
+        let mut objects = vec![];
+        let mut last_scanned = None;
+        let mut scanned = 0;
+        for info in indexes.get_objects_by_type_iterator(type_filter, cursor)? {
+            if objects.len() == limit {
+                return Ok((objects, None));
+            }
+            if scanned == MAX_OBJECTS_BY_TYPE_SCAN {
+                return Ok((objects, last_scanned));
+            }
+            scanned += 1;
+            if let ObjectType::Struct(type_) = &info.type_ {
+                last_scanned = Some((type_.clone().into(), info.object_id));
+            }
+            if filter.matches(&info) {
+                objects.push(info);
+            }
+        }
+        Ok((objects, None))
+    }
+
+    #[instrument(level = "trace", skip_all)]
+    pub fn get_balance_history(
+        &self,
+        owner: SuiAddress,
+        coin_type: &TypeTag,
+        // If `Some`, the query will start from the next item after the specified cursor
+        cursor: Option<TxSequenceNumber>,
+        limit: usize,
+        descending: bool,
+    ) -> SuiResult<Vec<(TxSequenceNumber, BalanceChangeInfo)>> {
+        if let Some(indexes) = &self.indexes {
+            indexes.get_balance_history(owner, coin_type, cursor, limit, descending)
+        } else {
+            Err(SuiError::IndexStoreNotAvailable)
+        }
+    }
+
+    #[instrument(level = "trace", skip_all)]
+    pub fn get_balance_at_checkpoint(
+        &self,
+        owner: SuiAddress,
+        coin_type: &TypeTag,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> SuiResult<Option<TotalBalance>> {
+        if let Some(indexes) = &self.indexes {
+            indexes.get_balance_at_checkpoint(owner, coin_type, checkpoint)
+        } else {
+            Err(SuiError::IndexStoreNotAvailable)
+        }
+    }
+
     #[instrument(level = "trace", skip_all)]
     pub fn get_owned_coins_iterator_with_cursor(
         &self,
@@ -3,6 +3,7 @@
 
 use std::cmp::Ordering;
 use std::ops::Not;
+use std::sync::atomic::{self, AtomicBool};
 use std::sync::Arc;
 use std::{iter, mem, thread};
 
@@ -137,7 +138,7 @@ pub struct AuthorityStore {
     indirect_objects_threshold: usize,
 
     /// Whether to enable expensive SUI conservation check at epoch boundaries.
-    enable_epoch_sui_conservation_check: bool,
+    enable_epoch_sui_conservation_check: AtomicBool,
 
     metrics: AuthorityStoreMetrics,
 
@@ -196,6 +197,12 @@ impl AuthorityStore {
         Ok(this)
     }
 
+    /// Enable or disable the SUI conservation check run at the next epoch boundaries.
+    pub fn set_enable_epoch_sui_conservation_check(&self, enable: bool) {
+        self.enable_epoch_sui_conservation_check
+            .store(enable, atomic::Ordering::Relaxed);
+    }
+
     pub fn update_epoch_flags_metrics(&self, old: &[EpochFlag], new: &[EpochFlag]) {
         for flag in old {
             self.metrics
@@ -251,7 +258,9 @@ impl AuthorityStore {
             execution_lock: RwLock::new(epoch),
             objects_lock_table: Arc::new(RwLockTable::new(NUM_SHARDS)),
             indirect_objects_threshold,
-            enable_epoch_sui_conservation_check,
+            enable_epoch_sui_conservation_check: AtomicBool::new(
+                enable_epoch_sui_conservation_check,
+            ),
             metrics: AuthorityStoreMetrics::new(registry),
             package_cache: PackageObjectCache::new(),
         });
@@ -1779,7 +1788,10 @@ impl AuthorityStore {
         self: &Arc<Self>,
         old_epoch_store: &AuthorityPerEpochStore,
     ) -> SuiResult {
-        if !self.enable_epoch_sui_conservation_check {
+        if !self
+            .enable_epoch_sui_conservation_check
+            .load(atomic::Ordering::Relaxed)
+        {
             return Ok(());
         }
 
@@ -4,6 +4,7 @@
 use crate::authority::authority_store_types::{ObjectContentDigest, StoreData, StoreObject};
 use crate::checkpoints::{CheckpointStore, CheckpointWatermark};
 use anyhow::anyhow;
+use move_core_types::language_storage::StructTag;
 use mysten_metrics::{monitored_scope, spawn_monitored_task};
 use once_cell::sync::Lazy;
 use prometheus::{
@@ -16,20 +17,24 @@ use std::collections::{HashMap, HashSet};
 use std::time::SystemTime;
 use std::{sync::Arc, time::Duration};
 use sui_archival::reader::ArchiveReaderBalancer;
-use sui_config::node::AuthorityStorePruningConfig;
+use sui_config::node::{AuthorityStorePruningConfig, RetainedHistoryConfig};
 use sui_storage::mutex_table::RwLockTable;
-use sui_types::base_types::SequenceNumber;
+use sui_types::base_types::{SequenceNumber, SuiAddress, TransactionDigest};
 use sui_types::effects::TransactionEffects;
 use sui_types::effects::TransactionEffectsAPI;
 use sui_types::message_envelope::Message;
 use sui_types::messages_checkpoint::{
     CheckpointContents, CheckpointDigest, CheckpointSequenceNumber,
 };
+use sui_types::object::{Object, Owner};
+use sui_types::parse_sui_struct_tag;
+use sui_types::transaction::TransactionDataAPI;
 use sui_types::{
     base_types::{ObjectID, VersionNumber},
     storage::ObjectKey,
 };
 use tokio::sync::oneshot::{self, Sender};
+use tokio::sync::watch;
 use tokio::time::Instant;
 use tracing::{debug, error, info};
 use typed_store::{Map, TypedStoreError};
@@ -51,6 +56,7 @@ static PERIODIC_PRUNING_TABLES: Lazy<HashSet<String>> = Lazy::new(|| {
 });
 pub struct AuthorityStorePruner {
     _objects_pruner_cancel_handle: oneshot::Sender<()>,
+    config_sender: watch::Sender<AuthorityStorePruningConfig>,
 }
 
 pub struct AuthorityStorePruningMetrics {
@@ -95,6 +101,130 @@ pub enum PruningMode {
     Checkpoints,
 }
 
+/// The objects and transactions selected by a `RetainedHistoryConfig`, which are never pruned.
+struct RetainedHistory {
+    addresses: HashSet<SuiAddress>,
+    packages: HashSet<ObjectID>,
+    object_types: Vec<StructTag>,
+}
+
+impl RetainedHistory {
+    /// Returns `None` when nothing is retained, so that pruning can skip the extra reads.
+    fn new(config: &RetainedHistoryConfig) -> anyhow::Result<Option<Self>> {
+        if config.is_empty() {
+            return Ok(None);
+        }
+        let object_types = config
+            .object_types
+            .iter()
+            .map(|object_type| parse_sui_struct_tag(object_type))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Some(Self {
+            addresses: config.addresses.iter().copied().collect(),
+            packages: config.packages.iter().copied().collect(),
+            object_types,
+        }))
+    }
+
+    fn retains_object(&self, object: &Object) -> bool {
+        if let Owner::AddressOwner(address) | Owner::ObjectOwner(address) = object.owner {
+            if self.addresses.contains(&address) {
+                return true;
+            }
+        }
+        if self.packages.contains(&object.id()) {
+            return true;
+        }
+        let Some(tag) = object.struct_tag() else {
+            return false;
+        };
+        self.packages.contains(&ObjectID::from(tag.address))
+            || self.object_types.iter().any(|object_type| {
+                object_type.address == tag.address
+                    && object_type.module == tag.module
+                    && object_type.name == tag.name
+                    && (object_type.type_params.is_empty()
+                        || object_type.type_params == tag.type_params)
+            })
+    }
+
+    /// Returns the keys among `object_keys` whose object version is retained.
+    fn retained_objects(
+        &self,
+        perpetual_db: &AuthorityPerpetualTables,
+        object_keys: &[ObjectKey],
+    ) -> anyhow::Result<HashSet<ObjectKey>> {
+        let mut retained = HashSet::new();
+        for (object_key, store_object) in object_keys
+            .iter()
+            .zip(perpetual_db.objects.multi_get(object_keys)?)
+        {
+            let Some(store_object) = store_object else {
+                continue;
+            };
+            if let Some(object) = perpetual_db.object(object_key, store_object)? {
+                if self.retains_object(&object) {
+                    retained.insert(*object_key);
+                }
+            }
+        }
+        Ok(retained)
+    }
+
+    /// Returns the digests of the transactions among `effects` that are retained: those sent by a
+    /// retained address, calling into a retained package, or reading or writing a retained object.
+    fn retained_transactions(
+        &self,
+        perpetual_db: &AuthorityPerpetualTables,
+        effects: &[TransactionEffects],
+    ) -> anyhow::Result<HashSet<TransactionDigest>> {
+        let digests: Vec<_> = effects
+            .iter()
+            .map(|effects| *effects.transaction_digest())
+            .collect();
+        let transactions = perpetual_db.transactions.multi_get(&digests)?;
+
+        let mut retained = HashSet::new();
+        for ((digest, transaction), effects) in digests.into_iter().zip(transactions).zip(effects) {
+            if let Some(transaction) = transaction {
+                let data = transaction.inner().data().transaction_data();
+                if self.addresses.contains(&data.sender())
+                    || data
+                        .move_calls()
+                        .into_iter()
+                        .any(|(package, _, _)| self.packages.contains(package))
+                {
+                    retained.insert(digest);
+                    continue;
+                }
+            }
+
+            let mut object_keys: Vec<_> = effects
+                .modified_at_versions()
+                .into_iter()
+                .map(|(object_id, version)| ObjectKey(object_id, version))
+                .collect();
+            for ((object_id, version, _), owner, _) in effects.all_changed_objects() {
+                if let Owner::AddressOwner(address) | Owner::ObjectOwner(address) = owner {
+                    if self.addresses.contains(&address) {
+                        retained.insert(digest);
+                        break;
+                    }
+                }
+                object_keys.push(ObjectKey(object_id, version));
+            }
+            if !retained.contains(&digest)
+                && !self
+                    .retained_objects(perpetual_db, &object_keys)?
+                    .is_empty()
+            {
+                retained.insert(digest);
+            }
+        }
+        Ok(retained)
+    }
+}
+
 impl AuthorityStorePruner {
     /// prunes old versions of objects based on transaction effects
     async fn prune_objects(
@@ -105,6 +235,7 @@ impl AuthorityStorePruner {
         metrics: Arc<AuthorityStorePruningMetrics>,
         indirect_objects_threshold: usize,
         enable_pruning_tombstones: bool,
+        retained_history: Option<&RetainedHistory>,
     ) -> anyhow::Result<()> {
         let _scope = monitored_scope("ObjectsLivePruner");
         let mut wb = perpetual_db.objects.batch();
@@ -125,6 +256,16 @@ impl AuthorityStorePruner {
             }
         }
 
+        // Versions of retained objects are deleted one by one, so that a range delete can't cover
+        // a retained version in between them.
+        let mut objects_with_retained_versions = HashSet::new();
+        if let Some(retained_history) = retained_history {
+            let retained =
+                retained_history.retained_objects(perpetual_db, &live_object_keys_to_prune)?;
+            live_object_keys_to_prune.retain(|object_key| !retained.contains(object_key));
+            objects_with_retained_versions.extend(retained.into_iter().map(|key| key.0));
+        }
+
         metrics
             .num_pruned_objects
             .inc_by((live_object_keys_to_prune.len() + object_tombstones_to_prune.len()) as u64);
@@ -146,6 +287,11 @@ impl AuthorityStorePruner {
         }
 
         let mut updates: HashMap<ObjectID, (VersionNumber, VersionNumber)> = HashMap::new();
+        let (object_keys_to_delete, live_object_keys_to_prune): (Vec<_>, Vec<_>) =
+            live_object_keys_to_prune
+                .into_iter()
+                .partition(|object_key| objects_with_retained_versions.contains(&object_key.0));
+        wb.delete_batch(&perpetual_db.objects, object_keys_to_delete)?;
         for ObjectKey(object_id, seq_number) in live_object_keys_to_prune {
             updates
                 .entry(object_id)
@@ -179,6 +325,11 @@ impl AuthorityStorePruner {
                     object_keys_to_delete.push(object_key);
                 }
             }
+            if let Some(retained_history) = retained_history {
+                let retained =
+                    retained_history.retained_objects(perpetual_db, &object_keys_to_delete)?;
+                object_keys_to_delete.retain(|object_key| !retained.contains(object_key));
+            }
 
             wb.delete_batch(&perpetual_db.objects, object_keys_to_delete)?;
         }
@@ -207,13 +358,22 @@ impl AuthorityStorePruner {
         checkpoint_content_to_prune: Vec<CheckpointContents>,
         effects_to_prune: &Vec<TransactionEffects>,
         metrics: Arc<AuthorityStorePruningMetrics>,
+        retained_history: Option<&RetainedHistory>,
     ) -> anyhow::Result<()> {
         let _scope = monitored_scope("EffectsLivePruner");
 
+        let retained_transactions = match retained_history {
+            Some(retained_history) => {
+                retained_history.retained_transactions(perpetual_db, effects_to_prune)?
+            }
+            None => HashSet::new(),
+        };
+
         let mut perpetual_batch = perpetual_db.objects.batch();
         let transactions: Vec<_> = checkpoint_content_to_prune
             .iter()
             .flat_map(|content| content.iter().map(|tx| tx.transaction))
+            .filter(|digest| !retained_transactions.contains(digest))
             .collect();
 
         perpetual_batch.delete_batch(&perpetual_db.transactions, transactions.iter())?;
@@ -225,6 +385,9 @@ impl AuthorityStorePruner {
 
         let mut effect_digests = vec![];
         for effects in effects_to_prune {
+            if retained_transactions.contains(effects.transaction_digest()) {
+                continue;
+            }
             let effects_digest = effects.digest();
             debug!("Pruning effects {:?}", effects_digest);
             effect_digests.push(effects_digest);
@@ -356,6 +519,7 @@ impl AuthorityStorePruner {
         metrics: Arc<AuthorityStorePruningMetrics>,
         indirect_objects_threshold: usize,
     ) -> anyhow::Result<()> {
+        let retained_history = RetainedHistory::new(&config.retained_history)?;
         let mut checkpoint_number = starting_checkpoint_number;
         let current_epoch = checkpoint_store
             .get_highest_executed_checkpoint()?
@@ -414,6 +578,7 @@ impl AuthorityStorePruner {
                             metrics.clone(),
                             indirect_objects_threshold,
                             config.enable_pruning_tombstones,
+                            retained_history.as_ref(),
                         )
                         .await?
                     }
@@ -425,6 +590,7 @@ impl AuthorityStorePruner {
                         checkpoint_content_to_prune,
                         &effects_to_prune,
                         metrics.clone(),
+                        retained_history.as_ref(),
                     )?,
                 };
                 checkpoints_to_prune = vec![];
@@ -444,6 +610,7 @@ impl AuthorityStorePruner {
                         metrics.clone(),
                         indirect_objects_threshold,
                         config.enable_pruning_tombstones,
+                        retained_history.as_ref(),
                     )
                     .await?
                 }
@@ -455,6 +622,7 @@ impl AuthorityStorePruner {
                     checkpoint_content_to_prune,
                     &effects_to_prune,
                     metrics.clone(),
+                    retained_history.as_ref(),
                 )?,
             };
         }
@@ -502,8 +670,24 @@ impl AuthorityStorePruner {
         Ok(Some(sst_file))
     }
 
+    fn pruning_tick_duration(
+        config: &AuthorityStorePruningConfig,
+        epoch_duration_ms: u64,
+    ) -> Duration {
+        Duration::from_millis(match config.pruning_run_delay_seconds {
+            None => {
+                if config.num_epochs_to_retain > 0 {
+                    min(epoch_duration_ms / 2, 60 * 60 * 1000)
+                } else {
+                    min(epoch_duration_ms / 2, 60 * 1000)
+                }
+            }
+            Some(duration_seconds) => duration_seconds * 1000,
+        })
+    }
+
     fn setup_pruning(
-        config: AuthorityStorePruningConfig,
+        mut config_receiver: watch::Receiver<AuthorityStorePruningConfig>,
         epoch_duration_ms: u64,
         perpetual_db: Arc<AuthorityPerpetualTables>,
         checkpoint_store: Arc<CheckpointStore>,
@@ -513,20 +697,12 @@ impl AuthorityStorePruner {
         archive_readers: ArchiveReaderBalancer,
     ) -> Sender<()> {
         let (sender, mut recv) = tokio::sync::oneshot::channel();
+        let mut config = config_receiver.borrow_and_update().clone();
         debug!(
             "Starting object pruning service with num_epochs_to_retain={}",
             config.num_epochs_to_retain
         );
-        let tick_duration = Duration::from_millis(match config.pruning_run_delay_seconds {
-            None => {
-                if config.num_epochs_to_retain > 0 {
-                    min(epoch_duration_ms / 2, 60 * 60 * 1000)
-                } else {
-                    min(epoch_duration_ms / 2, 60 * 1000)
-                }
-            }
-            Some(duration_seconds) => duration_seconds * 1000,
-        });
+        let tick_duration = Self::pruning_tick_duration(&config, epoch_duration_ms);
         let pruning_initial_delay = if cfg!(msim) {
             Duration::from_millis(1)
         } else {
@@ -538,41 +714,62 @@ impl AuthorityStorePruner {
             tokio::time::interval_at(Instant::now() + pruning_initial_delay, tick_duration);
 
         let perpetual_db_for_compaction = perpetual_db.clone();
-        if let Some(delay_days) = config.periodic_compaction_threshold_days {
-            spawn_monitored_task!(async move {
-                loop {
-                    let db = perpetual_db_for_compaction.clone();
-                    let result = tokio::task::spawn_blocking(move || {
-                        Self::compact_next_sst_file(db, delay_days)
-                    })
-                    .await;
-                    let mut sleep_interval_secs = 1;
-                    match result {
-                        Err(err) => error!("Failed to compact sst file: {:?}", err),
-                        Ok(Err(err)) => error!("Failed to compact sst file: {:?}", err),
-                        Ok(Ok(None)) => {
-                            sleep_interval_secs = 3600;
-                        }
-                        _ => {}
+        let mut compaction_config_receiver = config_receiver.clone();
+        spawn_monitored_task!(async move {
+            loop {
+                let periodic_compaction_threshold_days = compaction_config_receiver
+                    .borrow_and_update()
+                    .periodic_compaction_threshold_days;
+                let Some(delay_days) = periodic_compaction_threshold_days else {
+                    // Wait for periodic compaction to be enabled, or for the pruner to stop.
+                    if compaction_config_receiver.changed().await.is_err() {
+                        break;
                     }
-                    tokio::time::sleep(Duration::from_secs(sleep_interval_secs)).await;
+                    continue;
+                };
+                let db = perpetual_db_for_compaction.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    Self::compact_next_sst_file(db, delay_days)
+                })
+                .await;
+                let mut sleep_interval_secs = 1;
+                match result {
+                    Err(err) => error!("Failed to compact sst file: {:?}", err),
+                    Ok(Err(err)) => error!("Failed to compact sst file: {:?}", err),
+                    Ok(Ok(None)) => {
+                        sleep_interval_secs = 3600;
+                    }
+                    _ => {}
                 }
-            });
-        }
+                tokio::time::sleep(Duration::from_secs(sleep_interval_secs)).await;
+            }
+        });
 
         tokio::task::spawn(async move {
             loop {
                 tokio::select! {
                     _ = objects_prune_interval.tick(), if config.num_epochs_to_retain != u64::MAX => {
-                        if let Err(err) = Self::prune_objects_for_eligible_epochs(&perpetual_db, &checkpoint_store, &objects_lock_table, config, metrics.clone(), indirect_objects_threshold).await {
+                        if let Err(err) = Self::prune_objects_for_eligible_epochs(&perpetual_db, &checkpoint_store, &objects_lock_table, config.clone(), metrics.clone(), indirect_objects_threshold).await {
                             error!("Failed to prune objects: {:?}", err);
                         }
                     },
                     _ = checkpoints_prune_interval.tick(), if !matches!(config.num_epochs_to_retain_for_checkpoints(), None | Some(u64::MAX) | Some(0)) => {
-                        if let Err(err) = Self::prune_checkpoints_for_eligible_epochs(&perpetual_db, &checkpoint_store, &objects_lock_table, config, metrics.clone(), indirect_objects_threshold, archive_readers.clone()).await {
+                        if let Err(err) = Self::prune_checkpoints_for_eligible_epochs(&perpetual_db, &checkpoint_store, &objects_lock_table, config.clone(), metrics.clone(), indirect_objects_threshold, archive_readers.clone()).await {
                             error!("Failed to prune checkpoints: {:?}", err);
                         }
                     },
+                    Ok(()) = config_receiver.changed() => {
+                        config = config_receiver.borrow_and_update().clone();
+                        info!(
+                            "Updated the pruning config, num_epochs_to_retain={}",
+                            config.num_epochs_to_retain
+                        );
+                        let tick_duration = Self::pruning_tick_duration(&config, epoch_duration_ms);
+                        objects_prune_interval =
+                            tokio::time::interval_at(Instant::now() + tick_duration, tick_duration);
+                        checkpoints_prune_interval =
+                            tokio::time::interval_at(Instant::now() + tick_duration, tick_duration);
+                    },
                     _ = &mut recv => break,
                 }
             }
@@ -590,9 +787,10 @@ impl AuthorityStorePruner {
         indirect_objects_threshold: usize,
         archive_readers: ArchiveReaderBalancer,
     ) -> Self {
+        let (config_sender, config_receiver) = watch::channel(pruning_config);
         AuthorityStorePruner {
             _objects_pruner_cancel_handle: Self::setup_pruning(
-                pruning_config,
+                config_receiver,
                 epoch_duration_ms,
                 perpetual_db,
                 checkpoint_store,
@@ -601,9 +799,16 @@ impl AuthorityStorePruner {
                 indirect_objects_threshold,
                 archive_readers,
             ),
+            config_sender,
         }
     }
 
+    /// Replace the pruning config. The next pruning runs use the new retention periods and batch
+    /// sizes, and are rescheduled according to its run delay.
+    pub fn update_config(&self, pruning_config: AuthorityStorePruningConfig) {
+        self.config_sender.send_replace(pruning_config);
+    }
+
     pub fn compact(perpetual_db: &Arc<AuthorityPerpetualTables>) -> Result<(), TypedStoreError> {
         perpetual_db.objects.compact_range(
             &ObjectKey(ObjectID::ZERO, SequenceNumber::MIN),
@@ -627,8 +832,9 @@ mod tests {
         StoreObjectWrapper,
     };
     use prometheus::Registry;
+    use sui_config::node::RetainedHistoryConfig;
     use sui_storage::mutex_table::RwLockTable;
-    use sui_types::base_types::ObjectDigest;
+    use sui_types::base_types::{ObjectDigest, SuiAddress};
     use sui_types::effects::TransactionEffects;
     use sui_types::effects::TransactionEffectsAPI;
     use sui_types::{
@@ -640,7 +846,14 @@ mod tests {
     use typed_store::rocks::{DBMap, MetricConf, ReadWriteOptions};
     use typed_store::Map;
 
-    use super::AuthorityStorePruner;
+    use super::{AuthorityStorePruner, RetainedHistory};
+    use crate::checkpoints::CheckpointStore;
+    use sui_types::base_types::{random_object_ref, ExecutionDigests};
+    use sui_types::crypto::{get_key_pair, AccountKeyPair};
+    use sui_types::message_envelope::Message;
+    use sui_types::messages_checkpoint::CheckpointContents;
+    use sui_types::transaction::{TransactionData, VerifiedTransaction};
+    use sui_types::utils::to_sender_signed_transaction;
 
     fn get_keys_after_pruning(path: &Path) -> anyhow::Result<HashSet<ObjectKey>> {
         let perpetual_db_path = path.join(Path::new("perpetual"));
@@ -790,6 +1003,7 @@ mod tests {
                 metrics,
                 indirect_object_threshold,
                 true,
+                None,
             )
             .await
             .unwrap();
@@ -825,6 +1039,136 @@ mod tests {
         assert_eq!(get_keys_after_pruning(&path).unwrap().len(), 0);
     }
 
+    // Tests that the versions of retained objects survive pruning, and only them.
+    #[tokio::test]
+    async fn test_pruning_retained_history() {
+        let path = tempfile::tempdir().unwrap().into_path();
+        let retained_owner = SuiAddress::random_for_testing_only();
+        let other_owner = SuiAddress::random_for_testing_only();
+        let retained_id = ObjectID::random();
+        let other_id = ObjectID::random();
+        {
+            let db = Arc::new(AuthorityPerpetualTables::open(&path, None));
+            let mut batch = db.objects.batch();
+            let mut effects = TransactionEffects::default();
+            for (id, owner) in [(retained_id, retained_owner), (other_id, other_owner)] {
+                for version in 0..3 {
+                    let version = SequenceNumber::from_u64(version);
+                    let StoreObjectPair(obj, _) = get_store_object_pair(
+                        Object::with_id_owner_version_for_testing(id, version, owner),
+                        0,
+                    );
+                    batch
+                        .insert_batch(&db.objects, [(ObjectKey(id, version), obj)])
+                        .unwrap();
+                    if version.value() < 2 {
+                        effects.unsafe_add_deleted_live_object_for_testing((
+                            id,
+                            version,
+                            ObjectDigest::MIN,
+                        ));
+                    }
+                }
+            }
+            batch.write().unwrap();
+
+            let retained_history = RetainedHistory::new(&RetainedHistoryConfig {
+                addresses: vec![retained_owner],
+                ..Default::default()
+            })
+            .unwrap();
+            AuthorityStorePruner::prune_objects(
+                vec![effects],
+                &db,
+                &lock_table(),
+                0,
+                AuthorityStorePruningMetrics::new_for_test(),
+                0,
+                true,
+                retained_history.as_ref(),
+            )
+            .await
+            .unwrap();
+        }
+        tokio::time::sleep(Duration::from_secs(3)).await;
+
+        let expected: HashSet<_> = (0..3)
+            .map(|version| ObjectKey(retained_id, SequenceNumber::from_u64(version)))
+            .chain([ObjectKey(other_id, SequenceNumber::from_u64(2))])
+            .collect();
+        assert_eq!(expected, get_keys_after_pruning(&path).unwrap());
+    }
+
+    // Tests that the transactions, effects and events of retained transactions survive checkpoint
+    // pruning, and only them.
+    #[tokio::test]
+    async fn test_pruning_checkpoints_retained_history() {
+        let path = tempfile::tempdir().unwrap().into_path();
+        let db = Arc::new(AuthorityPerpetualTables::open(&path, None));
+        let checkpoint_store = CheckpointStore::new(&path.join("checkpoints"));
+
+        let (retained_sender, retained_key): (_, AccountKeyPair) = get_key_pair();
+        let (other_sender, other_key): (_, AccountKeyPair) = get_key_pair();
+        let mut digests = vec![];
+        let mut all_effects = vec![];
+        for (sender, key) in [(retained_sender, &retained_key), (other_sender, &other_key)] {
+            let data = TransactionData::new_transfer_sui(
+                SuiAddress::random_for_testing_only(),
+                sender,
+                None,
+                random_object_ref(),
+                1_000_000,
+                1_000,
+            );
+            let transaction =
+                VerifiedTransaction::new_unchecked(to_sender_signed_transaction(data, key));
+            let effects = TransactionEffects::new_with_tx(transaction.data());
+            let digest = *transaction.digest();
+            db.transactions
+                .insert(&digest, transaction.serializable_ref())
+                .unwrap();
+            db.effects.insert(&effects.digest(), &effects).unwrap();
+            db.executed_effects
+                .insert(&digest, &effects.digest())
+                .unwrap();
+            digests.push(ExecutionDigests::new(digest, effects.digest()));
+            all_effects.push(effects);
+        }
+
+        let retained_history = RetainedHistory::new(&RetainedHistoryConfig {
+            addresses: vec![retained_sender],
+            ..Default::default()
+        })
+        .unwrap();
+        AuthorityStorePruner::prune_checkpoints(
+            &db,
+            &checkpoint_store,
+            0,
+            vec![],
+            vec![CheckpointContents::new_with_digests_only_for_tests(
+                digests.clone(),
+            )],
+            &all_effects,
+            AuthorityStorePruningMetrics::new_for_test(),
+            retained_history.as_ref(),
+        )
+        .unwrap();
+
+        let (retained, pruned) = (&digests[0], &digests[1]);
+        assert!(db.transactions.contains_key(&retained.transaction).unwrap());
+        assert!(db
+            .executed_effects
+            .contains_key(&retained.transaction)
+            .unwrap());
+        assert!(db.effects.contains_key(&retained.effects).unwrap());
+        assert!(!db.transactions.contains_key(&pruned.transaction).unwrap());
+        assert!(!db
+            .executed_effects
+            .contains_key(&pruned.transaction)
+            .unwrap());
+        assert!(!db.effects.contains_key(&pruned.effects).unwrap());
+    }
+
     #[tokio::test]
     async fn test_ref_count_pruning() {
         let path = tempfile::tempdir().unwrap().into_path();
@@ -434,6 +434,23 @@ impl AuthorityPerpetualTables {
         }
     }
 
+    /// Like `iter_live_object_set`, but starting from the objects whose ID is `start` or greater.
+    pub fn iter_live_object_set_from(
+        &self,
+        start: &ObjectID,
+        include_wrapped_object: bool,
+    ) -> SuiResult<LiveSetIter<'_>> {
+        Ok(LiveSetIter {
+            iter: self
+                .objects
+                .unbounded_iter()
+                .skip_to(&ObjectKey::min_for_id(start))?,
+            tables: self,
+            prev: None,
+            include_wrapped_object,
+        })
+    }
+
     pub fn checkpoint_db(&self, path: &Path) -> SuiResult {
         // This checkpoints the entire db and not just objects table
         self.objects
@@ -242,6 +242,8 @@ impl<'a> TestAuthorityBuilder<'a> {
                 epoch_store
                     .protocol_config()
                     .max_move_identifier_len_as_option(),
+                false,
+                false,
             )))
         };
         let transaction_deny_config = self.transaction_deny_config.unwrap_or_default();
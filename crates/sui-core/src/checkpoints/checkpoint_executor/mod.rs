@@ -30,6 +30,7 @@ use mysten_metrics::{spawn_monitored_task, MonitoredFutureExt};
 use prometheus::Registry;
 use sui_config::node::CheckpointExecutorConfig;
 use sui_macros::{fail_point, fail_point_async};
+use sui_storage::IndexStore;
 use sui_types::effects::{TransactionEffects, TransactionEffectsAPI};
 use sui_types::executable_transaction::VerifiedExecutableTransaction;
 use sui_types::message_envelope::Message;
@@ -72,6 +73,7 @@ pub struct CheckpointExecutor {
     authority_store: Arc<AuthorityStore>,
     tx_manager: Arc<TransactionManager>,
     accumulator: Arc<StateAccumulator>,
+    indexes: Option<Arc<IndexStore>>,
     config: CheckpointExecutorConfig,
     metrics: Arc<CheckpointExecutorMetrics>,
 }
@@ -83,6 +85,7 @@ impl CheckpointExecutor {
         authority_store: Arc<AuthorityStore>,
         tx_manager: Arc<TransactionManager>,
         accumulator: Arc<StateAccumulator>,
+        indexes: Option<Arc<IndexStore>>,
         config: CheckpointExecutorConfig,
         prometheus_registry: &Registry,
     ) -> Self {
@@ -92,6 +95,7 @@ impl CheckpointExecutor {
             authority_store,
             tx_manager,
             accumulator,
+            indexes,
             config,
             metrics: CheckpointExecutorMetrics::new(prometheus_registry),
         }
@@ -110,6 +114,7 @@ impl CheckpointExecutor {
             authority_store,
             tx_manager,
             accumulator,
+            indexes: None,
             config: Default::default(),
             metrics: CheckpointExecutorMetrics::new_for_tests(),
         }
@@ -271,6 +276,8 @@ impl CheckpointExecutor {
                 .expect("Failed to delete contents digest -> sequence number mapping");
         }
 
+        self.index_checkpoint_balances(checkpoint);
+
         self.checkpoint_store
             .update_highest_executed_checkpoint(checkpoint)
             .unwrap();
@@ -282,6 +289,29 @@ impl CheckpointExecutor {
         checkpoint.report_checkpoint_age_ms(&self.metrics.last_executed_checkpoint_age_ms);
     }
 
+    /// Index the balances at the end of an executed checkpoint, if the balance history is
+    /// enabled. All the transactions of the checkpoint have been indexed when they were executed.
+    fn index_checkpoint_balances(&self, checkpoint: &VerifiedCheckpoint) {
+        let Some(indexes) = self
+            .indexes
+            .as_ref()
+            .filter(|indexes| indexes.balance_history_index_enabled())
+        else {
+            return;
+        };
+        let tx_digests: Vec<_> = self
+            .checkpoint_store
+            .get_checkpoint_contents(&checkpoint.content_digest)
+            .expect("read cannot fail")
+            .expect("Checkpoint contents should exist")
+            .iter()
+            .map(|digests| digests.transaction)
+            .collect();
+        indexes
+            .index_checkpoint_balances(*checkpoint.sequence_number(), &tx_digests)
+            .expect("Failed to index checkpoint balances");
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn schedule_synced_checkpoints(
         &self,
@@ -623,6 +653,9 @@ impl CheckpointExecutor {
                     )
                     .expect("Finalizing checkpoint cannot fail");
 
+                    // The change_epoch tx was executed after the checkpoint was processed.
+                    self.index_checkpoint_balances(checkpoint);
+
                     self.accumulator
                         .accumulate_epoch(
                             &cur_epoch,
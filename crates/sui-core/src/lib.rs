@@ -23,6 +23,7 @@ pub mod module_cache_metrics;
 pub mod mysticeti_adapter;
 pub(crate) mod post_consensus_tx_reorder;
 pub mod quorum_driver;
+pub mod rebuild_indexes;
 pub mod safe_client;
 mod scoring_decision;
 mod stake_aggregator;
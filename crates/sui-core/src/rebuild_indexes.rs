@@ -0,0 +1,469 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cmp::min, collections::BTreeMap, sync::Arc};
+
+use anyhow::Result;
+use futures::StreamExt;
+use move_bytecode_utils::module_cache::{GetModule, SyncModuleCache};
+use prometheus::Registry;
+use sui_storage::indexes::{IndexGroup, IndexStoreTables, IndexedTransaction};
+use sui_types::{
+    base_types::ObjectID,
+    digests::{TransactionDigest, TransactionEffectsDigest},
+    effects::{TransactionEffects, TransactionEffectsAPI},
+    object::Owner,
+    transaction::{TransactionDataAPI, VerifiedTransaction},
+};
+use tracing::{info, warn};
+
+use crate::{
+    authority::{
+        authority_store_tables::LiveObject, AuthorityState, AuthorityStore, ResolverWrapper,
+    },
+    checkpoints::CheckpointStore,
+    module_cache_metrics::ResolverMetrics,
+    verify_indexes::verify_indexes,
+};
+
+/// The live object set is split into chunks by the first byte of the object IDs.
+const OBJECT_CHUNKS: u64 = 256;
+const CHECKPOINTS_PER_CHUNK: u64 = 1000;
+const OBJECTS_PER_BATCH: usize = 10_000;
+const TRANSACTIONS_PER_BATCH: usize = 10_000;
+
+/// Rebuild the index `groups` from the live object set and the executed checkpoints, with up to
+/// `parallelism` chunks of work in flight. A rebuild that was interrupted is resumed, unless
+/// `restart` is set. The node must not be running.
+///
+/// Transactions are numbered by their position in the checkpoints, followed by those executed ahead
+/// of their checkpoint, and those of checkpoints that were pruned are not indexed. Once done, the owner and coin indexes are verified if they were
+/// rebuilt.
+pub async fn rebuild_indexes(
+    store: Arc<AuthorityStore>,
+    checkpoint_store: Arc<CheckpointStore>,
+    indexes: Arc<IndexStoreTables>,
+    groups: &[IndexGroup],
+    parallelism: usize,
+    restart: bool,
+) -> Result<()> {
+    for group in groups {
+        if !restart && indexes.is_rebuild_in_progress(*group)? {
+            info!("Resuming the rebuild of the {group:?} index");
+        } else {
+            info!("Clearing the {group:?} index");
+            indexes.reset_index_group(*group)?;
+        }
+    }
+
+    let object_groups: Vec<_> = groups
+        .iter()
+        .copied()
+        .filter(IndexGroup::is_object_index)
+        .collect();
+    if !object_groups.is_empty() {
+        rebuild_object_indexes(store.clone(), indexes.clone(), &object_groups, parallelism).await?;
+        for group in &object_groups {
+            indexes.finish_rebuild(*group)?;
+        }
+    }
+
+    if groups.contains(&IndexGroup::Transactions) {
+        rebuild_transaction_indexes(
+            store.clone(),
+            checkpoint_store,
+            indexes.clone(),
+            parallelism,
+        )
+        .await?;
+        indexes.finish_rebuild(IndexGroup::Transactions)?;
+    }
+
+    if groups.contains(&IndexGroup::Owner) || groups.contains(&IndexGroup::Coin) {
+        verify_indexes(store, &indexes)?;
+    } else {
+        info!("There are no checks for the rebuilt indexes, skipping verification");
+    }
+    Ok(())
+}
+
+async fn rebuild_object_indexes(
+    store: Arc<AuthorityStore>,
+    indexes: Arc<IndexStoreTables>,
+    groups: &[IndexGroup],
+    parallelism: usize,
+) -> Result<()> {
+    let mut chunks = vec![];
+    for chunk in 0..OBJECT_CHUNKS {
+        let mut pending_groups = vec![];
+        for group in groups {
+            if !indexes.is_rebuild_chunk_done(*group, chunk)? {
+                pending_groups.push(*group);
+            }
+        }
+        if !pending_groups.is_empty() {
+            chunks.push((chunk, pending_groups));
+        }
+    }
+    info!(
+        "Rebuilding {groups:?} indexes, {} of {OBJECT_CHUNKS} chunks of the live object set left",
+        chunks.len()
+    );
+
+    let resolver = Arc::new(SyncModuleCache::new(ResolverWrapper::new(
+        store.clone(),
+        Arc::new(ResolverMetrics::new(&Registry::default())),
+    )));
+    run_chunks(chunks, parallelism, move |(chunk, groups)| {
+        rebuild_object_chunk(&store, resolver.as_ref(), &indexes, &groups, chunk)
+    })
+    .await
+}
+
+fn rebuild_object_chunk(
+    store: &AuthorityStore,
+    resolver: &impl GetModule,
+    indexes: &IndexStoreTables,
+    groups: &[IndexGroup],
+    chunk: u64,
+) -> Result<()> {
+    let mut start = [0; ObjectID::LENGTH];
+    start[0] = chunk as u8;
+    let objects = store
+        .perpetual_tables
+        .iter_live_object_set_from(&ObjectID::new(start), false)?
+        .filter_map(|object| match object {
+            LiveObject::Normal(object) => Some(object),
+            LiveObject::Wrapped(_) => None,
+        })
+        .take_while(|object| object.id().into_bytes()[0] as u64 == chunk);
+
+    let index_dynamic_fields = groups.contains(&IndexGroup::DynamicField);
+    let mut batch = vec![];
+    for object in objects {
+        let dynamic_field_info =
+            if index_dynamic_fields && matches!(object.owner, Owner::ObjectOwner(_)) {
+                AuthorityState::try_create_dynamic_field_info(
+                    store,
+                    &object,
+                    &BTreeMap::new(),
+                    resolver,
+                )?
+            } else {
+                None
+            };
+        batch.push((object, dynamic_field_info));
+        if batch.len() >= OBJECTS_PER_BATCH {
+            indexes.rebuild_objects(groups, &batch, None)?;
+            batch.clear();
+        }
+    }
+    indexes.rebuild_objects(groups, &batch, Some(chunk))?;
+    Ok(())
+}
+
+async fn rebuild_transaction_indexes(
+    store: Arc<AuthorityStore>,
+    checkpoint_store: Arc<CheckpointStore>,
+    indexes: Arc<IndexStoreTables>,
+    parallelism: usize,
+) -> Result<()> {
+    let Some(highest_executed) = checkpoint_store.get_highest_executed_checkpoint()? else {
+        info!("No checkpoint was executed, indexing the transactions executed ahead of them");
+        return rebuild_pending_transactions(&store, &indexes, 0, 0);
+    };
+    let highest_pruned = checkpoint_store.get_highest_pruned_checkpoint_seq_number()?;
+    if highest_pruned > 0 {
+        warn!(
+            "Checkpoints up to {highest_pruned} were pruned, their transactions won't be indexed"
+        );
+    }
+
+    let highest_sequence_number = highest_executed.sequence_number;
+    let mut chunks = vec![];
+    for chunk in
+        highest_pruned / CHECKPOINTS_PER_CHUNK..=highest_sequence_number / CHECKPOINTS_PER_CHUNK
+    {
+        if !indexes.is_rebuild_chunk_done(IndexGroup::Transactions, chunk)? {
+            chunks.push(chunk);
+        }
+    }
+    info!(
+        "Rebuilding transaction indexes up to checkpoint {highest_sequence_number}, {} chunks of {CHECKPOINTS_PER_CHUNK} checkpoints left",
+        chunks.len()
+    );
+
+    run_chunks(chunks, parallelism, {
+        let store = store.clone();
+        let indexes = indexes.clone();
+        move |chunk| {
+            rebuild_transaction_chunk(
+                &store,
+                &checkpoint_store,
+                &indexes,
+                chunk,
+                highest_sequence_number,
+            )
+        }
+    })
+    .await?;
+
+    rebuild_pending_transactions(
+        &store,
+        &indexes,
+        highest_executed.network_total_transactions,
+        highest_executed.timestamp_ms,
+    )
+}
+
+fn rebuild_transaction_chunk(
+    store: &AuthorityStore,
+    checkpoint_store: &CheckpointStore,
+    indexes: &IndexStoreTables,
+    chunk: u64,
+    highest_executed: u64,
+) -> Result<()> {
+    let first = chunk * CHECKPOINTS_PER_CHUNK;
+    let last = min(first + CHECKPOINTS_PER_CHUNK - 1, highest_executed);
+    let mut batch = vec![];
+    for sequence_number in first..=last {
+        // Skip the checkpoints, and below the transactions, that were pruned.
+        let Some(checkpoint) =
+            checkpoint_store.get_checkpoint_by_sequence_number(sequence_number)?
+        else {
+            continue;
+        };
+        let Some(contents) =
+            checkpoint_store.get_checkpoint_contents(&checkpoint.content_digest)?
+        else {
+            continue;
+        };
+        let first_transaction = checkpoint.network_total_transactions - contents.size() as u64;
+        let digests: Vec<_> = contents.iter().map(|digests| digests.transaction).collect();
+        let transactions = store.multi_get_transaction_blocks(&digests)?;
+        let effects = store.multi_get_effects(contents.iter().map(|digests| &digests.effects))?;
+
+        for (i, (transaction, effects)) in transactions.into_iter().zip(effects).enumerate() {
+            let (Some(transaction), Some(effects)) = (transaction, effects) else {
+                continue;
+            };
+            batch.push(indexed_transaction(
+                store,
+                first_transaction + i as u64,
+                &transaction,
+                &effects,
+                checkpoint.timestamp_ms,
+            )?);
+        }
+        if batch.len() >= TRANSACTIONS_PER_BATCH {
+            indexes.rebuild_transactions(&batch, None)?;
+            batch.clear();
+        }
+    }
+    indexes.rebuild_transactions(&batch, Some(chunk))?;
+    Ok(())
+}
+
+/// Index the transactions that were executed ahead of the checkpoint that includes them. They have
+/// no position in the checkpoints yet, so they are numbered from `first_sequence`, after the
+/// transactions of the executed checkpoints, in digest order, and given the `timestamp_ms` of the
+/// highest executed checkpoint. Indexing them again overwrites the same entries, so this step is not
+/// tracked for resumption.
+fn rebuild_pending_transactions(
+    store: &AuthorityStore,
+    indexes: &IndexStoreTables,
+    first_sequence: u64,
+    timestamp_ms: u64,
+) -> Result<()> {
+    let mut sequence = first_sequence;
+    let mut executed = vec![];
+    for digests in store.perpetual_tables.executed_effects.unbounded_iter() {
+        executed.push(digests);
+        if executed.len() >= TRANSACTIONS_PER_BATCH {
+            sequence =
+                index_pending_transactions(store, indexes, &executed, sequence, timestamp_ms)?;
+            executed.clear();
+        }
+    }
+    sequence = index_pending_transactions(store, indexes, &executed, sequence, timestamp_ms)?;
+    if sequence > first_sequence {
+        info!(
+            "Indexed {} transactions executed ahead of their checkpoint",
+            sequence - first_sequence
+        );
+    }
+    Ok(())
+}
+
+/// Index those of the `executed` transactions that are not in an executed checkpoint, numbered
+/// from `sequence`, and return the sequence number of the next one.
+fn index_pending_transactions(
+    store: &AuthorityStore,
+    indexes: &IndexStoreTables,
+    executed: &[(TransactionDigest, TransactionEffectsDigest)],
+    mut sequence: u64,
+    timestamp_ms: u64,
+) -> Result<u64> {
+    let checkpoints = store
+        .perpetual_tables
+        .executed_transactions_to_checkpoint
+        .multi_get(executed.iter().map(|(digest, _)| digest))?;
+    let pending: Vec<_> = executed
+        .iter()
+        .zip(checkpoints)
+        .filter(|(_, checkpoint)| checkpoint.is_none())
+        .map(|(digests, _)| *digests)
+        .collect();
+    if pending.is_empty() {
+        return Ok(sequence);
+    }
+
+    let digests: Vec<_> = pending.iter().map(|(digest, _)| *digest).collect();
+    let transactions = store.multi_get_transaction_blocks(&digests)?;
+    let effects = store.multi_get_effects(pending.iter().map(|(_, effects)| effects))?;
+    let mut batch = vec![];
+    for (transaction, effects) in transactions.into_iter().zip(effects) {
+        let (Some(transaction), Some(effects)) = (transaction, effects) else {
+            continue;
+        };
+        batch.push(indexed_transaction(
+            store,
+            sequence,
+            &transaction,
+            &effects,
+            timestamp_ms,
+        )?);
+        sequence += 1;
+    }
+    indexes.rebuild_transactions(&batch, None)?;
+    Ok(sequence)
+}
+
+fn indexed_transaction(
+    store: &AuthorityStore,
+    sequence: u64,
+    transaction: &VerifiedTransaction,
+    effects: &TransactionEffects,
+    timestamp_ms: u64,
+) -> Result<IndexedTransaction> {
+    let data = transaction.data().transaction_data();
+    let events = match effects.events_digest() {
+        Some(events_digest) => store.get_events(events_digest)?.unwrap_or_default(),
+        None => Default::default(),
+    };
+    Ok(IndexedTransaction {
+        sequence,
+        digest: *effects.transaction_digest(),
+        sender: data.sender(),
+        input_objects: data
+            .input_objects()?
+            .iter()
+            .map(|object| object.object_id())
+            .collect(),
+        mutated_objects: effects
+            .all_changed_objects()
+            .into_iter()
+            .map(|(object_ref, owner, _)| (object_ref, owner))
+            .collect(),
+        move_functions: data
+            .move_calls()
+            .into_iter()
+            .map(|(package, module, function)| (*package, module.to_owned(), function.to_owned()))
+            .collect(),
+        events,
+        timestamp_ms,
+    })
+}
+
+/// Run `rebuild_chunk` on each of `chunks` on the blocking thread pool, `parallelism` at a time.
+async fn run_chunks<T, F>(chunks: Vec<T>, parallelism: usize, rebuild_chunk: F) -> Result<()>
+where
+    T: Send + 'static,
+    F: Fn(T) -> Result<()> + Send + Sync + 'static,
+{
+    let total = chunks.len();
+    let rebuild_chunk = Arc::new(rebuild_chunk);
+    let mut results = futures::stream::iter(chunks)
+        .map(|chunk| {
+            let rebuild_chunk = rebuild_chunk.clone();
+            tokio::task::spawn_blocking(move || rebuild_chunk(chunk))
+        })
+        .buffer_unordered(parallelism.max(1));
+    let mut done = 0;
+    while let Some(result) = results.next().await {
+        result??;
+        done += 1;
+        info!("Rebuilt {done} of {total} chunks");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authority::test_authority_builder::TestAuthorityBuilder;
+    use sui_types::{base_types::SuiAddress, object::Object};
+    use typed_store::rocks::MetricConf;
+
+    #[tokio::test]
+    async fn test_resume_interrupted_rebuild() -> Result<()> {
+        let owner = SuiAddress::random_for_testing_only();
+        let objects: Vec<_> = (0..100)
+            .map(|_| Object::with_id_owner_for_testing(ObjectID::random(), owner))
+            .collect();
+        let state = TestAuthorityBuilder::new()
+            .with_starting_objects(&objects)
+            .build()
+            .await;
+        let store = state.db();
+        let dir = tempfile::tempdir()?;
+        let checkpoint_store = CheckpointStore::new(&dir.path().join("checkpoints"));
+        let indexes = Arc::new(IndexStoreTables::open_tables_read_write(
+            dir.path().join("indexes"),
+            MetricConf::default(),
+            None,
+            None,
+        ));
+        let groups = [
+            IndexGroup::Owner,
+            IndexGroup::Coin,
+            IndexGroup::Transactions,
+        ];
+
+        // Interrupt a rebuild after the first half of the chunks of the live object set.
+        for group in groups {
+            indexes.reset_index_group(group)?;
+        }
+        let resolver = SyncModuleCache::new(ResolverWrapper::new(
+            store.clone(),
+            Arc::new(ResolverMetrics::new(&Registry::default())),
+        ));
+        for chunk in 0..OBJECT_CHUNKS / 2 {
+            rebuild_object_chunk(
+                &store,
+                &resolver,
+                &indexes,
+                &[IndexGroup::Owner, IndexGroup::Coin],
+                chunk,
+            )?;
+        }
+        assert!(indexes.is_rebuild_in_progress(IndexGroup::Owner)?);
+        assert!(indexes.is_rebuild_in_progress(IndexGroup::Coin)?);
+        assert!(verify_indexes(store.clone(), &indexes).is_err());
+
+        rebuild_indexes(
+            store.clone(),
+            checkpoint_store,
+            indexes.clone(),
+            &groups,
+            4,
+            false,
+        )
+        .await?;
+        for group in groups {
+            assert!(!indexes.is_rebuild_in_progress(group)?);
+        }
+        verify_indexes(store, &indexes)?;
+        Ok(())
+    }
+}
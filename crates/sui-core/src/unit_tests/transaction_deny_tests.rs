@@ -13,7 +13,9 @@ use move_core_types::ident_str;
 use std::path::PathBuf;
 use std::sync::Arc;
 use sui_config::certificate_deny_config::CertificateDenyConfigBuilder;
-use sui_config::transaction_deny_config::{TransactionDenyConfig, TransactionDenyConfigBuilder};
+use sui_config::transaction_deny_config::{
+    DenyList, TransactionDenyConfig, TransactionDenyConfigBuilder,
+};
 use sui_swarm_config::genesis_config::{AccountConfig, DEFAULT_GAS_AMOUNT};
 use sui_swarm_config::network_config::NetworkConfig;
 use sui_test_transaction_builder::TestTransactionBuilder;
@@ -241,6 +243,24 @@ async fn test_signer_denied() {
     assert_denied(&transfer_with_account(&accounts[2], &accounts[1], &state).await);
 }
 
+#[tokio::test]
+async fn test_deny_list_updated_at_runtime() {
+    let (network_config, state) = setup_test(TransactionDenyConfigBuilder::new().build()).await;
+    let accounts = get_accounts_and_coins(&network_config, &state);
+
+    // Deny the sender without restarting the authority.
+    let mut config = (*state.transaction_deny_config()).clone();
+    assert!(config.add_to_deny_list(DenyList::Address, accounts[0].0));
+    state.set_transaction_deny_config(config.clone());
+    assert_denied(&transfer_with_account(&accounts[0], &accounts[0], &state).await);
+
+    assert!(config.remove_from_deny_list(DenyList::Address, accounts[0].0));
+    state.set_transaction_deny_config(config);
+    assert!(transfer_with_account(&accounts[0], &accounts[0], &state)
+        .await
+        .is_ok());
+}
+
 #[tokio::test]
 async fn test_shared_object_transaction_disabled() {
     let (network_config, state) = setup_test(
@@ -4,7 +4,7 @@
 use std::{collections::BTreeMap, sync::Arc};
 
 use anyhow::{anyhow, bail, Result};
-use sui_storage::{indexes::CoinInfo, IndexStore};
+use sui_storage::indexes::{CoinInfo, IndexStoreTables};
 use sui_types::{base_types::ObjectInfo, object::Owner};
 use tracing::info;
 use typed_store::traits::Map;
@@ -13,7 +13,7 @@ use crate::authority::{authority_store_tables::LiveObject, AuthorityStore};
 
 /// This is a very expensive function that verifies some of the secondary indexes. This is done by
 /// iterating through the live object set and recalculating these secodary indexes.
-pub fn verify_indexes(database: Arc<AuthorityStore>, indexes: Arc<IndexStore>) -> Result<()> {
+pub fn verify_indexes(database: Arc<AuthorityStore>, indexes: &IndexStoreTables) -> Result<()> {
     info!("Begin running index verification checks");
 
     let mut owner_index = BTreeMap::new();
@@ -47,7 +47,7 @@ pub fn verify_indexes(database: Arc<AuthorityStore>, indexes: Arc<IndexStore>) -
     tracing::info!("Live objects set is prepared, about to verify indexes");
 
     // Verify Owner Index
-    for (key, info) in indexes.tables().owner_index().unbounded_iter() {
+    for (key, info) in indexes.owner_index().unbounded_iter() {
         let calculated_info = owner_index.remove(&key).ok_or_else(|| {
             anyhow!(
                 "owner_index: found extra, unexpected entry {:?}",
@@ -66,7 +66,7 @@ pub fn verify_indexes(database: Arc<AuthorityStore>, indexes: Arc<IndexStore>) -
     tracing::info!("Owner index is good");
 
     // Verify Coin Index
-    for (key, info) in indexes.tables().coin_index().unbounded_iter() {
+    for (key, info) in indexes.coin_index().unbounded_iter() {
         let calculated_info = coin_index.remove(&key).ok_or_else(|| {
             anyhow!(
                 "coin_index: found extra, unexpected entry {:?}",
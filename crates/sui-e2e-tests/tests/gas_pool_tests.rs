@@ -0,0 +1,141 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use futures::future::join_all;
+use sui_json_rpc_types::{SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponseOptions};
+use sui_keys::keystore::{AccountKeystore, InMemKeystore, Keystore};
+use sui_macros::sim_test;
+use sui_sdk::gas_pool::{GasPool, GasPoolConfig};
+use sui_types::crypto::{EncodeDecodeBase64, SuiKeyPair};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
+use sui_types::transaction::TransactionData;
+use test_cluster::TestClusterBuilder;
+
+const COIN_COUNT: usize = 4;
+const GAS_BUDGET: u64 = 50_000_000;
+
+#[sim_test]
+async fn test_gas_pool_concurrent_transactions() -> Result<(), anyhow::Error> {
+    let test_cluster = TestClusterBuilder::new().build().await;
+    let address = test_cluster.get_address_0();
+    let client = test_cluster.sui_client().clone();
+
+    let key = test_cluster.wallet.config.keystore.get_key(&address)?;
+    let mut keystore = Keystore::from(InMemKeystore::default());
+    keystore.add_key(SuiKeyPair::decode_base64(&key.encode_base64()).unwrap())?;
+
+    let state_dir = tempfile::tempdir()?;
+    let config = GasPoolConfig {
+        coin_count: COIN_COUNT,
+        rebalance_interval: Duration::from_secs(3600),
+        state_path: Some(state_dir.path().join("gas_pool.yaml")),
+        ..Default::default()
+    };
+    let pool = GasPool::new(client.clone(), keystore, address, config).await?;
+    assert_eq!(pool.status().available_coins, COIN_COUNT);
+
+    // Send more transactions at once than there are coins, so that some of them wait for a coin
+    // used by another one. None of them may equivocate.
+    let gas_price = client.read_api().get_reference_gas_price().await?;
+    let transactions = (0..COIN_COUNT * 3).map(|_| {
+        let pool = pool.clone();
+        let client = client.clone();
+        let wallet = &test_cluster.wallet;
+        async move {
+            let lease = pool.lease(GAS_BUDGET).await?;
+            let gas_coin = lease.object_ref();
+            let pt = {
+                let mut builder = ProgrammableTransactionBuilder::new();
+                builder.pay_sui(vec![address], vec![1])?;
+                builder.finish()
+            };
+            let data = TransactionData::new_programmable(
+                address,
+                vec![gas_coin],
+                pt,
+                GAS_BUDGET,
+                gas_price,
+            );
+            let response = client
+                .quorum_driver_api()
+                .execute_transaction_block(
+                    wallet.sign_transaction(&data),
+                    SuiTransactionBlockResponseOptions::new().with_effects(),
+                    Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+                )
+                .await?;
+            let effects = response.effects.expect("effects were requested");
+            assert!(effects.status().is_ok(), "{:?}", effects.status());
+            pool.reclaim(lease, &effects).await;
+            Ok::<_, anyhow::Error>(gas_coin)
+        }
+    });
+    let gas_coins = join_all(transactions)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Each transaction paid with its own version of a pool coin.
+    assert_eq!(
+        gas_coins.iter().collect::<HashSet<_>>().len(),
+        gas_coins.len()
+    );
+    assert!(
+        gas_coins
+            .iter()
+            .map(|(id, _, _)| id)
+            .collect::<HashSet<_>>()
+            .len()
+            <= COIN_COUNT
+    );
+
+    let status = pool.status();
+    assert_eq!(status.available_coins, COIN_COUNT);
+    assert_eq!(status.leased_coins, 0);
+    Ok(())
+}
+
+#[sim_test]
+async fn test_gas_pool_recovers_leases_after_crash() -> Result<(), anyhow::Error> {
+    let test_cluster = TestClusterBuilder::new().build().await;
+    let address = test_cluster.get_address_0();
+    let client = test_cluster.sui_client().clone();
+    let key = test_cluster.wallet.config.keystore.get_key(&address)?;
+    let keystore = || -> Result<Keystore, anyhow::Error> {
+        let mut keystore = Keystore::from(InMemKeystore::default());
+        keystore.add_key(SuiKeyPair::decode_base64(&key.encode_base64()).unwrap())?;
+        Ok(keystore)
+    };
+
+    let state_dir = tempfile::tempdir()?;
+    let config = GasPoolConfig {
+        coin_count: COIN_COUNT,
+        rebalance_interval: Duration::from_secs(3600),
+        state_path: Some(state_dir.path().join("gas_pool.yaml")),
+        ..Default::default()
+    };
+    let pool = GasPool::new(client.clone(), keystore()?, address, config.clone()).await?;
+
+    // A lease which is neither reclaimed nor dropped is what a crash leaves behind.
+    let lease = pool.lease(GAS_BUDGET).await?;
+    let leased_coin = lease.object_ref();
+    std::mem::forget(lease);
+
+    // The pool reopened from the state file doesn't lease the coin again in this epoch.
+    let pool = GasPool::new(client.clone(), keystore()?, address, config).await?;
+    let status = pool.status();
+    assert_eq!(status.quarantined_coins, 1);
+    assert_eq!(status.available_coins, COIN_COUNT - 1);
+    let mut leases = vec![];
+    for _ in 0..COIN_COUNT - 1 {
+        let lease = pool.lease(GAS_BUDGET).await?;
+        assert_ne!(lease.object_ref().0, leased_coin.0);
+        leases.push(lease);
+    }
+    assert_eq!(pool.status().available_coins, 0);
+    Ok(())
+}
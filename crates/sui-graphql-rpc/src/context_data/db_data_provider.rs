@@ -47,7 +47,7 @@ use diesel::{
     query_builder::{AstPass, BoxedSelectStatement, FromClause, QueryFragment, QueryId},
     sql_types::Text,
     BoolExpressionMethods, ExpressionMethods, OptionalExtension, PgConnection, QueryDsl,
-    QueryResult, RunQueryDsl,
+    QueryResult, RunQueryDsl, TextExpressionMethods,
 };
 use move_core_types::language_storage::StructTag;
 use std::str::FromStr;
@@ -95,7 +95,7 @@ use sui_types::{
     dynamic_field::{DynamicFieldType, Field},
     event::EventID,
     governance::StakedSui,
-    Identifier,
+    parse_sui_struct_tag, Identifier,
 };
 
 use super::DEFAULT_PAGE_SIZE;
@@ -108,8 +108,6 @@ pub enum DbValidationError {
     InvalidCheckpointCombination,
     #[error("Before checkpoint must be greater than after checkpoint")]
     InvalidCheckpointOrder,
-    #[error("Filtering objects by package::module::type is not currently supported")]
-    UnsupportedPMT,
     #[error("Filtering objects by object keys is not currently supported")]
     UnsupportedObjectKeys,
     #[error("Requires package and module")]
@@ -128,6 +126,15 @@ pub enum DbValidationError {
     QueryCostExceeded(u64, u64),
 }
 
+/// Escape the wildcards of a `LIKE` pattern, so that `value` is matched literally.
+fn escape_like(value: &impl ToString) -> String {
+    value
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 type BalanceQuery<'a> = BoxedSelectStatement<
     'a,
     (
@@ -480,8 +487,43 @@ impl QueryBuilder {
                 }
             }
 
-            if let Some(object_type) = filter.ty {
-                query = query.filter(objects::dsl::object_type.eq(object_type));
+            match (filter.package, filter.module, filter.ty) {
+                (Some(package), None, None) => {
+                    query = query.filter(
+                        objects::dsl::object_type.like(format!("{}::%", escape_like(&package))),
+                    );
+                }
+                (Some(package), Some(module), None) => {
+                    query = query.filter(objects::dsl::object_type.like(format!(
+                        "{}::{}::%",
+                        escape_like(&package),
+                        escape_like(&module)
+                    )));
+                }
+                (package, module, Some(ty)) => {
+                    // `ty` is the name of a struct in the given module, or a fully qualified type
+                    // if no module is given.
+                    let ty = match (package, module) {
+                        (Some(package), Some(module)) => format!("{package}::{module}::{ty}"),
+                        _ => ty,
+                    };
+                    let tag = parse_sui_struct_tag(&ty).map_err(|_| Error::InvalidFilter)?;
+                    let canonical = tag.to_canonical_string(/* with_prefix */ true);
+                    // A type without type parameters matches all of its instantiations.
+                    if tag.type_params.is_empty() {
+                        query = query.filter(
+                            objects::dsl::object_type
+                                .eq(canonical.clone())
+                                .or(objects::dsl::object_type
+                                    .like(format!("{}<%", escape_like(&canonical)))),
+                        );
+                    } else {
+                        query = query.filter(objects::dsl::object_type.eq(canonical));
+                    }
+                }
+                // Rejected by `validate_obj_filter`.
+                (None, Some(_), None) => Err(DbValidationError::RequiresPackage)?,
+                (None, None, None) => {}
             }
         }
 
@@ -1002,8 +1044,11 @@ impl PgManager {
     }
 
     pub(crate) fn validate_obj_filter(&self, filter: &ObjectFilter) -> Result<(), Error> {
-        if filter.package.is_some() || filter.module.is_some() || filter.ty.is_some() {
-            return Err(DbValidationError::UnsupportedPMT.into());
+        if filter.module.is_some() && filter.package.is_none() {
+            return Err(DbValidationError::RequiresPackage.into());
+        }
+        if filter.ty.is_some() && filter.package.is_some() && filter.module.is_none() {
+            return Err(DbValidationError::RequiresPackageAndModule.into());
         }
         if filter.object_keys.is_some() {
             return Err(DbValidationError::UnsupportedObjectKeys.into());
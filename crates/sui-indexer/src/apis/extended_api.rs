@@ -10,10 +10,12 @@ use sui_json_rpc::api::{
 };
 use sui_json_rpc::SuiRpcModule;
 use sui_json_rpc_types::{
-    AddressMetrics, CheckpointedObjectID, EpochInfo, EpochPage, MoveCallMetrics, NetworkMetrics,
-    Page, QueryObjectsPage, SuiObjectDataFilter, SuiObjectResponse, SuiObjectResponseQuery,
+    AddressMetrics, Balance, BalanceHistoryPage, CheckpointedObjectID, EpochInfo, EpochPage,
+    MoveCallMetrics, NetworkMetrics, Page, QueryObjectsPage, SuiObjectDataFilter,
+    SuiObjectResponse, SuiObjectResponseQuery,
 };
 use sui_open_rpc::Module;
+use sui_types::base_types::SuiAddress;
 use sui_types::sui_serde::BigInt;
 
 use crate::errors::IndexerError;
@@ -73,6 +75,7 @@ impl<S: IndexerStore> ExtendedApi<S> {
                 obj.object().map(|o| CheckpointedObjectID {
                     object_id: o.object_id,
                     at_checkpoint: Some(at_checkpoint),
+                    object_type: None,
                 })
             })
             .transpose()?;
@@ -122,6 +125,32 @@ impl<S: IndexerStore + Sync + Send + 'static> ExtendedApiServer for ExtendedApi<
         Ok(self.query_objects_internal(query, cursor, limit).await?)
     }
 
+    async fn get_balance_history(
+        &self,
+        _owner: SuiAddress,
+        _coin_type: Option<String>,
+        _cursor: Option<BigInt<u64>>,
+        _limit: Option<usize>,
+        _descending_order: Option<bool>,
+    ) -> RpcResult<BalanceHistoryPage> {
+        Err(jsonrpsee::types::error::CallError::Custom(
+            jsonrpsee::types::error::ErrorCode::MethodNotFound.into(),
+        )
+        .into())
+    }
+
+    async fn get_balance_at_checkpoint(
+        &self,
+        _owner: SuiAddress,
+        _coin_type: Option<String>,
+        _checkpoint: BigInt<u64>,
+    ) -> RpcResult<Balance> {
+        Err(jsonrpsee::types::error::CallError::Custom(
+            jsonrpsee::types::error::ErrorCode::MethodNotFound.into(),
+        )
+        .into())
+    }
+
     async fn get_network_metrics(&self) -> RpcResult<NetworkMetrics> {
         Ok(self.state.get_network_metrics().await?)
     }
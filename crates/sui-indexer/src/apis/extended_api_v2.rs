@@ -8,10 +8,11 @@ use sui_json_rpc::{
     SuiRpcModule,
 };
 use sui_json_rpc_types::{
-    AddressMetrics, CheckpointedObjectID, EpochInfo, EpochPage, MoveCallMetrics, NetworkMetrics,
-    Page, QueryObjectsPage, SuiObjectResponseQuery,
+    AddressMetrics, Balance, BalanceHistoryPage, CheckpointedObjectID, EpochInfo, EpochPage,
+    MoveCallMetrics, NetworkMetrics, Page, QueryObjectsPage, SuiObjectResponseQuery,
 };
 use sui_open_rpc::Module;
+use sui_types::base_types::SuiAddress;
 use sui_types::sui_serde::BigInt;
 
 pub(crate) struct ExtendedApiV2 {
@@ -74,6 +75,32 @@ impl ExtendedApiServer for ExtendedApiV2 {
         .into())
     }
 
+    async fn get_balance_history(
+        &self,
+        _owner: SuiAddress,
+        _coin_type: Option<String>,
+        _cursor: Option<BigInt<u64>>,
+        _limit: Option<usize>,
+        _descending_order: Option<bool>,
+    ) -> RpcResult<BalanceHistoryPage> {
+        Err(jsonrpsee::types::error::CallError::Custom(
+            jsonrpsee::types::error::ErrorCode::MethodNotFound.into(),
+        )
+        .into())
+    }
+
+    async fn get_balance_at_checkpoint(
+        &self,
+        _owner: SuiAddress,
+        _coin_type: Option<String>,
+        _checkpoint: BigInt<u64>,
+    ) -> RpcResult<Balance> {
+        Err(jsonrpsee::types::error::CallError::Custom(
+            jsonrpsee::types::error::ErrorCode::MethodNotFound.into(),
+        )
+        .into())
+    }
+
     async fn get_network_metrics(&self) -> RpcResult<NetworkMetrics> {
         let network_metrics = self
             .inner
@@ -11,6 +11,7 @@ use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::rpc_params;
 use jsonrpsee::RpcModule;
 use prometheus::Registry;
+use serde_json::{json, Value};
 use std::env;
 use sui_config::local_ip_utils;
 use sui_json_rpc::{JsonRpcServerBuilder, SuiRpcModule, CLIENT_TARGET_API_VERSION_HEADER};
@@ -130,72 +131,85 @@ async fn test_disable_routing() {
     assert!(response.is_err());
 }
 
-// TODO(chris): clean up this after March 27th, 2023
-// #[tokio::test]
-// async fn test_rpc_backward_compatibility_batched_request() {
-//     let mut builder = JsonRpcServerBuilder::new("1.5", &Registry::new());
-//     builder.register_module(TestApiModule).unwrap();
-
-//     let port = get_available_port("0.0.0.0");
-//     let handle = builder
-//         .start(SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, port)))
-//         .await
-//         .unwrap();
-//     let url = format!("http://0.0.0.0:{}", port);
-
-//     // Test with un-versioned client
-//     let client = HttpClientBuilder::default().build(&url).unwrap();
-
-//     let mut builder = BatchRequestBuilder::default();
-//     builder.insert("test_foo", rpc_params!(true)).unwrap();
-//     builder.insert("test_foo", rpc_params!(true)).unwrap();
-//     builder.insert("test_foo", rpc_params!(true)).unwrap();
-
-//     let response = client.batch_request::<String>(builder).await.unwrap();
-//     assert_eq!(3, response.num_successful_calls());
-
-//     // try to access old method directly should fail
-//     let mut builder = BatchRequestBuilder::default();
-//     builder.insert("test_foo_1_5", rpc_params!(true)).unwrap();
-//     builder.insert("test_foo", rpc_params!(true)).unwrap();
-//     builder.insert("test_foo", rpc_params!(true)).unwrap();
-
-//     let response = client.batch_request::<String>(builder).await.unwrap();
-//     assert_eq!(2, response.num_successful_calls());
-
-//     // One malformed request shouldn't fail the whole batch
-//     let client = Client::new();
-//     let response = client
-//         .post(format!("http://127.0.0.1:{}/", port))
-//         .json(&vec![
-//             json!(&Request {
-//                 jsonrpc: Default::default(),
-//                 id: Id::Number(1),
-//                 method: "test_foo".into(),
-//                 params: Some(&JsonRawValue::from_string("[true]".into()).unwrap()),
-//             }),
-//             json!("Bad json input"),
-//         ])
-//         .send()
-//         .await
-//         .unwrap();
-
-//     let responses = response.text().await.unwrap();
-//     let responses: Vec<&JsonRawValue> = serde_json::from_str(&responses).unwrap();
-
-//     // Should have 2 results
-//     assert_eq!(2, responses.len());
-
-//     // First response should success
-//     let response = serde_json::from_str::<Response<String>>(responses[0].get());
-//     assert!(matches!(response, Ok(result) if result.result == "Some string"));
-
-//     // Second response should fail
-//     let response = serde_json::from_str::<ErrorResponse>(responses[1].get());
-//     assert!(matches!(response, Ok(result) if result.error_object().message() == "Invalid request"));
-
-//     handle.stop().unwrap()
-// }
+#[tokio::test]
+async fn test_rpc_backward_compatibility_batched_request() {
+    let mut builder = JsonRpcServerBuilder::new("1.5", &Registry::new());
+    builder.register_module(TestApiModule).unwrap();
+    builder.set_max_batch_size(3);
+
+    let address = local_ip_utils::new_local_tcp_socket_for_testing();
+    let _handle = builder.start(address, None, None).await.unwrap();
+    let url = format!("http://0.0.0.0:{}", address.port());
+    let client = reqwest::Client::new();
+    let call = |id: u64, method: &str, params: Value| json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+
+    let responses: Vec<Value> = client
+        .post(&url)
+        .json(&json!([
+            call(1, "test_foo", json!([true])),
+            call(2, "test_foo", json!([true])),
+            call(3, "test_foo", json!([true])),
+        ]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(3, responses.len());
+    for (id, response) in (1..).zip(&responses) {
+        assert_eq!(json!(id), response["id"]);
+        assert_eq!(json!("Some string"), response["result"]);
+    }
+
+    // try to access old method directly should fail, without failing the whole batch
+    let responses: Vec<Value> = client
+        .post(&url)
+        .json(&json!([
+            call(1, "test_foo_1_5", json!(["string"])),
+            call(2, "test_foo", json!([true])),
+        ]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(2, responses.len());
+    assert!(responses[0].get("error").is_some());
+    assert_eq!(json!("Some string"), responses[1]["result"]);
+
+    // One malformed request shouldn't fail the whole batch
+    let responses: Vec<Value> = client
+        .post(&url)
+        .json(&json!([
+            call(1, "test_foo", json!([true])),
+            "Bad json input"
+        ]))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(2, responses.len());
+    assert_eq!(json!("Some string"), responses[0]["result"]);
+    assert_eq!(json!("Invalid request"), responses[1]["error"]["message"]);
+
+    // Batches over the maximum size are rejected as a whole
+    let response: Value = client
+        .post(&url)
+        .json(&json!((1..=4)
+            .map(|id| call(id, "test_foo", json!([true])))
+            .collect::<Vec<_>>()))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json!(-32600), response["error"]["code"]);
+}
 
 #[open_rpc(namespace = "test")]
 #[rpc(server, client, namespace = "test")]
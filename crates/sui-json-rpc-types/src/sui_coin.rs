@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use serde_with::DisplayFromStr;
 
 use crate::Page;
 use sui_types::base_types::{
@@ -18,6 +19,7 @@ use sui_types::sui_serde::BigInt;
 use sui_types::sui_serde::SequenceNumber as AsSequenceNumber;
 
 pub type CoinPage = Page<Coin, ObjectID>;
+pub type BalanceHistoryPage = Page<BalanceHistoryEntry, BigInt<u64>>;
 
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq, Eq, Clone)]
@@ -45,6 +47,28 @@ impl Balance {
     }
 }
 
+/// A change to the balance of an address in one coin type, made by a transaction.
+#[serde_as]
+#[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BalanceHistoryEntry {
+    /// Position of the transaction in the history, used as the paging cursor.
+    #[schemars(with = "BigInt<u64>")]
+    #[serde_as(as = "BigInt<u64>")]
+    pub sequence_number: u64,
+    pub transaction_digest: TransactionDigest,
+    /// The amount received in the transaction, negative if more was spent than received.
+    #[schemars(with = "String")]
+    #[serde_as(as = "DisplayFromStr")]
+    pub amount: i128,
+    /// The total balance after the transaction.
+    #[schemars(with = "BigInt<u128>")]
+    #[serde_as(as = "BigInt<u128>")]
+    pub total_balance: u128,
+    /// The number of coins after the transaction.
+    pub coin_object_count: usize,
+}
+
 #[serde_as]
 #[derive(Serialize, Deserialize, Debug, JsonSchema, PartialEq, Eq, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -1096,7 +1096,7 @@ pub type QueryObjectsPage = Page<SuiObjectResponse, CheckpointedObjectID>;
 pub type ObjectsPage = Page<SuiObjectResponse, ObjectID>;
 
 #[serde_as]
-#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Clone, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct CheckpointedObjectID {
     pub object_id: ObjectID,
@@ -1104,6 +1104,12 @@ pub struct CheckpointedObjectID {
     #[serde_as(as = "Option<BigInt<u64>>")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub at_checkpoint: Option<CheckpointSequenceNumber>,
+    /// Type of the object, set by fullnodes, which list objects by type. Paginating from it does
+    /// not depend on the object still existing.
+    #[schemars(with = "Option<String>")]
+    #[serde_as(as = "Option<SuiStructTag>")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_type: Option<StructTag>,
 }
 
 #[serde_as]
@@ -5,10 +5,11 @@ use jsonrpsee::core::RpcResult;
 use jsonrpsee::proc_macros::rpc;
 
 use sui_json_rpc_types::{
-    AddressMetrics, CheckpointedObjectID, EpochInfo, EpochPage, MoveCallMetrics, NetworkMetrics,
-    QueryObjectsPage, SuiObjectResponseQuery,
+    AddressMetrics, Balance, BalanceHistoryPage, CheckpointedObjectID, EpochInfo, EpochPage,
+    MoveCallMetrics, NetworkMetrics, QueryObjectsPage, SuiObjectResponseQuery,
 };
 use sui_open_rpc_macros::open_rpc;
+use sui_types::base_types::SuiAddress;
 use sui_types::sui_serde::BigInt;
 
 #[open_rpc(namespace = "suix", tag = "Extended API")]
@@ -42,6 +43,34 @@ pub trait ExtendedApi {
         limit: Option<usize>,
     ) -> RpcResult<QueryObjectsPage>;
 
+    /// Return the changes to the balance of an address in one coin type. Note that this is an enhanced full node only api.
+    #[method(name = "getBalanceHistory")]
+    async fn get_balance_history(
+        &self,
+        /// the owner's Sui address
+        owner: SuiAddress,
+        /// optional type name for the coin (e.g., 0x168da5bf1f48dafc111b0a488fa454aca95e0b5e::usdc::USDC), default to 0x2::sui::SUI if not specified.
+        coin_type: Option<String>,
+        /// An optional paging cursor. If provided, the query will start from the next item after the specified cursor. Default to start from the first item if not specified.
+        cursor: Option<BigInt<u64>>,
+        /// Max number of items returned per page, default to [QUERY_MAX_RESULT_LIMIT] if not specified.
+        limit: Option<usize>,
+        /// query result ordering, default to false (ascending order), oldest record first.
+        descending_order: Option<bool>,
+    ) -> RpcResult<BalanceHistoryPage>;
+
+    /// Return the total coin balance for one coin type, owned by the address owner as of the end of a checkpoint. Note that this is an enhanced full node only api.
+    #[method(name = "getBalanceAtCheckpoint")]
+    async fn get_balance_at_checkpoint(
+        &self,
+        /// the owner's Sui address
+        owner: SuiAddress,
+        /// optional type name for the coin (e.g., 0x168da5bf1f48dafc111b0a488fa454aca95e0b5e::usdc::USDC), default to 0x2::sui::SUI if not specified.
+        coin_type: Option<String>,
+        /// the checkpoint sequence number
+        checkpoint: BigInt<u64>,
+    ) -> RpcResult<Balance>;
+
     /// Return Network metrics
     #[method(name = "getNetworkMetrics")]
     async fn get_network_metrics(&self) -> RpcResult<NetworkMetrics>;
@@ -4,7 +4,7 @@
 use anyhow::anyhow;
 use arc_swap::Guard;
 use async_trait::async_trait;
-use move_core_types::language_storage::TypeTag;
+use move_core_types::language_storage::{StructTag, TypeTag};
 use mysten_metrics::spawn_monitored_task;
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
@@ -15,13 +15,13 @@ use sui_json_rpc_types::{
     Coin as SuiCoin, DevInspectResults, DryRunTransactionBlockResponse, EventFilter, SuiEvent,
     SuiObjectDataFilter, TransactionFilter,
 };
-use sui_storage::indexes::TotalBalance;
+use sui_storage::indexes::{BalanceChangeInfo, TotalBalance};
 use sui_storage::key_value_store::{
     KVStoreCheckpointData, KVStoreTransactionData, TransactionKeyValueStore,
     TransactionKeyValueStoreTrait,
 };
 use sui_types::base_types::{
-    MoveObjectType, ObjectID, ObjectInfo, ObjectRef, SequenceNumber, SuiAddress,
+    MoveObjectType, ObjectID, ObjectInfo, ObjectRef, SequenceNumber, SuiAddress, TxSequenceNumber,
 };
 use sui_types::committee::{Committee, EpochId};
 use sui_types::digests::{ChainIdentifier, TransactionDigest, TransactionEventsDigest};
@@ -137,6 +137,30 @@ pub trait StateRead: Send + Sync {
         filter: Option<SuiObjectDataFilter>,
     ) -> StateReadResult<Vec<ObjectInfo>>;
 
+    // extended_api
+    fn get_objects_by_type(
+        &self,
+        filter: &SuiObjectDataFilter,
+        cursor: Option<(Option<StructTag>, ObjectID)>,
+        limit: usize,
+    ) -> StateReadResult<(Vec<ObjectInfo>, Option<(StructTag, ObjectID)>)>;
+
+    fn get_balance_history(
+        &self,
+        owner: SuiAddress,
+        coin_type: &TypeTag,
+        cursor: Option<TxSequenceNumber>,
+        limit: usize,
+        descending: bool,
+    ) -> StateReadResult<Vec<(TxSequenceNumber, BalanceChangeInfo)>>;
+
+    fn get_balance_at_checkpoint(
+        &self,
+        owner: SuiAddress,
+        coin_type: &TypeTag,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> StateReadResult<Option<TotalBalance>>;
+
     async fn get_transactions(
         &self,
         kv_store: &Arc<TransactionKeyValueStore>,
@@ -367,6 +391,35 @@ impl StateRead for AuthorityState {
         Ok(self.get_owner_objects(owner, cursor, limit, filter)?)
     }
 
+    fn get_objects_by_type(
+        &self,
+        filter: &SuiObjectDataFilter,
+        cursor: Option<(Option<StructTag>, ObjectID)>,
+        limit: usize,
+    ) -> StateReadResult<(Vec<ObjectInfo>, Option<(StructTag, ObjectID)>)> {
+        Ok(self.get_objects_by_type(filter, cursor, limit)?)
+    }
+
+    fn get_balance_history(
+        &self,
+        owner: SuiAddress,
+        coin_type: &TypeTag,
+        cursor: Option<TxSequenceNumber>,
+        limit: usize,
+        descending: bool,
+    ) -> StateReadResult<Vec<(TxSequenceNumber, BalanceChangeInfo)>> {
+        Ok(self.get_balance_history(owner, coin_type, cursor, limit, descending)?)
+    }
+
+    fn get_balance_at_checkpoint(
+        &self,
+        owner: SuiAddress,
+        coin_type: &TypeTag,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> StateReadResult<Option<TotalBalance>> {
+        Ok(self.get_balance_at_checkpoint(owner, coin_type, checkpoint)?)
+    }
+
     async fn get_transactions(
         &self,
         kv_store: &Arc<TransactionKeyValueStore>,
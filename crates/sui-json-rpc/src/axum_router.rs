@@ -1,8 +1,10 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 
+use axum::extract::ConnectInfo;
 use axum::extract::Json;
 use axum::extract::State;
 use futures::StreamExt;
@@ -13,16 +15,22 @@ use jsonrpsee::core::server::helpers::MethodSink;
 use jsonrpsee::core::server::rpc_module::MethodKind;
 use jsonrpsee::server::logger::{self, TransportProtocol};
 use jsonrpsee::server::RandomIntegerIdProvider;
-use jsonrpsee::types::error::{ErrorCode, BATCHES_NOT_SUPPORTED_CODE, BATCHES_NOT_SUPPORTED_MSG};
+use jsonrpsee::types::error::ErrorCode;
 use jsonrpsee::types::{ErrorObject, Id, InvalidRequest, Params, Request};
 use jsonrpsee::{core::server::rpc_module::Methods, server::logger::Logger};
 use serde_json::value::RawValue;
 
+use crate::error::LIMIT_EXCEEDED_ERROR_CODE;
+use crate::metrics::BatchLogger;
+use crate::rate_limit::{ClientId, RateLimiter};
 use crate::routing_layer::RpcRouter;
 use crate::CLIENT_TARGET_API_VERSION_HEADER;
 
 pub const MAX_RESPONSE_SIZE: u32 = 2 << 30;
 
+/// Default maximum number of calls in a JSON-RPC batch request.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 50;
+
 #[derive(Clone, Debug)]
 pub struct JsonRpcService<L> {
     logger: L,
@@ -32,25 +40,48 @@ pub struct JsonRpcService<L> {
     /// Registered server methods.
     methods: Methods,
     rpc_router: RpcRouter,
+    /// Batch requests with more calls are rejected.
+    max_batch_size: usize,
+    rate_limiter: Option<Arc<RateLimiter>>,
 }
 
 impl<L> JsonRpcService<L> {
-    pub fn new(methods: Methods, rpc_router: RpcRouter, logger: L) -> Self {
+    pub fn new(
+        methods: Methods,
+        rpc_router: RpcRouter,
+        logger: L,
+        max_batch_size: usize,
+        rate_limiter: Option<Arc<RateLimiter>>,
+    ) -> Self {
         Self {
             methods,
             rpc_router,
             logger,
             id_provider: Arc::new(RandomIntegerIdProvider),
+            max_batch_size,
+            rate_limiter,
         }
     }
+
+    /// The client of a request, if it is subject to rate limits.
+    fn client_id(
+        &self,
+        headers: &HeaderMap,
+        connect_info: Option<ConnectInfo<SocketAddr>>,
+    ) -> Option<ClientId> {
+        self.rate_limiter
+            .as_ref()?
+            .client_id(headers, connect_info.map(|ConnectInfo(addr)| addr))
+    }
 }
 
-impl<L: Logger> JsonRpcService<L> {
-    fn call_data(&self) -> CallData<'_, L> {
+impl<L: BatchLogger> JsonRpcService<L> {
+    fn call_data<'a>(&'a self, client: Option<&'a ClientId>) -> CallData<'a, L> {
         CallData {
             logger: &self.logger,
             methods: &self.methods,
             rpc_router: &self.rpc_router,
+            rate_limit: self.rate_limiter.as_deref().zip(client),
             max_response_body_size: MAX_RESPONSE_SIZE,
             request_start: self.logger.on_request(TransportProtocol::Http),
         }
@@ -58,12 +89,14 @@ impl<L: Logger> JsonRpcService<L> {
 
     fn ws_call_data<'c, 'a: 'c, 'b: 'c>(
         &'a self,
+        client: Option<&'b ClientId>,
         bounded_subscriptions: BoundedSubscriptions,
         sink: &'b MethodSink,
     ) -> ws::WsCallData<'c, L> {
         ws::WsCallData {
             logger: &self.logger,
             methods: &self.methods,
+            rate_limit: self.rate_limiter.as_deref().zip(client),
             max_response_body_size: MAX_RESPONSE_SIZE,
             request_start: self.logger.on_request(TransportProtocol::Http),
             bounded_subscriptions,
@@ -71,6 +104,62 @@ impl<L: Logger> JsonRpcService<L> {
             sink,
         }
     }
+
+    /// Returns the error response of a batch request that cannot be processed, if any.
+    fn check_batch_size(
+        &self,
+        size: usize,
+        transport: TransportProtocol,
+    ) -> Option<MethodResponse> {
+        if size == 0 {
+            Some(MethodResponse::error(
+                Id::Null,
+                ErrorObject::from(ErrorCode::InvalidRequest),
+            ))
+        } else if size > self.max_batch_size {
+            self.logger.on_batch_rejected(size, transport);
+            Some(MethodResponse::error(
+                Id::Null,
+                ErrorObject::owned(
+                    ErrorCode::InvalidRequest.code(),
+                    format!(
+                        "Batch request has {size} calls, exceeding the limit of {}",
+                        self.max_batch_size
+                    ),
+                    None::<()>,
+                ),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Combine the responses to the calls of a batch request into a single JSON array.
+    fn batch_response(
+        &self,
+        calls: usize,
+        responses: impl IntoIterator<Item = MethodResponse>,
+        started_at: L::Instant,
+        transport: TransportProtocol,
+    ) -> MethodResponse {
+        let mut failures = 0;
+        let results = responses
+            .into_iter()
+            .map(|response| {
+                if !response.success {
+                    failures += 1;
+                }
+                response.result
+            })
+            .collect::<Vec<_>>();
+        self.logger
+            .on_batch_result(calls, failures, started_at, transport);
+        MethodResponse {
+            result: format!("[{}]", results.join(",")),
+            success: failures == 0,
+            error_code: None,
+        }
+    }
 }
 
 /// Create a response body.
@@ -91,14 +180,21 @@ fn from_template<S: Into<hyper::Body>>(
         .expect("Unable to parse response body for type conversion")
 }
 
+const JSON: &str = "application/json; charset=utf-8";
+
 /// Create a valid JSON response.
 pub(crate) fn ok_response(body: String) -> hyper::Response<hyper::Body> {
-    const JSON: &str = "application/json; charset=utf-8";
     from_template(hyper::StatusCode::OK, body, JSON)
 }
 
-pub async fn json_rpc_handler<L: Logger>(
+/// Create a JSON response rejecting a client over its rate limit.
+fn too_many_requests_response(body: String) -> hyper::Response<hyper::Body> {
+    from_template(hyper::StatusCode::TOO_MANY_REQUESTS, body, JSON)
+}
+
+pub async fn json_rpc_handler<L: BatchLogger>(
     State(service): State<JsonRpcService<L>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     headers: HeaderMap,
     Json(raw_request): Json<Box<RawValue>>,
 ) -> impl axum::response::IntoResponse {
@@ -106,29 +202,59 @@ pub async fn json_rpc_handler<L: Logger>(
     let api_version = headers
         .get(CLIENT_TARGET_API_VERSION_HEADER)
         .and_then(|h| h.to_str().ok());
-    let response = process_raw_request(&service, api_version, raw_request.get()).await;
+    let client = service.client_id(&headers, connect_info);
+    let response =
+        process_raw_request(&service, api_version, client.as_ref(), raw_request.get()).await;
 
-    ok_response(response.result)
+    if response.error_code == Some(LIMIT_EXCEEDED_ERROR_CODE) {
+        too_many_requests_response(response.result)
+    } else {
+        ok_response(response.result)
+    }
 }
 
-async fn process_raw_request<L: Logger>(
+async fn process_raw_request<L: BatchLogger>(
     service: &JsonRpcService<L>,
     api_version: Option<&str>,
+    client: Option<&ClientId>,
     raw_request: &str,
 ) -> MethodResponse {
     if let Ok(request) = serde_json::from_str::<Request>(raw_request) {
-        process_request(request, api_version, service.call_data()).await
-    } else if let Ok(_batch) = serde_json::from_str::<Vec<&RawValue>>(raw_request) {
-        MethodResponse::error(
-            Id::Null,
-            ErrorObject::borrowed(BATCHES_NOT_SUPPORTED_CODE, &BATCHES_NOT_SUPPORTED_MSG, None),
-        )
+        process_request(request, api_version, service.call_data(client)).await
+    } else if let Ok(batch) = serde_json::from_str::<Vec<&RawValue>>(raw_request) {
+        process_batch(service, api_version, client, batch).await
     } else {
         let (id, code) = prepare_error(raw_request);
         MethodResponse::error(id, ErrorObject::from(code))
     }
 }
 
+/// Process the calls of a JSON-RPC 2.0 batch request concurrently, and answer with the array of
+/// their responses.
+async fn process_batch<L: BatchLogger>(
+    service: &JsonRpcService<L>,
+    api_version: Option<&str>,
+    client: Option<&ClientId>,
+    batch: Vec<&RawValue>,
+) -> MethodResponse {
+    let started_at = service.logger.on_request(TransportProtocol::Http);
+    if let Some(response) = service.check_batch_size(batch.len(), TransportProtocol::Http) {
+        return response;
+    }
+    let calls = batch.len();
+    let responses = futures::future::join_all(batch.into_iter().map(|raw_call| async move {
+        if let Ok(request) = serde_json::from_str::<Request>(raw_call.get()) {
+            process_request(request, api_version, service.call_data(client)).await
+        } else {
+            // Every call of a batch is valid JSON, but might not be a valid request
+            let (id, _) = prepare_error(raw_call.get());
+            MethodResponse::error(id, ErrorObject::from(ErrorCode::InvalidRequest))
+        }
+    }))
+    .await;
+    service.batch_response(calls, responses, started_at, TransportProtocol::Http)
+}
+
 async fn process_request<L: Logger>(
     req: Request<'_>,
     api_version: Option<&str>,
@@ -137,6 +263,7 @@ async fn process_request<L: Logger>(
     let CallData {
         methods,
         rpc_router,
+        rate_limit,
         logger,
         max_response_body_size,
         request_start,
@@ -147,6 +274,24 @@ async fn process_request<L: Logger>(
     let name = rpc_router.route(&req.method, api_version);
     let id = req.id;
 
+    if let Some(error) = throttle(rate_limit, methods, name) {
+        logger.on_call(
+            name,
+            params,
+            logger::MethodKind::MethodCall,
+            TransportProtocol::Http,
+        );
+        let response = MethodResponse::error(id, error);
+        logger.on_result(
+            name,
+            response.success,
+            response.error_code,
+            request_start,
+            TransportProtocol::Http,
+        );
+        return response;
+    }
+
     let response = match methods.method_with_name(name) {
         None => {
             logger.on_call(
@@ -203,6 +348,18 @@ async fn process_request<L: Logger>(
     response
 }
 
+/// Returns the error to answer a call with when it is to a registered method, and its client is
+/// over its rate limit.
+fn throttle(
+    rate_limit: Option<(&RateLimiter, &ClientId)>,
+    methods: &Methods,
+    name: &str,
+) -> Option<ErrorObject<'static>> {
+    let (limiter, client) = rate_limit?;
+    let (name, _) = methods.method_with_name(name)?;
+    limiter.check(client, name).err()
+}
+
 /// Figure out if this is a sufficiently complete request that we can extract an [`Id`] out of, or just plain
 /// unparseable garbage.
 pub fn prepare_error(data: &str) -> (Id<'_>, ErrorCode) {
@@ -217,6 +374,7 @@ pub(crate) struct CallData<'a, L: Logger> {
     logger: &'a L,
     methods: &'a Methods,
     rpc_router: &'a RpcRouter,
+    rate_limit: Option<(&'a RateLimiter, &'a ClientId)>,
     max_response_body_size: u32,
     request_start: L::Instant,
 }
@@ -246,6 +404,7 @@ pub mod ws {
         pub bounded_subscriptions: BoundedSubscriptions,
         pub id_provider: &'a dyn IdProvider,
         pub methods: &'a Methods,
+        pub rate_limit: Option<(&'a RateLimiter, &'a ClientId)>,
         pub max_response_body_size: u32,
         pub sink: &'a MethodSink,
         pub logger: &'a L,
@@ -255,14 +414,21 @@ pub mod ws {
     // A WebSocket handler that echos any message it receives.
     //
     // This one we'll be integration testing so it can be written in the regular way.
-    pub async fn ws_json_rpc_upgrade<L: Logger>(
+    pub async fn ws_json_rpc_upgrade<L: BatchLogger>(
         ws: WebSocketUpgrade,
         State(service): State<JsonRpcService<L>>,
+        connect_info: Option<ConnectInfo<SocketAddr>>,
+        headers: HeaderMap,
     ) -> Response {
-        ws.on_upgrade(|ws| ws_json_rpc_handler(ws, service))
+        let client = service.client_id(&headers, connect_info);
+        ws.on_upgrade(|ws| ws_json_rpc_handler(ws, service, client))
     }
 
-    async fn ws_json_rpc_handler<L: Logger>(mut socket: WebSocket, service: JsonRpcService<L>) {
+    async fn ws_json_rpc_handler<L: BatchLogger>(
+        mut socket: WebSocket,
+        service: JsonRpcService<L>,
+        client: Option<ClientId>,
+    ) {
         #[allow(clippy::disallowed_methods)]
         let (tx, mut rx) = mpsc::unbounded::<String>();
         let sink = MethodSink::new_with_limit(tx, MAX_RESPONSE_SIZE, MAX_RESPONSE_SIZE);
@@ -274,7 +440,7 @@ pub mod ws {
                     if let Some(Ok(message)) = maybe_message {
                         if let Message::Text(msg) = message {
                             let response =
-                                process_raw_request(&service, &msg, bounded_subscriptions.clone(), &sink).await;
+                                process_raw_request(&service, &msg, client.as_ref(), bounded_subscriptions.clone(), &sink).await;
                             if let Some(response) = response {
                                 let _ = sink.send_raw(response.result);
                             }
@@ -292,31 +458,75 @@ pub mod ws {
         }
     }
 
-    async fn process_raw_request<L: Logger>(
+    async fn process_raw_request<L: BatchLogger>(
         service: &JsonRpcService<L>,
         raw_request: &str,
+        client: Option<&ClientId>,
         bounded_subscriptions: BoundedSubscriptions,
         sink: &MethodSink,
     ) -> Option<MethodResponse> {
         if let Ok(request) = serde_json::from_str::<Request>(raw_request) {
-            process_request(request, service.ws_call_data(bounded_subscriptions, sink)).await
-        } else if let Ok(_batch) = serde_json::from_str::<Vec<&RawValue>>(raw_request) {
-            Some(MethodResponse::error(
-                Id::Null,
-                ErrorObject::borrowed(BATCHES_NOT_SUPPORTED_CODE, &BATCHES_NOT_SUPPORTED_MSG, None),
-            ))
+            process_request(
+                request,
+                service.ws_call_data(client, bounded_subscriptions, sink),
+            )
+            .await
+        } else if let Ok(batch) = serde_json::from_str::<Vec<&RawValue>>(raw_request) {
+            process_batch(service, batch, client, bounded_subscriptions, sink).await
         } else {
             let (id, code) = prepare_error(raw_request);
             Some(MethodResponse::error(id, ErrorObject::from(code)))
         }
     }
 
+    /// Process the calls of a batch request like over HTTP. Subscriptions are answered through
+    /// the sink, so they have no response in the array, which is omitted when empty.
+    async fn process_batch<L: BatchLogger>(
+        service: &JsonRpcService<L>,
+        batch: Vec<&RawValue>,
+        client: Option<&ClientId>,
+        bounded_subscriptions: BoundedSubscriptions,
+        sink: &MethodSink,
+    ) -> Option<MethodResponse> {
+        let started_at = service.logger.on_request(TransportProtocol::WebSocket);
+        if let Some(response) = service.check_batch_size(batch.len(), TransportProtocol::WebSocket)
+        {
+            return Some(response);
+        }
+        let calls = batch.len();
+        let responses = futures::future::join_all(batch.into_iter().map(|raw_call| {
+            let bounded_subscriptions = bounded_subscriptions.clone();
+            async move {
+                if let Ok(request) = serde_json::from_str::<Request>(raw_call.get()) {
+                    process_request(
+                        request,
+                        service.ws_call_data(client, bounded_subscriptions, sink),
+                    )
+                    .await
+                } else {
+                    let (id, _) = prepare_error(raw_call.get());
+                    Some(MethodResponse::error(
+                        id,
+                        ErrorObject::from(ErrorCode::InvalidRequest),
+                    ))
+                }
+            }
+        }))
+        .await;
+        let responses = responses.into_iter().flatten().collect::<Vec<_>>();
+        if responses.is_empty() {
+            return None;
+        }
+        Some(service.batch_response(calls, responses, started_at, TransportProtocol::WebSocket))
+    }
+
     async fn process_request<L: Logger>(
         req: Request<'_>,
         call: WsCallData<'_, L>,
     ) -> Option<MethodResponse> {
         let WsCallData {
             methods,
+            rate_limit,
             logger,
             max_response_body_size,
             request_start,
@@ -330,6 +540,24 @@ pub mod ws {
         let name = &req.method;
         let id = req.id;
 
+        if let Some(error) = throttle(rate_limit, methods, name) {
+            logger.on_call(
+                name,
+                params,
+                logger::MethodKind::MethodCall,
+                TransportProtocol::WebSocket,
+            );
+            let response = MethodResponse::error(id, error);
+            logger.on_result(
+                name,
+                response.success,
+                response.error_code,
+                request_start,
+                TransportProtocol::WebSocket,
+            );
+            return Some(response);
+        }
+
         let response = match methods.method_with_name(name) {
             None => {
                 logger.on_call(
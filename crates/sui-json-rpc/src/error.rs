@@ -17,6 +17,8 @@ use crate::authority_state::StateReadError;
 
 pub const TRANSIENT_ERROR_CODE: i32 = -32050;
 pub const TRANSACTION_EXECUTION_CLIENT_ERROR_CODE: i32 = -32002;
+/// Returned to clients over their rate limit, as "Limit exceeded" in EIP-1474.
+pub const LIMIT_EXCEEDED_ERROR_CODE: i32 = -32005;
 
 pub type RpcInterimResult<T = ()> = Result<T, Error>;
 
@@ -0,0 +1,243 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use jsonrpsee::{core::RpcResult, RpcModule};
+use sui_core::authority::AuthorityState;
+use sui_json_rpc_types::{
+    AddressMetrics, Balance, BalanceHistoryEntry, BalanceHistoryPage, CheckpointedObjectID,
+    EpochInfo, EpochPage, MoveCallMetrics, NetworkMetrics, Page, QueryObjectsPage,
+    SuiObjectResponse, SuiObjectResponseQuery,
+};
+use sui_open_rpc::Module;
+use sui_types::base_types::{ObjectType, SuiAddress};
+use sui_types::sui_serde::BigInt;
+use tracing::instrument;
+
+use crate::{
+    api::{
+        cap_page_limit, validate_limit, ExtendedApiOpenRpc, ExtendedApiServer, ReadApiServer,
+        QUERY_MAX_RESULT_LIMIT,
+    },
+    authority_state::StateRead,
+    coin_api::parse_to_type_tag,
+    error::{Error, SuiRpcInputError},
+    with_tracing, SuiRpcModule,
+};
+
+/// The subset of the extended API a fullnode can serve from its own indexes: `queryObjects`,
+/// backed by the objects_by_type index, whose results are not pinned to a checkpoint, and the
+/// balance history methods, backed by the balance_history index.
+pub struct ExtendedApi<R> {
+    state: Arc<dyn StateRead>,
+    read_api: R,
+}
+
+impl<R: ReadApiServer> ExtendedApi<R> {
+    pub fn new(state: Arc<AuthorityState>, read_api: R) -> Self {
+        Self { state, read_api }
+    }
+}
+
+fn method_not_found<T>() -> RpcResult<T> {
+    Err(jsonrpsee::types::error::CallError::Custom(
+        jsonrpsee::types::error::ErrorCode::MethodNotFound.into(),
+    )
+    .into())
+}
+
+#[async_trait]
+impl<R: ReadApiServer> ExtendedApiServer for ExtendedApi<R> {
+    async fn get_epochs(
+        &self,
+        _cursor: Option<BigInt<u64>>,
+        _limit: Option<usize>,
+        _descending_order: Option<bool>,
+    ) -> RpcResult<EpochPage> {
+        method_not_found()
+    }
+
+    async fn get_current_epoch(&self) -> RpcResult<EpochInfo> {
+        method_not_found()
+    }
+
+    #[instrument(skip(self))]
+    async fn query_objects(
+        &self,
+        query: SuiObjectResponseQuery,
+        cursor: Option<CheckpointedObjectID>,
+        limit: Option<usize>,
+    ) -> RpcResult<QueryObjectsPage> {
+        with_tracing!(async move {
+            let limit =
+                validate_limit(limit, *QUERY_MAX_RESULT_LIMIT).map_err(SuiRpcInputError::from)?;
+            let SuiObjectResponseQuery { filter, options } = query;
+            let filter = filter.ok_or_else(|| {
+                SuiRpcInputError::GenericInvalid(
+                    "queryObjects requires a package, module or struct type filter".to_string(),
+                )
+            })?;
+            let options = options.unwrap_or_default();
+            let cursor = cursor.map(|cursor| (cursor.object_type, cursor.object_id));
+            let (mut objects, last_scanned) = self
+                .state
+                .get_objects_by_type(&filter, cursor.clone(), limit + 1)
+                .map_err(Error::from)?;
+
+            // objects here are of size (limit + 1), where the last one is the cursor for the next
+            // page, unless the scan stopped before finding them, after the last object scanned.
+            let has_next_page = objects.len() > limit || last_scanned.is_some();
+            objects.truncate(limit);
+            let next_cursor = last_scanned
+                .map(|(type_, object_id)| (Some(type_), object_id))
+                .or_else(|| {
+                    objects.last().map(|o_info| {
+                        let type_ = match &o_info.type_ {
+                            ObjectType::Struct(type_) => Some(type_.clone().into()),
+                            ObjectType::Package => None,
+                        };
+                        (type_, o_info.object_id)
+                    })
+                })
+                .or(cursor)
+                .map(|(object_type, object_id)| CheckpointedObjectID {
+                    object_id,
+                    at_checkpoint: None,
+                    object_type,
+                });
+
+            let data = match options.is_not_in_object_info() {
+                true => {
+                    let object_ids = objects.iter().map(|obj| obj.object_id).collect();
+                    self.read_api
+                        .multi_get_objects(object_ids, Some(options))
+                        .await?
+                }
+                false => objects
+                    .into_iter()
+                    .map(|o_info| SuiObjectResponse::try_from((o_info, options.clone())))
+                    .collect::<Result<Vec<SuiObjectResponse>, _>>()?,
+            };
+
+            Ok(Page {
+                data,
+                next_cursor,
+                has_next_page,
+            })
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn get_balance_history(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+        cursor: Option<BigInt<u64>>,
+        limit: Option<usize>,
+        descending_order: Option<bool>,
+    ) -> RpcResult<BalanceHistoryPage> {
+        with_tracing!(async move {
+            let coin_type = parse_to_type_tag(coin_type)?;
+            let limit = cap_page_limit(limit);
+            let mut changes = self
+                .state
+                .get_balance_history(
+                    owner,
+                    &coin_type,
+                    cursor.map(|c| *c),
+                    limit + 1,
+                    descending_order.unwrap_or_default(),
+                )
+                .map_err(Error::from)?;
+
+            let has_next_page = changes.len() > limit;
+            changes.truncate(limit);
+            let next_cursor = changes
+                .last()
+                .map(|(sequence, _)| BigInt::from(*sequence))
+                .or(cursor);
+
+            let data = changes
+                .into_iter()
+                .map(|(sequence_number, change)| BalanceHistoryEntry {
+                    sequence_number,
+                    transaction_digest: change.digest,
+                    amount: change.amount(),
+                    total_balance: change.balance.balance as u128,
+                    coin_object_count: change.balance.num_coins as usize,
+                })
+                .collect();
+            Ok(Page {
+                data,
+                next_cursor,
+                has_next_page,
+            })
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn get_balance_at_checkpoint(
+        &self,
+        owner: SuiAddress,
+        coin_type: Option<String>,
+        checkpoint: BigInt<u64>,
+    ) -> RpcResult<Balance> {
+        with_tracing!(async move {
+            let coin_type = parse_to_type_tag(coin_type)?;
+            let balance = self
+                .state
+                .get_balance_at_checkpoint(owner, &coin_type, *checkpoint)?
+                .ok_or_else(|| {
+                    SuiRpcInputError::GenericInvalid(format!(
+                        "Balance history is not available at checkpoint {}",
+                        *checkpoint
+                    ))
+                })?;
+            Ok(Balance {
+                coin_type: coin_type.to_string(),
+                coin_object_count: balance.num_coins as usize,
+                total_balance: balance.balance as u128,
+                locked_balance: Default::default(),
+            })
+        })
+    }
+
+    async fn get_network_metrics(&self) -> RpcResult<NetworkMetrics> {
+        method_not_found()
+    }
+
+    async fn get_move_call_metrics(&self) -> RpcResult<MoveCallMetrics> {
+        method_not_found()
+    }
+
+    async fn get_latest_address_metrics(&self) -> RpcResult<AddressMetrics> {
+        method_not_found()
+    }
+
+    async fn get_checkpoint_address_metrics(&self, _checkpoint: u64) -> RpcResult<AddressMetrics> {
+        method_not_found()
+    }
+
+    async fn get_all_epoch_address_metrics(
+        &self,
+        _descending_order: Option<bool>,
+    ) -> RpcResult<Vec<AddressMetrics>> {
+        method_not_found()
+    }
+
+    async fn get_total_transactions(&self) -> RpcResult<BigInt<u64>> {
+        method_not_found()
+    }
+}
+
+impl<R: ReadApiServer> SuiRpcModule for ExtendedApi<R> {
+    fn rpc(self) -> RpcModule<Self> {
+        self.into_rpc()
+    }
+
+    fn rpc_doc_module() -> Module {
+        ExtendedApiOpenRpc::module_doc()
+    }
+}
@@ -4,6 +4,7 @@
 use std::env;
 use std::net::SocketAddr;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use hyper::header::HeaderName;
 use hyper::header::HeaderValue;
@@ -12,6 +13,7 @@ use hyper::Method;
 use hyper::Request;
 use jsonrpsee::RpcModule;
 use prometheus::Registry;
+use sui_config::node::JsonRpcRateLimitConfig;
 use tokio::runtime::Handle;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 use tower_http::trace::TraceLayer;
@@ -23,6 +25,7 @@ use sui_open_rpc::{Module, Project};
 
 use crate::error::Error;
 use crate::metrics::MetricsLogger;
+use crate::rate_limit::RateLimiter;
 use crate::routing_layer::RpcRouter;
 
 pub mod api;
@@ -31,6 +34,7 @@ pub mod axum_router;
 mod balance_changes;
 pub mod coin_api;
 pub mod error;
+pub mod extended_api;
 pub mod governance_api;
 pub mod indexer_api;
 pub mod logger;
@@ -38,6 +42,7 @@ mod metrics;
 pub mod move_utils;
 pub mod name_service;
 mod object_changes;
+pub mod rate_limit;
 pub mod read_api;
 mod routing_layer;
 pub mod transaction_builder_api;
@@ -57,6 +62,8 @@ pub struct JsonRpcServerBuilder {
     module: RpcModule<()>,
     rpc_doc: Project,
     registry: Registry,
+    max_batch_size: usize,
+    rate_limit_config: Option<JsonRpcRateLimitConfig>,
 }
 
 pub fn sui_rpc_doc(version: &str) -> Project {
@@ -83,9 +90,22 @@ impl JsonRpcServerBuilder {
             module: RpcModule::new(()),
             rpc_doc: sui_rpc_doc(version),
             registry: prometheus_registry.clone(),
+            max_batch_size: crate::axum_router::DEFAULT_MAX_BATCH_SIZE,
+            rate_limit_config: None,
         }
     }
 
+    /// Set the maximum number of calls accepted in a batch request.
+    pub fn set_max_batch_size(&mut self, max_batch_size: usize) {
+        self.max_batch_size = max_batch_size;
+    }
+
+    /// Limit the rate of calls of each client. Clients are identified by the address of their
+    /// connection, so the router must be served with `ConnectInfo<SocketAddr>`.
+    pub fn set_rate_limit_config(&mut self, rate_limit_config: JsonRpcRateLimitConfig) {
+        self.rate_limit_config = Some(rate_limit_config);
+    }
+
     pub fn register_module<T: SuiRpcModule>(&mut self, module: T) -> Result<(), Error> {
         self.rpc_doc.add_module(T::rpc_doc_module());
         Ok(self.module.merge(module.rpc())?)
@@ -168,13 +188,22 @@ impl JsonRpcServerBuilder {
         let methods_names = module.method_names().collect::<Vec<_>>();
 
         let metrics_logger = MetricsLogger::new(&self.registry, &methods_names);
+        let rate_limiter = self
+            .rate_limit_config
+            .clone()
+            .map(|config| Arc::new(RateLimiter::new(config, &self.registry)));
 
         let middleware = tower::ServiceBuilder::new()
             .layer(Self::trace_layer())
             .layer(Self::cors()?);
 
-        let service =
-            crate::axum_router::JsonRpcService::new(module.into(), rpc_router, metrics_logger);
+        let service = crate::axum_router::JsonRpcService::new(
+            module.into(),
+            rpc_router,
+            metrics_logger,
+            self.max_batch_size,
+            rate_limiter,
+        );
 
         let mut router = axum::Router::new();
 
@@ -219,7 +248,8 @@ impl JsonRpcServerBuilder {
     ) -> Result<ServerHandle, Error> {
         let app = self.to_router(server_type)?;
 
-        let server = axum::Server::bind(&listen_address).serve(app.into_make_service());
+        let server = axum::Server::bind(&listen_address)
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>());
 
         let addr = server.local_addr();
         let handle = tokio::spawn(async move { server.await.unwrap() });
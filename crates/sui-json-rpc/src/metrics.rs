@@ -14,11 +14,13 @@ use prometheus::{
     register_int_gauge_vec_with_registry, HistogramVec, IntCounterVec, IntGaugeVec,
 };
 use tokio::time::Instant;
+use tracing::debug;
 
 const SPAM_LABEL: &str = "SPAM";
 const LATENCY_SEC_BUCKETS: &[f64] = &[
     0.001, 0.005, 0.01, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 5., 10., 20., 30., 60., 90.,
 ];
+const BATCH_SIZE_BUCKETS: &[f64] = &[1., 2., 5., 10., 20., 50., 100., 200., 500., 1000.];
 
 #[derive(Debug, Clone)]
 pub struct Metrics {
@@ -41,6 +43,30 @@ pub struct Metrics {
     rpc_request_size: HistogramVec,
     /// Response size
     rpc_response_size: HistogramVec,
+    /// Number of calls in a batch request
+    rpc_batch_size: HistogramVec,
+    /// Latency of a whole batch request
+    rpc_batch_latency: HistogramVec,
+    /// Failed calls of batch requests
+    rpc_batch_failed_calls: IntCounterVec,
+    /// Batch requests rejected for having too many calls
+    rpc_rejected_batches: IntCounterVec,
+}
+
+/// Extension of [Logger] accounting for JSON-RPC batch requests as a whole, on top of the
+/// accounting of each of their calls.
+pub trait BatchLogger: Logger {
+    /// Called once every call of a batch request has been answered.
+    fn on_batch_result(
+        &self,
+        calls: usize,
+        failed_calls: usize,
+        started_at: Self::Instant,
+        transport: TransportProtocol,
+    );
+
+    /// Called when a batch request is rejected without processing any of its calls.
+    fn on_batch_rejected(&self, calls: usize, transport: TransportProtocol);
 }
 
 #[derive(Clone)]
@@ -144,6 +170,36 @@ impl MetricsLogger {
                 registry,
             )
             .unwrap(),
+            rpc_batch_size: register_histogram_vec_with_registry!(
+                "rpc_batch_size",
+                "Number of calls in batch rpc requests",
+                &["protocol"],
+                BATCH_SIZE_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            rpc_batch_latency: register_histogram_vec_with_registry!(
+                "rpc_batch_latency",
+                "Latency of batch rpc requests",
+                &["protocol"],
+                LATENCY_SEC_BUCKETS.to_vec(),
+                registry,
+            )
+            .unwrap(),
+            rpc_batch_failed_calls: register_int_counter_vec_with_registry!(
+                "rpc_batch_failed_calls",
+                "Number of failed calls in batch rpc requests",
+                &["protocol"],
+                registry,
+            )
+            .unwrap(),
+            rpc_rejected_batches: register_int_counter_vec_with_registry!(
+                "rpc_rejected_batches",
+                "Number of batch rpc requests rejected for exceeding the maximum batch size",
+                &["protocol"],
+                registry,
+            )
+            .unwrap(),
         };
 
         Self {
@@ -269,3 +325,42 @@ impl Logger for MetricsLogger {
             .dec();
     }
 }
+
+impl BatchLogger for MetricsLogger {
+    fn on_batch_result(
+        &self,
+        calls: usize,
+        failed_calls: usize,
+        started_at: Self::Instant,
+        transport: TransportProtocol,
+    ) {
+        let t = transport.to_string();
+        let latency = Instant::now() - started_at;
+        self.metrics
+            .rpc_batch_size
+            .with_label_values(&[&t])
+            .observe(calls as f64);
+        self.metrics
+            .rpc_batch_latency
+            .with_label_values(&[&t])
+            .observe(latency.as_secs_f64());
+        self.metrics
+            .rpc_batch_failed_calls
+            .with_label_values(&[&t])
+            .inc_by(failed_calls as u64);
+        debug!(
+            calls,
+            failed_calls,
+            ?latency,
+            transport = t,
+            "Processed batch request"
+        );
+    }
+
+    fn on_batch_rejected(&self, calls: usize, transport: TransportProtocol) {
+        self.metrics
+            .rpc_rejected_batches
+            .with_label_values(&[&transport.to_string()])
+            .inc();
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use hyper::HeaderMap;
+use jsonrpsee::types::ErrorObject;
+use linked_hash_map::LinkedHashMap;
+use prometheus::{
+    register_int_counter_vec_with_registry, register_int_gauge_with_registry, IntCounterVec,
+    IntGauge, Registry,
+};
+use sui_config::node::{JsonRpcRateLimitConfig, TokenBucketConfig};
+
+use crate::error::LIMIT_EXCEEDED_ERROR_CODE;
+
+/// Beyond this number of clients, the buckets of the least recently seen ones are dropped.
+const MAX_TRACKED_CLIENTS: usize = 100_000;
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+
+/// Identifies the client a rate limit applies to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ClientId {
+    Ip(IpAddr),
+    ApiKey(String),
+}
+
+impl ClientId {
+    fn kind(&self) -> &'static str {
+        match self {
+            ClientId::Ip(_) => "ip",
+            ClientId::ApiKey(_) => "api_key",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: &TokenBucketConfig, now: Instant) -> Self {
+        Self {
+            tokens: config.capacity as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, config: &TokenBucketConfig, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * config.refill_per_second as f64).min(config.capacity as f64);
+        self.last_refill = now;
+    }
+
+    /// Take `weight` tokens, or return how long to wait until there are enough of them.
+    fn try_acquire(
+        &mut self,
+        config: &TokenBucketConfig,
+        weight: u32,
+        now: Instant,
+    ) -> Result<(), Duration> {
+        self.refill(config, now);
+        // A call weighing more than the capacity can be made with a full bucket.
+        let weight = (weight as f64).min(config.capacity as f64);
+        if self.tokens >= weight {
+            self.tokens -= weight;
+            Ok(())
+        } else if config.refill_per_second == 0 {
+            Err(Duration::MAX)
+        } else {
+            Err(Duration::from_secs_f64(
+                (weight - self.tokens) / config.refill_per_second as f64,
+            ))
+        }
+    }
+
+    fn is_full(&self, config: &TokenBucketConfig, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens + elapsed * config.refill_per_second as f64 >= config.capacity as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RateLimiterMetrics {
+    /// Calls rejected by the rate limiter, by route and kind of client
+    throttled_calls: IntCounterVec,
+    /// Number of clients with a token bucket
+    tracked_clients: IntGauge,
+}
+
+impl RateLimiterMetrics {
+    fn new(registry: &Registry) -> Self {
+        Self {
+            throttled_calls: register_int_counter_vec_with_registry!(
+                "rpc_throttled_calls",
+                "Number of rpc calls rejected by the rate limiter",
+                &["route", "client_kind"],
+                registry,
+            )
+            .unwrap(),
+            tracked_clients: register_int_gauge_with_registry!(
+                "rpc_rate_limited_clients",
+                "Number of clients tracked by the rpc rate limiter",
+                registry,
+            )
+            .unwrap(),
+        }
+    }
+}
+
+/// Token bucket rate limiter of the clients of the JSON-RPC server.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: JsonRpcRateLimitConfig,
+    /// Buckets of the clients, from the least to the most recently seen
+    buckets: Mutex<LinkedHashMap<ClientId, TokenBucket>>,
+    metrics: RateLimiterMetrics,
+}
+
+impl RateLimiter {
+    pub fn new(config: JsonRpcRateLimitConfig, registry: &Registry) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(LinkedHashMap::new()),
+            metrics: RateLimiterMetrics::new(registry),
+        }
+    }
+
+    /// Identify the client of a request from its headers and the address of its connection.
+    /// Returns `None` when the request is not subject to any limit.
+    pub fn client_id(
+        &self,
+        headers: &HeaderMap,
+        remote_addr: Option<SocketAddr>,
+    ) -> Option<ClientId> {
+        if let Some(api_key) = headers
+            .get(&self.config.api_key_header)
+            .and_then(|v| v.to_str().ok())
+        {
+            // Unknown keys are limited by IP address like requests without a key
+            if self.config.api_keys.contains_key(api_key) {
+                return Some(ClientId::ApiKey(api_key.to_string()));
+            }
+        }
+        self.config.per_ip?;
+        self.forwarded_for(headers)
+            .or(remote_addr.map(|addr| addr.ip()))
+            .map(ClientId::Ip)
+    }
+
+    /// The client address appended to `X-Forwarded-For` by the first trusted proxy. The entries
+    /// before it are set by the client, and cannot be trusted.
+    fn forwarded_for(&self, headers: &HeaderMap) -> Option<IpAddr> {
+        let hops = self.config.trusted_proxy_hops;
+        if hops == 0 {
+            return None;
+        }
+        let entries: Vec<_> = headers
+            .get_all(FORWARDED_FOR_HEADER)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .collect();
+        // Fewer entries than proxies when the request skipped the first ones, the first entry is
+        // still appended by a trusted proxy.
+        let entry = entries.get(entries.len().saturating_sub(hops))?;
+        entry.trim().parse().ok()
+    }
+
+    fn weight(&self, method: &str) -> u32 {
+        self.config.method_weights.get(method).copied().unwrap_or(1)
+    }
+
+    fn bucket_config(&self, client: &ClientId) -> Option<&TokenBucketConfig> {
+        match client {
+            ClientId::Ip(_) => self.config.per_ip.as_ref(),
+            ClientId::ApiKey(key) => self.config.api_keys.get(key),
+        }
+    }
+
+    /// Consume the tokens of a call to `method` by `client`, or return the error to answer the
+    /// call with when the client is over its limit.
+    pub fn check(&self, client: &ClientId, method: &str) -> Result<(), ErrorObject<'static>> {
+        self.try_acquire(client, self.weight(method), Instant::now())
+            .map_err(|retry_after| {
+                self.metrics
+                    .throttled_calls
+                    .with_label_values(&[method, client.kind()])
+                    .inc();
+                ErrorObject::owned(
+                    LIMIT_EXCEEDED_ERROR_CODE,
+                    format!(
+                        "Too many requests, retry in {}ms",
+                        retry_after.as_millis().max(1)
+                    ),
+                    None::<()>,
+                )
+            })
+    }
+
+    fn try_acquire(&self, client: &ClientId, weight: u32, now: Instant) -> Result<(), Duration> {
+        let Some(config) = self.bucket_config(client) else {
+            return Ok(());
+        };
+        let mut buckets = self.buckets.lock().unwrap();
+        // A full bucket is the same as a new one, so it can be dropped. The least recently seen
+        // clients are the first ones to be full again.
+        while let Some((oldest, bucket)) = buckets.front() {
+            if self
+                .bucket_config(oldest)
+                .map_or(true, |config| bucket.is_full(config, now))
+            {
+                buckets.pop_front();
+            } else {
+                break;
+            }
+        }
+        if buckets.get_refresh(client).is_none() {
+            if buckets.len() >= MAX_TRACKED_CLIENTS {
+                buckets.pop_front();
+            }
+            buckets.insert(client.clone(), TokenBucket::new(config, now));
+        }
+        let result = buckets
+            .get_mut(client)
+            .expect("Client has a bucket")
+            .try_acquire(config, weight, now);
+        self.metrics.tracked_clients.set(buckets.len() as i64);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+    use std::collections::BTreeMap;
+
+    fn limiter() -> RateLimiter {
+        let config = JsonRpcRateLimitConfig {
+            per_ip: Some(TokenBucketConfig {
+                capacity: 10,
+                refill_per_second: 5,
+            }),
+            api_keys: BTreeMap::from([(
+                "key".to_string(),
+                TokenBucketConfig {
+                    capacity: 100,
+                    refill_per_second: 50,
+                },
+            )]),
+            method_weights: BTreeMap::from([("suix_queryEvents".to_string(), 4)]),
+            ..Default::default()
+        };
+        RateLimiter::new(config, &Registry::new())
+    }
+
+    #[test]
+    fn test_token_bucket_refill() {
+        let limiter = limiter();
+        let client = ClientId::Ip([127, 0, 0, 1].into());
+        let now = Instant::now();
+        assert!(limiter.try_acquire(&client, 6, now).is_ok());
+        assert!(limiter.try_acquire(&client, 4, now).is_ok());
+        assert_eq!(
+            limiter.try_acquire(&client, 1, now),
+            Err(Duration::from_millis(200))
+        );
+        // Two tokens are refilled after 400ms
+        let later = now + Duration::from_millis(400);
+        assert!(limiter.try_acquire(&client, 2, later).is_ok());
+        assert!(limiter.try_acquire(&client, 1, later).is_err());
+        // Calls weighing more than the capacity need a full bucket
+        let much_later = later + Duration::from_secs(10);
+        assert!(limiter.try_acquire(&client, 50, much_later).is_ok());
+        assert!(limiter.try_acquire(&client, 1, much_later).is_err());
+    }
+
+    #[test]
+    fn test_bucket_eviction() {
+        let limiter = limiter();
+        let client = |i: u32| ClientId::Ip(std::net::Ipv4Addr::from(i).into());
+        let now = Instant::now();
+
+        // Full buckets are dropped
+        assert!(limiter.try_acquire(&client(0), 10, now).is_ok());
+        let later = now + Duration::from_secs(2);
+        assert!(limiter.try_acquire(&client(1), 1, later).is_ok());
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+
+        // The least recently seen clients are dropped when there are too many of them
+        for i in 1..=MAX_TRACKED_CLIENTS as u32 + 1 {
+            assert!(limiter.try_acquire(&client(i), 1, later).is_ok());
+        }
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.len(), MAX_TRACKED_CLIENTS);
+        assert!(!buckets.contains_key(&client(1)));
+        assert!(buckets.contains_key(&client(2)));
+    }
+
+    #[test]
+    fn test_method_weights() {
+        let limiter = limiter();
+        let client = ClientId::Ip([127, 0, 0, 1].into());
+        assert!(limiter.check(&client, "suix_queryEvents").is_ok());
+        assert!(limiter.check(&client, "suix_queryEvents").is_ok());
+        assert!(limiter.check(&client, "sui_getObject").is_ok());
+        assert!(limiter.check(&client, "sui_getObject").is_ok());
+        let error = limiter.check(&client, "suix_queryEvents").unwrap_err();
+        assert_eq!(LIMIT_EXCEEDED_ERROR_CODE, error.code());
+        assert!(limiter.check(&client, "sui_getObject").is_err());
+    }
+
+    #[test]
+    fn test_client_id() {
+        let limiter = limiter();
+        let remote_addr = Some(SocketAddr::from(([10, 0, 0, 1], 9000)));
+        let ip = ClientId::Ip([10, 0, 0, 1].into());
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(Some(ip.clone()), limiter.client_id(&headers, remote_addr));
+        assert_eq!(None, limiter.client_id(&headers, None));
+
+        headers.insert("x-api-key", HeaderValue::from_static("key"));
+        assert_eq!(
+            Some(ClientId::ApiKey("key".to_string())),
+            limiter.client_id(&headers, remote_addr)
+        );
+        // Unknown API keys don't escape the IP limit
+        headers.insert("x-api-key", HeaderValue::from_static("unknown"));
+        assert_eq!(Some(ip.clone()), limiter.client_id(&headers, remote_addr));
+
+        // X-Forwarded-For is ignored unless there are trusted proxies
+        headers.insert(
+            FORWARDED_FOR_HEADER,
+            HeaderValue::from_static("6.6.6.6, 1.2.3.4, 5.6.7.8"),
+        );
+        assert_eq!(Some(ip), limiter.client_id(&headers, remote_addr));
+        let with_hops = |trusted_proxy_hops| {
+            RateLimiter::new(
+                JsonRpcRateLimitConfig {
+                    trusted_proxy_hops,
+                    ..Default::default()
+                },
+                &Registry::new(),
+            )
+        };
+        // The entries before the one of the first trusted proxy can be spoofed by the client
+        assert_eq!(
+            Some(ClientId::Ip([5, 6, 7, 8].into())),
+            with_hops(1).client_id(&headers, remote_addr)
+        );
+        assert_eq!(
+            Some(ClientId::Ip([1, 2, 3, 4].into())),
+            with_hops(2).client_id(&headers, remote_addr)
+        );
+        assert_eq!(
+            Some(ClientId::Ip([6, 6, 6, 6].into())),
+            with_hops(5).client_id(&headers, remote_addr)
+        );
+        // Entries of repeated headers are in the order of the headers
+        headers.append(FORWARDED_FOR_HEADER, HeaderValue::from_static("9.9.9.9"));
+        assert_eq!(
+            Some(ClientId::Ip([9, 9, 9, 9].into())),
+            with_hops(1).client_id(&headers, remote_addr)
+        );
+    }
+}
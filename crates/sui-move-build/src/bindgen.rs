@@ -0,0 +1,733 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation of Rust bindings for the modules of a compiled Move package.
+//!
+//! For every module of the package, [generate] emits a Rust module containing:
+//! - a struct with the same BCS layout for each Move struct, which decodes the contents of the
+//!   objects and events of that type,
+//! - a function for each public or entry function, appending a call to it to a
+//!   `ProgrammableTransactionBuilder`. Arguments of types that can be passed as pure values take
+//!   the corresponding Rust value, other arguments take an `Argument` of the transaction,
+//! - a `decode_<function>` function for each of those functions that returns values, decoding the
+//!   BCS values it returns, like the return values of a dev inspect, into their Rust types.
+//!
+//! Phantom type parameters don't change the layout of a struct, so they are left out of the
+//! generated structs. Structs of dependencies used by the package are emitted under a `deps`
+//! module. The generated code uses the `anyhow`, `bcs`, `move-core-types`, `serde` and `sui-types`
+//! crates.
+//!
+//! The bindings can be generated by `sui move bindgen`, or by a build script:
+//!
+//! ```rust,no_run
+//! use sui_move_build::{bindgen, BuildConfig};
+//!
+//! let package = BuildConfig::default().build("move/my_package".into()).unwrap();
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! std::fs::write(format!("{out_dir}/my_package.rs"), bindgen::generate(&package)).unwrap();
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::Write;
+
+use move_binary_format::file_format::Visibility;
+use move_binary_format::normalized::{Function, Module, Struct, Type};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::{IdentStr, Identifier};
+use move_core_types::language_storage::ModuleId;
+use sui_types::{DEEPBOOK_ADDRESS, MOVE_STDLIB_ADDRESS, SUI_FRAMEWORK_ADDRESS, SUI_SYSTEM_ADDRESS};
+
+use crate::CompiledPackage;
+
+const ARGUMENT: &str = "::sui_types::transaction::Argument";
+const OBJECT_ID: &str = "::sui_types::base_types::ObjectID";
+const TYPE_TAG: &str = "::move_core_types::language_storage::TypeTag";
+const STRUCT_TAG: &str = "::move_core_types::language_storage::StructTag";
+const BUILDER: &str =
+    "::sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder";
+
+/// Rust keywords which are not Move keywords, and so may name Move modules, structs, fields or
+/// functions.
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "dyn", "enum", "extern", "fn", "impl", "in", "match", "mod",
+    "pub", "ref", "static", "trait", "type", "union", "unsafe", "where", "yield", "try", "priv",
+    "final", "override", "virtual", "typeof", "unsized", "macro", "become", "do", "abstract",
+    "for", "gen",
+];
+
+/// Lints that generated code may not follow, as it keeps the names and arities of Move items.
+const ALLOWED_LINTS: &str = "#[allow(clippy::all, non_camel_case_types, non_snake_case)]";
+
+/// Generate the Rust bindings of the modules of `package`.
+pub fn generate(package: &CompiledPackage) -> String {
+    let root = package
+        .get_modules()
+        .map(|module| module.self_id())
+        .collect();
+    let modules = package
+        .get_modules_and_deps()
+        .map(|module| {
+            let module = Module::new(module);
+            (ModuleId::new(module.address, module.name.clone()), module)
+        })
+        .collect();
+    Generator { modules, root }
+        .generate(package.package.compiled_package_info.package_name.as_str())
+}
+
+/// Where generated code is emitted, to find the path of the other generated structs from it.
+#[derive(Clone, Copy)]
+enum Location {
+    /// `<module>`
+    Root,
+    /// `deps::<address>::<module>`
+    Dependency,
+}
+
+impl Location {
+    fn prefix(self) -> String {
+        match self {
+            Location::Root => "super::".to_string(),
+            Location::Dependency => "super::super::super::".to_string(),
+        }
+    }
+}
+
+/// Move types with a dedicated Rust type.
+enum WellKnown {
+    Uid,
+    Id,
+    String,
+    Option,
+}
+
+impl WellKnown {
+    fn of(address: &AccountAddress, module: &IdentStr, name: &IdentStr) -> Option<Self> {
+        match (*address, module.as_str(), name.as_str()) {
+            (SUI_FRAMEWORK_ADDRESS, "object", "UID") => Some(WellKnown::Uid),
+            (SUI_FRAMEWORK_ADDRESS, "object", "ID") => Some(WellKnown::Id),
+            (MOVE_STDLIB_ADDRESS, "string" | "ascii", "String") => Some(WellKnown::String),
+            (MOVE_STDLIB_ADDRESS, "option", "Option") => Some(WellKnown::Option),
+            _ => None,
+        }
+    }
+}
+
+struct Generator {
+    /// The modules of the package and its dependencies
+    modules: BTreeMap<ModuleId, Module>,
+    /// The modules of the package
+    root: BTreeSet<ModuleId>,
+}
+
+impl Generator {
+    fn generate(&self, package_name: &str) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "// Rust bindings of the `{package_name}` Move package, generated by `sui move bindgen`."
+        )
+        .unwrap();
+        writeln!(out, "// Do not edit.").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "#[allow(dead_code)]").unwrap();
+        writeln!(
+            out,
+            "fn nested_results<const N: usize>(result: {ARGUMENT}) -> [{ARGUMENT}; N] {{"
+        )
+        .unwrap();
+        writeln!(out, "    let {ARGUMENT}::Result(command) = result else {{").unwrap();
+        writeln!(
+            out,
+            "        unreachable!(\"A Move call returns the result of its command\")"
+        )
+        .unwrap();
+        writeln!(out, "    }};").unwrap();
+        writeln!(
+            out,
+            "    ::std::array::from_fn(|i| {ARGUMENT}::NestedResult(command, i as u16))"
+        )
+        .unwrap();
+        writeln!(out, "}}").unwrap();
+
+        for id in &self.root {
+            let module = &self.modules[id];
+            writeln!(out).unwrap();
+            writeln!(out, "{ALLOWED_LINTS}").unwrap();
+            writeln!(out, "pub mod {} {{", rust_ident(module.name.as_str())).unwrap();
+            let mut items = vec![];
+            for (name, struct_) in &module.structs {
+                items.push(self.struct_binding(module, name, struct_, Location::Root));
+            }
+            for (name, function) in &module.functions {
+                if function.visibility == Visibility::Public || function.is_entry {
+                    items.push(self.function_binding(module, name, function));
+                    if !function.return_.is_empty() {
+                        items.push(self.return_values_binding(module, name, function));
+                    }
+                }
+            }
+            write_items(&mut out, &items, 1);
+            writeln!(out, "}}").unwrap();
+        }
+
+        let dependencies = self.used_dependency_structs();
+        if !dependencies.is_empty() {
+            writeln!(out).unwrap();
+            writeln!(out, "{ALLOWED_LINTS}").unwrap();
+            writeln!(out, "pub mod deps {{").unwrap();
+            let mut by_address: BTreeMap<_, BTreeMap<_, Vec<_>>> = BTreeMap::new();
+            for (id, name) in &dependencies {
+                by_address
+                    .entry(*id.address())
+                    .or_default()
+                    .entry(id)
+                    .or_default()
+                    .push(name);
+            }
+            for (i, (address, modules)) in by_address.iter().enumerate() {
+                if i > 0 {
+                    writeln!(out).unwrap();
+                }
+                writeln!(out, "    pub mod {} {{", address_alias(address)).unwrap();
+                for (j, (id, names)) in modules.iter().enumerate() {
+                    if j > 0 {
+                        writeln!(out).unwrap();
+                    }
+                    let module = &self.modules[*id];
+                    writeln!(out, "        pub mod {} {{", rust_ident(id.name().as_str())).unwrap();
+                    let items: Vec<_> = names
+                        .iter()
+                        .map(|name| {
+                            self.struct_binding(
+                                module,
+                                name,
+                                &module.structs[*name],
+                                Location::Dependency,
+                            )
+                        })
+                        .collect();
+                    write_items(&mut out, &items, 3);
+                    writeln!(out, "        }}").unwrap();
+                }
+                writeln!(out, "    }}").unwrap();
+            }
+            writeln!(out, "}}").unwrap();
+        }
+        out
+    }
+
+    /// The structs of dependencies that are used by the structs and functions of the package,
+    /// directly or through other structs.
+    fn used_dependency_structs(&self) -> BTreeSet<(ModuleId, Identifier)> {
+        let mut queue = VecDeque::new();
+        for id in &self.root {
+            let module = &self.modules[id];
+            for struct_ in module.structs.values() {
+                queue.extend(struct_.fields.iter().map(|field| &field.type_));
+            }
+            for function in module.functions.values() {
+                if function.visibility == Visibility::Public || function.is_entry {
+                    queue.extend(function.parameters.iter().chain(&function.return_));
+                }
+            }
+        }
+
+        let mut used = BTreeSet::new();
+        while let Some(type_) = queue.pop_front() {
+            match type_ {
+                Type::Vector(inner) | Type::Reference(inner) | Type::MutableReference(inner) => {
+                    queue.push_back(inner)
+                }
+                Type::Struct {
+                    address,
+                    module,
+                    name,
+                    type_arguments,
+                } => {
+                    queue.extend(type_arguments);
+                    let id = ModuleId::new(*address, module.clone());
+                    if WellKnown::of(address, module, name).is_some()
+                        || self.root.contains(&id)
+                        || is_tx_context(type_)
+                    {
+                        continue;
+                    }
+                    let Some(struct_) = self.modules.get(&id).and_then(|m| m.structs.get(name))
+                    else {
+                        continue;
+                    };
+                    if used.insert((id, name.clone())) {
+                        queue.extend(struct_.fields.iter().map(|field| &field.type_));
+                    }
+                }
+                _ => {}
+            }
+        }
+        used
+    }
+
+    fn struct_binding(
+        &self,
+        module: &Module,
+        name: &Identifier,
+        struct_: &Struct,
+        location: Location,
+    ) -> String {
+        let mut out = String::new();
+        let generics = struct_generics(struct_);
+        let move_name = match location {
+            Location::Root => format!("{}::{name}", module.name),
+            Location::Dependency => format!(
+                "{}::{}::{name}",
+                module.address.to_hex_literal(),
+                module.name
+            ),
+        };
+        writeln!(out, "/// Move struct `{move_name}`.").unwrap();
+        writeln!(
+            out,
+            "#[derive(Clone, Debug, PartialEq, Eq, ::serde::Serialize, ::serde::Deserialize)]"
+        )
+        .unwrap();
+        writeln!(out, "pub struct {}{generics} {{", rust_ident(name.as_str())).unwrap();
+        for field in &struct_.fields {
+            writeln!(
+                out,
+                "    pub {}: {},",
+                rust_ident(field.name.as_str()),
+                self.rust_type(&field.type_, location)
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+        writeln!(out).unwrap();
+
+        writeln!(
+            out,
+            "impl{generics} {}{generics} {{",
+            rust_ident(name.as_str())
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    pub const MODULE: &'static str = \"{}\";",
+            module.name
+        )
+        .unwrap();
+        writeln!(out, "    pub const NAME: &'static str = \"{name}\";").unwrap();
+        writeln!(out).unwrap();
+        let address = match location {
+            Location::Root => {
+                writeln!(
+                    out,
+                    "    /// The type of the struct defined by the package originally published at \
+                     `package`."
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "    pub fn struct_tag(package: {OBJECT_ID}, type_params: Vec<{TYPE_TAG}>) -> \
+                     {STRUCT_TAG} {{"
+                )
+                .unwrap();
+                "package.into()".to_string()
+            }
+            Location::Dependency => {
+                writeln!(out, "    /// The type of the struct.").unwrap();
+                writeln!(
+                    out,
+                    "    pub fn struct_tag(type_params: Vec<{TYPE_TAG}>) -> {STRUCT_TAG} {{"
+                )
+                .unwrap();
+                format!(
+                    "::move_core_types::account_address::AccountAddress::from_hex_literal(\"{}\").unwrap()",
+                    module.address.to_hex_literal()
+                )
+            }
+        };
+        writeln!(out, "        {STRUCT_TAG} {{").unwrap();
+        writeln!(out, "            address: {address},").unwrap();
+        writeln!(
+            out,
+            "            module: ::move_core_types::ident_str!(\"{}\").to_owned(),",
+            module.name
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "            name: ::move_core_types::ident_str!(\"{name}\").to_owned(),"
+        )
+        .unwrap();
+        writeln!(out, "            type_params,").unwrap();
+        writeln!(out, "        }}").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "    /// Decode the BCS contents of a value of the struct, like an object or an event."
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    pub fn from_bcs(bytes: &[u8]) -> Result<Self, ::bcs::Error>"
+        )
+        .unwrap();
+        writeln!(out, "    where").unwrap();
+        writeln!(out, "        Self: ::serde::de::DeserializeOwned,").unwrap();
+        writeln!(out, "    {{").unwrap();
+        writeln!(out, "        ::bcs::from_bytes(bytes)").unwrap();
+        writeln!(out, "    }}").unwrap();
+        writeln!(out, "}}").unwrap();
+        out
+    }
+
+    fn function_binding(&self, module: &Module, name: &Identifier, function: &Function) -> String {
+        let mut out = String::new();
+        let mut parameters = function.parameters.as_slice();
+        if let Some((last, rest)) = parameters.split_last() {
+            if is_tx_context(last) {
+                parameters = rest;
+            }
+        }
+
+        writeln!(
+            out,
+            "/// Append a call to `{}::{name}` to `builder`.",
+            module.name
+        )
+        .unwrap();
+        writeln!(out, "pub fn {}(", rust_ident(name.as_str())).unwrap();
+        writeln!(out, "    builder: &mut {BUILDER},").unwrap();
+        writeln!(out, "    package: {OBJECT_ID},").unwrap();
+        let type_parameters = function.type_parameters.len();
+        if type_parameters > 0 {
+            writeln!(out, "    type_arguments: [{TYPE_TAG}; {type_parameters}],").unwrap();
+        }
+        let mut arguments = vec![];
+        for (i, parameter) in parameters.iter().enumerate() {
+            if is_pure(parameter) {
+                writeln!(
+                    out,
+                    "    arg{i}: {},",
+                    self.rust_type(parameter, Location::Root)
+                )
+                .unwrap();
+                arguments.push(format!("builder.pure(arg{i})?"));
+            } else {
+                writeln!(out, "    arg{i}: {ARGUMENT},").unwrap();
+                arguments.push(format!("arg{i}"));
+            }
+        }
+        let (return_type, result) = match function.return_.len() {
+            0 => ("()".to_string(), "()".to_string()),
+            1 => (ARGUMENT.to_string(), "result".to_string()),
+            n => (
+                format!("[{ARGUMENT}; {n}]"),
+                "super::nested_results(result)".to_string(),
+            ),
+        };
+        writeln!(out, ") -> ::anyhow::Result<{return_type}> {{").unwrap();
+        writeln!(out, "    let arguments = vec![{}];", arguments.join(", ")).unwrap();
+        let binding = if function.return_.is_empty() {
+            "_result"
+        } else {
+            "result"
+        };
+        writeln!(out, "    let {binding} = builder.programmable_move_call(").unwrap();
+        writeln!(out, "        package,").unwrap();
+        writeln!(
+            out,
+            "        ::move_core_types::ident_str!(\"{}\").to_owned(),",
+            module.name
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "        ::move_core_types::ident_str!(\"{name}\").to_owned(),"
+        )
+        .unwrap();
+        if type_parameters > 0 {
+            writeln!(out, "        type_arguments.to_vec(),").unwrap();
+        } else {
+            writeln!(out, "        vec![],").unwrap();
+        }
+        writeln!(out, "        arguments,").unwrap();
+        writeln!(out, "    );").unwrap();
+        writeln!(out, "    Ok({result})").unwrap();
+        writeln!(out, "}}").unwrap();
+        out
+    }
+
+    /// A function decoding the BCS values returned by a call to `function` into their Rust types.
+    fn return_values_binding(
+        &self,
+        module: &Module,
+        name: &Identifier,
+        function: &Function,
+    ) -> String {
+        let mut out = String::new();
+        let mut type_parameters = BTreeSet::new();
+        for type_ in &function.return_ {
+            self.type_parameters_of(type_, &mut type_parameters);
+        }
+        let generics = if type_parameters.is_empty() {
+            String::new()
+        } else {
+            let parameters: Vec<_> = type_parameters
+                .iter()
+                .map(|i| format!("T{i}: ::serde::de::DeserializeOwned"))
+                .collect();
+            format!("<{}>", parameters.join(", "))
+        };
+        let types: Vec<_> = function
+            .return_
+            .iter()
+            .map(|type_| self.rust_type(type_, Location::Root))
+            .collect();
+        let values: Vec<_> = (0..types.len()).map(|i| format!("value{i}")).collect();
+
+        writeln!(
+            out,
+            "/// Decode the BCS values returned by a call to `{}::{name}`, like the return values \
+             of a dev inspect.",
+            module.name
+        )
+        .unwrap();
+        writeln!(out, "pub fn decode_{name}{generics}(").unwrap();
+        writeln!(out, "    return_values: &[Vec<u8>],").unwrap();
+        if let [type_] = types.as_slice() {
+            writeln!(out, ") -> ::anyhow::Result<{type_}> {{").unwrap();
+        } else {
+            writeln!(out, ") -> ::anyhow::Result<({})> {{", types.join(", ")).unwrap();
+        }
+        writeln!(
+            out,
+            "    let [{}] = return_values else {{",
+            values.join(", ")
+        )
+        .unwrap();
+        writeln!(out, "        ::anyhow::bail!(").unwrap();
+        writeln!(
+            out,
+            "            \"Expected {} return values, got {{}}\",",
+            values.len()
+        )
+        .unwrap();
+        writeln!(out, "            return_values.len()").unwrap();
+        writeln!(out, "        );").unwrap();
+        writeln!(out, "    }};").unwrap();
+        if let [value] = values.as_slice() {
+            writeln!(out, "    Ok(::bcs::from_bytes({value})?)").unwrap();
+        } else {
+            writeln!(out, "    Ok((").unwrap();
+            for value in &values {
+                writeln!(out, "        ::bcs::from_bytes({value})?,").unwrap();
+            }
+            writeln!(out, "    ))").unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+        out
+    }
+
+    /// Whether each type parameter of a struct is phantom, or nothing if the struct is unknown.
+    fn phantom_parameters(
+        &self,
+        address: &AccountAddress,
+        module: &Identifier,
+        name: &Identifier,
+    ) -> Vec<bool> {
+        self.modules
+            .get(&ModuleId::new(*address, module.clone()))
+            .and_then(|m| m.structs.get(name))
+            .map(|s| s.type_parameters.iter().map(|p| p.is_phantom).collect())
+            .unwrap_or_default()
+    }
+
+    /// Collect the type parameters used by the Rust type of values of the Move type `type_`.
+    fn type_parameters_of(&self, type_: &Type, type_parameters: &mut BTreeSet<u16>) {
+        match type_ {
+            Type::TypeParameter(i) => {
+                type_parameters.insert(*i);
+            }
+            Type::Vector(inner) | Type::Reference(inner) | Type::MutableReference(inner) => {
+                self.type_parameters_of(inner, type_parameters)
+            }
+            Type::Struct {
+                address,
+                module,
+                name,
+                type_arguments,
+            } => {
+                let phantoms = self.phantom_parameters(address, module, name);
+                for (i, type_argument) in type_arguments.iter().enumerate() {
+                    if !phantoms.get(i).copied().unwrap_or(false) {
+                        self.type_parameters_of(type_argument, type_parameters);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The Rust type of values of the Move type `type_`, in code emitted at `location`.
+    fn rust_type(&self, type_: &Type, location: Location) -> String {
+        match type_ {
+            Type::Bool => "bool".to_string(),
+            Type::U8 => "u8".to_string(),
+            Type::U16 => "u16".to_string(),
+            Type::U32 => "u32".to_string(),
+            Type::U64 => "u64".to_string(),
+            Type::U128 => "u128".to_string(),
+            Type::U256 => "::move_core_types::u256::U256".to_string(),
+            Type::Address | Type::Signer => "::sui_types::base_types::SuiAddress".to_string(),
+            Type::Vector(inner) => format!("Vec<{}>", self.rust_type(inner, location)),
+            Type::TypeParameter(i) => format!("T{i}"),
+            Type::Reference(inner) | Type::MutableReference(inner) => {
+                self.rust_type(inner, location)
+            }
+            Type::Struct {
+                address,
+                module,
+                name,
+                type_arguments,
+            } => {
+                match WellKnown::of(address, module, name) {
+                    Some(WellKnown::Uid) => return "::sui_types::id::UID".to_string(),
+                    Some(WellKnown::Id) => return "::sui_types::id::ID".to_string(),
+                    Some(WellKnown::String) => return "String".to_string(),
+                    Some(WellKnown::Option) => {
+                        return format!("Option<{}>", self.rust_type(&type_arguments[0], location))
+                    }
+                    None => {}
+                }
+                let id = ModuleId::new(*address, module.clone());
+                let path = if self.root.contains(&id) {
+                    format!("{}{}", location.prefix(), rust_ident(module.as_str()))
+                } else {
+                    format!(
+                        "{}deps::{}::{}",
+                        location.prefix(),
+                        address_alias(address),
+                        rust_ident(module.as_str())
+                    )
+                };
+                // Phantom type parameters are left out of the generated structs
+                let phantoms = self.phantom_parameters(address, module, name);
+                let type_arguments: Vec<_> = type_arguments
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !phantoms.get(*i).copied().unwrap_or(false))
+                    .map(|(_, t)| self.rust_type(t, location))
+                    .collect();
+                if type_arguments.is_empty() {
+                    format!("{path}::{}", rust_ident(name.as_str()))
+                } else {
+                    format!(
+                        "{path}::{}<{}>",
+                        rust_ident(name.as_str()),
+                        type_arguments.join(", ")
+                    )
+                }
+            }
+        }
+    }
+}
+
+fn write_items(out: &mut String, items: &[String], depth: usize) {
+    let indent = "    ".repeat(depth);
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            writeln!(out).unwrap();
+        }
+        for line in item.lines() {
+            if line.is_empty() {
+                writeln!(out).unwrap();
+            } else {
+                writeln!(out, "{indent}{line}").unwrap();
+            }
+        }
+    }
+}
+
+/// The generic parameters of the Rust struct of a Move struct: its non-phantom type parameters,
+/// named after their index.
+fn struct_generics(struct_: &Struct) -> String {
+    let parameters: Vec<_> = struct_
+        .type_parameters
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| !p.is_phantom)
+        .map(|(i, _)| format!("T{i}"))
+        .collect();
+    if parameters.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", parameters.join(", "))
+    }
+}
+
+/// Whether values of the type can be passed to a call as pure arguments.
+fn is_pure(type_: &Type) -> bool {
+    match type_ {
+        Type::Bool
+        | Type::U8
+        | Type::U16
+        | Type::U32
+        | Type::U64
+        | Type::U128
+        | Type::U256
+        | Type::Address => true,
+        Type::Vector(inner) | Type::Reference(inner) | Type::MutableReference(inner) => {
+            is_pure(inner)
+        }
+        Type::Struct {
+            address,
+            module,
+            name,
+            type_arguments,
+        } => match WellKnown::of(address, module, name) {
+            Some(WellKnown::Id | WellKnown::String) => true,
+            Some(WellKnown::Option) => is_pure(&type_arguments[0]),
+            _ => false,
+        },
+        Type::Signer | Type::TypeParameter(_) => false,
+    }
+}
+
+fn is_tx_context(type_: &Type) -> bool {
+    match type_ {
+        Type::Reference(inner) | Type::MutableReference(inner) => is_tx_context(inner),
+        Type::Struct {
+            address,
+            module,
+            name,
+            ..
+        } => {
+            *address == SUI_FRAMEWORK_ADDRESS
+                && module.as_str() == "tx_context"
+                && name.as_str() == "TxContext"
+        }
+        _ => false,
+    }
+}
+
+/// Name of the module of the dependencies published at `address`.
+fn address_alias(address: &AccountAddress) -> String {
+    match *address {
+        MOVE_STDLIB_ADDRESS => "std".to_string(),
+        SUI_FRAMEWORK_ADDRESS => "sui".to_string(),
+        SUI_SYSTEM_ADDRESS => "sui_system".to_string(),
+        DEEPBOOK_ADDRESS => "deepbook".to_string(),
+        _ => format!("package_{}", address.short_str_lossless()),
+    }
+}
+
+/// A Rust identifier for a Move identifier, which may be a Rust keyword.
+fn rust_ident(name: &str) -> String {
+    match name {
+        "self" | "super" | "crate" | "Self" => format!("{name}_"),
+        _ if RUST_KEYWORDS.contains(&name) => format!("r#{name}"),
+        _ => name.to_string(),
+    }
+}
@@ -55,16 +55,23 @@ use sui_types::{
 use sui_verifier::verifier as sui_bytecode_verifier;
 
 use crate::linters::{
+    balance_arithmetic::BalanceArithmeticVisitor, capability_by_value::CapabilityByValueVisitor,
     coin_field::CoinFieldVisitor, collection_equality::CollectionEqualityVisitor,
-    custom_state_change::CustomStateChangeVerifier, freeze_wrapped::FreezeWrappedVisitor,
-    known_filters, self_transfer::SelfTransferVerifier, share_owned::ShareOwnedVerifier,
-    LINT_WARNING_PREFIX,
+    custom_state_change::CustomStateChangeVerifier, event_fields::EventFieldsVisitor,
+    freeze_wrapped::FreezeWrappedVisitor, known_filters,
+    non_store_transfer::NonStoreTransferVisitor, public_entry_return::PublicEntryReturnVisitor,
+    self_transfer::SelfTransferVerifier, share_owned::ShareOwnedVerifier, LINT_WARNING_PREFIX,
 };
 
 #[cfg(test)]
 #[path = "unit_tests/build_tests.rs"]
 mod build_tests;
 
+#[cfg(test)]
+#[path = "unit_tests/bindgen_tests.rs"]
+mod bindgen_tests;
+
+pub mod bindgen;
 pub mod linters;
 
 /// Wrapper around the core Move `CompiledPackage` with some Sui-specific traits and info
@@ -145,6 +152,11 @@ impl BuildConfig {
                     CoinFieldVisitor.visitor(),
                     FreezeWrappedVisitor.visitor(),
                     CollectionEqualityVisitor.visitor(),
+                    CapabilityByValueVisitor.visitor(),
+                    PublicEntryReturnVisitor.visitor(),
+                    BalanceArithmeticVisitor.visitor(),
+                    NonStoreTransferVisitor.visitor(),
+                    EventFieldsVisitor.visitor(),
                 ];
                 let (filter_attr_name, filters) = known_filters();
                 compiler
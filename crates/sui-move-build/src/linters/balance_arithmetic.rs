@@ -0,0 +1,153 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! This analysis flags arithmetic operations on values read from a sui::balance::Balance, either
+//! directly or through locals they were bound to. Such operations abort on overflow and underflow,
+//! and the computed amounts are not backed by any balance, so moving value between balances
+//! should be done with sui::balance::split and sui::balance::join instead.
+
+use std::collections::BTreeSet;
+
+use move_compiler::{
+    diag,
+    diagnostics::codes::{custom, DiagnosticInfo, Severity},
+    expansion::ast::ModuleIdent,
+    naming::ast as N,
+    parser::ast::{self as P, FunctionName},
+    shared::{program_info::TypingProgramInfo, CompilationEnv, Identifier},
+    typing::{
+        ast as T,
+        visitor::{TypingVisitorConstructor, TypingVisitorContext},
+    },
+};
+
+use super::{
+    LinterDiagCategory, BALANCE_MOD_NAME, BALANCE_VALUE_FUN, LINTER_DEFAULT_DIAG_CODE,
+    LINT_WARNING_PREFIX, SUI_PKG_NAME,
+};
+
+const BALANCE_ARITHMETIC_DIAG: DiagnosticInfo = custom(
+    LINT_WARNING_PREFIX,
+    Severity::Warning,
+    LinterDiagCategory::BalanceArithmetic as u8,
+    LINTER_DEFAULT_DIAG_CODE,
+    "unchecked arithmetic on a balance value",
+);
+
+pub struct BalanceArithmeticVisitor;
+pub struct Context<'a> {
+    env: &'a mut CompilationEnv,
+    /// Locals of the current function that hold the value of a balance
+    balance_values: BTreeSet<N::Var_>,
+}
+
+impl TypingVisitorConstructor for BalanceArithmeticVisitor {
+    type Context<'a> = Context<'a>;
+
+    fn context<'a>(
+        env: &'a mut CompilationEnv,
+        _program_info: &'a TypingProgramInfo,
+        _program: &T::Program_,
+    ) -> Self::Context<'a> {
+        Context {
+            env,
+            balance_values: BTreeSet::new(),
+        }
+    }
+}
+
+impl TypingVisitorContext for Context<'_> {
+    fn visit_function_custom(
+        &mut self,
+        _module: Option<ModuleIdent>,
+        _function_name: FunctionName,
+        _fdef: &mut T::Function,
+    ) -> bool {
+        self.balance_values.clear();
+        false
+    }
+
+    fn visit_seq_item(&mut self, sp!(_, seq_item): &mut T::SequenceItem) {
+        use T::SequenceItem_ as SI;
+        match seq_item {
+            SI::Seq(e) => self.visit_exp(e),
+            SI::Declare(lvalues) => self.bind(lvalues, None),
+            SI::Bind(lvalues, _, e) => {
+                self.visit_exp(e);
+                self.bind(lvalues, Some(&**e));
+            }
+        }
+    }
+
+    fn visit_exp_custom(&mut self, exp: &mut T::Exp) -> bool {
+        use T::UnannotatedExp_ as E;
+        if let E::Assign(lvalues, _, e) = &mut exp.exp.value {
+            self.visit_exp(e);
+            self.bind(lvalues, Some(&**e));
+            return true;
+        }
+        let E::BinopExp(lhs, op, _, rhs) = &exp.exp.value else {
+            return false;
+        };
+        if !matches!(op.value, P::BinOp_::Add | P::BinOp_::Sub | P::BinOp_::Mul) {
+            return false;
+        }
+        if self.is_balance_value(lhs) || self.is_balance_value(rhs) {
+            let msg = format!(
+                "Unchecked '{}' on the value of a '{SUI_PKG_NAME}::{BALANCE_MOD_NAME}::Balance'",
+                op.value
+            );
+            let mut d = diag!(BALANCE_ARITHMETIC_DIAG, (op.loc, msg));
+            d.add_note(
+                "The operation aborts on overflow or underflow, and its result is not backed by \
+                 any balance. Consider moving value between balances with 'sui::balance::split' \
+                 and 'sui::balance::join'",
+            );
+            self.env.add_diag(d);
+        }
+        // always return false to process the operands
+        false
+    }
+
+    fn add_warning_filter_scope(&mut self, filter: move_compiler::diagnostics::WarningFilters) {
+        self.env.add_warning_filter_scope(filter)
+    }
+
+    fn pop_warning_filter_scope(&mut self) {
+        self.env.pop_warning_filter_scope()
+    }
+}
+
+impl Context<'_> {
+    /// Returns true if the expression is a call to sui::balance::value or a local holding its
+    /// result.
+    fn is_balance_value(&self, exp: &T::Exp) -> bool {
+        use T::UnannotatedExp_ as E;
+        match &exp.exp.value {
+            E::ModuleCall(fun) => {
+                fun.module.value.is(SUI_PKG_NAME, BALANCE_MOD_NAME)
+                    && fun.name.value().as_str() == BALANCE_VALUE_FUN
+            }
+            E::Move { var, .. } | E::Copy { var, .. } | E::Use(var) => {
+                self.balance_values.contains(&var.value)
+            }
+            E::Annotate(e, _) => self.is_balance_value(e),
+            _ => false,
+        }
+    }
+
+    /// Records whether the locals assigned by `lvalues` hold the value of a balance afterwards.
+    fn bind(&mut self, sp!(_, lvalues): &T::LValueList, rhs: Option<&T::Exp>) {
+        let is_balance_value =
+            lvalues.len() == 1 && rhs.is_some_and(|rhs| self.is_balance_value(rhs));
+        for sp!(_, lvalue) in lvalues {
+            if let T::LValue_::Var { var, .. } = lvalue {
+                if is_balance_value {
+                    self.balance_values.insert(var.value);
+                } else {
+                    self.balance_values.remove(&var.value);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! This analysis flags public functions taking capabilities (objects whose type name ends with
+//! "Cap") by value. Any module can call a public function, and taking a capability by value lets
+//! the function keep, transfer or destroy it, while taking it by reference is enough to prove its
+//! ownership.
+
+use move_compiler::{
+    diag,
+    diagnostics::codes::{custom, DiagnosticInfo, Severity},
+    expansion::ast::{ModuleIdent, Visibility},
+    naming::ast as N,
+    parser::ast::{Ability_, FunctionName},
+    shared::{program_info::TypingProgramInfo, CompilationEnv, Identifier},
+    typing::{
+        ast as T,
+        visitor::{TypingVisitorConstructor, TypingVisitorContext},
+    },
+};
+
+use super::{LinterDiagCategory, LINTER_DEFAULT_DIAG_CODE, LINT_WARNING_PREFIX};
+
+const CAPABILITY_BY_VALUE_DIAG: DiagnosticInfo = custom(
+    LINT_WARNING_PREFIX,
+    Severity::Warning,
+    LinterDiagCategory::CapabilityByValue as u8,
+    LINTER_DEFAULT_DIAG_CODE,
+    "capability passed by value",
+);
+
+/// Suffix of the names of capability types, e.g. 'AdminCap' or 'TreasuryCap'.
+const CAPABILITY_SUFFIX: &str = "Cap";
+
+pub struct CapabilityByValueVisitor;
+pub struct Context<'a> {
+    env: &'a mut CompilationEnv,
+}
+
+impl TypingVisitorConstructor for CapabilityByValueVisitor {
+    type Context<'a> = Context<'a>;
+
+    fn context<'a>(
+        env: &'a mut CompilationEnv,
+        _program_info: &'a TypingProgramInfo,
+        _program: &T::Program_,
+    ) -> Self::Context<'a> {
+        Context { env }
+    }
+}
+
+impl TypingVisitorContext for Context<'_> {
+    fn visit_function_custom(
+        &mut self,
+        _module: Option<ModuleIdent>,
+        function_name: FunctionName,
+        fdef: &mut T::Function,
+    ) -> bool {
+        if !matches!(fdef.visibility, Visibility::Public(_)) {
+            return true;
+        }
+        for (_, _, ptype) in &fdef.signature.parameters {
+            let N::Type_::Apply(Some(abilities), sp!(_, N::TypeName_::ModuleType(_, sname)), _) =
+                &ptype.value
+            else {
+                continue;
+            };
+            if !abilities.has_ability_(Ability_::Key)
+                || !sname.value().as_str().ends_with(CAPABILITY_SUFFIX)
+            {
+                continue;
+            }
+            let msg = format!(
+                "The capability '{sname}' is passed by value to the public function '{function_name}'"
+            );
+            let mut d = diag!(CAPABILITY_BY_VALUE_DIAG, (ptype.loc, msg));
+            d.add_note(format!(
+                "Any module can call a public function. Taking '&{sname}' proves ownership of \
+                 the capability without letting the function keep, transfer or destroy it"
+            ));
+            self.env.add_diag(d);
+        }
+        // the function body does not need to be visited
+        true
+    }
+
+    fn add_warning_filter_scope(&mut self, filter: move_compiler::diagnostics::WarningFilters) {
+        self.env.add_warning_filter_scope(filter)
+    }
+
+    fn pop_warning_filter_scope(&mut self) {
+        self.env.pop_warning_filter_scope()
+    }
+}
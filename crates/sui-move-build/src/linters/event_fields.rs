@@ -0,0 +1,162 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! This analysis flags calls to sui::event::emit whose event type has a field without the copy
+//! ability once instantiated with the type arguments of the call, typically a field of a generic
+//! type that the event is instantiated with a non-copy type for. Such events cannot be emitted,
+//! and the field responsible for it is pointed out. Generic events that are only emitted with
+//! type arguments that have the copy ability are not flagged.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use move_compiler::{
+    diag,
+    diagnostics::codes::{custom, DiagnosticInfo, Severity},
+    expansion::ast as E,
+    naming::ast as N,
+    parser::ast::{self as P, Ability_},
+    shared::{program_info::TypingProgramInfo, CompilationEnv, Identifier},
+    typing::{
+        ast as T,
+        visitor::{TypingVisitorConstructor, TypingVisitorContext},
+    },
+};
+use move_symbol_pool::Symbol;
+
+use super::{
+    LinterDiagCategory, EVENT_EMIT_FUN, EVENT_MOD_NAME, LINTER_DEFAULT_DIAG_CODE,
+    LINT_WARNING_PREFIX, SUI_PKG_NAME,
+};
+
+const EVENT_FIELDS_DIAG: DiagnosticInfo = custom(
+    LINT_WARNING_PREFIX,
+    Severity::Warning,
+    LinterDiagCategory::EventFields as u8,
+    LINTER_DEFAULT_DIAG_CODE,
+    "event field without copy",
+);
+
+pub struct EventFieldsVisitor;
+
+pub struct Context<'a> {
+    env: &'a mut CompilationEnv,
+    program_info: &'a TypingProgramInfo,
+    /// Event fields that were already reported, so that each is reported once
+    reported: BTreeSet<(E::ModuleIdent, P::StructName, Symbol)>,
+}
+
+impl TypingVisitorConstructor for EventFieldsVisitor {
+    type Context<'a> = Context<'a>;
+
+    fn context<'a>(
+        env: &'a mut CompilationEnv,
+        program_info: &'a TypingProgramInfo,
+        _program: &T::Program_,
+    ) -> Self::Context<'a> {
+        Context {
+            env,
+            program_info,
+            reported: BTreeSet::new(),
+        }
+    }
+}
+
+impl TypingVisitorContext for Context<'_> {
+    fn visit_exp_custom(&mut self, exp: &mut T::Exp) -> bool {
+        use T::UnannotatedExp_ as E;
+        let E::ModuleCall(fun) = &exp.exp.value else {
+            return false;
+        };
+        if !fun.module.value.is(SUI_PKG_NAME, EVENT_MOD_NAME)
+            || fun.name.value().as_str() != EVENT_EMIT_FUN
+        {
+            return false;
+        }
+        let Some(N::Type_::Apply(_, sp!(_, N::TypeName_::ModuleType(mident, sname)), targs)) =
+            fun.type_arguments.first().map(|t| &t.value)
+        else {
+            return false;
+        };
+        let sdef = self.program_info.struct_definition(mident, sname);
+        let N::StructFields::Defined(sfields) = &sdef.fields else {
+            return false;
+        };
+        let subst: BTreeMap<_, _> = sdef
+            .type_parameters
+            .iter()
+            .map(|tparam| tparam.param.id)
+            .zip(targs)
+            .collect();
+        for (_, fname, (_, ftype)) in sfields.iter() {
+            let key = (*mident, *sname, *fname);
+            if self.reported.contains(&key) || has_copy(self.program_info, &subst, ftype) {
+                continue;
+            }
+            let msg = format!(
+                "The field '{fname}' of the event '{sname}' does not have the 'copy' ability \
+                 in the emitted instantiation of '{sname}'"
+            );
+            let mut d = diag!(
+                EVENT_FIELDS_DIAG,
+                (exp.exp.loc, msg),
+                (ftype.loc, "Field declared here")
+            );
+            d.add_note(format!(
+                "Events are emitted with '{SUI_PKG_NAME}::{EVENT_MOD_NAME}::{EVENT_EMIT_FUN}', \
+                 which requires the 'copy' ability. Consider emitting '{sname}' with type \
+                 arguments that have the 'copy' ability"
+            ));
+            // Only count the field as reported if the warning was not suppressed.
+            let diags = self.env.count_diags();
+            self.env.add_diag(d);
+            if self.env.count_diags() > diags {
+                self.reported.insert(key);
+            }
+        }
+        false
+    }
+
+    fn add_warning_filter_scope(&mut self, filter: move_compiler::diagnostics::WarningFilters) {
+        self.env.add_warning_filter_scope(filter)
+    }
+
+    fn pop_warning_filter_scope(&mut self) {
+        self.env.pop_warning_filter_scope()
+    }
+}
+
+/// Returns true if a type has the copy ability once the type parameters in `subst` are replaced by
+/// the types they map to. Type parameters that are not in `subst` only have the abilities they are
+/// constrained with.
+fn has_copy(
+    info: &TypingProgramInfo,
+    subst: &BTreeMap<N::TParamID, &N::Type>,
+    sp!(_, ty_): &N::Type,
+) -> bool {
+    use N::{TypeName_ as TN, Type_ as T};
+    match ty_ {
+        T::Param(p) => match subst.get(&p.id) {
+            Some(targ) => has_copy(info, &BTreeMap::new(), targ),
+            None => p.abilities.has_ability_(Ability_::Copy),
+        },
+        T::Apply(_, sp!(_, TN::ModuleType(mident, sname)), targs) => {
+            let sdef = info.struct_definition(mident, sname);
+            sdef.abilities.has_ability_(Ability_::Copy)
+                && sdef
+                    .type_parameters
+                    .iter()
+                    .zip(targs)
+                    .all(|(tparam, targ)| tparam.is_phantom || has_copy(info, subst, targ))
+        }
+        T::Apply(_, sp!(loc, TN::Builtin(sp!(_, builtin))), targs) => {
+            builtin
+                .declared_abilities(*loc)
+                .has_ability_(Ability_::Copy)
+                && targs.iter().all(|targ| has_copy(info, subst, targ))
+        }
+        T::Apply(_, sp!(_, TN::Multiple(_)), targs) => {
+            targs.iter().all(|targ| has_copy(info, subst, targ))
+        }
+        T::Unit | T::Ref(_, _) | T::Var(_) | T::Anything | T::UnresolvedError => true,
+    }
+}
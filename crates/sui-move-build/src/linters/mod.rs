@@ -4,10 +4,15 @@
 use move_compiler::{diagnostics::codes::WarningFilter, expansion::ast as E, naming::ast as N};
 use move_ir_types::location::Loc;
 
+pub mod balance_arithmetic;
+pub mod capability_by_value;
 pub mod coin_field;
 pub mod collection_equality;
 pub mod custom_state_change;
+pub mod event_fields;
 pub mod freeze_wrapped;
+pub mod non_store_transfer;
+pub mod public_entry_return;
 pub mod self_transfer;
 pub mod share_owned;
 
@@ -23,6 +28,12 @@ pub const PUBLIC_FREEZE_FUN: &str = "public_freeze_object";
 pub const RECEIVE_FUN: &str = "receive";
 pub const PUBLIC_RECEIVE_FUN: &str = "public_receive";
 
+pub const BALANCE_MOD_NAME: &str = "balance";
+pub const BALANCE_VALUE_FUN: &str = "value";
+
+pub const EVENT_MOD_NAME: &str = "event";
+pub const EVENT_EMIT_FUN: &str = "emit";
+
 pub const COIN_MOD_NAME: &str = "coin";
 pub const COIN_STRUCT_NAME: &str = "Coin";
 
@@ -59,6 +70,11 @@ pub const CUSTOM_STATE_CHANGE_FILTER_NAME: &str = "custom_state_change";
 pub const COIN_FIELD_FILTER_NAME: &str = "coin_field";
 pub const FREEZE_WRAPPED_FILTER_NAME: &str = "freeze_wrapped";
 pub const COLLECTION_EQUALITY_FILTER_NAME: &str = "collection_equality";
+pub const CAPABILITY_BY_VALUE_FILTER_NAME: &str = "capability_by_value";
+pub const PUBLIC_ENTRY_RETURN_FILTER_NAME: &str = "public_entry_return";
+pub const BALANCE_ARITHMETIC_FILTER_NAME: &str = "balance_arithmetic";
+pub const NON_STORE_TRANSFER_FILTER_NAME: &str = "non_store_transfer";
+pub const EVENT_FIELDS_FILTER_NAME: &str = "event_fields";
 
 pub const INVALID_LOC: Loc = Loc::invalid();
 
@@ -69,6 +85,11 @@ pub enum LinterDiagCategory {
     CoinField,
     FreezeWrapped,
     CollectionEquality,
+    CapabilityByValue,
+    PublicEntryReturn,
+    BalanceArithmetic,
+    NonStoreTransfer,
+    EventFields,
 }
 
 /// A default code for each linter category (as long as only one code per category is used, no other
@@ -116,6 +137,36 @@ pub fn known_filters() -> (E::AttributeName_, Vec<WarningFilter>) {
                 LINTER_DEFAULT_DIAG_CODE,
                 Some(COLLECTION_EQUALITY_FILTER_NAME),
             ),
+            WarningFilter::code(
+                Some(LINT_WARNING_PREFIX),
+                LinterDiagCategory::CapabilityByValue as u8,
+                LINTER_DEFAULT_DIAG_CODE,
+                Some(CAPABILITY_BY_VALUE_FILTER_NAME),
+            ),
+            WarningFilter::code(
+                Some(LINT_WARNING_PREFIX),
+                LinterDiagCategory::PublicEntryReturn as u8,
+                LINTER_DEFAULT_DIAG_CODE,
+                Some(PUBLIC_ENTRY_RETURN_FILTER_NAME),
+            ),
+            WarningFilter::code(
+                Some(LINT_WARNING_PREFIX),
+                LinterDiagCategory::BalanceArithmetic as u8,
+                LINTER_DEFAULT_DIAG_CODE,
+                Some(BALANCE_ARITHMETIC_FILTER_NAME),
+            ),
+            WarningFilter::code(
+                Some(LINT_WARNING_PREFIX),
+                LinterDiagCategory::NonStoreTransfer as u8,
+                LINTER_DEFAULT_DIAG_CODE,
+                Some(NON_STORE_TRANSFER_FILTER_NAME),
+            ),
+            WarningFilter::code(
+                Some(LINT_WARNING_PREFIX),
+                LinterDiagCategory::EventFields as u8,
+                LINTER_DEFAULT_DIAG_CODE,
+                Some(EVENT_FIELDS_FILTER_NAME),
+            ),
         ],
     )
 }
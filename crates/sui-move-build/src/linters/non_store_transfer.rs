@@ -0,0 +1,134 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! This analysis flags public functions transferring objects that have the key ability but not the
+//! store ability to an address passed by their caller. Only the module defining such objects can
+//! transfer them, which is usually the reason they lack the store ability, but a public function
+//! transferring them to an arbitrary address lets any other module transfer them as well.
+
+use std::collections::BTreeSet;
+
+use move_compiler::{
+    diag,
+    diagnostics::codes::{custom, DiagnosticInfo, Severity},
+    expansion::ast::{ModuleIdent, Visibility},
+    naming::ast as N,
+    parser::ast::{Ability_, FunctionName},
+    shared::{program_info::TypingProgramInfo, CompilationEnv, Identifier},
+    typing::{
+        ast as T,
+        visitor::{TypingVisitorConstructor, TypingVisitorContext},
+    },
+};
+
+use super::{
+    LinterDiagCategory, LINTER_DEFAULT_DIAG_CODE, LINT_WARNING_PREFIX, SUI_PKG_NAME, TRANSFER_FUN,
+    TRANSFER_MOD_NAME,
+};
+
+const NON_STORE_TRANSFER_DIAG: DiagnosticInfo = custom(
+    LINT_WARNING_PREFIX,
+    Severity::Warning,
+    LinterDiagCategory::NonStoreTransfer as u8,
+    LINTER_DEFAULT_DIAG_CODE,
+    "transfer of an object without store to an arbitrary address",
+);
+
+pub struct NonStoreTransferVisitor;
+pub struct Context<'a> {
+    env: &'a mut CompilationEnv,
+    /// The public function being visited, and its parameters
+    public_function: Option<(FunctionName, BTreeSet<N::Var_>)>,
+}
+
+impl TypingVisitorConstructor for NonStoreTransferVisitor {
+    type Context<'a> = Context<'a>;
+
+    fn context<'a>(
+        env: &'a mut CompilationEnv,
+        _program_info: &'a TypingProgramInfo,
+        _program: &T::Program_,
+    ) -> Self::Context<'a> {
+        Context {
+            env,
+            public_function: None,
+        }
+    }
+}
+
+impl TypingVisitorContext for Context<'_> {
+    fn visit_function_custom(
+        &mut self,
+        _module: Option<ModuleIdent>,
+        function_name: FunctionName,
+        fdef: &mut T::Function,
+    ) -> bool {
+        if !matches!(fdef.visibility, Visibility::Public(_)) {
+            self.public_function = None;
+            // private and friend functions cannot be called by arbitrary modules
+            return true;
+        }
+        let params = fdef
+            .signature
+            .parameters
+            .iter()
+            .map(|(_, var, _)| var.value)
+            .collect();
+        self.public_function = Some((function_name, params));
+        false
+    }
+
+    fn visit_exp_custom(&mut self, exp: &mut T::Exp) -> bool {
+        use T::UnannotatedExp_ as E;
+        let Some((function_name, params)) = &self.public_function else {
+            return false;
+        };
+        let E::ModuleCall(fun) = &exp.exp.value else {
+            return false;
+        };
+        if !fun.module.value.is(SUI_PKG_NAME, TRANSFER_MOD_NAME)
+            || fun.name.value().as_str() != TRANSFER_FUN
+        {
+            return false;
+        }
+        let Some(N::Type_::Apply(Some(abilities), sp!(_, N::TypeName_::ModuleType(_, sname)), _)) =
+            fun.type_arguments.first().map(|t| &t.value)
+        else {
+            return false;
+        };
+        if !abilities.has_ability_(Ability_::Key) || abilities.has_ability_(Ability_::Store) {
+            return false;
+        }
+        let E::ExpList(args) = &fun.arguments.exp.value else {
+            return false;
+        };
+        let Some(T::ExpListItem::Single(recipient, _)) = args.get(1) else {
+            return false;
+        };
+        let (E::Move { var, .. } | E::Copy { var, .. } | E::Use(var)) = &recipient.exp.value else {
+            return false;
+        };
+        if params.contains(&var.value) {
+            let msg = format!(
+                "An object of type '{sname}' is transferred to an address chosen by the caller \
+                 of the public function '{function_name}'"
+            );
+            let mut d = diag!(NON_STORE_TRANSFER_DIAG, (recipient.exp.loc, msg));
+            d.add_note(format!(
+                "Objects without the 'store' ability can only be transferred by the module \
+                 defining them. This function lets any module transfer objects of type \
+                 '{sname}', as if it had the 'store' ability"
+            ));
+            self.env.add_diag(d);
+        }
+        false
+    }
+
+    fn add_warning_filter_scope(&mut self, filter: move_compiler::diagnostics::WarningFilters) {
+        self.env.add_warning_filter_scope(filter)
+    }
+
+    fn pop_warning_filter_scope(&mut self) {
+        self.env.pop_warning_filter_scope()
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! This analysis flags 'public entry' functions that return values. A 'public' function can
+//! already be called from programmable transactions, where its results can be used by later
+//! commands, so 'entry' only adds restrictions on how such a function can be called.
+
+use move_compiler::{
+    diag,
+    diagnostics::codes::{custom, DiagnosticInfo, Severity},
+    expansion::ast::{ModuleIdent, Visibility},
+    naming::ast as N,
+    parser::ast::FunctionName,
+    shared::{program_info::TypingProgramInfo, CompilationEnv},
+    typing::{
+        ast as T,
+        visitor::{TypingVisitorConstructor, TypingVisitorContext},
+    },
+};
+
+use super::{LinterDiagCategory, LINTER_DEFAULT_DIAG_CODE, LINT_WARNING_PREFIX};
+
+const PUBLIC_ENTRY_RETURN_DIAG: DiagnosticInfo = custom(
+    LINT_WARNING_PREFIX,
+    Severity::Warning,
+    LinterDiagCategory::PublicEntryReturn as u8,
+    LINTER_DEFAULT_DIAG_CODE,
+    "'public entry' function returning a value",
+);
+
+pub struct PublicEntryReturnVisitor;
+pub struct Context<'a> {
+    env: &'a mut CompilationEnv,
+}
+
+impl TypingVisitorConstructor for PublicEntryReturnVisitor {
+    type Context<'a> = Context<'a>;
+
+    fn context<'a>(
+        env: &'a mut CompilationEnv,
+        _program_info: &'a TypingProgramInfo,
+        _program: &T::Program_,
+    ) -> Self::Context<'a> {
+        Context { env }
+    }
+}
+
+impl TypingVisitorContext for Context<'_> {
+    fn visit_function_custom(
+        &mut self,
+        _module: Option<ModuleIdent>,
+        function_name: FunctionName,
+        fdef: &mut T::Function,
+    ) -> bool {
+        let return_type = &fdef.signature.return_type;
+        if matches!(fdef.visibility, Visibility::Public(_))
+            && fdef.entry.is_some()
+            && !matches!(return_type.value, N::Type_::Unit)
+        {
+            let msg = format!("The 'public entry' function '{function_name}' returns a value");
+            let mut d = diag!(PUBLIC_ENTRY_RETURN_DIAG, (return_type.loc, msg));
+            d.add_note(
+                "'public' functions can be called from programmable transactions, which can use \
+                 their results in later commands. Consider removing 'entry' from this function",
+            );
+            self.env.add_diag(d);
+        }
+        // the function body does not need to be visited
+        true
+    }
+
+    fn add_warning_filter_scope(&mut self, filter: move_compiler::diagnostics::WarningFilters) {
+        self.env.add_warning_filter_scope(filter)
+    }
+
+    fn pop_warning_filter_scope(&mut self) {
+        self.env.pop_warning_filter_scope()
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::{Path, PathBuf};
+
+use move_command_line_common::testing::read_env_update_baseline;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::id::{ID, UID};
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::transaction::Argument;
+
+use crate::{bindgen, BuildConfig};
+
+// The bindings of the test package, checked in so that they are compiled with the tests.
+#[allow(dead_code)]
+mod generated {
+    include!("data/bindgen.rs");
+}
+
+fn test_package_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src")
+        .join("unit_tests")
+        .join("data")
+        .join("bindgen")
+}
+
+fn test_package_bindings() -> String {
+    let pkg = BuildConfig::new_for_testing()
+        .build(test_package_path())
+        .unwrap();
+    bindgen::generate(&pkg)
+}
+
+#[test]
+fn compiled_bindings_are_up_to_date() {
+    let bindings = test_package_bindings();
+    let path = test_package_path().with_extension("rs");
+    if read_env_update_baseline() {
+        std::fs::write(path, bindings).unwrap();
+        return;
+    }
+    assert_eq!(
+        bindings,
+        std::fs::read_to_string(path).unwrap(),
+        "Run with UPDATE_BASELINE=1 to update the compiled bindings"
+    );
+}
+
+#[test]
+fn use_compiled_bindings() {
+    use generated::counter;
+
+    let package = ObjectID::random();
+    let mut builder = ProgrammableTransactionBuilder::new();
+    let object = counter::create(&mut builder, package, 1, SuiAddress::ZERO).unwrap();
+    counter::increment(&mut builder, package, object, 2).unwrap();
+    let [_, _] = counter::info(&mut builder, package, object).unwrap();
+    assert!(matches!(object, Argument::Result(0)));
+
+    // Returned objects decode into the generated structs.
+    let created = counter::Counter {
+        id: UID::new(ObjectID::random()),
+        value: 1,
+        owner: SuiAddress::ZERO,
+        label: Some(b"label".to_vec()),
+    };
+    let return_values = vec![bcs::to_bytes(&created).unwrap()];
+    assert_eq!(counter::decode_create(&return_values).unwrap(), created);
+    assert_eq!(
+        counter::Counter::from_bcs(&return_values[0]).unwrap(),
+        created
+    );
+    counter::decode_create(&[]).unwrap_err();
+
+    let id = ID::new(ObjectID::random());
+    let return_values = vec![bcs::to_bytes(&3u64).unwrap(), bcs::to_bytes(&id).unwrap()];
+    assert_eq!(counter::decode_info(&return_values).unwrap(), (3, id));
+    assert_eq!(
+        counter::decode_item::<u64>(&[bcs::to_bytes(&4u64).unwrap()]).unwrap(),
+        4
+    );
+}
+
+#[test]
+fn generate_bindings() {
+    let bindings = test_package_bindings();
+
+    assert!(bindings.contains("pub mod counter {"));
+    // Well known types map to their Rust equivalents
+    assert!(bindings.contains("pub id: ::sui_types::id::UID,"));
+    assert!(bindings.contains("pub owner: ::sui_types::base_types::SuiAddress,"));
+    assert!(bindings.contains("pub label: Option<Vec<u8>>,"));
+    // Phantom type parameters are left out, structs of dependencies are generated
+    assert!(bindings.contains("pub struct Vault<T0> {"));
+    assert!(bindings.contains("pub fees: super::deps::sui::balance::Balance,"));
+    assert!(bindings.contains("pub mod balance {"));
+    assert!(bindings.contains("pub value: u64,"));
+
+    // Pure arguments are typed, the transaction context is left out
+    assert!(bindings.contains(
+        "        arg0: u64,
+        arg1: ::sui_types::base_types::SuiAddress,
+    ) -> ::anyhow::Result<::sui_types::transaction::Argument> {"
+    ));
+    assert!(bindings.contains(
+        "    pub fn increment(
+        builder: &mut ::sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder,
+        package: ::sui_types::base_types::ObjectID,
+        arg0: ::sui_types::transaction::Argument,
+        arg1: u64,
+    ) -> ::anyhow::Result<()> {"
+    ));
+    assert!(bindings.contains("pub fn share("));
+    // Several results are returned as nested results
+    assert!(bindings.contains("-> ::anyhow::Result<[::sui_types::transaction::Argument; 2]> {"));
+    assert!(bindings.contains("type_arguments: [::move_core_types::language_storage::TypeTag; 2],"));
+    // Private functions are not callable
+    assert!(!bindings.contains("pub fn reset("));
+    // Returned values can be decoded
+    assert!(bindings.contains(
+        "    pub fn decode_info(
+        return_values: &[Vec<u8>],
+    ) -> ::anyhow::Result<(u64, ::sui_types::id::ID)> {"
+    ));
+    assert!(bindings.contains("pub fn decode_item<T0: ::serde::de::DeserializeOwned>("));
+    assert!(!bindings.contains("pub fn decode_increment("));
+}
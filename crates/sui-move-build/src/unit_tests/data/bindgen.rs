@@ -0,0 +1,273 @@
+// Rust bindings of the `Bindgen` Move package, generated by `sui move bindgen`.
+// Do not edit.
+
+#[allow(dead_code)]
+fn nested_results<const N: usize>(result: ::sui_types::transaction::Argument) -> [::sui_types::transaction::Argument; N] {
+    let ::sui_types::transaction::Argument::Result(command) = result else {
+        unreachable!("A Move call returns the result of its command")
+    };
+    ::std::array::from_fn(|i| ::sui_types::transaction::Argument::NestedResult(command, i as u16))
+}
+
+#[allow(clippy::all, non_camel_case_types, non_snake_case)]
+pub mod counter {
+    /// Move struct `counter::Counter`.
+    #[derive(Clone, Debug, PartialEq, Eq, ::serde::Serialize, ::serde::Deserialize)]
+    pub struct Counter {
+        pub id: ::sui_types::id::UID,
+        pub value: u64,
+        pub owner: ::sui_types::base_types::SuiAddress,
+        pub label: Option<Vec<u8>>,
+    }
+
+    impl Counter {
+        pub const MODULE: &'static str = "counter";
+        pub const NAME: &'static str = "Counter";
+
+        /// The type of the struct defined by the package originally published at `package`.
+        pub fn struct_tag(package: ::sui_types::base_types::ObjectID, type_params: Vec<::move_core_types::language_storage::TypeTag>) -> ::move_core_types::language_storage::StructTag {
+            ::move_core_types::language_storage::StructTag {
+                address: package.into(),
+                module: ::move_core_types::ident_str!("counter").to_owned(),
+                name: ::move_core_types::ident_str!("Counter").to_owned(),
+                type_params,
+            }
+        }
+
+        /// Decode the BCS contents of a value of the struct, like an object or an event.
+        pub fn from_bcs(bytes: &[u8]) -> Result<Self, ::bcs::Error>
+        where
+            Self: ::serde::de::DeserializeOwned,
+        {
+            ::bcs::from_bytes(bytes)
+        }
+    }
+
+    /// Move struct `counter::Vault`.
+    #[derive(Clone, Debug, PartialEq, Eq, ::serde::Serialize, ::serde::Deserialize)]
+    pub struct Vault<T0> {
+        pub id: ::sui_types::id::UID,
+        pub item: T0,
+        pub fees: super::deps::sui::balance::Balance,
+    }
+
+    impl<T0> Vault<T0> {
+        pub const MODULE: &'static str = "counter";
+        pub const NAME: &'static str = "Vault";
+
+        /// The type of the struct defined by the package originally published at `package`.
+        pub fn struct_tag(package: ::sui_types::base_types::ObjectID, type_params: Vec<::move_core_types::language_storage::TypeTag>) -> ::move_core_types::language_storage::StructTag {
+            ::move_core_types::language_storage::StructTag {
+                address: package.into(),
+                module: ::move_core_types::ident_str!("counter").to_owned(),
+                name: ::move_core_types::ident_str!("Vault").to_owned(),
+                type_params,
+            }
+        }
+
+        /// Decode the BCS contents of a value of the struct, like an object or an event.
+        pub fn from_bcs(bytes: &[u8]) -> Result<Self, ::bcs::Error>
+        where
+            Self: ::serde::de::DeserializeOwned,
+        {
+            ::bcs::from_bytes(bytes)
+        }
+    }
+
+    /// Append a call to `counter::create` to `builder`.
+    pub fn create(
+        builder: &mut ::sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder,
+        package: ::sui_types::base_types::ObjectID,
+        arg0: u64,
+        arg1: ::sui_types::base_types::SuiAddress,
+    ) -> ::anyhow::Result<::sui_types::transaction::Argument> {
+        let arguments = vec![builder.pure(arg0)?, builder.pure(arg1)?];
+        let result = builder.programmable_move_call(
+            package,
+            ::move_core_types::ident_str!("counter").to_owned(),
+            ::move_core_types::ident_str!("create").to_owned(),
+            vec![],
+            arguments,
+        );
+        Ok(result)
+    }
+
+    /// Decode the BCS values returned by a call to `counter::create`, like the return values of a dev inspect.
+    pub fn decode_create(
+        return_values: &[Vec<u8>],
+    ) -> ::anyhow::Result<super::counter::Counter> {
+        let [value0] = return_values else {
+            ::anyhow::bail!(
+                "Expected 1 return values, got {}",
+                return_values.len()
+            );
+        };
+        Ok(::bcs::from_bytes(value0)?)
+    }
+
+    /// Append a call to `counter::increment` to `builder`.
+    pub fn increment(
+        builder: &mut ::sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder,
+        package: ::sui_types::base_types::ObjectID,
+        arg0: ::sui_types::transaction::Argument,
+        arg1: u64,
+    ) -> ::anyhow::Result<()> {
+        let arguments = vec![arg0, builder.pure(arg1)?];
+        let _result = builder.programmable_move_call(
+            package,
+            ::move_core_types::ident_str!("counter").to_owned(),
+            ::move_core_types::ident_str!("increment").to_owned(),
+            vec![],
+            arguments,
+        );
+        Ok(())
+    }
+
+    /// Append a call to `counter::info` to `builder`.
+    pub fn info(
+        builder: &mut ::sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder,
+        package: ::sui_types::base_types::ObjectID,
+        arg0: ::sui_types::transaction::Argument,
+    ) -> ::anyhow::Result<[::sui_types::transaction::Argument; 2]> {
+        let arguments = vec![arg0];
+        let result = builder.programmable_move_call(
+            package,
+            ::move_core_types::ident_str!("counter").to_owned(),
+            ::move_core_types::ident_str!("info").to_owned(),
+            vec![],
+            arguments,
+        );
+        Ok(super::nested_results(result))
+    }
+
+    /// Decode the BCS values returned by a call to `counter::info`, like the return values of a dev inspect.
+    pub fn decode_info(
+        return_values: &[Vec<u8>],
+    ) -> ::anyhow::Result<(u64, ::sui_types::id::ID)> {
+        let [value0, value1] = return_values else {
+            ::anyhow::bail!(
+                "Expected 2 return values, got {}",
+                return_values.len()
+            );
+        };
+        Ok((
+            ::bcs::from_bytes(value0)?,
+            ::bcs::from_bytes(value1)?,
+        ))
+    }
+
+    /// Append a call to `counter::item` to `builder`.
+    pub fn item(
+        builder: &mut ::sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder,
+        package: ::sui_types::base_types::ObjectID,
+        type_arguments: [::move_core_types::language_storage::TypeTag; 2],
+        arg0: ::sui_types::transaction::Argument,
+    ) -> ::anyhow::Result<::sui_types::transaction::Argument> {
+        let arguments = vec![arg0];
+        let result = builder.programmable_move_call(
+            package,
+            ::move_core_types::ident_str!("counter").to_owned(),
+            ::move_core_types::ident_str!("item").to_owned(),
+            type_arguments.to_vec(),
+            arguments,
+        );
+        Ok(result)
+    }
+
+    /// Decode the BCS values returned by a call to `counter::item`, like the return values of a dev inspect.
+    pub fn decode_item<T0: ::serde::de::DeserializeOwned>(
+        return_values: &[Vec<u8>],
+    ) -> ::anyhow::Result<T0> {
+        let [value0] = return_values else {
+            ::anyhow::bail!(
+                "Expected 1 return values, got {}",
+                return_values.len()
+            );
+        };
+        Ok(::bcs::from_bytes(value0)?)
+    }
+
+    /// Append a call to `counter::share` to `builder`.
+    pub fn share(
+        builder: &mut ::sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder,
+        package: ::sui_types::base_types::ObjectID,
+        arg0: u64,
+    ) -> ::anyhow::Result<()> {
+        let arguments = vec![builder.pure(arg0)?];
+        let _result = builder.programmable_move_call(
+            package,
+            ::move_core_types::ident_str!("counter").to_owned(),
+            ::move_core_types::ident_str!("share").to_owned(),
+            vec![],
+            arguments,
+        );
+        Ok(())
+    }
+}
+
+#[allow(clippy::all, non_camel_case_types, non_snake_case)]
+pub mod deps {
+    pub mod sui {
+        pub mod balance {
+            /// Move struct `0x2::balance::Balance`.
+            #[derive(Clone, Debug, PartialEq, Eq, ::serde::Serialize, ::serde::Deserialize)]
+            pub struct Balance {
+                pub value: u64,
+            }
+
+            impl Balance {
+                pub const MODULE: &'static str = "balance";
+                pub const NAME: &'static str = "Balance";
+
+                /// The type of the struct.
+                pub fn struct_tag(type_params: Vec<::move_core_types::language_storage::TypeTag>) -> ::move_core_types::language_storage::StructTag {
+                    ::move_core_types::language_storage::StructTag {
+                        address: ::move_core_types::account_address::AccountAddress::from_hex_literal("0x2").unwrap(),
+                        module: ::move_core_types::ident_str!("balance").to_owned(),
+                        name: ::move_core_types::ident_str!("Balance").to_owned(),
+                        type_params,
+                    }
+                }
+
+                /// Decode the BCS contents of a value of the struct, like an object or an event.
+                pub fn from_bcs(bytes: &[u8]) -> Result<Self, ::bcs::Error>
+                where
+                    Self: ::serde::de::DeserializeOwned,
+                {
+                    ::bcs::from_bytes(bytes)
+                }
+            }
+        }
+
+        pub mod sui {
+            /// Move struct `0x2::sui::SUI`.
+            #[derive(Clone, Debug, PartialEq, Eq, ::serde::Serialize, ::serde::Deserialize)]
+            pub struct SUI {
+                pub dummy_field: bool,
+            }
+
+            impl SUI {
+                pub const MODULE: &'static str = "sui";
+                pub const NAME: &'static str = "SUI";
+
+                /// The type of the struct.
+                pub fn struct_tag(type_params: Vec<::move_core_types::language_storage::TypeTag>) -> ::move_core_types::language_storage::StructTag {
+                    ::move_core_types::language_storage::StructTag {
+                        address: ::move_core_types::account_address::AccountAddress::from_hex_literal("0x2").unwrap(),
+                        module: ::move_core_types::ident_str!("sui").to_owned(),
+                        name: ::move_core_types::ident_str!("SUI").to_owned(),
+                        type_params,
+                    }
+                }
+
+                /// Decode the BCS contents of a value of the struct, like an object or an event.
+                pub fn from_bcs(bytes: &[u8]) -> Result<Self, ::bcs::Error>
+                where
+                    Self: ::serde::de::DeserializeOwned,
+                {
+                    ::bcs::from_bytes(bytes)
+                }
+            }
+        }
+    }
+}
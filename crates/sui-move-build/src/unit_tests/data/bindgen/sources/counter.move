@@ -0,0 +1,48 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module bindgen::counter {
+    use std::option::{Self, Option};
+    use sui::balance::Balance;
+    use sui::object::{Self, ID, UID};
+    use sui::sui::SUI;
+    use sui::transfer;
+    use sui::tx_context::TxContext;
+
+    struct Counter has key, store {
+        id: UID,
+        value: u64,
+        owner: address,
+        label: Option<vector<u8>>,
+    }
+
+    struct Vault<T: store, phantom C> has key {
+        id: UID,
+        item: T,
+        fees: Balance<SUI>,
+    }
+
+    public fun create(value: u64, owner: address, ctx: &mut TxContext): Counter {
+        Counter { id: object::new(ctx), value, owner, label: option::none() }
+    }
+
+    public entry fun share(value: u64, ctx: &mut TxContext) {
+        transfer::share_object(create(value, @0x0, ctx))
+    }
+
+    public fun increment(counter: &mut Counter, by: u64) {
+        counter.value = counter.value + by
+    }
+
+    public fun info(counter: &Counter): (u64, ID) {
+        (counter.value, object::id(counter))
+    }
+
+    public fun item<T: store, C>(vault: &Vault<T, C>): &T {
+        &vault.item
+    }
+
+    fun reset(counter: &mut Counter) {
+        counter.value = 0
+    }
+}
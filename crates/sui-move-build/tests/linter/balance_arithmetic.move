@@ -0,0 +1,39 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module 0x42::test {
+    use sui::balance::{Self, Balance};
+
+    public fun sub_bad<T>(b: &Balance<T>, amount: u64): u64 {
+        balance::value(b) - amount
+    }
+
+    public fun add_bad<T>(b1: &Balance<T>, b2: &Balance<T>): u64 {
+        balance::value(b1) + balance::value(b2)
+    }
+
+    public fun compare_ok<T>(b: &Balance<T>, amount: u64): bool {
+        balance::value(b) >= amount
+    }
+
+    public fun split_ok<T>(b: &mut Balance<T>, amount: u64): Balance<T> {
+        balance::split(b, amount)
+    }
+
+    #[lint_allow(balance_arithmetic)]
+    public fun mul_suppressed<T>(b: &Balance<T>, factor: u64): u64 {
+        balance::value(b) * factor
+    }
+
+    public fun local_bad<T>(b: &Balance<T>, amount: u64): u64 {
+        let value = balance::value(b);
+        value + amount
+    }
+
+    public fun shadowed_ok<T>(b: &Balance<T>, amount: u64): u64 {
+        let value = balance::value(b);
+        assert!(value >= amount, 0);
+        let value = amount;
+        value * 2
+    }
+}
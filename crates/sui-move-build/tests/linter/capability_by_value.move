@@ -0,0 +1,36 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#[allow(unused_field)]
+module 0x42::test {
+    use sui::object::UID;
+
+    struct AdminCap has key, store {
+        id: UID
+    }
+
+    struct Config has key {
+        id: UID
+    }
+
+    public fun by_value_bad(cap: AdminCap): AdminCap {
+        cap
+    }
+
+    public fun by_ref_ok(_cap: &AdminCap) {}
+
+    public fun by_mut_ref_ok(_cap: &mut AdminCap) {}
+
+    public fun not_a_capability_ok(config: Config): Config {
+        config
+    }
+
+    public(friend) fun friend_ok(cap: AdminCap): AdminCap {
+        cap
+    }
+
+    #[lint_allow(capability_by_value)]
+    public fun by_value_suppressed(cap: AdminCap): AdminCap {
+        cap
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#[allow(unused_field)]
+module 0x42::test {
+    use sui::event;
+
+    struct Event has copy, drop {
+        value: u64,
+    }
+
+    struct GenericEvent<T> has copy, drop {
+        value: T,
+    }
+
+    struct ConstrainedEvent<T: copy + drop> has copy, drop {
+        value: T,
+    }
+
+    public fun emit_ok(value: u64) {
+        event::emit(Event { value })
+    }
+
+    public fun emit_generic_copy_instantiation_ok(value: u64) {
+        event::emit(GenericEvent { value })
+    }
+
+    public fun emit_generic_copy_param_ok<T: copy + drop>(value: T) {
+        event::emit(GenericEvent { value })
+    }
+
+    public fun emit_nested_copy_instantiation_ok(value: u64) {
+        event::emit(GenericEvent { value: GenericEvent { value } })
+    }
+
+    public fun emit_constrained_ok(value: u64) {
+        event::emit(ConstrainedEvent { value })
+    }
+}
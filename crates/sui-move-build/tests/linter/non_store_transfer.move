@@ -0,0 +1,42 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+#[allow(unused_field)]
+module 0x42::test {
+    use sui::object::{Self, UID};
+    use sui::transfer;
+    use sui::tx_context::TxContext;
+
+    struct Ticket has key {
+        id: UID
+    }
+
+    struct Item has key, store {
+        id: UID
+    }
+
+    public fun transfer_ticket_bad(ticket: Ticket, recipient: address) {
+        transfer::transfer(ticket, recipient)
+    }
+
+    public fun mint_ticket_bad(recipient: address, ctx: &mut TxContext) {
+        transfer::transfer(Ticket { id: object::new(ctx) }, recipient)
+    }
+
+    public fun transfer_item_ok(item: Item, recipient: address) {
+        transfer::public_transfer(item, recipient)
+    }
+
+    public fun fixed_recipient_ok(ctx: &mut TxContext) {
+        transfer::transfer(Ticket { id: object::new(ctx) }, @0x42)
+    }
+
+    public(friend) fun friend_transfer_ok(ticket: Ticket, recipient: address) {
+        transfer::transfer(ticket, recipient)
+    }
+
+    #[lint_allow(non_store_transfer)]
+    public fun transfer_ticket_suppressed(ticket: Ticket, recipient: address) {
+        transfer::transfer(ticket, recipient)
+    }
+}
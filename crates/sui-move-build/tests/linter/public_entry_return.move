@@ -0,0 +1,23 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module 0x42::test {
+    public entry fun returns_value_bad(x: u64): u64 {
+        x
+    }
+
+    public entry fun returns_nothing_ok(_x: u64) {}
+
+    public fun not_entry_ok(x: u64): u64 {
+        x
+    }
+
+    entry fun private_entry_ok(x: u64): u64 {
+        x
+    }
+
+    #[lint_allow(public_entry_return)]
+    public entry fun returns_value_suppressed(x: u64): u64 {
+        x
+    }
+}
@@ -19,10 +19,12 @@ use move_compiler::{
 };
 
 use sui_move_build::linters::{
+    balance_arithmetic::BalanceArithmeticVisitor, capability_by_value::CapabilityByValueVisitor,
     coin_field::CoinFieldVisitor, collection_equality::CollectionEqualityVisitor,
-    custom_state_change::CustomStateChangeVerifier, freeze_wrapped::FreezeWrappedVisitor,
-    known_filters, self_transfer::SelfTransferVerifier, share_owned::ShareOwnedVerifier,
-    LINT_WARNING_PREFIX,
+    custom_state_change::CustomStateChangeVerifier, event_fields::EventFieldsVisitor,
+    freeze_wrapped::FreezeWrappedVisitor, known_filters,
+    non_store_transfer::NonStoreTransferVisitor, public_entry_return::PublicEntryReturnVisitor,
+    self_transfer::SelfTransferVerifier, share_owned::ShareOwnedVerifier, LINT_WARNING_PREFIX,
 };
 
 const SUI_FRAMEWORK_PATH: &str = "../sui-framework/packages/sui-framework";
@@ -71,6 +73,11 @@ fn run_tests(path: &Path) -> anyhow::Result<()> {
         CoinFieldVisitor.visitor(),
         FreezeWrappedVisitor.visitor(),
         CollectionEqualityVisitor.visitor(),
+        CapabilityByValueVisitor.visitor(),
+        PublicEntryReturnVisitor.visitor(),
+        BalanceArithmeticVisitor.visitor(),
+        NonStoreTransferVisitor.visitor(),
+        EventFieldsVisitor.visitor(),
     ];
     let (filter_attr_name, filters) = known_filters_for_test();
     let (files, comments_and_compiler_res) = Compiler::from_files(
@@ -118,7 +125,7 @@ fn run_tests(path: &Path) -> anyhow::Result<()> {
             );
             anyhow::bail!(add_update_baseline_fix(msg));
         }
-    } else {
+    } else if has_diags {
         let msg = format!("Unexpected output :\n{}", rendered_diags);
         anyhow::bail!(add_update_baseline_fix(msg));
     }
@@ -0,0 +1,40 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::Parser;
+use move_cli::base;
+use move_package::BuildConfig as MoveBuildConfig;
+use std::{fs, path::PathBuf};
+use sui_move_build::{bindgen, BuildConfig};
+
+/// Generate Rust bindings for the structs and functions of the package
+#[derive(Parser)]
+#[group(id = "sui-move-bindgen")]
+pub struct Bindgen {
+    /// File to write the bindings to. They are printed to stdout if not set.
+    #[clap(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+impl Bindgen {
+    pub fn execute(
+        &self,
+        path: Option<PathBuf>,
+        build_config: MoveBuildConfig,
+    ) -> anyhow::Result<()> {
+        let rerooted_path = base::reroot_path(path)?;
+        let pkg = BuildConfig {
+            config: build_config,
+            run_bytecode_verifier: true,
+            print_diags_to_stderr: true,
+            lint: false,
+        }
+        .build(rerooted_path)?;
+        let bindings = bindgen::generate(&pkg);
+        match &self.output {
+            Some(output) => fs::write(output, bindings)?,
+            None => print!("{bindings}"),
+        }
+        Ok(())
+    }
+}
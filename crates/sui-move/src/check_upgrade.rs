@@ -0,0 +1,528 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail};
+use clap::{Parser, ValueEnum};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{AbilitySet, FunctionDefinitionIndex, StructDefinitionIndex, Visibility},
+    normalized, CompiledModule,
+};
+use move_cli::base;
+use move_compiler::compiled_unit::{CompiledUnitEnum, NamedCompiledModule};
+use move_core_types::identifier::Identifier;
+use move_ir_types::location::Loc;
+use move_package::BuildConfig as MoveBuildConfig;
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+use sui_adapter::programmable_transactions::execution;
+use sui_move_build::{BuildConfig, CompiledPackage};
+use sui_types::{base_types::ObjectID, move_package::UpgradePolicy};
+
+use crate::build::resolve_lock_file_path;
+
+const BYTECODE_MODULES_DIR: &str = "bytecode_modules";
+
+/// The upgrade policies that can be checked, named as in `sui client upgrade`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CheckUpgradePolicy {
+    #[value(name = "compatible")]
+    Compatible,
+    #[value(name = "additive")]
+    Additive,
+    #[value(name = "dep_only")]
+    DepOnly,
+}
+
+impl From<CheckUpgradePolicy> for UpgradePolicy {
+    fn from(policy: CheckUpgradePolicy) -> Self {
+        match policy {
+            CheckUpgradePolicy::Compatible => UpgradePolicy::Compatible,
+            CheckUpgradePolicy::Additive => UpgradePolicy::Additive,
+            CheckUpgradePolicy::DepOnly => UpgradePolicy::DepOnly,
+        }
+    }
+}
+
+impl fmt::Display for CheckUpgradePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CheckUpgradePolicy::Compatible => "compatible",
+            CheckUpgradePolicy::Additive => "additive",
+            CheckUpgradePolicy::DepOnly => "dep_only",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Check that the package can be published as an upgrade of a previous version of it, without
+/// submitting the upgrade.
+#[derive(Parser)]
+#[group(id = "sui-move-check-upgrade")]
+pub struct CheckUpgrade {
+    /// The upgrade policy to check the package against.
+    #[clap(long, value_enum, default_value_t = CheckUpgradePolicy::Compatible)]
+    pub policy: CheckUpgradePolicy,
+    /// Directory of a previous build of the package: either its `build/<package name>`
+    /// directory, or the `bytecode_modules` directory inside of it.
+    #[clap(
+        long,
+        conflicts_with = "package_id",
+        required_unless_present = "package_id"
+    )]
+    pub previous_build: Option<PathBuf>,
+    /// ID of the on-chain package to check the upgrade against. Only supported through the
+    /// `sui` binary, which uses the active environment of the client to fetch the package.
+    #[clap(long)]
+    pub package_id: Option<ObjectID>,
+    /// Sets the file storing the state of our user accounts, used to fetch the package given by
+    /// `--package-id`.
+    #[clap(long = "client.config")]
+    pub client_config: Option<PathBuf>,
+}
+
+impl CheckUpgrade {
+    pub fn execute(
+        &self,
+        path: Option<PathBuf>,
+        build_config: MoveBuildConfig,
+    ) -> anyhow::Result<()> {
+        let Some(previous_build) = &self.previous_build else {
+            bail!(
+                "Checking an upgrade against an on-chain package requires a Sui client, \
+                 run `sui move check-upgrade --package-id` instead"
+            );
+        };
+        let previous_modules = read_previous_build(previous_build)?;
+        self.execute_with_previous(path, build_config, &previous_modules)
+    }
+
+    /// Builds the package at `path` and checks it against the modules of the previous version of
+    /// the package, printing every incompatible change.
+    pub fn execute_with_previous(
+        &self,
+        path: Option<PathBuf>,
+        build_config: MoveBuildConfig,
+        previous_modules: &[CompiledModule],
+    ) -> anyhow::Result<()> {
+        let rerooted_path = base::reroot_path(path.clone())?;
+        let build_config = resolve_lock_file_path(build_config, path)?;
+        let package = BuildConfig {
+            config: build_config,
+            run_bytecode_verifier: true,
+            print_diags_to_stderr: true,
+            lint: false,
+        }
+        .build(rerooted_path)?;
+
+        let incompatibilities = check_upgrade(self.policy.into(), previous_modules, &package)?;
+        if incompatibilities.is_empty() {
+            println!("Upgrade is compatible with the `{}` policy", self.policy);
+            return Ok(());
+        }
+        for incompatibility in &incompatibilities {
+            eprintln!("{incompatibility}");
+        }
+        bail!(
+            "Upgrade is not compatible with the `{}` policy: found {} incompatible change(s)",
+            self.policy,
+            incompatibilities.len()
+        )
+    }
+}
+
+/// Reads the root modules of a previous build of a package.
+pub fn read_previous_build(path: &Path) -> anyhow::Result<Vec<CompiledModule>> {
+    let bytecode_dir = if path.join(BYTECODE_MODULES_DIR).is_dir() {
+        path.join(BYTECODE_MODULES_DIR)
+    } else {
+        path.to_path_buf()
+    };
+    let mut modules = vec![];
+    for entry in fs::read_dir(&bytecode_dir)? {
+        let file = entry?.path();
+        if file.extension().map_or(true, |ext| ext != "mv") {
+            continue;
+        }
+        let bytes = fs::read(&file)?;
+        let module = CompiledModule::deserialize_with_defaults(&bytes)
+            .map_err(|e| anyhow!("Failed to deserialize module {}: {e}", file.display()))?;
+        modules.push(module);
+    }
+    if modules.is_empty() {
+        bail!("No compiled modules found in {}", bytecode_dir.display());
+    }
+    Ok(modules)
+}
+
+/// An item of the previous version of a package that the upgrade breaks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Incompatibility {
+    pub module: String,
+    /// The struct or function the incompatibility is about, if it is not about the whole module.
+    pub item: Option<String>,
+    pub reason: String,
+    /// `path:line:column` of the item in the sources of the upgraded package, if it still exists.
+    pub location: Option<String>,
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{location}: ")?;
+        }
+        match &self.item {
+            Some(item) => write!(f, "{}::{}: {}", self.module, item, self.reason),
+            None => write!(f, "{}: {}", self.module, self.reason),
+        }
+    }
+}
+
+/// Checks the modules of `package` against `previous_modules` with the same compatibility checks
+/// that the adapter applies when executing an upgrade with `policy`. Modules failing the checks
+/// are then checked one struct and function at a time, to report the items that break them.
+pub fn check_upgrade(
+    policy: UpgradePolicy,
+    previous_modules: &[CompiledModule],
+    package: &CompiledPackage,
+) -> anyhow::Result<Vec<Incompatibility>> {
+    let Some(previous_address) = previous_modules.first().map(|m| *m.address()) else {
+        bail!("Previous version of the package has no modules");
+    };
+
+    let mut new_modules = BTreeMap::new();
+    for unit in package.package.root_modules() {
+        let CompiledUnitEnum::Module(named_module) = &unit.unit else {
+            continue;
+        };
+        // The adapter substitutes the self address of upgraded modules with the package ID
+        // before checking them, so references to the package's own types compare equal.
+        let mut module = named_module.module.clone();
+        let self_address_idx = module.self_handle().address;
+        module.address_identifiers[self_address_idx.0 as usize] = previous_address;
+        let normalized = normalized::Module::new(&module);
+        let locator = SourceLocator {
+            module: named_module,
+            source_path: &unit.source_path,
+        };
+        new_modules.insert(normalized.name.to_string(), (normalized, locator));
+    }
+
+    let mut incompatibilities = vec![];
+    for previous in previous_modules {
+        let old = normalized::Module::new(previous);
+        let name = old.name.to_string();
+        let Some((new, locator)) = new_modules.get(&name) else {
+            incompatibilities.push(Incompatibility {
+                module: name,
+                item: None,
+                reason: "module was removed".to_string(),
+                location: None,
+            });
+            continue;
+        };
+        if check_module_compatibility(&policy, &old, new) {
+            continue;
+        }
+        let found = incompatibilities.len();
+        incompatibilities.extend(diagnose_module(&policy, &old, new, locator));
+        if incompatibilities.len() == found {
+            incompatibilities.push(Incompatibility {
+                module: name,
+                item: None,
+                reason: "module is incompatible with its previous version".to_string(),
+                location: locator.module_location(),
+            });
+        }
+    }
+    Ok(incompatibilities)
+}
+
+/// Whether `new_module` passes the checks that the adapter applies to upgrades with `policy`.
+fn check_module_compatibility(
+    policy: &UpgradePolicy,
+    cur_module: &normalized::Module,
+    new_module: &normalized::Module,
+) -> bool {
+    execution::check_module_compatibility(policy, cur_module, new_module).is_ok()
+}
+
+/// Finds the items of `old` that make `new` fail the compatibility checks, by checking modules
+/// that only contain one of them.
+fn diagnose_module(
+    policy: &UpgradePolicy,
+    old: &normalized::Module,
+    new: &normalized::Module,
+    locator: &SourceLocator,
+) -> Vec<Incompatibility> {
+    let module = old.name.to_string();
+    let mut incompatibilities = vec![];
+
+    if old.file_format_version > new.file_format_version {
+        incompatibilities.push(Incompatibility {
+            module: module.clone(),
+            item: None,
+            reason: format!(
+                "bytecode version decreased from {} to {}",
+                old.file_format_version, new.file_format_version
+            ),
+            location: locator.module_location(),
+        });
+    }
+    if matches!(policy, UpgradePolicy::DepOnly) && old.friends != new.friends {
+        incompatibilities.push(Incompatibility {
+            module: module.clone(),
+            item: None,
+            reason: "friend declarations changed".to_string(),
+            location: locator.module_location(),
+        });
+    }
+
+    for (name, old_struct) in &old.structs {
+        let new_struct = new.structs.get(name);
+        let old_only = isolate(old, old, Some((name, old_struct)), None);
+        let new_only = isolate(new, old, new_struct.map(|s| (name, s)), None);
+        if !check_module_compatibility(policy, &old_only, &new_only) {
+            incompatibilities.push(Incompatibility {
+                module: module.clone(),
+                item: Some(name.to_string()),
+                reason: describe_struct_change(old_struct, new_struct),
+                location: locator
+                    .struct_location(name.as_str())
+                    .or_else(|| locator.module_location()),
+            });
+        }
+    }
+
+    for (name, old_func) in &old.functions {
+        let new_func = new.functions.get(name);
+        let old_only = isolate(old, old, None, Some((name, old_func)));
+        let new_only = isolate(new, old, None, new_func.map(|f| (name, f)));
+        if !check_module_compatibility(policy, &old_only, &new_only) {
+            incompatibilities.push(Incompatibility {
+                module: module.clone(),
+                item: Some(name.to_string()),
+                reason: describe_function_change(old_func, new_func),
+                location: locator
+                    .function_location(name.as_str())
+                    .or_else(|| locator.module_location()),
+            });
+        }
+    }
+
+    if matches!(policy, UpgradePolicy::DepOnly) {
+        let added_structs = new
+            .structs
+            .keys()
+            .filter(|name| !old.structs.contains_key(*name))
+            .map(|name| (name, "struct", locator.struct_location(name.as_str())));
+        let added_functions = new
+            .functions
+            .keys()
+            .filter(|name| !old.functions.contains_key(*name))
+            .map(|name| (name, "function", locator.function_location(name.as_str())));
+        for (name, kind, location) in added_structs.chain(added_functions) {
+            incompatibilities.push(Incompatibility {
+                module: module.clone(),
+                item: Some(name.to_string()),
+                reason: format!("{kind} was added, which the `dep_only` policy does not allow"),
+                location,
+            });
+        }
+    }
+
+    incompatibilities
+}
+
+/// A copy of `module` that only contains the given struct or function. Friends and the bytecode
+/// version are taken from `reference`, so that only the item itself is compared.
+fn isolate(
+    module: &normalized::Module,
+    reference: &normalized::Module,
+    struct_: Option<(&Identifier, &normalized::Struct)>,
+    function: Option<(&Identifier, &normalized::Function)>,
+) -> normalized::Module {
+    normalized::Module {
+        file_format_version: module
+            .file_format_version
+            .max(reference.file_format_version),
+        address: module.address,
+        name: module.name.clone(),
+        dependencies: module.dependencies.clone(),
+        friends: reference.friends.clone(),
+        structs: struct_
+            .map(|(name, s)| (name.clone(), s.clone()))
+            .into_iter()
+            .collect(),
+        functions: function
+            .map(|(name, f)| (name.clone(), f.clone()))
+            .into_iter()
+            .collect(),
+        constants: module.constants.clone(),
+    }
+}
+
+fn describe_struct_change(old: &normalized::Struct, new: Option<&normalized::Struct>) -> String {
+    let Some(new) = new else {
+        return "struct was removed".to_string();
+    };
+    let mut changes = vec![];
+    if old.abilities != new.abilities {
+        changes.push(format!(
+            "abilities changed from `{}` to `{}`",
+            format_abilities(old.abilities),
+            format_abilities(new.abilities)
+        ));
+    }
+    if old.type_parameters != new.type_parameters {
+        changes.push("type parameters or their constraints changed".to_string());
+    }
+    if old.fields != new.fields {
+        changes.push(format!(
+            "fields changed from `{{ {} }}` to `{{ {} }}`",
+            format_fields(&old.fields),
+            format_fields(&new.fields)
+        ));
+    }
+    if changes.is_empty() {
+        return "struct definition changed".to_string();
+    }
+    changes.join("; ")
+}
+
+fn describe_function_change(
+    old: &normalized::Function,
+    new: Option<&normalized::Function>,
+) -> String {
+    let Some(new) = new else {
+        return format!("{} function was removed", format_visibility(old));
+    };
+    let mut changes = vec![];
+    if old.visibility != new.visibility {
+        changes.push(format!(
+            "visibility changed from `{}` to `{}`",
+            format_visibility(old),
+            format_visibility(new)
+        ));
+    }
+    if old.is_entry && !new.is_entry {
+        changes.push("function is no longer `entry`".to_string());
+    }
+    if old.type_parameters != new.type_parameters {
+        changes.push("type parameters or their constraints changed".to_string());
+    }
+    if old.parameters != new.parameters {
+        changes.push(format!(
+            "parameters changed from `({})` to `({})`",
+            format_types(&old.parameters),
+            format_types(&new.parameters)
+        ));
+    }
+    if old.return_ != new.return_ {
+        changes.push(format!(
+            "return type changed from `({})` to `({})`",
+            format_types(&old.return_),
+            format_types(&new.return_)
+        ));
+    }
+    if changes.is_empty() && old.code != new.code {
+        changes.push("function body changed".to_string());
+    }
+    if changes.is_empty() {
+        return "function definition changed".to_string();
+    }
+    changes.join("; ")
+}
+
+fn format_abilities(abilities: AbilitySet) -> String {
+    abilities
+        .into_iter()
+        .map(|ability| format!("{ability:?}").to_lowercase())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_visibility(function: &normalized::Function) -> &'static str {
+    match function.visibility {
+        Visibility::Public => "public",
+        Visibility::Friend => "friend",
+        Visibility::Private if function.is_entry => "entry",
+        Visibility::Private => "private",
+    }
+}
+
+fn format_fields(fields: &[normalized::Field]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("{}: {}", field.name, field.type_))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_types(types: &[normalized::Type]) -> String {
+    types
+        .iter()
+        .map(|type_| type_.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Maps the definitions of a compiled module back to its source file.
+struct SourceLocator<'a> {
+    module: &'a NamedCompiledModule,
+    source_path: &'a Path,
+}
+
+impl SourceLocator<'_> {
+    fn module_location(&self) -> Option<String> {
+        self.format_loc(self.module.source_map.definition_location)
+    }
+
+    fn struct_location(&self, name: &str) -> Option<String> {
+        let module = &self.module.module;
+        let idx = module.struct_defs().iter().position(|def| {
+            module
+                .identifier_at(module.struct_handle_at(def.struct_handle).name)
+                .as_str()
+                == name
+        })?;
+        let source_map = self
+            .module
+            .source_map
+            .get_struct_source_map(StructDefinitionIndex(idx as u16))
+            .ok()?;
+        self.format_loc(source_map.definition_location)
+    }
+
+    fn function_location(&self, name: &str) -> Option<String> {
+        let module = &self.module.module;
+        let idx = module.function_defs().iter().position(|def| {
+            module
+                .identifier_at(module.function_handle_at(def.function).name)
+                .as_str()
+                == name
+        })?;
+        let source_map = self
+            .module
+            .source_map
+            .get_function_source_map(FunctionDefinitionIndex(idx as u16))
+            .ok()?;
+        self.format_loc(source_map.definition_location)
+    }
+
+    fn format_loc(&self, loc: Loc) -> Option<String> {
+        let source = fs::read_to_string(self.source_path).ok()?;
+        let start = (loc.start() as usize).min(source.len());
+        let prefix = source.get(..start)?;
+        let line = prefix.matches('\n').count() + 1;
+        let column = start - prefix.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+        Some(format!("{}:{line}:{column}", self.source_path.display()))
+    }
+}
+
+#[cfg(test)]
+#[path = "unit_tests/check_upgrade_tests.rs"]
+mod check_upgrade_tests;
@@ -9,8 +9,12 @@ use move_package::BuildConfig;
 use move_unit_test::UnitTestingConfig;
 use std::path::PathBuf;
 
+#[cfg(feature = "bindgen")]
+pub mod bindgen;
 #[cfg(feature = "build")]
 pub mod build;
+#[cfg(feature = "check_upgrade")]
+pub mod check_upgrade;
 #[cfg(feature = "coverage")]
 pub mod coverage;
 #[cfg(feature = "disassemble")]
@@ -23,8 +27,12 @@ pub mod unit_test;
 
 #[derive(Parser)]
 pub enum Command {
+    #[cfg(feature = "bindgen")]
+    Bindgen(bindgen::Bindgen),
     #[cfg(feature = "build")]
     Build(build::Build),
+    #[cfg(feature = "check_upgrade")]
+    CheckUpgrade(check_upgrade::CheckUpgrade),
     #[cfg(feature = "coverage")]
     Coverage(coverage::Coverage),
     #[cfg(feature = "disassemble")]
@@ -49,8 +57,12 @@ pub fn execute_move_command(
     command: Command,
 ) -> anyhow::Result<()> {
     match command {
+        #[cfg(feature = "bindgen")]
+        Command::Bindgen(c) => c.execute(package_path, build_config),
         #[cfg(feature = "build")]
         Command::Build(c) => c.execute(package_path, build_config),
+        #[cfg(feature = "check_upgrade")]
+        Command::CheckUpgrade(c) => c.execute(package_path, build_config),
         #[cfg(feature = "coverage")]
         Command::Coverage(c) => c.execute(package_path, build_config),
         #[cfg(feature = "disassemble")]
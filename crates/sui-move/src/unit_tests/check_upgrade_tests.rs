@@ -0,0 +1,67 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+
+use move_binary_format::CompiledModule;
+use sui_move_build::{BuildConfig, CompiledPackage};
+use sui_types::move_package::UpgradePolicy;
+
+use super::{check_upgrade, Incompatibility};
+
+fn build(test_dir: &str) -> CompiledPackage {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.extend(["src", "unit_tests", "data", "check_upgrade", test_dir]);
+    BuildConfig::new_for_testing().build(path).unwrap()
+}
+
+fn check(policy: UpgradePolicy, test_dir: &str) -> Vec<Incompatibility> {
+    let previous: Vec<CompiledModule> = build("base").into_modules();
+    check_upgrade(policy, &previous, &build(test_dir)).unwrap()
+}
+
+fn items(incompatibilities: &[Incompatibility]) -> Vec<(&str, Option<&str>)> {
+    incompatibilities
+        .iter()
+        .map(|i| (i.module.as_str(), i.item.as_deref()))
+        .collect()
+}
+
+#[test]
+fn compatible_allows_changing_function_bodies() {
+    assert_eq!(check(UpgradePolicy::Compatible, "body_changed"), vec![]);
+}
+
+#[test]
+fn compatible_rejects_changing_struct_layout() {
+    let incompatibilities = check(UpgradePolicy::Compatible, "struct_changed");
+    assert_eq!(items(&incompatibilities), vec![("m", Some("S"))]);
+    assert!(incompatibilities[0].reason.starts_with("fields changed"));
+}
+
+#[test]
+fn additive_allows_adding_functions() {
+    assert_eq!(check(UpgradePolicy::Additive, "function_added"), vec![]);
+}
+
+#[test]
+fn additive_rejects_changing_function_bodies() {
+    let incompatibilities = check(UpgradePolicy::Additive, "body_changed");
+    assert_eq!(items(&incompatibilities), vec![("m", Some("f"))]);
+    assert_eq!(incompatibilities[0].reason, "function body changed");
+}
+
+#[test]
+fn dep_only_allows_unchanged_modules() {
+    assert_eq!(check(UpgradePolicy::DepOnly, "unchanged"), vec![]);
+}
+
+#[test]
+fn dep_only_rejects_adding_functions() {
+    let incompatibilities = check(UpgradePolicy::DepOnly, "function_added");
+    assert_eq!(items(&incompatibilities), vec![("m", Some("g"))]);
+    assert_eq!(
+        incompatibilities[0].reason,
+        "function was added, which the `dep_only` policy does not allow"
+    );
+}
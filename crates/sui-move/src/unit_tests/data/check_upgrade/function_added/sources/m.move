@@ -0,0 +1,10 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module upgrade::m {
+    struct S has store { x: u64 }
+
+    public fun f(): u64 { 1 }
+
+    public fun g(): u64 { 3 }
+}
@@ -0,0 +1,8 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+module upgrade::m {
+    struct S has store { x: u64, y: u64 }
+
+    public fun f(): u64 { 1 }
+}
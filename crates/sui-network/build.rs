@@ -164,6 +164,24 @@ fn build_anemo_services(out_dir: &Path) {
                 .codec_path(codec_path)
                 .build(),
         )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("get_state_snapshot_manifest")
+                .route_name("GetStateSnapshotManifest")
+                .request_type("()")
+                .response_type("Option<crate::state_sync::StateSnapshotManifest>")
+                .codec_path(codec_path)
+                .build(),
+        )
+        .method(
+            anemo_build::manual::Method::builder()
+                .name("get_state_snapshot_chunk")
+                .route_name("GetStateSnapshotChunk")
+                .request_type("crate::state_sync::GetStateSnapshotChunkRequest")
+                .response_type("Option<Vec<u8>>")
+                .codec_path(codec_path)
+                .build(),
+        )
         .build();
 
     anemo_build::manual::Builder::new()
@@ -13,13 +13,15 @@ use sui_config::p2p::StateSyncConfig;
 use sui_types::{messages_checkpoint::VerifiedCheckpoint, storage::ReadStore};
 use tap::Pipe;
 use tokio::{
-    sync::{broadcast, mpsc},
+    sync::{broadcast, mpsc, watch},
     task::JoinSet,
 };
 
 use super::{
     metrics::Metrics,
-    server::{CheckpointContentsDownloadLimitLayer, Server},
+    peer_scores::PeerScores,
+    server::{CheckpointContentsDownloadLimitLayer, Server, StateSnapshotChunkDownloadLimitLayer},
+    snapshot::StateSnapshotStore,
     Handle, PeerHeights, StateSync, StateSyncEventLoop, StateSyncMessage, StateSyncServer,
 };
 use sui_types::storage::WriteStore;
@@ -29,6 +31,7 @@ pub struct Builder<S> {
     config: Option<StateSyncConfig>,
     metrics: Option<Metrics>,
     archive_readers: Option<ArchiveReaderBalancer>,
+    snapshot_store: Option<Arc<dyn StateSnapshotStore>>,
 }
 
 impl Builder<()> {
@@ -39,6 +42,7 @@ impl Builder<()> {
             config: None,
             metrics: None,
             archive_readers: None,
+            snapshot_store: None,
         }
     }
 }
@@ -50,6 +54,7 @@ impl<S> Builder<S> {
             config: self.config,
             metrics: self.metrics,
             archive_readers: self.archive_readers,
+            snapshot_store: self.snapshot_store,
         }
     }
 
@@ -67,6 +72,12 @@ impl<S> Builder<S> {
         self.archive_readers = Some(archive_readers);
         self
     }
+
+    /// Serve the state snapshots of `snapshot_store` to peers bootstrapping from us.
+    pub fn snapshot_store(mut self, snapshot_store: Arc<dyn StateSnapshotStore>) -> Self {
+        self.snapshot_store = Some(snapshot_store);
+        self
+    }
 }
 
 impl<S> Builder<S>
@@ -118,6 +129,11 @@ where
             state_sync_server = state_sync_server
                 .add_layer_for_get_checkpoint_contents(InboundRequestLayer::new(layer));
         }
+        state_sync_server = state_sync_server.add_layer_for_get_state_snapshot_chunk(
+            InboundRequestLayer::new(StateSnapshotChunkDownloadLimitLayer::new(
+                state_sync_config.get_state_snapshot_chunk_per_peer_limit(),
+            )),
+        );
 
         (builder, state_sync_server)
     }
@@ -128,6 +144,7 @@ where
             config,
             metrics,
             archive_readers,
+            snapshot_store,
         } = self;
         let store = store.unwrap();
         let config = config.unwrap_or_default();
@@ -138,14 +155,17 @@ where
         let (checkpoint_event_sender, _receiver) =
             broadcast::channel(config.synced_checkpoint_broadcast_channel_capacity());
         let weak_sender = sender.downgrade();
+        let peer_scores = PeerScores::new(config.peer_ban_duration(), metrics.clone());
         let handle = Handle {
             sender,
             checkpoint_event_sender: checkpoint_event_sender.clone(),
+            peer_scores: peer_scores.clone(),
         };
         let peer_heights = PeerHeights {
             peers: HashMap::new(),
             unprocessed_checkpoints: HashMap::new(),
             sequence_number_to_digest: HashMap::new(),
+            peer_scores,
             wait_interval_when_no_peer_to_sync_content: Duration::from_secs(10),
         }
         .pipe(RwLock::new)
@@ -155,6 +175,7 @@ where
             store: store.clone(),
             peer_heights: peer_heights.clone(),
             sender: weak_sender,
+            snapshot_store,
         };
 
         (
@@ -204,6 +225,7 @@ where
             archive_readers,
         } = self;
 
+        let (sync_checkpoint_contents_config, _receiver) = watch::channel(config.clone());
         (
             StateSyncEventLoop {
                 config,
@@ -212,6 +234,7 @@ where
                 tasks: JoinSet::new(),
                 sync_checkpoint_summaries_task: None,
                 sync_checkpoint_contents_task: None,
+                sync_checkpoint_contents_config,
                 download_limit_layer,
                 store,
                 peer_heights,
@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use mysten_metrics::histogram::Histogram;
-use prometheus::{register_int_gauge_with_registry, IntGauge, Registry};
+use prometheus::{
+    register_int_counter_vec_with_registry, register_int_counter_with_registry,
+    register_int_gauge_vec_with_registry, register_int_gauge_with_registry, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec, Registry,
+};
 use std::sync::Arc;
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 use tap::Pipe;
@@ -51,6 +55,33 @@ impl Metrics {
         }
         None
     }
+
+    pub fn set_peer_score(&self, peer: &str, score: i64) {
+        if let Some(inner) = &self.0 {
+            inner.peer_score.with_label_values(&[peer]).set(score);
+        }
+    }
+
+    pub fn remove_peer_score(&self, peer: &str) {
+        if let Some(inner) = &self.0 {
+            let _ = inner.peer_score.remove_label_values(&[peer]);
+        }
+    }
+
+    pub fn inc_peer_request_outcome(&self, outcome: &str) {
+        if let Some(inner) = &self.0 {
+            inner
+                .peer_request_outcomes
+                .with_label_values(&[outcome])
+                .inc();
+        }
+    }
+
+    pub fn inc_peer_bans(&self) {
+        if let Some(inner) = &self.0 {
+            inner.peer_bans.inc();
+        }
+    }
 }
 
 struct Inner {
@@ -58,6 +89,9 @@ struct Inner {
     highest_verified_checkpoint: IntGauge,
     highest_synced_checkpoint: IntGauge,
     checkpoint_summary_age_ms: Histogram,
+    peer_score: IntGaugeVec,
+    peer_request_outcomes: IntCounterVec,
+    peer_bans: IntCounter,
 }
 
 impl Inner {
@@ -89,6 +123,29 @@ impl Inner {
                 "Age of checkpoints summaries when they arrive and are verified.",
                 registry,
             ),
+
+            peer_score: register_int_gauge_vec_with_registry!(
+                "state_sync_peer_score",
+                "Reputation score of each peer used for state sync",
+                &["peer_id"],
+                registry
+            )
+            .unwrap(),
+
+            peer_request_outcomes: register_int_counter_vec_with_registry!(
+                "state_sync_peer_request_outcomes",
+                "Outcomes of state sync requests sent to peers",
+                &["outcome"],
+                registry
+            )
+            .unwrap(),
+
+            peer_bans: register_int_counter_with_registry!(
+                "state_sync_peer_bans",
+                "Number of times a peer was temporarily banned from state sync",
+                registry
+            )
+            .unwrap(),
         }
         .pipe(Arc::new)
     }
@@ -72,6 +72,7 @@ use tokio::sync::oneshot;
 use tokio::{
     sync::{broadcast, mpsc, watch},
     task::{AbortHandle, JoinSet},
+    time::Instant,
 };
 use tracing::{debug, error, info, trace};
 
@@ -80,7 +81,9 @@ mod generated {
 }
 mod builder;
 mod metrics;
+mod peer_scores;
 mod server;
+mod snapshot;
 #[cfg(test)]
 mod tests;
 
@@ -89,12 +92,22 @@ pub use generated::{
     state_sync_client::StateSyncClient,
     state_sync_server::{StateSync, StateSyncServer},
 };
+pub use peer_scores::PeerScore;
 pub use server::GetCheckpointAvailabilityResponse;
 pub use server::GetCheckpointSummaryRequest;
+pub use snapshot::{
+    root_state_digest, GetStateSnapshotChunkRequest, StateSnapshotChunkInfo,
+    StateSnapshotDownloader, StateSnapshotManifest, StateSnapshotSink, StateSnapshotStore,
+    MAX_STATE_SNAPSHOT_CHUNK_READ,
+};
 use sui_archival::reader::ArchiveReaderBalancer;
 use sui_storage::verify_checkpoint;
 
-use self::{metrics::Metrics, server::CheckpointContentsDownloadLimitLayer};
+use self::{
+    metrics::Metrics,
+    peer_scores::{PeerScores, RequestOutcome},
+    server::CheckpointContentsDownloadLimitLayer,
+};
 
 /// A handle to the StateSync subsystem.
 ///
@@ -104,6 +117,7 @@ use self::{metrics::Metrics, server::CheckpointContentsDownloadLimitLayer};
 pub struct Handle {
     sender: mpsc::Sender<StateSyncMessage>,
     checkpoint_event_sender: broadcast::Sender<VerifiedCheckpoint>,
+    peer_scores: PeerScores,
 }
 
 impl Handle {
@@ -126,6 +140,20 @@ impl Handle {
     pub fn subscribe_to_synced_checkpoints(&self) -> broadcast::Receiver<VerifiedCheckpoint> {
         self.checkpoint_event_sender.subscribe()
     }
+
+    /// Snapshot of the reputation of every peer we have sent sync requests to.
+    pub fn peer_scores(&self) -> Vec<PeerScore> {
+        self.peer_scores.snapshot()
+    }
+
+    /// Apply the polling interval, download concurrency and timeout settings of `config` to the
+    /// running StateSync. Its other settings are fixed at startup and are ignored.
+    pub async fn update_config(&self, config: StateSyncConfig) {
+        self.sender
+            .send(StateSyncMessage::UpdateConfig(Box::new(config)))
+            .await
+            .unwrap()
+    }
 }
 
 struct PeerHeights {
@@ -133,6 +161,8 @@ struct PeerHeights {
     peers: HashMap<PeerId, PeerStateSyncInfo>,
     unprocessed_checkpoints: HashMap<CheckpointDigest, Checkpoint>,
     sequence_number_to_digest: HashMap<CheckpointSequenceNumber, CheckpointDigest>,
+    /// Reputation of the peers we sync from, used to skip or deprioritize misbehaving ones.
+    peer_scores: PeerScores,
 
     // The amount of time to wait before retry if there are no peers to sync content from.
     wait_interval_when_no_peer_to_sync_content: Duration,
@@ -268,9 +298,11 @@ impl PeerHeights {
 }
 
 // PeerBalancer is an Iterator that selects peers based on RTT with some added randomness.
+// Banned peers are skipped, and peers with a low score are only selected after all others.
 #[derive(Clone)]
 struct PeerBalancer {
     peers: VecDeque<(anemo::Peer, PeerStateSyncInfo)>,
+    peer_scores: PeerScores,
     requested_checkpoint: Option<CheckpointSequenceNumber>,
     request_type: PeerCheckpointRequestType,
 }
@@ -287,18 +319,23 @@ impl PeerBalancer {
         peer_heights: Arc<RwLock<PeerHeights>>,
         request_type: PeerCheckpointRequestType,
     ) -> Self {
+        let peer_heights = peer_heights.read().unwrap();
+        let peer_scores = peer_heights.peer_scores.clone();
         let mut peers: Vec<_> = peer_heights
-            .read()
-            .unwrap()
             .peers_on_same_chain()
+            .filter(|(peer_id, _info)| !peer_scores.is_banned(peer_id))
             // Filter out any peers who we aren't connected with.
             .filter_map(|(peer_id, info)| network.peer(*peer_id).map(|peer| (peer, *info)))
             .collect();
-        peers.sort_by(|(peer_a, _), (peer_b, _)| {
-            peer_a.connection_rtt().cmp(&peer_b.connection_rtt())
+        peers.sort_by_cached_key(|(peer, _)| {
+            (
+                peer_scores.is_deprioritized(&peer.peer_id()),
+                peer.connection_rtt(),
+            )
         });
         Self {
             peers: peers.into(),
+            peer_scores,
             requested_checkpoint: None,
             request_type,
         }
@@ -308,6 +345,10 @@ impl PeerBalancer {
         self.requested_checkpoint = Some(checkpoint);
         self
     }
+
+    pub fn peer_scores(&self) -> &PeerScores {
+        &self.peer_scores
+    }
 }
 
 impl Iterator for PeerBalancer {
@@ -316,8 +357,16 @@ impl Iterator for PeerBalancer {
     fn next(&mut self) -> Option<Self::Item> {
         while !self.peers.is_empty() {
             const SELECTION_WINDOW: usize = 2;
-            let idx =
-                rand::thread_rng().gen_range(0..std::cmp::min(SELECTION_WINDOW, self.peers.len()));
+            // Don't let randomness pick a deprioritized peer over a well behaved one.
+            let deprioritized =
+                |peer: &anemo::Peer| self.peer_scores.is_deprioritized(&peer.peer_id());
+            let window = self
+                .peers
+                .iter()
+                .take(SELECTION_WINDOW)
+                .take_while(|(peer, _)| deprioritized(peer) == deprioritized(&self.peers[0].0))
+                .count();
+            let idx = rand::thread_rng().gen_range(0..window);
             let (peer, info) = self.peers.remove(idx).unwrap();
             let requested_checkpoint = self.requested_checkpoint.unwrap_or(0);
             match &self.request_type {
@@ -348,6 +397,8 @@ enum StateSyncMessage {
     // it was able to successfully sync a checkpoint's contents. If multiple checkpoints were
     // synced at the same time, only the highest checkpoint is sent.
     SyncedCheckpoint(Box<VerifiedCheckpoint>),
+    // Sent by the node when its config is reloaded at runtime.
+    UpdateConfig(Box<StateSyncConfig>),
 }
 
 struct StateSyncEventLoop<S> {
@@ -360,6 +411,8 @@ struct StateSyncEventLoop<S> {
     tasks: JoinSet<()>,
     sync_checkpoint_summaries_task: Option<AbortHandle>,
     sync_checkpoint_contents_task: Option<AbortHandle>,
+    /// Forwards config updates to the checkpoint contents sync task.
+    sync_checkpoint_contents_config: watch::Sender<StateSyncConfig>,
     download_limit_layer: Option<CheckpointContentsDownloadLimitLayer>,
 
     store: S,
@@ -414,9 +467,7 @@ where
             self.peer_heights.clone(),
             self.weak_sender.clone(),
             self.checkpoint_event_sender.clone(),
-            self.config.checkpoint_content_download_concurrency(),
-            self.config.checkpoint_content_download_tx_concurrency(),
-            self.config.checkpoint_content_timeout(),
+            self.sync_checkpoint_contents_config.subscribe(),
             target_checkpoint_contents_sequence_receiver,
         );
         let task_handle = self.tasks.spawn(task);
@@ -449,6 +500,9 @@ where
                     // will yield `None` and we can terminate the event loop
                     if let Some(message) = maybe_message {
                         self.handle_message(message);
+                        if interval.period() != self.config.interval_period() {
+                            interval = tokio::time::interval(self.config.interval_period());
+                        }
                     } else {
                         break;
                     }
@@ -505,9 +559,25 @@ where
             StateSyncMessage::SyncedCheckpoint(checkpoint) => {
                 self.spawn_notify_peers_of_checkpoint(*checkpoint)
             }
+            StateSyncMessage::UpdateConfig(config) => self.update_config(*config),
         }
     }
 
+    fn update_config(&mut self, config: StateSyncConfig) {
+        self.config.interval_period_ms = config.interval_period_ms;
+        self.config.checkpoint_header_download_concurrency =
+            config.checkpoint_header_download_concurrency;
+        self.config.checkpoint_content_download_concurrency =
+            config.checkpoint_content_download_concurrency;
+        self.config.checkpoint_content_download_tx_concurrency =
+            config.checkpoint_content_download_tx_concurrency;
+        self.config.timeout_ms = config.timeout_ms;
+        self.config.checkpoint_content_timeout_ms = config.checkpoint_content_timeout_ms;
+        self.sync_checkpoint_contents_config
+            .send_replace(self.config.clone());
+        info!("State sync config updated");
+    }
+
     // Handle a checkpoint that we received from consensus
     fn handle_checkpoint_from_consensus(&mut self, checkpoint: Box<VerifiedCheckpoint>) {
         // Always check previous_digest matches in case there is a gap between
@@ -649,6 +719,18 @@ where
         if let Some(layer) = self.download_limit_layer.as_ref() {
             layer.maybe_prune_map();
         }
+
+        let peer_heights = self.peer_heights.read().unwrap();
+        if let Some(highest_known) = peer_heights.highest_known_checkpoint_sequence_number() {
+            for (peer_id, info) in peer_heights.peers_on_same_chain() {
+                peer_heights
+                    .peer_scores
+                    .record_height(*peer_id, info.height, highest_known);
+            }
+        }
+        peer_heights
+            .peer_scores
+            .prune(|peer_id| self.network.peer(*peer_id).is_some());
     }
 
     fn maybe_start_checkpoint_summary_sync_task(&mut self) {
@@ -969,6 +1051,7 @@ where
         ..=*checkpoint.sequence_number())
         .map(|next| {
             let peers = peer_balancer.clone().with_checkpoint(next);
+            let peer_scores = peer_balancer.peer_scores().clone();
             let peer_heights = peer_heights.clone();
             let pinned_checkpoints = &pinned_checkpoints;
             async move {
@@ -983,12 +1066,19 @@ where
                 // Iterate through peers trying each one in turn until we're able to
                 // successfully get the target checkpoint
                 for mut peer in peers {
+                    let peer_id = peer.inner().peer_id();
                     let request = Request::new(GetCheckpointSummaryRequest::BySequenceNumber(next))
                         .with_timeout(timeout);
+                    let start = Instant::now();
                     if let Some(checkpoint) = peer
                         .get_checkpoint_summary(request)
                         .await
-                        .tap_err(|e| trace!("{e:?}"))
+                        .tap_err(|e| {
+                            trace!("{e:?}");
+                            if let Some(outcome) = RequestOutcome::from_status(e) {
+                                peer_scores.record(peer_id, outcome);
+                            }
+                        })
                         .ok()
                         .and_then(Response::into_inner)
                         .tap_none(|| trace!("peer unable to help sync"))
@@ -999,6 +1089,7 @@ where
                                 "peer returned checkpoint with wrong sequence number: expected {next}, got {}",
                                 checkpoint.sequence_number()
                             );
+                            peer_scores.record(peer_id, RequestOutcome::InvalidResponse);
                             continue;
                         }
 
@@ -1014,16 +1105,18 @@ where
                                     pinned_checkpoints[pinned_digest_index].1,
                                     checkpoint_digest
                                 );
+                                peer_scores.record(peer_id, RequestOutcome::InvalidResponse);
                                 continue;
                             }
                         }
+                        peer_scores.record(peer_id, RequestOutcome::Success(start.elapsed()));
 
                         // Insert in our store in the event that things fail and we need to retry
                         peer_heights
                             .write()
                             .unwrap()
                             .insert_checkpoint(checkpoint.clone());
-                        return (Some(checkpoint), next, Some(peer.inner().peer_id()));
+                        return (Some(checkpoint), next, Some(peer_id));
                     }
                 }
                 (None, next, None)
@@ -1058,6 +1151,9 @@ where
                     // Mark peer as not on the same chain as us
                     if let Some(peer_id) = maybe_peer_id {
                         peer_heights.mark_peer_as_not_on_same_chain(peer_id);
+                        peer_heights
+                            .peer_scores
+                            .record(peer_id, RequestOutcome::InvalidResponse);
                     }
 
                     return Err(anyhow::anyhow!(
@@ -1088,6 +1184,81 @@ where
     Ok(())
 }
 
+/// Download, verify and store the checkpoint summaries that `peers` have synced past the highest
+/// verified checkpoint of `store`, without syncing any checkpoint contents. This lets a process
+/// that doesn't run state sync, e.g. one bootstrapping a node from a state snapshot served by its
+/// peers, learn about the latest epochs. Returns the new highest verified checkpoint.
+pub async fn sync_checkpoint_summaries_from_peers<S>(
+    network: &anemo::Network,
+    store: S,
+    peers: &[PeerId],
+    config: &StateSyncConfig,
+) -> Result<VerifiedCheckpoint>
+where
+    S: WriteStore + Clone,
+    <S as ReadStore>::Error: std::error::Error,
+{
+    let genesis_checkpoint_digest = *store
+        .get_checkpoint_by_sequence_number(0)
+        .expect("store operation should not fail")
+        .expect("store should contain genesis checkpoint")
+        .digest();
+    let peer_heights = PeerHeights {
+        peers: HashMap::new(),
+        unprocessed_checkpoints: HashMap::new(),
+        sequence_number_to_digest: HashMap::new(),
+        peer_scores: PeerScores::new(config.peer_ban_duration(), Metrics::disabled()),
+        wait_interval_when_no_peer_to_sync_content: Duration::from_secs(10),
+    }
+    .pipe(RwLock::new)
+    .pipe(Arc::new);
+
+    futures::future::join_all(
+        peers
+            .iter()
+            .filter_map(|peer_id| network.peer(*peer_id))
+            .map(|peer| {
+                get_latest_from_peer(
+                    genesis_checkpoint_digest,
+                    peer,
+                    peer_heights.clone(),
+                    config.timeout(),
+                )
+            }),
+    )
+    .await;
+
+    let highest_known_checkpoint = peer_heights
+        .read()
+        .unwrap()
+        .highest_known_checkpoint()
+        .cloned();
+    let highest_verified_checkpoint = store
+        .get_highest_verified_checkpoint()
+        .expect("store operation should not fail");
+    if let Some(checkpoint) = highest_known_checkpoint {
+        if checkpoint.sequence_number() > highest_verified_checkpoint.sequence_number() {
+            let mut pinned_checkpoints = config.pinned_checkpoints.clone();
+            pinned_checkpoints.sort();
+            sync_to_checkpoint(
+                network.clone(),
+                store.clone(),
+                peer_heights,
+                Metrics::disabled(),
+                pinned_checkpoints,
+                config.checkpoint_header_download_concurrency(),
+                config.timeout(),
+                checkpoint,
+            )
+            .await?;
+        }
+    }
+
+    Ok(store
+        .get_highest_verified_checkpoint()
+        .expect("store operation should not fail"))
+}
+
 async fn sync_checkpoint_contents_from_archive<S>(
     network: anemo::Network,
     archive_readers: ArchiveReaderBalancer,
@@ -1157,9 +1328,7 @@ async fn sync_checkpoint_contents<S>(
     peer_heights: Arc<RwLock<PeerHeights>>,
     sender: mpsc::WeakSender<StateSyncMessage>,
     checkpoint_event_sender: broadcast::Sender<VerifiedCheckpoint>,
-    checkpoint_content_download_concurrency: usize,
-    checkpoint_content_download_tx_concurrency: u64,
-    timeout: Duration,
+    mut config: watch::Receiver<StateSyncConfig>,
     mut target_sequence_channel: watch::Receiver<CheckpointSequenceNumber>,
 ) where
     S: WriteStore + Clone,
@@ -1174,10 +1343,22 @@ async fn sync_checkpoint_contents<S>(
     let mut highest_started_network_total_transactions = highest_synced.network_total_transactions;
     let mut checkpoint_contents_tasks = FuturesOrdered::new();
 
-    let mut tx_concurrency_remaining = checkpoint_content_download_tx_concurrency;
+    let (
+        mut checkpoint_content_download_concurrency,
+        mut checkpoint_content_download_tx_concurrency,
+        mut timeout,
+    ) = checkpoint_contents_download_settings(&config.borrow_and_update());
+    let mut tx_concurrency_in_use = 0;
 
     loop {
         tokio::select! {
+            Ok(()) = config.changed() => {
+                (
+                    checkpoint_content_download_concurrency,
+                    checkpoint_content_download_tx_concurrency,
+                    timeout,
+                ) = checkpoint_contents_download_settings(&config.borrow_and_update());
+            },
             result = target_sequence_channel.changed() => {
                 match result {
                     Ok(()) => {
@@ -1199,7 +1380,7 @@ async fn sync_checkpoint_contents<S>(
                             highest_synced.network_total_transactions + num_txns,
                             checkpoint.network_total_transactions
                         );
-                        tx_concurrency_remaining += num_txns;
+                        tx_concurrency_in_use -= num_txns;
 
                         store
                             .update_highest_synced_checkpoint(&checkpoint)
@@ -1247,10 +1428,12 @@ async fn sync_checkpoint_contents<S>(
             // Enforce transaction count concurrency limit.
             let tx_count = next_checkpoint.network_total_transactions
                 - highest_started_network_total_transactions;
-            if tx_count > tx_concurrency_remaining {
+            if tx_count
+                > checkpoint_content_download_tx_concurrency.saturating_sub(tx_concurrency_in_use)
+            {
                 break;
             }
-            tx_concurrency_remaining -= tx_count;
+            tx_concurrency_in_use += tx_count;
 
             highest_started_network_total_transactions = next_checkpoint.network_total_transactions;
             current_sequence += 1;
@@ -1275,6 +1458,14 @@ async fn sync_checkpoint_contents<S>(
     }
 }
 
+fn checkpoint_contents_download_settings(config: &StateSyncConfig) -> (usize, u64, Duration) {
+    (
+        config.checkpoint_content_download_concurrency(),
+        config.checkpoint_content_download_tx_concurrency(),
+        config.checkpoint_content_timeout(),
+    )
+}
+
 async fn sync_one_checkpoint_contents<S>(
     network: anemo::Network,
     store: S,
@@ -1333,23 +1524,33 @@ where
 
     // Iterate through our selected peers trying each one in turn until we're able to
     // successfully get the target checkpoint
+    let peer_scores = peers.peer_scores().clone();
     for mut peer in peers {
+        let peer_id = peer.inner().peer_id();
         let request = Request::new(digest).with_timeout(timeout);
+        let start = Instant::now();
         if let Some(contents) = peer
             .get_checkpoint_contents(request)
             .await
-            .tap_err(|e| trace!("{e:?}"))
+            .tap_err(|e| {
+                trace!("{e:?}");
+                if let Some(outcome) = RequestOutcome::from_status(e) {
+                    peer_scores.record(peer_id, outcome);
+                }
+            })
             .ok()
             .and_then(Response::into_inner)
             .tap_none(|| trace!("peer unable to help sync"))
         {
             if contents.verify_digests(digest).is_ok() {
+                peer_scores.record(peer_id, RequestOutcome::Success(start.elapsed()));
                 let verified_contents = VerifiedCheckpointContents::new_unchecked(contents.clone());
                 store
                     .insert_checkpoint_contents(checkpoint, verified_contents)
                     .expect("store operation should not fail");
                 return Some(contents);
             }
+            peer_scores.record(peer_id, RequestOutcome::InvalidResponse);
         }
     }
     None
@@ -0,0 +1,296 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reputation tracking for the peers we sync checkpoints from.
+//!
+//! Every request sent to a peer while syncing checkpoint summaries or contents is recorded as a
+//! `RequestOutcome`. Successful requests slowly raise a peer's score, while timeouts, errors and
+//! invalid responses lower it, as does advertising a height that stays far behind the highest
+//! checkpoint known from other peers. Peers whose score falls below `DEPRIORITIZE_THRESHOLD` are only
+//! queried once all other peers have been tried, and peers whose score reaches `BAN_THRESHOLD`
+//! are excluded from sync requests for the configured ban duration. Once a ban expires the peer
+//! starts over with a deprioritized score, so that it has to serve requests correctly again
+//! before it is preferred over other peers.
+//!
+//! Scores are kept for peers we disconnect from, so that a banned peer cannot clear its ban by
+//! reconnecting. They are only forgotten once the peer is neither connected nor banned and
+//! nothing was recorded for it for `PEER_SCORE_TTL`.
+
+use super::metrics::Metrics;
+use anemo::{rpc::Status, types::response::StatusCode, PeerId};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tokio::time::Instant;
+use tracing::info;
+
+const MAX_SCORE: i64 = 100;
+const DEPRIORITIZE_THRESHOLD: i64 = 50;
+const BAN_THRESHOLD: i64 = 0;
+
+const SUCCESS_REWARD: i64 = 2;
+const ERROR_PENALTY: i64 = 10;
+const TIMEOUT_PENALTY: i64 = 20;
+const INVALID_RESPONSE_PENALTY: i64 = 50;
+const LAG_PENALTY: i64 = 5;
+
+// Peers advertising a height further than this behind the highest known checkpoint for longer
+// than `PEER_LAG_GRACE_PERIOD` are penalized every time their height is checked.
+const MAX_PEER_LAG: CheckpointSequenceNumber = 1_000;
+const PEER_LAG_GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+// How long the score of a disconnected peer is kept after anything was last recorded for it.
+const PEER_SCORE_TTL: Duration = Duration::from_secs(3_600);
+
+// Weight given to the latest sample when updating a peer's average latency.
+const LATENCY_EWMA_WEIGHT: f64 = 0.2;
+
+/// The result of a single sync request sent to a peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum RequestOutcome {
+    /// The peer answered the request with valid data, taking the given amount of time.
+    Success(Duration),
+    /// The request timed out.
+    Timeout,
+    /// The request failed for any other reason.
+    Error,
+    /// The peer returned data that failed validation.
+    InvalidResponse,
+    /// The peer's advertised height stayed far behind the highest known checkpoint.
+    Lagging,
+}
+
+impl RequestOutcome {
+    /// Classify a failed request, or return `None` if the failure shouldn't count against the
+    /// peer. Peers that rate limit us are protecting themselves rather than misbehaving.
+    pub fn from_status(status: &Status) -> Option<Self> {
+        match status.status() {
+            StatusCode::TooManyRequests => None,
+            StatusCode::RequestTimeout => Some(Self::Timeout),
+            _ => Some(Self::Error),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success(_) => "success",
+            Self::Timeout => "timeout",
+            Self::Error => "error",
+            Self::InvalidResponse => "invalid_response",
+            Self::Lagging => "lagging",
+        }
+    }
+}
+
+/// A snapshot of the reputation of a single peer.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PeerScore {
+    pub peer_id: PeerId,
+    pub score: i64,
+    /// Moving average of the time taken by the peer to answer successful requests.
+    pub average_latency_ms: Option<u64>,
+    pub successes: u64,
+    pub timeouts: u64,
+    pub errors: u64,
+    pub invalid_responses: u64,
+    /// Time left before the peer can be queried again, if it is currently banned.
+    pub banned_for_ms: Option<u64>,
+}
+
+#[derive(Debug)]
+struct PeerState {
+    score: i64,
+    average_latency: Option<Duration>,
+    successes: u64,
+    timeouts: u64,
+    errors: u64,
+    invalid_responses: u64,
+    banned_until: Option<Instant>,
+    lagging_since: Option<Instant>,
+    last_seen: Instant,
+}
+
+impl Default for PeerState {
+    fn default() -> Self {
+        Self {
+            score: MAX_SCORE,
+            average_latency: None,
+            successes: 0,
+            timeouts: 0,
+            errors: 0,
+            invalid_responses: 0,
+            banned_until: None,
+            lagging_since: None,
+            last_seen: Instant::now(),
+        }
+    }
+}
+
+impl PeerState {
+    fn is_banned(&self, now: Instant) -> bool {
+        self.banned_until.is_some_and(|until| now < until)
+    }
+}
+
+/// Shared table of peer reputations. Cloning it yields a handle to the same table.
+#[derive(Clone, Debug)]
+pub(super) struct PeerScores {
+    peers: Arc<Mutex<HashMap<PeerId, PeerState>>>,
+    ban_duration: Duration,
+    metrics: Metrics,
+}
+
+impl PeerScores {
+    pub fn new(ban_duration: Duration, metrics: Metrics) -> Self {
+        Self {
+            peers: Default::default(),
+            ban_duration,
+            metrics,
+        }
+    }
+
+    pub fn record(&self, peer_id: PeerId, outcome: RequestOutcome) {
+        let mut peers = self.peers.lock().unwrap();
+        let state = peers.entry(peer_id).or_default();
+        state.last_seen = Instant::now();
+
+        let delta = match outcome {
+            RequestOutcome::Success(latency) => {
+                state.successes += 1;
+                state.average_latency = Some(match state.average_latency {
+                    Some(average) => average
+                        .mul_f64(1.0 - LATENCY_EWMA_WEIGHT)
+                        .saturating_add(latency.mul_f64(LATENCY_EWMA_WEIGHT)),
+                    None => latency,
+                });
+                SUCCESS_REWARD
+            }
+            RequestOutcome::Timeout => {
+                state.timeouts += 1;
+                -TIMEOUT_PENALTY
+            }
+            RequestOutcome::Error => {
+                state.errors += 1;
+                -ERROR_PENALTY
+            }
+            RequestOutcome::InvalidResponse => {
+                state.invalid_responses += 1;
+                -INVALID_RESPONSE_PENALTY
+            }
+            RequestOutcome::Lagging => -LAG_PENALTY,
+        };
+        self.metrics.inc_peer_request_outcome(outcome.as_str());
+
+        // Requests that were already in flight when the peer got banned don't affect its score.
+        let now = Instant::now();
+        if state.is_banned(now) {
+            return;
+        }
+
+        state.score = std::cmp::min(state.score + delta, MAX_SCORE);
+        if state.score <= BAN_THRESHOLD {
+            if self.ban_duration.is_zero() {
+                state.score = BAN_THRESHOLD;
+            } else {
+                info!(
+                    "banning peer {peer_id} from state sync for {:?}",
+                    self.ban_duration
+                );
+                state.banned_until = Some(now + self.ban_duration);
+                state.score = DEPRIORITIZE_THRESHOLD - 1;
+                self.metrics.inc_peer_bans();
+            }
+        }
+        self.metrics
+            .set_peer_score(&peer_id.to_string(), state.score);
+    }
+
+    /// Check the height advertised by a peer against the highest checkpoint known from any peer,
+    /// penalizing the peer if it has been lagging far behind for too long.
+    pub fn record_height(
+        &self,
+        peer_id: PeerId,
+        height: CheckpointSequenceNumber,
+        highest_known: CheckpointSequenceNumber,
+    ) {
+        let now = Instant::now();
+        let lagging = {
+            let mut peers = self.peers.lock().unwrap();
+            let state = peers.entry(peer_id).or_default();
+            if height.saturating_add(MAX_PEER_LAG) >= highest_known {
+                state.lagging_since = None;
+                return;
+            }
+            let since = *state.lagging_since.get_or_insert(now);
+            now.duration_since(since) >= PEER_LAG_GRACE_PERIOD
+        };
+        if lagging {
+            self.record(peer_id, RequestOutcome::Lagging);
+        }
+    }
+
+    /// Forget the peers that are neither connected nor banned and that nothing was recorded for
+    /// during `PEER_SCORE_TTL`, along with their score metric.
+    pub fn prune(&self, is_connected: impl Fn(&PeerId) -> bool) {
+        let now = Instant::now();
+        self.peers.lock().unwrap().retain(|peer_id, state| {
+            let keep = is_connected(peer_id)
+                || state.is_banned(now)
+                || now.duration_since(state.last_seen) < PEER_SCORE_TTL;
+            if !keep {
+                self.metrics.remove_peer_score(&peer_id.to_string());
+            }
+            keep
+        });
+    }
+
+    /// Whether the peer must not be sent sync requests at the moment.
+    pub fn is_banned(&self, peer_id: &PeerId) -> bool {
+        let now = Instant::now();
+        self.peers
+            .lock()
+            .unwrap()
+            .get(peer_id)
+            .is_some_and(|state| state.is_banned(now))
+    }
+
+    /// Whether the peer should only be sent sync requests after all other peers were tried.
+    pub fn is_deprioritized(&self, peer_id: &PeerId) -> bool {
+        self.peers
+            .lock()
+            .unwrap()
+            .get(peer_id)
+            .is_some_and(|state| state.score < DEPRIORITIZE_THRESHOLD)
+    }
+
+    pub fn snapshot(&self) -> Vec<PeerScore> {
+        let now = Instant::now();
+        let mut scores: Vec<_> = self
+            .peers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(peer_id, state)| PeerScore {
+                peer_id: *peer_id,
+                score: state.score,
+                average_latency_ms: state
+                    .average_latency
+                    .map(|latency| latency.as_millis() as u64),
+                successes: state.successes,
+                timeouts: state.timeouts,
+                errors: state.errors,
+                invalid_responses: state.invalid_responses,
+                banned_for_ms: state
+                    .banned_until
+                    .filter(|until| now < *until)
+                    .map(|until| (until - now).as_millis() as u64),
+            })
+            .collect();
+        scores.sort_by_key(|score| score.peer_id);
+        scores
+    }
+}
@@ -1,8 +1,14 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{PeerHeights, StateSync, StateSyncMessage};
-use anemo::{rpc::Status, types::response::StatusCode, Request, Response, Result};
+use super::{
+    snapshot::{
+        GetStateSnapshotChunkRequest, StateSnapshotManifest, StateSnapshotStore,
+        MAX_STATE_SNAPSHOT_CHUNK_READ,
+    },
+    PeerHeights, StateSync, StateSyncMessage,
+};
+use anemo::{rpc::Status, types::response::StatusCode, PeerId, Request, Response, Result};
 use dashmap::DashMap;
 use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
@@ -36,6 +42,7 @@ pub(super) struct Server<S> {
     pub(super) store: S,
     pub(super) peer_heights: Arc<RwLock<PeerHeights>>,
     pub(super) sender: mpsc::WeakSender<StateSyncMessage>,
+    pub(super) snapshot_store: Option<Arc<dyn StateSnapshotStore>>,
 }
 
 #[anemo::async_trait]
@@ -131,6 +138,45 @@ where
             .map_err(|e| Status::internal(e.to_string()))?;
         Ok(Response::new(contents))
     }
+
+    async fn get_state_snapshot_manifest(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<Option<StateSnapshotManifest>>, Status> {
+        let Some(snapshot_store) = self.snapshot_store.clone() else {
+            return Ok(Response::new(None));
+        };
+        let manifest = tokio::task::spawn_blocking(move || snapshot_store.manifest())
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(manifest))
+    }
+
+    async fn get_state_snapshot_chunk(
+        &self,
+        request: Request<GetStateSnapshotChunkRequest>,
+    ) -> Result<Response<Option<Vec<u8>>>, Status> {
+        let Some(snapshot_store) = self.snapshot_store.clone() else {
+            return Ok(Response::new(None));
+        };
+        let GetStateSnapshotChunkRequest {
+            epoch,
+            index,
+            offset,
+            length,
+        } = request.into_inner();
+        let Ok(index) = usize::try_from(index) else {
+            return Ok(Response::new(None));
+        };
+        let length = length.min(MAX_STATE_SNAPSHOT_CHUNK_READ);
+        let chunk =
+            tokio::task::spawn_blocking(move || snapshot_store.chunk(epoch, index, offset, length))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+                .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(chunk))
+    }
 }
 
 /// [`Layer`] for adding a per-checkpoint limit to the number of inflight GetCheckpointContent
@@ -233,3 +279,105 @@ where
         Box::pin(fut)
     }
 }
+
+/// [`Layer`] for adding a per-peer limit to the number of inflight GetStateSnapshotChunk requests.
+#[derive(Clone)]
+pub(super) struct StateSnapshotChunkDownloadLimitLayer {
+    inflight_per_peer: Arc<DashMap<PeerId, Arc<Semaphore>>>,
+    max_inflight_per_peer: usize,
+}
+
+impl StateSnapshotChunkDownloadLimitLayer {
+    pub(super) fn new(max_inflight_per_peer: usize) -> Self {
+        Self {
+            inflight_per_peer: Arc::new(DashMap::new()),
+            max_inflight_per_peer,
+        }
+    }
+}
+
+impl<S> tower::layer::Layer<S> for StateSnapshotChunkDownloadLimitLayer {
+    type Service = StateSnapshotChunkDownloadLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        StateSnapshotChunkDownloadLimit {
+            inner,
+            inflight_per_peer: self.inflight_per_peer.clone(),
+            max_inflight_per_peer: self.max_inflight_per_peer,
+        }
+    }
+}
+
+/// Middleware for adding a per-peer limit to the number of inflight GetStateSnapshotChunk
+/// requests.
+#[derive(Clone)]
+pub(super) struct StateSnapshotChunkDownloadLimit<S> {
+    inner: S,
+    inflight_per_peer: Arc<DashMap<PeerId, Arc<Semaphore>>>,
+    max_inflight_per_peer: usize,
+}
+
+impl<S> tower::Service<Request<GetStateSnapshotChunkRequest>> for StateSnapshotChunkDownloadLimit<S>
+where
+    S: tower::Service<
+            Request<GetStateSnapshotChunkRequest>,
+            Response = Response<Option<Vec<u8>>>,
+            Error = Status,
+        >
+        + 'static
+        + Clone
+        + Send,
+    <S as tower::Service<Request<GetStateSnapshotChunkRequest>>>::Future: Send,
+    Request<GetStateSnapshotChunkRequest>: 'static + Send + Sync,
+{
+    type Response = Response<Option<Vec<u8>>>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<GetStateSnapshotChunkRequest>) -> Self::Future {
+        let inflight_per_peer = self.inflight_per_peer.clone();
+        let max_inflight_per_peer = self.max_inflight_per_peer;
+        let mut inner = self.inner.clone();
+
+        let fut = async move {
+            let Some(peer_id) = req.peer_id().copied() else {
+                return inner.call(req).await;
+            };
+
+            // Drop the semaphores of peers without inflight requests once there are many of them.
+            const PRUNE_THRESHOLD: usize = 5000;
+            if inflight_per_peer.len() >= PRUNE_THRESHOLD {
+                inflight_per_peer
+                    .retain(|_, semaphore| semaphore.available_permits() < max_inflight_per_peer);
+            }
+
+            let semaphore = {
+                let semaphore_entry = inflight_per_peer
+                    .entry(peer_id)
+                    .or_insert_with(|| Arc::new(Semaphore::new(max_inflight_per_peer)));
+                semaphore_entry.value().clone()
+            };
+            let permit = semaphore.try_acquire_owned().map_err(|e| match e {
+                tokio::sync::TryAcquireError::Closed => {
+                    anemo::rpc::Status::new(StatusCode::InternalServerError)
+                }
+                tokio::sync::TryAcquireError::NoPermits => {
+                    anemo::rpc::Status::new(StatusCode::TooManyRequests)
+                }
+            })?;
+
+            struct SemaphoreExtension(OwnedSemaphorePermit);
+            inner.call(req).await.map(move |mut response| {
+                // Insert permit as extension so it's not dropped until the response is sent.
+                response.extensions_mut().insert(SemaphoreExtension(permit));
+                response
+            })
+        };
+        Box::pin(fut)
+    }
+}
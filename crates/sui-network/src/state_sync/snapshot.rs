@@ -0,0 +1,336 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Peer-to-peer transfer of state snapshots.
+//!
+//! Nodes that keep state snapshots on local disk can serve the live object set of the latest epoch
+//! they have a snapshot of, split into chunks. A node bootstrapping from its peers first downloads
+//! the manifest listing those chunks, and checks that the chunks add up to the root state digest
+//! committed to by the last checkpoint of the epoch. Chunks are then downloaded in parallel, and
+//! each of them is checked against the manifest before it is handed over to be stored, so a peer
+//! serving bad data is detected as soon as one of its chunks is received.
+//!
+//! How chunks are encoded is left to the [`StateSnapshotStore`] serving them and to the
+//! [`StateSnapshotSink`] consuming them.
+
+use super::{
+    peer_scores::{PeerScores, RequestOutcome},
+    StateSyncClient,
+};
+use anemo::{PeerId, Request, Response};
+use anyhow::{anyhow, Result};
+use fastcrypto::hash::MultisetHash;
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use sui_types::{
+    accumulator::Accumulator,
+    committee::EpochId,
+    messages_checkpoint::{CheckpointCommitment, ECMHLiveObjectSetDigest, VerifiedCheckpoint},
+};
+use tap::TapFallible;
+use tokio::time::Instant;
+use tracing::{debug, info, trace, warn};
+
+/// Most bytes of a chunk served by a single GetStateSnapshotChunk request. Larger chunks are
+/// downloaded in several requests.
+pub const MAX_STATE_SNAPSHOT_CHUNK_READ: u64 = 8 << 20;
+
+/// Chunks that a manifest announces as larger than this are not downloaded.
+const MAX_STATE_SNAPSHOT_CHUNK_SIZE: u64 = 1 << 30;
+
+/// The chunks making up the state snapshot of an epoch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshotManifest {
+    pub epoch: EpochId,
+    pub chunks: Vec<StateSnapshotChunkInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshotChunkInfo {
+    /// Accumulator of the digests of the objects in the chunk.
+    pub accumulator: Accumulator,
+    /// Size of the encoded chunk, in bytes.
+    pub size: u64,
+    /// Store specific description of the chunk, e.g. how it is encoded.
+    pub metadata: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GetStateSnapshotChunkRequest {
+    pub epoch: EpochId,
+    pub index: u64,
+    /// Offset of the first byte of the chunk requested.
+    pub offset: u64,
+    /// Number of bytes requested, served up to `MAX_STATE_SNAPSHOT_CHUNK_READ`.
+    pub length: u64,
+}
+
+impl StateSnapshotManifest {
+    /// Whether the chunks of this manifest add up to `root_state_digest`.
+    pub fn verify(&self, root_state_digest: &ECMHLiveObjectSetDigest) -> bool {
+        let mut accumulator = Accumulator::default();
+        for chunk in &self.chunks {
+            accumulator.union(&chunk.accumulator);
+        }
+        ECMHLiveObjectSetDigest::from(accumulator.digest()) == *root_state_digest
+    }
+}
+
+/// Source of the state snapshot served to peers.
+pub trait StateSnapshotStore: Send + Sync + 'static {
+    /// The manifest of the latest snapshot available, if any.
+    fn manifest(&self) -> Result<Option<StateSnapshotManifest>>;
+
+    /// The bytes `offset..offset + length` of the encoded contents of a chunk of the snapshot of
+    /// `epoch`, fewer if the chunk ends before, or `None` if that snapshot or chunk isn't
+    /// available.
+    fn chunk(
+        &self,
+        epoch: EpochId,
+        index: usize,
+        offset: u64,
+        length: u64,
+    ) -> Result<Option<Vec<u8>>>;
+}
+
+/// Destination of a state snapshot downloaded from peers.
+pub trait StateSnapshotSink {
+    type Chunk;
+
+    /// Decode a chunk received from a peer, returning it along with the accumulator of the digests
+    /// of its objects. Nothing must be stored until the chunk is passed to `insert`, since the
+    /// chunk may still be rejected.
+    fn decode(
+        &self,
+        info: &StateSnapshotChunkInfo,
+        bytes: Vec<u8>,
+    ) -> Result<(Self::Chunk, Accumulator)>;
+
+    /// Store a chunk that matched the manifest.
+    fn insert(&self, chunk: Self::Chunk) -> Result<()>;
+}
+
+/// The root state digest committed to by an end of epoch checkpoint.
+pub fn root_state_digest(checkpoint: &VerifiedCheckpoint) -> Option<ECMHLiveObjectSetDigest> {
+    checkpoint
+        .end_of_epoch_data
+        .as_ref()?
+        .epoch_commitments
+        .last()
+        .map(|commitment| match commitment {
+            CheckpointCommitment::ECMHLiveObjectSetDigest(digest) => digest.clone(),
+        })
+}
+
+/// Downloads state snapshots from a set of peers.
+pub struct StateSnapshotDownloader {
+    network: anemo::Network,
+    peers: Vec<PeerId>,
+    peer_scores: PeerScores,
+    timeout: Duration,
+    concurrency: usize,
+}
+
+impl StateSnapshotDownloader {
+    pub fn new(
+        network: anemo::Network,
+        peers: Vec<PeerId>,
+        timeout: Duration,
+        concurrency: usize,
+    ) -> Self {
+        Self {
+            network,
+            peers,
+            // Peers that served bad chunks are only asked for more once all others were tried.
+            peer_scores: PeerScores::new(Duration::ZERO, super::metrics::Metrics::disabled()),
+            timeout,
+            concurrency,
+        }
+    }
+
+    /// The snapshot manifests offered by our peers.
+    pub async fn manifests(&self) -> Vec<(PeerId, StateSnapshotManifest)> {
+        let requests = self.peers.iter().filter_map(|peer_id| {
+            let mut client = StateSyncClient::new(self.network.peer(*peer_id)?);
+            let request = Request::new(()).with_timeout(self.timeout);
+            Some(async move {
+                let manifest = client
+                    .get_state_snapshot_manifest(request)
+                    .await
+                    .tap_err(|e| trace!("{e:?}"))
+                    .ok()
+                    .and_then(Response::into_inner)?;
+                Some((*peer_id, manifest))
+            })
+        });
+        futures::future::join_all(requests)
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// The most recent epoch one of our peers has a snapshot of.
+    pub async fn latest_epoch(&self) -> Option<EpochId> {
+        self.manifests()
+            .await
+            .into_iter()
+            .map(|(_, manifest)| manifest.epoch)
+            .max()
+    }
+
+    /// Download the snapshot of the epoch ended by `checkpoint` into `sink`, and return the
+    /// accumulator of the live object set, whose digest matches the root state digest of the
+    /// checkpoint.
+    pub async fn download<K: StateSnapshotSink>(
+        &self,
+        checkpoint: &VerifiedCheckpoint,
+        sink: &K,
+    ) -> Result<Accumulator> {
+        let epoch = checkpoint.epoch();
+        let root_state_digest = root_state_digest(checkpoint).ok_or_else(|| {
+            anyhow!(
+                "checkpoint {} does not commit to the root state digest of epoch {epoch}",
+                checkpoint.sequence_number()
+            )
+        })?;
+
+        // Settle on the first manifest matching the root state digest, and download its chunks
+        // from the peers offering the same one.
+        let mut manifest = None;
+        let mut peers = vec![];
+        for (peer_id, candidate) in self.manifests().await {
+            if candidate.epoch != epoch {
+                continue;
+            }
+            if let Some(manifest) = &manifest {
+                if *manifest == candidate {
+                    peers.push(peer_id);
+                } else {
+                    debug!(
+                        "peer {peer_id} offered a differently chunked snapshot of epoch {epoch}"
+                    );
+                }
+            } else if candidate.verify(&root_state_digest) {
+                manifest = Some(candidate);
+                peers.push(peer_id);
+            } else {
+                warn!("peer {peer_id} offered a snapshot of epoch {epoch} that does not match its root state digest");
+            }
+        }
+        let manifest =
+            manifest.ok_or_else(|| anyhow!("no peer offered a valid snapshot of epoch {epoch}"))?;
+        info!(
+            "downloading the {} chunks of the snapshot of epoch {epoch} from {} peers",
+            manifest.chunks.len(),
+            peers.len()
+        );
+
+        let mut accumulator = Accumulator::default();
+        futures::stream::iter(manifest.chunks.iter().enumerate())
+            .map(|(index, info)| self.download_chunk(epoch, index, info, &peers, sink))
+            .buffer_unordered(self.concurrency)
+            .try_for_each(|chunk_accumulator| {
+                accumulator.union(&chunk_accumulator);
+                futures::future::ready(Ok(()))
+            })
+            .await?;
+
+        // Guaranteed by the checks on the manifest and on each chunk.
+        debug_assert!(ECMHLiveObjectSetDigest::from(accumulator.digest()) == root_state_digest);
+        Ok(accumulator)
+    }
+
+    async fn download_chunk<K: StateSnapshotSink>(
+        &self,
+        epoch: EpochId,
+        index: usize,
+        info: &StateSnapshotChunkInfo,
+        peers: &[PeerId],
+        sink: &K,
+    ) -> Result<Accumulator> {
+        if info.size > MAX_STATE_SNAPSHOT_CHUNK_SIZE {
+            return Err(anyhow!(
+                "chunk {index} of the snapshot of epoch {epoch} is too large: {} bytes",
+                info.size
+            ));
+        }
+        // Spread chunks across peers, starting with the well behaved ones.
+        let mut peers: Vec<_> = peers
+            .iter()
+            .cycle()
+            .skip(index % peers.len())
+            .take(peers.len())
+            .copied()
+            .collect();
+        peers.sort_by_key(|peer_id| self.peer_scores.is_deprioritized(peer_id));
+
+        for peer_id in peers {
+            let Some(peer) = self.network.peer(peer_id) else {
+                continue;
+            };
+            let start = Instant::now();
+            let bytes = match self.fetch_chunk(peer, epoch, index, info.size).await {
+                Ok(bytes) => bytes,
+                Err(status) => {
+                    trace!("{status:?}");
+                    if let Some(outcome) = RequestOutcome::from_status(&status) {
+                        self.peer_scores.record(peer_id, outcome);
+                    }
+                    continue;
+                }
+            };
+            let Some(bytes) = bytes else {
+                trace!("peer unable to serve snapshot chunk {index}");
+                continue;
+            };
+
+            match sink.decode(info, bytes) {
+                Ok((chunk, accumulator)) if accumulator == info.accumulator => {
+                    self.peer_scores
+                        .record(peer_id, RequestOutcome::Success(start.elapsed()));
+                    sink.insert(chunk)?;
+                    return Ok(accumulator);
+                }
+                Ok(_) => warn!("peer {peer_id} served snapshot chunk {index} with wrong contents"),
+                Err(e) => {
+                    warn!("peer {peer_id} served snapshot chunk {index} that failed to decode: {e}")
+                }
+            }
+            self.peer_scores
+                .record(peer_id, RequestOutcome::InvalidResponse);
+        }
+        Err(anyhow!(
+            "no peer was able to serve chunk {index} of the snapshot of epoch {epoch}"
+        ))
+    }
+
+    /// Download the `size` bytes of a chunk from `peer`, `MAX_STATE_SNAPSHOT_CHUNK_READ` bytes at
+    /// a time. Returns `None` if the peer stops serving the chunk before its end.
+    async fn fetch_chunk(
+        &self,
+        peer: anemo::Peer,
+        epoch: EpochId,
+        index: usize,
+        size: u64,
+    ) -> Result<Option<Vec<u8>>, anemo::rpc::Status> {
+        let mut client = StateSyncClient::new(peer);
+        let mut bytes = Vec::new();
+        while (bytes.len() as u64) < size {
+            let offset = bytes.len() as u64;
+            let request = Request::new(GetStateSnapshotChunkRequest {
+                epoch,
+                index: index as u64,
+                offset,
+                length: (size - offset).min(MAX_STATE_SNAPSHOT_CHUNK_READ),
+            })
+            .with_timeout(self.timeout);
+            match client.get_state_snapshot_chunk(request).await?.into_inner() {
+                Some(part) if !part.is_empty() => bytes.extend(part),
+                _ => return Ok(None),
+            }
+        }
+        Ok(Some(bytes))
+    }
+}
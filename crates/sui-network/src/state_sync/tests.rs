@@ -3,15 +3,20 @@
 
 use crate::{
     state_sync::{
-        Builder, GetCheckpointSummaryRequest, PeerStateSyncInfo, StateSync, StateSyncMessage,
-        UnstartedStateSync,
+        metrics::Metrics,
+        peer_scores::{PeerScores, RequestOutcome},
+        Builder, GetCheckpointSummaryRequest, GetStateSnapshotChunkRequest, PeerStateSyncInfo,
+        StateSnapshotChunkInfo, StateSnapshotDownloader, StateSnapshotManifest, StateSnapshotSink,
+        StateSnapshotStore, StateSync, StateSyncMessage, UnstartedStateSync,
     },
     utils::build_network,
 };
 use anemo::{PeerId, Request};
 use anyhow::anyhow;
+use fastcrypto::hash::MultisetHash;
 use prometheus::Registry;
 use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
 use std::{collections::HashMap, time::Duration};
 use sui_archival::reader::ArchiveReaderBalancer;
 use sui_archival::writer::ArchiveWriter;
@@ -20,7 +25,10 @@ use sui_storage::object_store::{ObjectStoreConfig, ObjectStoreType};
 use sui_storage::{FileCompression, StorageFormat};
 use sui_swarm_config::test_utils::{empty_contents, CommitteeFixture};
 use sui_types::{
-    messages_checkpoint::CheckpointDigest,
+    accumulator::Accumulator,
+    base_types::ObjectDigest,
+    committee::{EpochId, ProtocolVersion},
+    messages_checkpoint::{CheckpointDigest, ECMHLiveObjectSetDigest, EndOfEpochData},
     storage::{ReadStore, SharedInMemoryStore, WriteStore},
 };
 use tempfile::tempdir;
@@ -180,6 +188,306 @@ async fn server_get_checkpoint() {
     }
 }
 
+#[tokio::test(start_paused = true)]
+async fn peer_scores_ban_misbehaving_peers() {
+    let peer_scores = PeerScores::new(Duration::from_secs(60), Metrics::disabled());
+    let good = PeerId([1; 32]);
+    let bad = PeerId([2; 32]);
+
+    peer_scores.record(good, RequestOutcome::Success(Duration::from_millis(100)));
+    peer_scores.record(good, RequestOutcome::Success(Duration::from_millis(200)));
+    assert!(!peer_scores.is_deprioritized(&good));
+
+    peer_scores.record(bad, RequestOutcome::InvalidResponse);
+    assert!(!peer_scores.is_deprioritized(&bad));
+    peer_scores.record(bad, RequestOutcome::Timeout);
+    assert!(peer_scores.is_deprioritized(&bad));
+    assert!(!peer_scores.is_banned(&bad));
+    peer_scores.record(bad, RequestOutcome::InvalidResponse);
+    assert!(peer_scores.is_banned(&bad));
+
+    // Requests that were in flight when the peer got banned don't extend the ban.
+    peer_scores.record(bad, RequestOutcome::Error);
+    let snapshot = peer_scores.snapshot();
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot[0].peer_id, good);
+    assert_eq!(snapshot[0].average_latency_ms, Some(120));
+    assert_eq!(snapshot[0].banned_for_ms, None);
+    assert_eq!(snapshot[1].peer_id, bad);
+    assert_eq!(snapshot[1].invalid_responses, 2);
+    assert_eq!(snapshot[1].errors, 1);
+    assert_eq!(snapshot[1].banned_for_ms, Some(60_000));
+
+    // Once the ban expires the peer has to behave before it is preferred again.
+    tokio::time::advance(Duration::from_secs(61)).await;
+    assert!(!peer_scores.is_banned(&bad));
+    assert!(peer_scores.is_deprioritized(&bad));
+    peer_scores.record(bad, RequestOutcome::Success(Duration::from_millis(100)));
+    assert!(!peer_scores.is_deprioritized(&bad));
+}
+
+#[tokio::test]
+async fn peer_scores_without_banning() {
+    let peer_scores = PeerScores::new(Duration::ZERO, Metrics::disabled());
+    let peer = PeerId([1; 32]);
+
+    for _ in 0..10 {
+        peer_scores.record(peer, RequestOutcome::Timeout);
+    }
+    assert!(!peer_scores.is_banned(&peer));
+    assert!(peer_scores.is_deprioritized(&peer));
+    assert_eq!(peer_scores.snapshot()[0].score, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn peer_scores_penalize_lagging_peers() {
+    let peer_scores = PeerScores::new(Duration::from_secs(60), Metrics::disabled());
+    let peer = PeerId([1; 32]);
+
+    // Peers close to the highest known checkpoint, or only lagging briefly, aren't penalized.
+    peer_scores.record_height(peer, 9_500, 10_000);
+    peer_scores.record_height(peer, 5_000, 10_000);
+    tokio::time::advance(Duration::from_secs(30)).await;
+    peer_scores.record_height(peer, 5_000, 10_000);
+    assert_eq!(peer_scores.snapshot()[0].score, 100);
+
+    // Catching up resets the grace period.
+    peer_scores.record_height(peer, 10_000, 10_000);
+    tokio::time::advance(Duration::from_secs(40)).await;
+    peer_scores.record_height(peer, 5_000, 20_000);
+    assert_eq!(peer_scores.snapshot()[0].score, 100);
+
+    // Peers that stay far behind lose score until they are banned.
+    tokio::time::advance(Duration::from_secs(60)).await;
+    for _ in 0..11 {
+        peer_scores.record_height(peer, 5_000, 20_000);
+    }
+    assert!(peer_scores.is_deprioritized(&peer));
+    for _ in 0..10 {
+        peer_scores.record_height(peer, 5_000, 20_000);
+    }
+    assert!(peer_scores.is_banned(&peer));
+}
+
+#[tokio::test(start_paused = true)]
+async fn peer_scores_prune_idle_peers() {
+    let peer_scores = PeerScores::new(Duration::from_secs(7_200), Metrics::disabled());
+    let connected = PeerId([1; 32]);
+    let disconnected = PeerId([2; 32]);
+    let banned = PeerId([3; 32]);
+
+    for peer in [connected, disconnected] {
+        peer_scores.record(peer, RequestOutcome::Success(Duration::from_millis(100)));
+    }
+    for _ in 0..5 {
+        peer_scores.record(banned, RequestOutcome::Timeout);
+    }
+    assert!(peer_scores.is_banned(&banned));
+
+    // Peers are kept for a while after they were last seen.
+    tokio::time::advance(Duration::from_secs(1_800)).await;
+    peer_scores.prune(|peer_id| *peer_id == connected);
+    assert_eq!(peer_scores.snapshot().len(), 3);
+
+    // Then only connected and banned peers are kept.
+    tokio::time::advance(Duration::from_secs(1_800)).await;
+    peer_scores.prune(|peer_id| *peer_id == connected);
+    let peers: Vec<_> = peer_scores
+        .snapshot()
+        .into_iter()
+        .map(|score| score.peer_id)
+        .collect();
+    assert_eq!(peers, vec![connected, banned]);
+
+    // Once its ban expired, an idle peer is forgotten.
+    tokio::time::advance(Duration::from_secs(3_600)).await;
+    peer_scores.prune(|peer_id| *peer_id == connected);
+    assert_eq!(peer_scores.snapshot().len(), 1);
+}
+
+// Serves a snapshot made of chunks of raw object digests.
+struct TestSnapshotStore {
+    manifest: StateSnapshotManifest,
+    chunks: Vec<Vec<u8>>,
+}
+
+impl TestSnapshotStore {
+    fn new(epoch: EpochId, chunks: Vec<Vec<ObjectDigest>>) -> Self {
+        let manifest = StateSnapshotManifest {
+            epoch,
+            chunks: chunks
+                .iter()
+                .map(|digests| {
+                    let mut accumulator = Accumulator::default();
+                    accumulator.insert_all(digests);
+                    StateSnapshotChunkInfo {
+                        accumulator,
+                        metadata: vec![],
+                        size: 32 * digests.len() as u64,
+                    }
+                })
+                .collect(),
+        };
+        let chunks = chunks
+            .iter()
+            .map(|digests| digests.iter().flat_map(|d| *d.inner()).collect())
+            .collect();
+        Self { manifest, chunks }
+    }
+}
+
+impl StateSnapshotStore for TestSnapshotStore {
+    fn manifest(&self) -> anyhow::Result<Option<StateSnapshotManifest>> {
+        Ok(Some(self.manifest.clone()))
+    }
+
+    fn chunk(
+        &self,
+        epoch: EpochId,
+        index: usize,
+        offset: u64,
+        length: u64,
+    ) -> anyhow::Result<Option<Vec<u8>>> {
+        if epoch != self.manifest.epoch {
+            return Ok(None);
+        }
+        Ok(self.chunks.get(index).map(|chunk| {
+            let start = (offset as usize).min(chunk.len());
+            let end = start.saturating_add(length as usize).min(chunk.len());
+            chunk[start..end].to_vec()
+        }))
+    }
+}
+
+#[derive(Default)]
+struct TestSnapshotSink(Mutex<Vec<ObjectDigest>>);
+
+impl StateSnapshotSink for TestSnapshotSink {
+    type Chunk = Vec<ObjectDigest>;
+
+    fn decode(
+        &self,
+        _info: &StateSnapshotChunkInfo,
+        bytes: Vec<u8>,
+    ) -> anyhow::Result<(Self::Chunk, Accumulator)> {
+        let digests = bytes
+            .chunks(32)
+            .map(|bytes| Ok(ObjectDigest::new(bytes.try_into()?)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut accumulator = Accumulator::default();
+        accumulator.insert_all(&digests);
+        Ok((digests, accumulator))
+    }
+
+    fn insert(&self, chunk: Self::Chunk) -> anyhow::Result<()> {
+        self.0.lock().unwrap().extend(chunk);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn server_get_state_snapshot_chunk() {
+    let chunks = vec![(0..5u8).map(|i| ObjectDigest::new([i; 32])).collect()];
+    let store = TestSnapshotStore::new(0, chunks);
+    let chunk = store.chunks[0].clone();
+    let (_builder, server) = Builder::new()
+        .store(SharedInMemoryStore::default())
+        .snapshot_store(Arc::new(store))
+        .build_internal();
+
+    let get_chunk = |epoch, index, offset, length| {
+        server.get_state_snapshot_chunk(Request::new(GetStateSnapshotChunkRequest {
+            epoch,
+            index,
+            offset,
+            length,
+        }))
+    };
+    // Only the requested range of the chunk is served.
+    let response = get_chunk(0, 0, 32, 64).await.unwrap().into_inner();
+    assert_eq!(response.unwrap(), chunk[32..96]);
+    let response = get_chunk(0, 0, 128, 64).await.unwrap().into_inner();
+    assert_eq!(response.unwrap(), chunk[128..]);
+    let response = get_chunk(0, 0, 0, u64::MAX).await.unwrap().into_inner();
+    assert_eq!(response.unwrap(), chunk);
+
+    // Unknown epochs and chunks aren't served.
+    let response = get_chunk(1, 0, 0, 64).await.unwrap().into_inner();
+    assert!(response.is_none());
+    let response = get_chunk(0, 1, 0, 64).await.unwrap().into_inner();
+    assert!(response.is_none());
+}
+
+#[tokio::test]
+async fn state_snapshot_download() {
+    let committee = CommitteeFixture::generate(rand::rngs::OsRng, 0, 4);
+    let (ordered_checkpoints, _, _, _) = committee.make_empty_checkpoints(1, None);
+    let chunks: Vec<Vec<_>> = (0..4u8)
+        .map(|chunk| {
+            (0..5u8)
+                .map(|i| ObjectDigest::new([chunk * 5 + i; 32]))
+                .collect()
+        })
+        .collect();
+    let mut live_objects = Accumulator::default();
+    live_objects.insert_all(chunks.iter().flatten());
+
+    let end_of_epoch = |root: &Accumulator| {
+        committee
+            .make_end_of_epoch_checkpoint(
+                ordered_checkpoints[0].clone(),
+                Some(EndOfEpochData {
+                    next_epoch_committee: committee.committee().voting_rights.clone(),
+                    next_epoch_protocol_version: ProtocolVersion::MIN,
+                    epoch_commitments: vec![ECMHLiveObjectSetDigest::from(root.digest()).into()],
+                }),
+            )
+            .2
+    };
+    let checkpoint = end_of_epoch(&live_objects);
+
+    // One peer serves the snapshot as committed to by the checkpoint, and another one serves
+    // chunks that don't match its manifest.
+    let good_store = TestSnapshotStore::new(0, chunks.clone());
+    let mut bad_store = TestSnapshotStore::new(0, chunks.clone());
+    bad_store.chunks[1] = vec![42; 32 * 5];
+    let mut networks = vec![];
+    for store in [good_store, bad_store] {
+        let (_builder, server) = Builder::new()
+            .store(SharedInMemoryStore::default())
+            .snapshot_store(Arc::new(store))
+            .build();
+        networks.push(build_network(|router| router.add_rpc_service(server)));
+    }
+    let (_builder, server) = Builder::new().store(SharedInMemoryStore::default()).build();
+    let network = build_network(|router| router.add_rpc_service(server));
+    let mut peers = vec![];
+    for peer in &networks {
+        network.connect(peer.local_addr()).await.unwrap();
+        peers.push(peer.peer_id());
+    }
+
+    let downloader = StateSnapshotDownloader::new(network, peers, Duration::from_secs(5), 2);
+    assert_eq!(downloader.latest_epoch().await, Some(0));
+
+    let sink = TestSnapshotSink::default();
+    let accumulator = downloader.download(&checkpoint, &sink).await.unwrap();
+    assert_eq!(accumulator, live_objects);
+    let mut downloaded = sink.0.into_inner().unwrap();
+    downloaded.sort();
+    assert_eq!(downloaded, chunks.concat());
+
+    // Snapshots that don't match the root state digest of the checkpoint are rejected.
+    let mut other_objects = live_objects.clone();
+    other_objects.insert(ObjectDigest::new([100; 32]));
+    let sink = TestSnapshotSink::default();
+    downloader
+        .download(&end_of_epoch(&other_objects), &sink)
+        .await
+        .unwrap_err();
+    assert!(sink.0.into_inner().unwrap().is_empty());
+}
+
 #[tokio::test]
 async fn isolated_sync_job() {
     let committee = CommitteeFixture::generate(rand::rngs::OsRng, 0, 4);
@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::config_reload::ConfigFile;
 use crate::SuiNode;
 use axum::{
     extract::{Query, State},
@@ -11,7 +12,10 @@ use axum::{
 use humantime::parse_duration;
 use serde::Deserialize;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::str::FromStr;
 use std::sync::Arc;
+use sui_config::transaction_deny_config::DenyList;
+use sui_types::base_types::SuiAddress;
 use sui_types::error::SuiError;
 use telemetry_subscribers::TracingHandle;
 use tracing::info;
@@ -39,6 +43,14 @@ use tracing::info;
 //
 //   $ curl 'http://127.0.0.1:1337/node-config'
 //
+// Reload the node config file. The changes that are safe to make at runtime, such as the pruning,
+// expensive safety check and state sync download settings, are applied. The fields that changed
+// and the ones that need a restart to take effect are listed. The gRPC concurrency limit always
+// needs a restart, as it is fixed when the gRPC server starts. Pruning changes don't apply to
+// the pruning of DB checkpoints before they are uploaded, which also needs a restart:
+//
+//   $ curl -X POST 'http://127.0.0.1:1337/node-config/reload'
+//
 // Set a time-limited tracing config. After the duration expires, tracing will be disabled
 // automatically.
 //
@@ -47,6 +59,20 @@ use tracing::info;
 // Reset tracing to the TRACE_FILTER env var.
 //
 //   $ curl -X POST 'http://127.0.0.1:1337/reset-tracing'
+//
+// View the transaction deny config currently in effect:
+//
+//   $ curl 'http://127.0.0.1:1337/transaction-deny-config'
+//
+// Deny transactions using an object, calling into a package, or sent or sponsored by an address,
+// and allow them again. Updates take effect immediately and persist across restarts.
+//
+//   $ curl -X POST 'http://127.0.0.1:1337/transaction-deny-list/add?list=object&id=0x1234'
+//   $ curl -X POST 'http://127.0.0.1:1337/transaction-deny-list/remove?list=address&id=0x1234'
+//
+// View the reputation state sync keeps for its peers, including peers currently banned from sync:
+//
+//   $ curl 'http://127.0.0.1:1337/state-sync-peers'
 
 const LOGGING_ROUTE: &str = "/logging";
 const TRACING_ROUTE: &str = "/enable-tracing";
@@ -56,24 +82,38 @@ const CLEAR_BUFFER_STAKE_ROUTE: &str = "/clear-override-buffer-stake";
 const FORCE_CLOSE_EPOCH: &str = "/force-close-epoch";
 const CAPABILITIES: &str = "/capabilities";
 const NODE_CONFIG: &str = "/node-config";
+const NODE_CONFIG_RELOAD: &str = "/node-config/reload";
+const TRANSACTION_DENY_CONFIG: &str = "/transaction-deny-config";
+const TRANSACTION_DENY_LIST_ADD: &str = "/transaction-deny-list/add";
+const TRANSACTION_DENY_LIST_REMOVE: &str = "/transaction-deny-list/remove";
+const STATE_SYNC_PEERS: &str = "/state-sync-peers";
 
 struct AppState {
     node: Arc<SuiNode>,
     tracing_handle: TracingHandle,
+    config_file: ConfigFile,
 }
 
-pub async fn run_admin_server(node: Arc<SuiNode>, port: u16, tracing_handle: TracingHandle) {
+pub async fn run_admin_server(
+    node: Arc<SuiNode>,
+    port: u16,
+    tracing_handle: TracingHandle,
+    config_file: ConfigFile,
+) {
     let filter = tracing_handle.get_log().unwrap();
 
     let app_state = AppState {
         node,
         tracing_handle,
+        config_file,
     };
 
     let app = Router::new()
         .route(LOGGING_ROUTE, get(get_filter))
         .route(CAPABILITIES, get(capabilities))
         .route(NODE_CONFIG, get(node_config))
+        .route(TRANSACTION_DENY_CONFIG, get(transaction_deny_config))
+        .route(STATE_SYNC_PEERS, get(state_sync_peers))
         .route(LOGGING_ROUTE, post(set_filter))
         .route(
             SET_BUFFER_STAKE_ROUTE,
@@ -84,8 +124,17 @@ pub async fn run_admin_server(node: Arc<SuiNode>, port: u16, tracing_handle: Tra
             post(clear_override_protocol_upgrade_buffer_stake),
         )
         .route(FORCE_CLOSE_EPOCH, post(force_close_epoch))
+        .route(NODE_CONFIG_RELOAD, post(reload_node_config))
         .route(TRACING_ROUTE, post(enable_tracing))
         .route(TRACING_RESET_ROUTE, post(reset_tracing))
+        .route(
+            TRANSACTION_DENY_LIST_ADD,
+            post(add_to_transaction_deny_list),
+        )
+        .route(
+            TRANSACTION_DENY_LIST_REMOVE,
+            post(remove_from_transaction_deny_list),
+        )
         .with_state(Arc::new(app_state));
 
     let socket_address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
@@ -210,12 +259,102 @@ async fn capabilities(State(state): State<Arc<AppState>>) -> (StatusCode, String
 }
 
 async fn node_config(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
-    let node_config = &state.node.config;
+    let node_config = state.node.current_config().await;
 
     // Note private keys will be masked
     (StatusCode::OK, format!("{:#?}\n", node_config))
 }
 
+async fn reload_node_config(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    let config = match state.config_file.load() {
+        Ok(config) => config,
+        Err(err) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("can't load {}: {err:?}\n", state.config_file.path.display()),
+            )
+        }
+    };
+
+    let report = match state.node.reload_config(config).await {
+        Ok(report) => report,
+        Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    };
+    match serde_yaml::to_string(&report) {
+        Ok(report) => (StatusCode::OK, report),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+async fn transaction_deny_config(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    match serde_yaml::to_string(&*state.node.transaction_deny_config()) {
+        Ok(config) => (StatusCode::OK, config),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+async fn state_sync_peers(State(state): State<Arc<AppState>>) -> (StatusCode, String) {
+    match serde_yaml::to_string(&state.node.state_sync.peer_scores()) {
+        Ok(scores) => (StatusCode::OK, scores),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct DenyListEntry {
+    list: DenyList,
+    id: String,
+}
+
+async fn add_to_transaction_deny_list(
+    State(state): State<Arc<AppState>>,
+    entry: Query<DenyListEntry>,
+) -> (StatusCode, String) {
+    update_transaction_deny_list(state, entry, true).await
+}
+
+async fn remove_from_transaction_deny_list(
+    State(state): State<Arc<AppState>>,
+    entry: Query<DenyListEntry>,
+) -> (StatusCode, String) {
+    update_transaction_deny_list(state, entry, false).await
+}
+
+async fn update_transaction_deny_list(
+    state: Arc<AppState>,
+    entry: Query<DenyListEntry>,
+    deny: bool,
+) -> (StatusCode, String) {
+    let Query(DenyListEntry { list, id }) = entry;
+    let Ok(id) = SuiAddress::from_str(&id) else {
+        return (StatusCode::BAD_REQUEST, format!("invalid id: {id}\n"));
+    };
+
+    match state
+        .node
+        .update_transaction_deny_list(list, id, deny)
+        .await
+    {
+        Ok(true) if deny => (
+            StatusCode::OK,
+            format!("{id} added to the {list:?} deny list\n"),
+        ),
+        Ok(true) => (
+            StatusCode::OK,
+            format!("{id} removed from the {list:?} deny list\n"),
+        ),
+        Ok(false) if deny => (
+            StatusCode::OK,
+            format!("{id} is already in the {list:?} deny list\n"),
+        ),
+        Ok(false) => (
+            StatusCode::OK,
+            format!("{id} is not in the {list:?} deny list\n"),
+        ),
+        Err(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+    }
+}
+
 #[derive(Deserialize)]
 struct Epoch {
     epoch: u64,
@@ -0,0 +1,318 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use sui_config::{Config, NodeConfig};
+use sui_protocol_config::SupportedProtocolVersions;
+use sui_types::multiaddr::Multiaddr;
+use tracing::{error, info};
+
+use crate::SuiNode;
+
+/// The fields of the node config that can be changed while the node is running, named by their
+/// path in the config file. An entry also covers the fields nested in it.
+///
+/// `grpc-concurrency-limit` is not reloadable: the limit is enforced by a layer built once when
+/// the validator gRPC server starts. The `authority-store-pruning-config` changes apply to the
+/// pruning of the node's DB, but the DB checkpoints pruned before being uploaded as state
+/// snapshots keep using the config the node was started with.
+const RELOADABLE_FIELDS: &[&str] = &[
+    "authority-store-pruning-config.num-epochs-to-retain",
+    "authority-store-pruning-config.pruning-run-delay-seconds",
+    "authority-store-pruning-config.max-checkpoints-in-batch",
+    "authority-store-pruning-config.max-transactions-in-batch",
+    "authority-store-pruning-config.periodic-compaction-threshold-days",
+    "authority-store-pruning-config.num-epochs-to-retain-for-checkpoints",
+    "authority-store-pruning-config.enable-pruning-tombstones",
+    "authority-store-pruning-config.retained-history",
+    "expensive-safety-check-config",
+    "p2p-config.state-sync.interval-period-ms",
+    "p2p-config.state-sync.checkpoint-header-download-concurrency",
+    "p2p-config.state-sync.checkpoint-content-download-concurrency",
+    "p2p-config.state-sync.checkpoint-content-download-tx-concurrency",
+    "p2p-config.state-sync.timeout-ms",
+    "p2p-config.state-sync.checkpoint-content-timeout-ms",
+];
+
+const CONFIG_FILE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The config file a node was started from, along with the command line overrides of its content.
+#[derive(Clone, Debug)]
+pub struct ConfigFile {
+    pub path: PathBuf,
+    pub listen_address: Option<Multiaddr>,
+}
+
+impl ConfigFile {
+    pub fn load(&self) -> Result<NodeConfig> {
+        let mut config = NodeConfig::load(&self.path)?;
+        ensure!(
+            config.supported_protocol_versions.is_none(),
+            "supported_protocol_versions cannot be read from the config file"
+        );
+        config.supported_protocol_versions = Some(SupportedProtocolVersions::SYSTEM_DEFAULT);
+        if let Some(listen_address) = &self.listen_address {
+            config.network_address = listen_address.clone();
+        }
+        Ok(config)
+    }
+}
+
+/// The fields that changed in a config reload.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ConfigReloadReport {
+    /// Fields whose new value is in effect.
+    pub applied: Vec<String>,
+    /// Fields that only take effect after a restart. The node keeps running with their previous
+    /// value.
+    pub restart_required: Vec<String>,
+}
+
+impl ConfigReloadReport {
+    /// Whether a field within `section` was applied.
+    pub(crate) fn is_applied(&self, section: &str) -> bool {
+        self.applied.iter().any(|field| is_within(field, section))
+    }
+}
+
+/// Reload the node config from `config_file` whenever the file is modified.
+pub async fn watch_config_file(node: Arc<SuiNode>, config_file: ConfigFile) {
+    let mut last_modified = modified_time(&config_file.path);
+    let mut interval = tokio::time::interval(CONFIG_FILE_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let modified = modified_time(&config_file.path);
+        if modified == last_modified {
+            continue;
+        }
+        last_modified = modified;
+
+        info!(
+            "Node config file {} was modified, reloading it",
+            config_file.path.display()
+        );
+        let result = match config_file.load() {
+            Ok(config) => node.reload_config(config).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = result {
+            error!("Failed to reload the node config: {err:?}");
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Returns `current` with the reloadable fields updated to their value in `new`, and the changes
+/// between the two configs.
+pub(crate) fn apply_reloadable_fields(
+    current: &NodeConfig,
+    new: &NodeConfig,
+) -> Result<(NodeConfig, ConfigReloadReport)> {
+    let mut merged = serde_yaml::to_value(current)?;
+    let report = apply_reloadable_values(&mut merged, &serde_yaml::to_value(new)?);
+    let mut config: NodeConfig = serde_yaml::from_value(merged)?;
+    config.supported_protocol_versions = current.supported_protocol_versions;
+    Ok((config, report))
+}
+
+fn apply_reloadable_values(current: &mut Value, new: &Value) -> ConfigReloadReport {
+    let mut changed = vec![];
+    changed_fields("", current, new, &mut changed);
+    let mut report = ConfigReloadReport::default();
+    for field in changed {
+        if RELOADABLE_FIELDS
+            .iter()
+            .any(|reloadable| is_within(&field, reloadable))
+        {
+            set_field(current, &field, get_field(new, &field));
+            report.applied.push(field);
+        } else {
+            report.restart_required.push(field);
+        }
+    }
+    report
+}
+
+/// Whether `field` is `section` or one of the fields nested in it.
+fn is_within(field: &str, section: &str) -> bool {
+    field
+        .strip_prefix(section)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Collect the paths of the fields that differ between `old` and `new`. Structs are compared
+/// field by field, a missing struct being the same as one with no fields set, and any other value
+/// as a whole.
+fn changed_fields(path: &str, old: &Value, new: &Value, changed: &mut Vec<String>) {
+    let empty = Mapping::new();
+    let (Some(old_fields), Some(new_fields)) = (as_struct(old, &empty), as_struct(new, &empty))
+    else {
+        if old != new {
+            changed.push(path.to_owned());
+        }
+        return;
+    };
+
+    let names: BTreeSet<&str> = old_fields
+        .iter()
+        .chain(new_fields.iter())
+        .filter_map(|(name, _)| name.as_str())
+        .collect();
+    for name in names {
+        let name_value = Value::String(name.to_owned());
+        let field = if path.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{path}.{name}")
+        };
+        changed_fields(
+            &field,
+            old_fields.get(&name_value).unwrap_or(&Value::Null),
+            new_fields.get(&name_value).unwrap_or(&Value::Null),
+            changed,
+        );
+    }
+}
+
+fn as_struct<'a>(value: &'a Value, empty: &'a Mapping) -> Option<&'a Mapping> {
+    match value {
+        Value::Null => Some(empty),
+        Value::Mapping(fields) if fields.iter().all(|(name, _)| name.is_string()) => Some(fields),
+        _ => None,
+    }
+}
+
+fn get_field(value: &Value, path: &str) -> Option<Value> {
+    path.split('.')
+        .try_fold(value, |value, name| value.get(name))
+        .filter(|value| !value.is_null())
+        .cloned()
+}
+
+/// Set the field at `path` to `field`, or remove it when `field` is `None`.
+fn set_field(mut value: &mut Value, path: &str, field: Option<Value>) {
+    let mut names: Vec<_> = path
+        .split('.')
+        .map(|name| Value::String(name.to_owned()))
+        .collect();
+    let name = names.pop().expect("field paths are not empty");
+    for parent in names {
+        if !value.is_mapping() {
+            *value = Value::Mapping(Mapping::new());
+        }
+        let fields = value.as_mapping_mut().unwrap();
+        if !fields.contains_key(&parent) {
+            fields.insert(parent.clone(), Value::Null);
+        }
+        value = fields.get_mut(&parent).unwrap();
+    }
+
+    if !value.is_mapping() {
+        *value = Value::Mapping(Mapping::new());
+    }
+    let fields = value.as_mapping_mut().unwrap();
+    match field {
+        Some(field) => {
+            fields.insert(name, field);
+        }
+        None => {
+            fields.remove(&name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply_reloadable_values;
+    use serde_yaml::Value;
+
+    #[test]
+    fn test_apply_reloadable_values() {
+        let mut current: Value = serde_yaml::from_str(
+            r#"
+            db-path: /opt/sui/db
+            grpc-concurrency-limit: 100
+            authority-store-pruning-config:
+              num-latest-epoch-dbs-to-retain: 3
+              num-epochs-to-retain: 2
+              max-checkpoints-in-batch: 10
+            p2p-config:
+              seed-peers: []
+            "#,
+        )
+        .unwrap();
+        let new: Value = serde_yaml::from_str(
+            r#"
+            db-path: /opt/sui/db
+            grpc-concurrency-limit: 200
+            authority-store-pruning-config:
+              num-latest-epoch-dbs-to-retain: 5
+              num-epochs-to-retain: 1
+            expensive-safety-check-config:
+              enable-secondary-index-checks: true
+            p2p-config:
+              seed-peers: []
+              state-sync:
+                timeout-ms: 5000
+                mailbox-capacity: 16
+            "#,
+        )
+        .unwrap();
+
+        let report = apply_reloadable_values(&mut current, &new);
+        assert_eq!(
+            report.applied,
+            [
+                "authority-store-pruning-config.max-checkpoints-in-batch",
+                "authority-store-pruning-config.num-epochs-to-retain",
+                "expensive-safety-check-config.enable-secondary-index-checks",
+                "p2p-config.state-sync.timeout-ms",
+            ]
+        );
+        assert_eq!(
+            report.restart_required,
+            [
+                "authority-store-pruning-config.num-latest-epoch-dbs-to-retain",
+                "grpc-concurrency-limit",
+                "p2p-config.state-sync.mailbox-capacity",
+            ]
+        );
+        assert!(report.is_applied("p2p-config.state-sync"));
+        assert!(!report.is_applied("p2p-config.discovery"));
+
+        let expected: Value = serde_yaml::from_str(
+            r#"
+            db-path: /opt/sui/db
+            grpc-concurrency-limit: 100
+            authority-store-pruning-config:
+              num-latest-epoch-dbs-to-retain: 3
+              num-epochs-to-retain: 1
+            p2p-config:
+              seed-peers: []
+              state-sync:
+                timeout-ms: 5000
+            expensive-safety-check-config:
+              enable-secondary-index-checks: true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(current, expected);
+
+        // Applying the same config again changes nothing but the fields needing a restart.
+        let report = apply_reloadable_values(&mut current, &new);
+        assert!(report.applied.is_empty());
+        assert_eq!(report.restart_required.len(), 3);
+    }
+}
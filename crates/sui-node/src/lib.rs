@@ -48,9 +48,10 @@ use narwhal_network::metrics::MetricsMakeCallbackHandler;
 use narwhal_network::metrics::{NetworkConnectionMetrics, NetworkMetrics};
 use sui_archival::reader::ArchiveReaderBalancer;
 use sui_archival::writer::ArchiveWriter;
-use sui_config::node::{ConsensusProtocol, DBCheckpointConfig};
+use sui_config::node::{AuthorityStorePruningConfig, ConsensusProtocol, DBCheckpointConfig};
 use sui_config::node_config_metrics::NodeConfigMetrics;
-use sui_config::{ConsensusConfig, NodeConfig};
+use sui_config::transaction_deny_config::{DenyList, TransactionDenyConfig};
+use sui_config::{Config, ConsensusConfig, NodeConfig};
 use sui_core::authority::authority_per_epoch_store::AuthorityPerEpochStore;
 use sui_core::authority::authority_store_tables::AuthorityPerpetualTables;
 use sui_core::authority::epoch_start_configuration::EpochStartConfigTrait;
@@ -85,6 +86,7 @@ use sui_core::{
     authority_client::NetworkAuthorityClient,
 };
 use sui_json_rpc::coin_api::CoinReadApi;
+use sui_json_rpc::extended_api::ExtendedApi;
 use sui_json_rpc::governance_api::GovernanceReadApi;
 use sui_json_rpc::indexer_api::IndexerApi;
 use sui_json_rpc::move_utils::MoveUtils;
@@ -99,6 +101,7 @@ use sui_network::discovery;
 use sui_network::discovery::TrustedPeerChangeEvent;
 use sui_network::state_sync;
 use sui_protocol_config::{Chain, ProtocolConfig, SupportedProtocolVersions};
+use sui_snapshot::p2p::LocalStateSnapshotStore;
 use sui_snapshot::uploader::StateSnapshotUploader;
 use sui_storage::object_store::{ObjectStoreConfig, ObjectStoreType};
 use sui_storage::{
@@ -107,7 +110,7 @@ use sui_storage::{
     key_value_store_metrics::KeyValueStoreMetrics,
 };
 use sui_storage::{FileCompression, IndexStore, StorageFormat};
-use sui_types::base_types::{AuthorityName, EpochId};
+use sui_types::base_types::{AuthorityName, EpochId, SuiAddress};
 use sui_types::committee::Committee;
 use sui_types::crypto::KeypairTraits;
 use sui_types::error::{SuiError, SuiResult};
@@ -121,9 +124,11 @@ use sui_types::sui_system_state::SuiSystemStateTrait;
 use typed_store::rocks::default_db_options;
 use typed_store::DBMetrics;
 
+use crate::config_reload::ConfigReloadReport;
 use crate::metrics::{GrpcMetrics, SuiNodeMetrics};
 
 pub mod admin;
+pub mod config_reload;
 mod handle;
 pub mod metrics;
 
@@ -230,6 +235,13 @@ pub struct SuiNode {
 
     _state_snapshot_uploader_handle: Option<oneshot::Sender<()>>,
     _kv_store_uploader_handle: Option<oneshot::Sender<()>>,
+
+    /// Serializes the updates of the transaction deny config through the admin API.
+    transaction_deny_config_lock: Mutex<()>,
+
+    /// The config in effect, with the changes applied by config reloads. Also serializes the
+    /// reloads.
+    current_config: Mutex<NodeConfig>,
 }
 
 impl fmt::Debug for SuiNode {
@@ -509,6 +521,8 @@ impl SuiNode {
                 epoch_store
                     .protocol_config()
                     .max_move_identifier_len_as_option(),
+                config.enable_objects_by_type_index,
+                config.enable_balance_history_index,
             )))
         } else {
             None
@@ -563,13 +577,7 @@ impl SuiNode {
             state_snapshot_handle.is_some(),
         )?;
 
-        let mut pruning_config = config.authority_store_pruning_config;
-        if !epoch_store
-            .protocol_config()
-            .simplified_unwrap_then_delete()
-        {
-            pruning_config.set_enable_pruning_tombstones(false);
-        }
+        let pruning_config = Self::pruning_config(config, &epoch_store);
 
         let state = AuthorityState::new(
             config.protocol_public_key(),
@@ -585,7 +593,7 @@ impl SuiNode {
             genesis.objects(),
             &db_checkpoint_config,
             config.expensive_safety_check_config.clone(),
-            config.transaction_deny_config.clone(),
+            Self::load_transaction_deny_config(config)?,
             config.certificate_deny_config.clone(),
             config.indirect_objects_threshold,
             config.state_debug_dump_config.clone(),
@@ -616,7 +624,7 @@ impl SuiNode {
             .enable_secondary_index_checks()
         {
             if let Some(indexes) = state.indexes.clone() {
-                sui_core::verify_indexes::verify_indexes(state.database.clone(), indexes)
+                sui_core::verify_indexes::verify_indexes(state.database.clone(), indexes.tables())
                     .expect("secondary indexes are inconsistent");
             }
         }
@@ -719,6 +727,9 @@ impl SuiNode {
             _state_archive_handle: state_archive_handle,
             _state_snapshot_uploader_handle: state_snapshot_handle,
             _kv_store_uploader_handle: kv_store_uploader_handle,
+
+            transaction_deny_config_lock: Mutex::new(()),
+            current_config: Mutex::new(config.clone()),
         };
 
         info!("SuiNode started!");
@@ -768,6 +779,126 @@ impl SuiNode {
             .set_override_protocol_upgrade_buffer_stake(epoch, buffer_stake_bps)
     }
 
+    /// Load the transaction deny config persisted by the updates made through the admin API, or
+    /// the one of the node config when there is none. The persisted config replaces the one of
+    /// the node config entirely, so a warning is logged when the two differ.
+    fn load_transaction_deny_config(config: &NodeConfig) -> Result<TransactionDenyConfig> {
+        let path = config.transaction_deny_config_path();
+        if !path.exists() {
+            return Ok(config.transaction_deny_config.clone());
+        }
+        info!(
+            "Loading transaction deny config from {}, overriding the node config",
+            path.display()
+        );
+        let deny_config = TransactionDenyConfig::load(&path)?;
+        if serde_yaml::to_value(&deny_config)?
+            != serde_yaml::to_value(&config.transaction_deny_config)?
+        {
+            warn!(
+                path = %path.display(),
+                "The transaction deny config updated through the admin API differs from the one \
+                 of the node config, which is ignored. Remove the file to use the node config"
+            );
+        }
+        Ok(deny_config)
+    }
+
+    fn pruning_config(
+        config: &NodeConfig,
+        epoch_store: &AuthorityPerEpochStore,
+    ) -> AuthorityStorePruningConfig {
+        let mut pruning_config = config.authority_store_pruning_config.clone();
+        if !epoch_store
+            .protocol_config()
+            .simplified_unwrap_then_delete()
+        {
+            pruning_config.set_enable_pruning_tombstones(false);
+        }
+        pruning_config
+    }
+
+    /// The node config in effect, including the changes applied by config reloads.
+    pub async fn current_config(&self) -> NodeConfig {
+        self.current_config.lock().await.clone()
+    }
+
+    /// Apply the changes of `new_config` that are safe to make while the node is running, and
+    /// report the other ones, which need a restart.
+    pub async fn reload_config(&self, new_config: NodeConfig) -> Result<ConfigReloadReport> {
+        new_config
+            .authority_store_pruning_config
+            .retained_history
+            .validate()?;
+        let mut current_config = self.current_config.lock().await;
+        let (config, report) =
+            config_reload::apply_reloadable_fields(&current_config, &new_config)?;
+
+        if report.is_applied("authority-store-pruning-config") {
+            let epoch_store = self.state.load_epoch_store_one_call_per_task();
+            self.state
+                .set_pruning_config(Self::pruning_config(&config, &epoch_store));
+        }
+        if report.is_applied("expensive-safety-check-config") {
+            self.state
+                .set_expensive_safety_check_config(config.expensive_safety_check_config.clone());
+        }
+        if report.is_applied("p2p-config.state-sync") {
+            self.state_sync
+                .update_config(config.p2p_config.state_sync.clone().unwrap_or_default())
+                .await;
+        }
+        *current_config = config;
+
+        info!(applied = ?report.applied, "Node config reloaded");
+        if !report.restart_required.is_empty() {
+            warn!(
+                fields = ?report.restart_required,
+                "Node config changes need a restart to take effect"
+            );
+        }
+        Ok(report)
+    }
+
+    pub fn transaction_deny_config(&self) -> Arc<TransactionDenyConfig> {
+        self.state.transaction_deny_config()
+    }
+
+    /// Add `id` to a transaction deny list when `deny` is set, or remove it otherwise. The update
+    /// applies to transactions signed from now on, and is persisted so that it survives restarts.
+    /// Returns whether the deny list changed.
+    pub async fn update_transaction_deny_list(
+        &self,
+        list: DenyList,
+        id: SuiAddress,
+        deny: bool,
+    ) -> Result<bool> {
+        let _guard = self.transaction_deny_config_lock.lock().await;
+        let mut deny_config = (*self.state.transaction_deny_config()).clone();
+        let updated = if deny {
+            deny_config.add_to_deny_list(list, id)
+        } else {
+            deny_config.remove_from_deny_list(list, id)
+        };
+        if updated {
+            // Persist the update before applying it, replacing the previous file atomically.
+            let path = self.config.transaction_deny_config_path();
+            let tmp_path = path.with_extension("yaml.tmp");
+            deny_config.save(&tmp_path)?;
+            std::fs::rename(&tmp_path, &path)?;
+            self.state.set_transaction_deny_config(deny_config);
+        }
+        warn!(
+            ?list,
+            %id,
+            deny,
+            updated,
+            path = %self.config.transaction_deny_config_path().display(),
+            "Transaction deny list update requested through the admin API"
+        );
+        Ok(updated)
+    }
+
     // Testing-only API to start epoch close process.
     // For production code, please use the non-testing version.
     pub async fn close_epoch_for_testing(&self) -> SuiResult {
@@ -869,7 +1000,7 @@ impl SuiNode {
                         .prune_and_compact_before_upload
                         .unwrap_or(true),
                     config.indirect_objects_threshold,
-                    config.authority_store_pruning_config,
+                    config.authority_store_pruning_config.clone(),
                     prometheus_registry,
                     state_snapshot_enabled,
                 )?;
@@ -889,12 +1020,28 @@ impl SuiNode {
         archive_readers: ArchiveReaderBalancer,
         prometheus_registry: &Registry,
     ) -> Result<(Network, discovery::Handle, state_sync::Handle)> {
-        let (state_sync, state_sync_server) = state_sync::Builder::new()
+        let mut state_sync_builder = state_sync::Builder::new()
             .config(config.p2p_config.state_sync.clone().unwrap_or_default())
             .store(state_sync_store)
             .archive_readers(archive_readers)
-            .with_metrics(prometheus_registry)
-            .build();
+            .with_metrics(prometheus_registry);
+        if config.state_snapshot_write_config.serve_to_peers {
+            match &config.state_snapshot_write_config.object_store_config {
+                Some(ObjectStoreConfig {
+                    object_store: Some(ObjectStoreType::File),
+                    directory: Some(directory),
+                    ..
+                }) => {
+                    state_sync_builder = state_sync_builder.snapshot_store(Arc::new(
+                        LocalStateSnapshotStore::new(directory.clone()),
+                    ));
+                }
+                _ => warn!(
+                    "Serving state snapshots to peers requires state snapshots to be written to a local directory"
+                ),
+            }
+        }
+        let (state_sync, state_sync_server) = state_sync_builder.build();
 
         let (discovery, discovery_server) = discovery::Builder::new(trusted_peer_change_rx)
             .config(config.p2p_config.clone())
@@ -1341,6 +1488,7 @@ impl SuiNode {
             self.state.database.clone(),
             self.state.transaction_manager().clone(),
             self.accumulator.clone(),
+            self.state.indexes.clone(),
             self.config.checkpoint_executor_config.clone(),
             &self.registry_service.default_registry(),
         );
@@ -1578,7 +1726,7 @@ impl SuiNode {
                 epoch_start_configuration,
                 checkpoint_executor,
                 self.accumulator.clone(),
-                &self.config.expensive_safety_check_config,
+                &self.state.expensive_safety_check_config(),
             )
             .await
             .expect("Reconfigure authority state cannot fail");
@@ -1709,6 +1857,12 @@ pub fn build_http_server(
 
     let json_rpc_router = {
         let mut server = JsonRpcServerBuilder::new(env!("CARGO_PKG_VERSION"), prometheus_registry);
+        if let Some(max_batch_size) = config.json_rpc_max_batch_size {
+            server.set_max_batch_size(max_batch_size);
+        }
+        if let Some(rate_limit_config) = &config.json_rpc_rate_limit_config {
+            server.set_rate_limit_config(rate_limit_config.clone());
+        }
 
         let kv_store = build_kv_store(&state, config, prometheus_registry)?;
 
@@ -1749,6 +1903,13 @@ pub fn build_http_server(
                 sui_json_rpc::name_service::NameServiceConfig::default()
             };
 
+        if config.enable_objects_by_type_index || config.enable_balance_history_index {
+            server.register_module(ExtendedApi::new(
+                state.clone(),
+                ReadApi::new(state.clone(), kv_store.clone(), metrics.clone()),
+            ))?;
+        }
+
         server.register_module(IndexerApi::new(
             state.clone(),
             ReadApi::new(state.clone(), kv_store.clone(), metrics.clone()),
@@ -1769,7 +1930,8 @@ pub fn build_http_server(
         router = router.nest("/rest", rest_router);
     }
 
-    let server = axum::Server::bind(&config.json_rpc_address).serve(router.into_make_service());
+    let server = axum::Server::bind(&config.json_rpc_address)
+        .serve(router.into_make_service_with_connect_info::<std::net::SocketAddr>());
 
     let addr = server.local_addr();
     let handle = tokio::spawn(async move { server.await.unwrap() });
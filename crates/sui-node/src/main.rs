@@ -6,10 +6,9 @@ use mysten_common::sync::async_once_cell::AsyncOnceCell;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use sui_config::{Config, NodeConfig};
 use sui_core::runtime::SuiRuntimes;
+use sui_node::config_reload::ConfigFile;
 use sui_node::metrics;
-use sui_protocol_config::SupportedProtocolVersions;
 use sui_telemetry::send_telemetry_event;
 use sui_types::multiaddr::Multiaddr;
 use tokio::time::sleep;
@@ -42,6 +41,12 @@ struct Args {
 
     #[clap(long, help = "Specify address to listen on")]
     listen_address: Option<Multiaddr>,
+
+    #[clap(
+        long,
+        help = "Reload the config file when it is modified, applying the changes that are safe to make at runtime"
+    )]
+    watch_config: bool,
 }
 
 fn main() {
@@ -50,12 +55,11 @@ fn main() {
     // ProtocolConfig::poison_get_for_min_version();
 
     let args = Args::parse();
-    let mut config = NodeConfig::load(&args.config_path).unwrap();
-    assert!(
-        config.supported_protocol_versions.is_none(),
-        "supported_protocol_versions cannot be read from the config file"
-    );
-    config.supported_protocol_versions = Some(SupportedProtocolVersions::SYSTEM_DEFAULT);
+    let config_file = ConfigFile {
+        path: args.config_path,
+        listen_address: args.listen_address,
+    };
+    let config = config_file.load().unwrap();
 
     let runtimes = SuiRuntimes::new(&config);
     let metrics_rt = runtimes.metrics.enter();
@@ -87,10 +91,6 @@ fn main() {
         metrics::start_metrics_push_task(&config, registry_service.clone());
     }
 
-    if let Some(listen_address) = args.listen_address {
-        config.network_address = listen_address;
-    }
-
     let is_validator = config.consensus_config().is_some();
 
     let admin_interface_port = config.admin_interface_port;
@@ -118,6 +118,15 @@ fn main() {
         }
     });
 
+    if args.watch_config {
+        let node_once_cell_clone = node_once_cell.clone();
+        let config_file = config_file.clone();
+        runtimes.metrics.spawn(async move {
+            let node = node_once_cell_clone.get().await;
+            sui_node::config_reload::watch_config_file(node, config_file).await
+        });
+    }
+
     let node_once_cell_clone = node_once_cell.clone();
     runtimes.metrics.spawn(async move {
         let node = node_once_cell_clone.get().await;
@@ -134,7 +143,8 @@ fn main() {
             ))
             .unwrap();
 
-        sui_node::admin::run_admin_server(node, admin_interface_port, filter_handle).await
+        sui_node::admin::run_admin_server(node, admin_interface_port, filter_handle, config_file)
+            .await
     });
 
     runtimes.metrics.spawn(async move {
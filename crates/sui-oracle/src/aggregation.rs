@@ -0,0 +1,153 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::AggregationMethod;
+
+/// A value retrieved from one of the data sources of a feed.
+#[derive(Debug)]
+pub(crate) struct SourceValue<'a> {
+    pub source: &'a str,
+    pub value: f64,
+    pub max_deviation: Option<f64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Aggregate<'a> {
+    pub value: f64,
+    /// Sources whose value deviated too much from the median of all sources
+    pub outliers: Vec<&'a str>,
+}
+
+/// Combine the values of the sources of a feed. Outliers are discarded first, by comparing each
+/// value with the median of all values, and the remaining values are aggregated with `method`.
+pub(crate) fn aggregate<'a>(
+    values: &[SourceValue<'a>],
+    method: AggregationMethod,
+    min_sources: usize,
+) -> anyhow::Result<Aggregate<'a>> {
+    let min_sources = min_sources.max(1);
+    if values.len() < min_sources {
+        anyhow::bail!(
+            "Not enough source values to aggregate: got {}, need {min_sources}",
+            values.len()
+        );
+    }
+    let median_value = median(values.iter().map(|v| v.value).collect());
+
+    let mut inliers = vec![];
+    let mut outliers = vec![];
+    for v in values {
+        match v.max_deviation {
+            Some(max_deviation) if relative_deviation(v.value, median_value) > max_deviation => {
+                outliers.push(v.source)
+            }
+            _ => inliers.push(v.value),
+        }
+    }
+    if inliers.len() < min_sources {
+        anyhow::bail!(
+            "Not enough source values to aggregate after discarding outliers {outliers:?}: \
+             got {}, need {min_sources}",
+            inliers.len()
+        );
+    }
+
+    let value = match method {
+        AggregationMethod::Median => median(inliers),
+        AggregationMethod::TrimmedMean(trim) => trimmed_mean(inliers, trim),
+    };
+    Ok(Aggregate { value, outliers })
+}
+
+/// The deviation of `value` relative to `reference`, e.g. 0.1 when it is 10% higher or lower.
+pub(crate) fn relative_deviation(value: f64, reference: f64) -> f64 {
+    if reference == 0.0 {
+        if value == 0.0 {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    } else {
+        ((value - reference) / reference).abs()
+    }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+fn trimmed_mean(mut values: Vec<f64>, trim: f64) -> f64 {
+    values.sort_by(f64::total_cmp);
+    // Never trim every value away
+    let trimmed =
+        ((values.len() as f64 * trim.clamp(0.0, 0.5)) as usize).min((values.len() - 1) / 2);
+    let kept = &values[trimmed..values.len() - trimmed];
+    kept.iter().sum::<f64>() / kept.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_values<'a>(values: &[(&'a str, f64)], max_deviation: f64) -> Vec<SourceValue<'a>> {
+        values
+            .iter()
+            .map(|(source, value)| SourceValue {
+                source: *source,
+                value: *value,
+                max_deviation: Some(max_deviation),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_median() {
+        let values = source_values(&[("a", 3.0), ("b", 1.0), ("c", 2.0)], 1.0);
+        let result = aggregate(&values, AggregationMethod::Median, 1).unwrap();
+        assert_eq!(result.value, 2.0);
+        assert!(result.outliers.is_empty());
+
+        let values = source_values(&[("a", 4.0), ("b", 1.0), ("c", 2.0), ("d", 3.0)], 1.0);
+        let result = aggregate(&values, AggregationMethod::Median, 1).unwrap();
+        assert_eq!(result.value, 2.5);
+    }
+
+    #[test]
+    fn test_trimmed_mean() {
+        let values = source_values(
+            &[("a", 1.0), ("b", 2.0), ("c", 3.0), ("d", 4.0), ("e", 100.0)],
+            100.0,
+        );
+        let result = aggregate(&values, AggregationMethod::TrimmedMean(0.2), 1).unwrap();
+        assert_eq!(result.value, 3.0);
+
+        // Trimming keeps at least the middle value
+        let values = source_values(&[("a", 1.0), ("b", 2.0)], 100.0);
+        let result = aggregate(&values, AggregationMethod::TrimmedMean(0.5), 1).unwrap();
+        assert_eq!(result.value, 1.5);
+    }
+
+    #[test]
+    fn test_outliers() {
+        let values = source_values(&[("a", 100.0), ("b", 101.0), ("c", 150.0)], 0.05);
+        let result = aggregate(&values, AggregationMethod::Median, 2).unwrap();
+        assert_eq!(result.value, 100.5);
+        assert_eq!(result.outliers, vec!["c"]);
+
+        // Not enough values left once the outlier is discarded
+        assert!(aggregate(&values, AggregationMethod::Median, 3).is_err());
+    }
+
+    #[test]
+    fn test_not_enough_sources() {
+        assert!(aggregate(&[], AggregationMethod::Median, 0).is_err());
+        let values = source_values(&[("a", 1.0)], 1.0);
+        assert!(aggregate(&values, AggregationMethod::Median, 2).is_err());
+    }
+}
@@ -13,6 +13,17 @@ use sui_types::base_types::ObjectID;
 pub struct DataSourceConfig {
     pub url: String,
     pub json_path: String,
+    /// Name of the source in metrics and logs, defaults to a name derived from the feed source.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Values retrieved longer ago than this are not aggregated, defaults to the submission
+    /// interval of the feed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_staleness: Option<Duration>,
+    /// Values deviating from the median of all sources by more than this fraction (e.g. 0.05 for
+    /// 5%) are outliers, and are not aggregated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_deviation: Option<f64>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -20,9 +31,52 @@ pub struct DataSourceConfig {
 pub struct UploadFeedConfig {
     pub submission_interval: Duration,
     pub data_source_config: DataSourceConfig,
+    /// Sources aggregated with `data_source_config` into the submitted value.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_data_source_configs: Vec<DataSourceConfig>,
+    #[serde(default)]
+    pub aggregation: AggregationMethod,
+    /// Minimum number of fresh, non-outlier source values needed to submit a value.
+    #[serde(default = "default_min_sources")]
+    pub min_sources: usize,
+    /// When set, values are only submitted when they moved enough since the last submission, or
+    /// when the heartbeat elapsed. Otherwise every value is submitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submission_threshold: Option<SubmissionThreshold>,
     pub upload_parameters: UploadParameters,
 }
 
+impl UploadFeedConfig {
+    /// All the data sources of the feed, `data_source_config` first.
+    pub fn data_source_configs(&self) -> impl Iterator<Item = &DataSourceConfig> {
+        std::iter::once(&self.data_source_config).chain(&self.additional_data_source_configs)
+    }
+}
+
+fn default_min_sources() -> usize {
+    1
+}
+
+/// How the values of the sources of a feed are combined into the submitted value.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AggregationMethod {
+    #[default]
+    Median,
+    /// Mean of the values, after discarding this fraction of the lowest and of the highest values.
+    TrimmedMean(f64),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SubmissionThreshold {
+    /// A value is submitted when the last submission is older than this, even if it did not move.
+    pub heartbeat: Duration,
+    /// A value is submitted when it moved by more than this fraction (e.g. 0.01 for 1%) since the
+    /// last submission.
+    pub deviation: f64,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct UploadParameters {
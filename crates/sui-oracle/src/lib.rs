@@ -1,15 +1,19 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use aggregation::{aggregate, relative_deviation, SourceValue};
 use chrono::{DateTime, Utc};
-use config::{DownloadFeedConfigs, UploadFeedConfig, UploadParameters};
+use config::{
+    DataSourceConfig, DownloadFeedConfigs, SubmissionThreshold, UploadFeedConfig, UploadParameters,
+};
+use futures::future::join_all;
 use metrics::OracleMetrics;
 use mysten_metrics::monitored_scope;
 use once_cell::sync::OnceCell;
 use prometheus::Registry;
 use std::ops::Add;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 use std::{collections::HashMap, time::Instant};
 use sui_json_rpc_types::SuiTransactionBlockResponse;
@@ -37,6 +41,7 @@ use tap::tap::TapFallible;
 use sui_sdk::wallet_context::WalletContext;
 use sui_types::base_types::{random_object_ref, ObjectID, ObjectRef};
 use tracing::{debug, error, info, warn};
+mod aggregation;
 pub mod config;
 mod metrics;
 
@@ -44,6 +49,7 @@ mod metrics;
 const DECIMAL: u8 = 6;
 const METRICS_MULTIPLIER: f64 = 10u64.pow(DECIMAL as u32) as f64;
 const UPLOAD_FAILURE_RECOVER_SEC: u64 = 10;
+const DATA_SOURCE_TIMEOUT_SEC: u64 = 5;
 static STALE_OBJ_ERROR: OnceCell<String> = OnceCell::new();
 
 pub struct OracleNode {
@@ -134,6 +140,10 @@ impl DataProviderRunner {
         let mut staleness_tolerance = HashMap::new();
         let mut oracle_object_args = HashMap::new();
         let (sender, receiver) = tokio::sync::mpsc::channel(10000);
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(DATA_SOURCE_TIMEOUT_SEC))
+            .build()
+            .expect("Failed to build the data source http client");
         for (feed_name, upload_feed) in upload_feeds {
             for (source_name, data_feed) in upload_feed {
                 staleness_tolerance.insert(
@@ -146,7 +156,10 @@ impl DataProviderRunner {
                     source_name: source_name.clone(),
                     upload_feed: Arc::new(data_feed),
                     sender: sender.clone(),
+                    http_client: http_client.clone(),
                     metrics: metrics.clone(),
+                    state: Default::default(),
+                    last_submission: Default::default(),
                 };
                 providers.push(Arc::new(data_provider));
                 if let std::collections::hash_map::Entry::Vacant(e) =
@@ -227,7 +240,18 @@ struct DataProvider {
     pub source_name: String,
     pub upload_feed: Arc<UploadFeedConfig>,
     pub sender: tokio::sync::mpsc::Sender<DataPoint>,
+    /// Shared by all providers, and times out requests after `DATA_SOURCE_TIMEOUT_SEC`
+    http_client: reqwest::Client,
     metrics: Arc<OracleMetrics>,
+    state: Mutex<DataProviderState>,
+    /// The last value uploaded on chain, and when, set by the uploader once the upload succeeds
+    last_submission: Arc<Mutex<Option<(f64, Instant)>>>,
+}
+
+#[derive(Default)]
+struct DataProviderState {
+    /// The last value retrieved from each data source, and when it was retrieved
+    source_values: HashMap<String, (f64, Instant)>,
 }
 
 impl DataProvider {
@@ -252,70 +276,162 @@ impl DataProvider {
             source_name = self.source_name,
             "Running data provider once."
         );
-        let value = self.retrieve_from_data_source().await;
-        if value.is_err() {
-            error!(
-                feed_name = self.feed_name,
-                source_name = self.source_name,
-                "Failed to retrieve data from data source: {:?}",
-                value
-            );
-            self.metrics
-                .data_source_errors
-                .with_label_values(&[&self.feed_name, &self.source_name])
-                .inc();
-            return;
+        // Fetch all data sources at once, so a slow source only delays the round by at most
+        // `DATA_SOURCE_TIMEOUT_SEC`.
+        let data_sources: Vec<_> = self.upload_feed.data_source_configs().collect();
+        let values = join_all(data_sources.iter().map(|data_source| async move {
+            tokio::time::timeout(
+                Duration::from_secs(DATA_SOURCE_TIMEOUT_SEC),
+                retrieve_from_data_source(&self.http_client, data_source),
+            )
+            .await
+            .unwrap_or_else(|_| {
+                Err(anyhow::anyhow!(
+                    "Timed out after {DATA_SOURCE_TIMEOUT_SEC}s fetching data"
+                ))
+            })
+        }))
+        .await;
+
+        let mut retrieved = vec![];
+        for (index, (data_source, value)) in data_sources.into_iter().zip(values).enumerate() {
+            let data_source_name = self.data_source_name(index, data_source);
+            match &value {
+                Ok(_) => self
+                    .metrics
+                    .data_source_successes
+                    .with_label_values(&[&self.feed_name, &data_source_name])
+                    .inc(),
+                Err(err) => {
+                    error!(
+                        feed_name = self.feed_name,
+                        source_name = self.source_name,
+                        data_source_name,
+                        "Failed to retrieve data from data source: {:?}",
+                        err
+                    );
+                    self.metrics
+                        .data_source_errors
+                        .with_label_values(&[&self.feed_name, &data_source_name])
+                        .inc();
+                }
+            }
+            retrieved.push((data_source_name, data_source, value.ok()));
         }
 
-        self.metrics
-            .data_source_successes
-            .with_label_values(&[&self.feed_name, &self.source_name])
-            .inc();
-
-        // TODO: allow more flexible multiplers and data types
-        let value = (value.unwrap() * METRICS_MULTIPLIER) as u64;
-        self.send_to_uploader(value).await;
+        if let Some(aggregate) = self.aggregate_retrieved(retrieved) {
+            // TODO: allow more flexible multiplers and data types
+            let value = (aggregate * METRICS_MULTIPLIER) as u64;
+            self.send_to_uploader(value, aggregate).await;
+        }
     }
 
-    async fn retrieve_from_data_source(&self) -> anyhow::Result<f64> {
-        // TODO: support websocket
-        let url = &self.upload_feed.data_source_config.url;
-        let json_path = &self.upload_feed.data_source_config.json_path;
-        let response = reqwest::Client::new().get(url).send().await?;
-
-        if !response.status().is_success() {
-            anyhow::bail!("Failed to fetch data: {:?}", response);
+    /// Aggregate the values just retrieved with the recent values of the sources that failed,
+    /// and return the aggregate if it should be submitted.
+    fn aggregate_retrieved(
+        &self,
+        retrieved: Vec<(String, &DataSourceConfig, Option<f64>)>,
+    ) -> Option<f64> {
+        let mut state = self.state.lock().unwrap();
+        // Aggregate the latest value of each data source, unless it is too stale
+        let mut source_values = vec![];
+        for (data_source_name, data_source, value) in &retrieved {
+            if let Some(value) = value {
+                state
+                    .source_values
+                    .insert(data_source_name.clone(), (*value, Instant::now()));
+            }
+            let Some((value, retrieval_instant)) = state.source_values.get(data_source_name) else {
+                continue;
+            };
+            let max_staleness = data_source
+                .max_staleness
+                .unwrap_or(self.upload_feed.submission_interval);
+            if retrieval_instant.elapsed() > max_staleness {
+                warn!(
+                    feed_name = self.feed_name,
+                    data_source_name,
+                    value,
+                    ?max_staleness,
+                    "Data source value is too stale, skipping."
+                );
+                self.metrics
+                    .data_source_stale_values
+                    .with_label_values(&[&self.feed_name, data_source_name])
+                    .inc();
+                continue;
+            }
+            source_values.push(SourceValue {
+                source: data_source_name,
+                value: *value,
+                max_deviation: data_source.max_deviation,
+            });
         }
 
-        let json_blob: serde_json::Value = response.json().await.unwrap();
-        let data = jsonpath_lib::select(&json_blob, json_path)?;
+        let aggregate = match aggregate(
+            &source_values,
+            self.upload_feed.aggregation,
+            self.upload_feed.min_sources,
+        ) {
+            Ok(aggregate) => aggregate,
+            Err(err) => {
+                error!(
+                    feed_name = self.feed_name,
+                    source_name = self.source_name,
+                    "Failed to aggregate data source values: {err}"
+                );
+                self.metrics
+                    .aggregation_errors
+                    .with_label_values(&[&self.feed_name, &self.source_name])
+                    .inc();
+                return None;
+            }
+        };
+        for outlier in &aggregate.outliers {
+            warn!(
+                feed_name = self.feed_name,
+                data_source_name = outlier,
+                aggregate = aggregate.value,
+                "Data source value deviates too much from the other sources, skipping."
+            );
+            self.metrics
+                .data_source_outliers
+                .with_label_values(&[&self.feed_name, outlier])
+                .inc();
+        }
 
-        if data.is_empty() {
-            anyhow::bail!(
-                "Failed to find data from json blob: {:?} with json path: {:?}",
-                json_blob,
-                json_path
+        let last_submission = *self.last_submission.lock().unwrap();
+        if !should_submit(
+            self.upload_feed.submission_threshold.as_ref(),
+            aggregate.value,
+            last_submission,
+        ) {
+            debug!(
+                feed_name = self.feed_name,
+                source_name = self.source_name,
+                value = aggregate.value,
+                "Value moved less than the submission threshold, skipping."
             );
+            self.metrics
+                .skipped_submissions
+                .with_label_values(&[&self.feed_name, &self.source_name])
+                .inc();
+            return None;
         }
-        // Assume there is one single value per request
-        match data[0].as_str() {
-            Some(value_str) => match value_str.parse::<f64>() {
-                Ok(value) => Ok(value),
-                Err(_) => anyhow::bail!(
-                    "Failed to parse data {:?} as f64 from json blob: {:?}",
-                    data[0],
-                    json_blob
-                ),
-            },
-            None => anyhow::bail!(
-                "Failed to parse data {:?} as string from json blob: {:?}",
-                data[0],
-                json_blob
-            ),
+        Some(aggregate.value)
+    }
+
+    /// The name of a data source in metrics and logs. The first data source is named after the
+    /// feed source, as when feeds had a single data source.
+    fn data_source_name(&self, index: usize, data_source: &DataSourceConfig) -> String {
+        match (&data_source.name, index) {
+            (Some(name), _) => name.clone(),
+            (None, 0) => self.source_name.clone(),
+            (None, index) => format!("{}-{index}", self.source_name),
         }
     }
 
-    async fn send_to_uploader(&self, value: u64) {
+    async fn send_to_uploader(&self, value: u64, aggregate: f64) {
         let _ = self
             .sender
             .send(DataPoint {
@@ -324,12 +440,28 @@ impl DataProvider {
                 value,
                 retrieval_timestamp: SystemTime::now(),
                 retrieval_instant: Instant::now(),
+                aggregate,
+                last_submission: self.last_submission.clone(),
             })
             .await
             .tap_err(|err| error!("Failed to send data point to uploader: {:?}", err));
     }
 }
 
+/// Whether `value` should be submitted, given the last value uploaded and when: always without a
+/// `threshold` or a previous upload, otherwise once the heartbeat expired or the value moved enough.
+fn should_submit(
+    threshold: Option<&SubmissionThreshold>,
+    value: f64,
+    last_submission: Option<(f64, Instant)>,
+) -> bool {
+    let (Some(threshold), Some((last_value, last_instant))) = (threshold, last_submission) else {
+        return true;
+    };
+    last_instant.elapsed() >= threshold.heartbeat
+        || relative_deviation(value, last_value) >= threshold.deviation
+}
+
 fn make_onchain_feed_name(feed_name: &str, source_name: &str) -> String {
     format!(
         "{}-{}",
@@ -338,6 +470,47 @@ fn make_onchain_feed_name(feed_name: &str, source_name: &str) -> String {
     )
 }
 
+async fn retrieve_from_data_source(
+    http_client: &reqwest::Client,
+    data_source: &DataSourceConfig,
+) -> anyhow::Result<f64> {
+    // TODO: support websocket
+    let url = &data_source.url;
+    let json_path = &data_source.json_path;
+    let response = http_client.get(url).send().await?;
+
+    if !response.status().is_success() {
+        anyhow::bail!("Failed to fetch data: {:?}", response);
+    }
+
+    let json_blob: serde_json::Value = response.json().await?;
+    let data = jsonpath_lib::select(&json_blob, json_path)?;
+
+    if data.is_empty() {
+        anyhow::bail!(
+            "Failed to find data from json blob: {:?} with json path: {:?}",
+            json_blob,
+            json_path
+        );
+    }
+    // Assume there is one single value per request
+    match data[0].as_str() {
+        Some(value_str) => match value_str.parse::<f64>() {
+            Ok(value) => Ok(value),
+            Err(_) => anyhow::bail!(
+                "Failed to parse data {:?} as f64 from json blob: {:?}",
+                data[0],
+                json_blob
+            ),
+        },
+        None => anyhow::bail!(
+            "Failed to parse data {:?} as string from json blob: {:?}",
+            data[0],
+            json_blob
+        ),
+    }
+}
+
 struct OnChainDataUploader {
     wallet_ctx: Arc<WalletContext>,
     client: Arc<SuiClient>,
@@ -543,6 +716,11 @@ impl OnChainDataUploader {
         self.metrics.total_gas_rebate.inc_by(storage_rebate);
 
         if success {
+            let uploaded_at = Instant::now();
+            for data_point in &data_points {
+                *data_point.last_submission.lock().unwrap() =
+                    Some((data_point.aggregate, uploaded_at));
+            }
             self.metrics
                 .total_data_points_uploaded
                 .inc_by(data_points.len() as u64);
@@ -624,6 +802,10 @@ struct DataPoint {
     value: u64,
     retrieval_timestamp: SystemTime,
     retrieval_instant: Instant,
+    /// The aggregate `value` was computed from
+    aggregate: f64,
+    /// Where to record the upload of this data point, see `DataProvider::last_submission`
+    last_submission: Arc<Mutex<Option<(f64, Instant)>>>,
 }
 
 struct OnChainDataReader {
@@ -709,3 +891,37 @@ async fn get_object_arg(
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: SubmissionThreshold = SubmissionThreshold {
+        heartbeat: Duration::from_secs(60),
+        deviation: 0.01,
+    };
+
+    fn uploaded(value: f64, ago: Duration) -> Option<(f64, Instant)> {
+        Some((value, Instant::now().checked_sub(ago).unwrap()))
+    }
+
+    #[test]
+    fn test_should_submit() {
+        // Without a threshold or a previous upload, every value is submitted.
+        assert!(should_submit(None, 100.0, uploaded(100.0, Duration::ZERO)));
+        assert!(should_submit(Some(&THRESHOLD), 100.0, None));
+
+        // A value that barely moved waits for the heartbeat.
+        let recent = uploaded(100.0, Duration::from_secs(1));
+        assert!(!should_submit(Some(&THRESHOLD), 100.5, recent));
+        assert!(should_submit(
+            Some(&THRESHOLD),
+            100.5,
+            uploaded(100.0, Duration::from_secs(61))
+        ));
+
+        // A value that moved by the deviation is submitted right away, in either direction.
+        assert!(should_submit(Some(&THRESHOLD), 102.0, recent));
+        assert!(should_submit(Some(&THRESHOLD), 98.0, recent));
+    }
+}
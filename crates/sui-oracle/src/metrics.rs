@@ -13,6 +13,10 @@ pub struct OracleMetrics {
     pub(crate) data_source_successes: IntCounterVec,
     pub(crate) data_source_errors: IntCounterVec,
     pub(crate) data_staleness: IntCounterVec,
+    pub(crate) data_source_stale_values: IntCounterVec,
+    pub(crate) data_source_outliers: IntCounterVec,
+    pub(crate) aggregation_errors: IntCounterVec,
+    pub(crate) skipped_submissions: IntCounterVec,
     pub(crate) upload_successes: IntCounterVec,
     pub(crate) upload_data_errors: IntCounterVec,
     pub(crate) download_successes: IntCounterVec,
@@ -50,6 +54,34 @@ impl OracleMetrics {
                 registry,
             )
             .unwrap(),
+            data_source_stale_values: register_int_counter_vec_with_registry!(
+                "oracle_data_source_stale_values",
+                "Total number of data source values too stale to be aggregated",
+                &["feed", "source"],
+                registry,
+            )
+            .unwrap(),
+            data_source_outliers: register_int_counter_vec_with_registry!(
+                "oracle_data_source_outliers",
+                "Total number of data source values deviating too much from the other sources",
+                &["feed", "source"],
+                registry,
+            )
+            .unwrap(),
+            aggregation_errors: register_int_counter_vec_with_registry!(
+                "oracle_aggregation_errors",
+                "Total number of aggregations without enough data source values",
+                &["feed", "source"],
+                registry,
+            )
+            .unwrap(),
+            skipped_submissions: register_int_counter_vec_with_registry!(
+                "oracle_skipped_submissions",
+                "Total number of values not submitted, as they moved less than the threshold",
+                &["feed", "source"],
+                registry,
+            )
+            .unwrap(),
             upload_successes: register_int_counter_vec_with_registry!(
                 "oracle_upload_successes",
                 "Total number of successful data upload",
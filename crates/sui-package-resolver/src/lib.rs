@@ -12,8 +12,8 @@ use move_binary_format::errors::Location;
 use move_binary_format::{
     access::ModuleAccess,
     file_format::{
-        SignatureToken, StructDefinitionIndex, StructFieldInformation, StructHandleIndex,
-        TableIndex,
+        AbilitySet, FunctionDefinitionIndex, SignatureToken, StructDefinitionIndex,
+        StructFieldInformation, StructHandleIndex, StructTypeParameter, TableIndex, Visibility,
     },
     CompiledModule,
 };
@@ -24,6 +24,7 @@ use move_core_types::{
 };
 use sui_types::move_package::TypeOrigin;
 use sui_types::object::Object;
+use sui_types::transaction::{Argument, CallArg, Command, ProgrammableTransaction};
 use sui_types::{base_types::SequenceNumber, is_system_package, Identifier};
 
 pub mod error;
@@ -152,6 +153,177 @@ impl<T: PackageStore> Resolver<T> {
         // (2). Use that information to resolve the tag into a layout.
         context.resolve_type_tag(&tag)
     }
+
+    /// Return the definition of the function `module::function` in the package at storage ID
+    /// `package`. Structs in its signature are referred to by their defining IDs.
+    pub async fn function_signature(
+        &self,
+        package: AccountAddress,
+        module: &str,
+        function: &str,
+    ) -> Result<FunctionDef> {
+        let context = self.package_store.fetch(package).await?;
+        let mut def = context.function_def(module, function)?;
+        for sig in def.parameters.iter_mut().chain(def.return_.iter_mut()) {
+            self.relocate_signature(&mut sig.body, &context).await?;
+        }
+        Ok(def)
+    }
+
+    /// Like `function_signature`, but with the function's type parameters instantiated by
+    /// `type_args`.
+    pub async fn instantiated_function_signature(
+        &self,
+        package: AccountAddress,
+        module: &str,
+        function: &str,
+        type_args: &[TypeTag],
+    ) -> Result<FunctionSignature> {
+        self.function_signature(package, module, function)
+            .await?
+            .instantiate(type_args)
+    }
+
+    /// Return the definition of the struct `module::name` in the package at storage ID `package`:
+    /// its abilities, type parameters and fields. Structs in the types of its fields are referred
+    /// to by their defining IDs.
+    pub async fn struct_definition(
+        &self,
+        package: AccountAddress,
+        module: &str,
+        name: &str,
+    ) -> Result<StructDef> {
+        let context = self.package_store.fetch(package).await?;
+        let mut def = context.struct_def(module, name)?;
+        for (_, sig) in &mut def.fields {
+            self.relocate_signature(sig, &context).await?;
+        }
+        Ok(def)
+    }
+
+    /// Return the layouts of the pure inputs of `tx`, inferred from the types of the parameters of
+    /// the `MoveCall`s they are passed to, or from the other commands using them. The result has
+    /// an entry per input, which is `None` for object inputs, and for pure inputs whose type could
+    /// not be inferred. Fails if an input is used with different types.
+    pub async fn pure_input_layouts(
+        &self,
+        tx: &ProgrammableTransaction,
+    ) -> Result<Vec<Option<MoveTypeLayout>>> {
+        let mut tags: Vec<Option<TypeTag>> = vec![None; tx.inputs.len()];
+
+        for command in &tx.commands {
+            match command {
+                Command::MoveCall(call) => {
+                    let signature = self
+                        .instantiated_function_signature(
+                            call.package.into(),
+                            call.module.as_str(),
+                            call.function.as_str(),
+                            &call.type_arguments,
+                        )
+                        .await?;
+
+                    // The `TxContext` parameter has no argument, but it is always last.
+                    for (arg, param) in call.arguments.iter().zip(&signature.parameters) {
+                        add_pure_input_type(&tx.inputs, &mut tags, arg, &param.body)?;
+                    }
+                }
+
+                Command::TransferObjects(_, recipient) => {
+                    add_pure_input_type(&tx.inputs, &mut tags, recipient, &TypeTag::Address)?;
+                }
+
+                Command::SplitCoins(_, amounts) => {
+                    for amount in amounts {
+                        add_pure_input_type(&tx.inputs, &mut tags, amount, &TypeTag::U64)?;
+                    }
+                }
+
+                Command::MakeMoveVec(Some(tag), elements) => {
+                    for element in elements {
+                        add_pure_input_type(&tx.inputs, &mut tags, element, tag)?;
+                    }
+                }
+
+                Command::MakeMoveVec(None, _)
+                | Command::MergeCoins(_, _)
+                | Command::Publish(_, _)
+                | Command::Upgrade(_, _, _, _) => { /* nop */ }
+            }
+        }
+
+        let mut layouts = Vec::with_capacity(tags.len());
+        for tag in tags {
+            layouts.push(match tag {
+                Some(tag) => Some(self.type_layout(tag).await?),
+                None => None,
+            });
+        }
+
+        Ok(layouts)
+    }
+
+    /// Translate the runtime IDs of the structs referred to by `sig`, a signature read from the
+    /// bytecode of `context`, into their defining IDs.
+    async fn relocate_signature(
+        &self,
+        sig: &mut OpenSignatureBody,
+        context: &Package,
+    ) -> Result<()> {
+        use OpenSignatureBody as O;
+
+        let mut frontier = vec![sig];
+        while let Some(sig) = frontier.pop() {
+            match sig {
+                O::Address
+                | O::Bool
+                | O::U8
+                | O::U16
+                | O::U32
+                | O::U64
+                | O::U128
+                | O::U256
+                | O::TypeParameter(_) => {
+                    // Nothing to relocate
+                }
+
+                O::Vector(sig) => frontier.push(sig.as_mut()),
+
+                O::Struct(key, params) => {
+                    let storage_id = context.relocate(key.package)?;
+                    let package = self.package_store.fetch(storage_id).await?;
+                    key.package = package.defining_id(&key.module, &key.name)?;
+                    frontier.extend(params.iter_mut());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Record that the argument `arg` of a command has type `tag`, if it is a pure input.
+fn add_pure_input_type(
+    inputs: &[CallArg],
+    tags: &mut [Option<TypeTag>],
+    arg: &Argument,
+    tag: &TypeTag,
+) -> Result<()> {
+    let &Argument::Input(ix) = arg else {
+        return Ok(());
+    };
+
+    if !matches!(inputs.get(ix as usize), Some(CallArg::Pure(_))) {
+        return Ok(());
+    }
+
+    match &tags[ix as usize] {
+        None => tags[ix as usize] = Some(tag.clone()),
+        Some(prev) if prev == tag => { /* nop */ }
+        Some(prev) => return Err(Error::InputTypeConflict(ix, prev.clone(), tag.clone())),
+    }
+
+    Ok(())
 }
 
 #[derive(Clone, Debug)]
@@ -183,40 +355,95 @@ struct Module {
     /// Index mapping struct names to their defining ID, and the index for their definition in the
     /// bytecode, to speed up definition lookups.
     struct_index: BTreeMap<String, (AccountAddress, StructDefinitionIndex)>,
+
+    /// Index mapping function names to the index for their definition in the bytecode, to speed up
+    /// definition lookups.
+    function_index: BTreeMap<String, FunctionDefinitionIndex>,
 }
 
 /// Deserialized representation of a struct definition.
-#[derive(Debug)]
-struct StructDef {
+#[derive(Clone, Debug)]
+pub struct StructDef {
     /// The storage ID of the package that first introduced this type.
-    defining_id: AccountAddress,
+    pub defining_id: AccountAddress,
+
+    /// The abilities of this type.
+    pub abilities: AbilitySet,
 
-    /// Number of type parameters.
-    type_params: u16,
+    /// Ability constraints and phantom status of the type parameters.
+    pub type_params: Vec<StructTypeParameter>,
 
     /// Serialized representation of fields (names and deserialized signatures). Signatures refer to
-    /// packages at their runtime IDs (not their storage ID or defining ID).
-    fields: Vec<(String, OpenSignature)>,
+    /// packages at their runtime IDs (not their storage ID or defining ID), except in definitions
+    /// returned by `Resolver::struct_definition`, which refer to their defining IDs.
+    pub fields: Vec<(String, OpenSignatureBody)>,
+}
+
+/// Deserialized representation of a function definition.
+#[derive(Clone, Debug)]
+pub struct FunctionDef {
+    /// Whether the function is `public`, `private` or `public(friend)`.
+    pub visibility: Visibility,
+
+    /// Whether the function is marked `entry` or not.
+    pub is_entry: bool,
+
+    /// Ability constraints for type parameters.
+    pub type_params: Vec<AbilitySet>,
+
+    /// Formal parameter types.
+    pub parameters: Vec<OpenSignature>,
+
+    /// Return types.
+    pub return_: Vec<OpenSignature>,
+}
+
+/// A function signature with its type parameters instantiated.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionSignature {
+    pub parameters: Vec<Signature>,
+    pub return_: Vec<Signature>,
 }
 
 /// Fully qualified struct identifier.  Uses copy-on-write strings so that when it is used as a key
 /// to a map, an instance can be created to query the map without having to allocate strings on the
 /// heap.
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
-struct StructRef<'m, 'n> {
-    package: AccountAddress,
-    module: Cow<'m, str>,
-    name: Cow<'n, str>,
+pub struct StructRef<'m, 'n> {
+    pub package: AccountAddress,
+    pub module: Cow<'m, str>,
+    pub name: Cow<'n, str>,
 }
 
 /// A `StructRef` that owns its strings.
-type StructKey = StructRef<'static, 'static>;
+pub type StructKey = StructRef<'static, 'static>;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Reference {
+    Immutable,
+    Mutable,
+}
+
+/// A function parameter or return signature, with its type parameters instantiated.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Signature {
+    pub ref_: Option<Reference>,
+    pub body: TypeTag,
+}
+
+/// Deserialized representation of a type signature that could appear as a function parameter or
+/// return type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OpenSignature {
+    pub ref_: Option<Reference>,
+    pub body: OpenSignatureBody,
+}
 
 /// Deserialized representation of a type signature that could appear as a field type for a struct.
 /// Signatures refer to structs at their runtime IDs and can contain references to free type
 /// parameters but will not contain reference types.
-#[derive(Clone, Debug)]
-enum OpenSignature {
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OpenSignatureBody {
     Address,
     Bool,
     U8,
@@ -225,8 +452,8 @@ enum OpenSignature {
     U64,
     U128,
     U256,
-    Vector(Box<OpenSignature>),
-    Struct(StructKey, Vec<OpenSignature>),
+    Vector(Box<OpenSignatureBody>),
+    Struct(StructKey, Vec<OpenSignatureBody>),
     TypeParameter(u16),
 }
 
@@ -256,7 +483,8 @@ impl Package {
 
         let struct_def = module.bytecode.struct_def_at(index);
         let struct_handle = module.bytecode.struct_handle_at(struct_def.struct_handle);
-        let type_params = struct_handle.type_parameters.len() as u16;
+        let abilities = struct_handle.abilities;
+        let type_params = struct_handle.type_parameters.clone();
 
         let fields = match &struct_def.field_information {
             StructFieldInformation::Native => vec![],
@@ -265,7 +493,7 @@ impl Package {
                 .map(|f| {
                     Ok((
                         module.bytecode.identifier_at(f.name).to_string(),
-                        OpenSignature::read(&f.signature.0, &module.bytecode)?,
+                        OpenSignatureBody::read(&f.signature.0, &module.bytecode)?,
                     ))
                 })
                 .collect::<Result<_>>()?,
@@ -273,11 +501,55 @@ impl Package {
 
         Ok(StructDef {
             defining_id,
+            abilities,
             type_params,
             fields,
         })
     }
 
+    /// The storage ID of the package that first introduced the struct `module_name::struct_name`.
+    fn defining_id(&self, module_name: &str, struct_name: &str) -> Result<AccountAddress> {
+        let module = self.module(module_name)?;
+        let Some(&(defining_id, _)) = module.struct_index.get(struct_name) else {
+            return Err(Error::StructNotFound(
+                self.storage_id,
+                module_name.to_string(),
+                struct_name.to_string(),
+            ));
+        };
+
+        Ok(defining_id)
+    }
+
+    fn function_def(&self, module_name: &str, function_name: &str) -> Result<FunctionDef> {
+        let module = self.module(module_name)?;
+        let Some(&index) = module.function_index.get(function_name) else {
+            return Err(Error::FunctionNotFound(
+                self.storage_id,
+                module_name.to_string(),
+                function_name.to_string(),
+            ));
+        };
+
+        let bytecode = &module.bytecode;
+        let function_def = bytecode.function_def_at(index);
+        let function_handle = bytecode.function_handle_at(function_def.function);
+
+        let read_signatures = |sigs: &[SignatureToken]| {
+            sigs.iter()
+                .map(|sig| OpenSignature::read(sig, bytecode))
+                .collect::<Result<Vec<_>>>()
+        };
+
+        Ok(FunctionDef {
+            visibility: function_def.visibility,
+            is_entry: function_def.is_entry,
+            type_params: function_handle.type_parameters.clone(),
+            parameters: read_signatures(&bytecode.signature_at(function_handle.parameters).0)?,
+            return_: read_signatures(&bytecode.signature_at(function_handle.return_).0)?,
+        })
+    }
+
     /// Translate the `runtime_id` of a package to a specific storage ID using this package's
     /// linkage table.  Returns an error if the package in question is not present in the linkage
     /// table.
@@ -315,16 +587,116 @@ impl Module {
             struct_index.insert(struct_, (defining_id, index));
         }
 
+        let mut function_index = BTreeMap::new();
+        for (index, def) in bytecode.function_defs.iter().enumerate() {
+            let fh = bytecode.function_handle_at(def.function);
+            let function = bytecode.identifier_at(fh.name).to_string();
+            let index = FunctionDefinitionIndex::new(index as TableIndex);
+
+            function_index.insert(function, index);
+        }
+
         Ok(Module {
             bytecode,
             struct_index,
+            function_index,
+        })
+    }
+}
+
+impl FunctionDef {
+    /// Instantiate the function's type parameters with `type_args`. Fails if the number of type
+    /// arguments does not match the number of type parameters.
+    pub fn instantiate(&self, type_args: &[TypeTag]) -> Result<FunctionSignature> {
+        if self.type_params.len() != type_args.len() {
+            return Err(Error::TypeArityMismatch(
+                self.type_params.len() as u16,
+                type_args.len(),
+            ));
+        }
+
+        let instantiate = |sigs: &[OpenSignature]| {
+            sigs.iter()
+                .map(|sig| sig.instantiate(type_args))
+                .collect::<Result<Vec<_>>>()
+        };
+
+        Ok(FunctionSignature {
+            parameters: instantiate(&self.parameters)?,
+            return_: instantiate(&self.return_)?,
         })
     }
 }
 
 impl OpenSignature {
     fn read(sig: &SignatureToken, bytecode: &CompiledModule) -> Result<Self> {
-        use OpenSignature as O;
+        use SignatureToken as S;
+
+        Ok(match sig {
+            S::Reference(sig) => OpenSignature {
+                ref_: Some(Reference::Immutable),
+                body: OpenSignatureBody::read(sig, bytecode)?,
+            },
+
+            S::MutableReference(sig) => OpenSignature {
+                ref_: Some(Reference::Mutable),
+                body: OpenSignatureBody::read(sig, bytecode)?,
+            },
+
+            sig => OpenSignature {
+                ref_: None,
+                body: OpenSignatureBody::read(sig, bytecode)?,
+            },
+        })
+    }
+
+    /// Substitute `type_params` for the type parameters in this signature.
+    pub fn instantiate(&self, type_params: &[TypeTag]) -> Result<Signature> {
+        Ok(Signature {
+            ref_: self.ref_,
+            body: self.body.instantiate(type_params)?,
+        })
+    }
+}
+
+impl OpenSignatureBody {
+    /// Substitute `type_params` for the type parameters in this signature, producing a type tag
+    /// that refers to structs at the addresses in this signature.
+    pub fn instantiate(&self, type_params: &[TypeTag]) -> Result<TypeTag> {
+        use OpenSignatureBody as O;
+        use TypeTag as T;
+
+        Ok(match self {
+            O::Address => T::Address,
+            O::Bool => T::Bool,
+            O::U8 => T::U8,
+            O::U16 => T::U16,
+            O::U32 => T::U32,
+            O::U64 => T::U64,
+            O::U128 => T::U128,
+            O::U256 => T::U256,
+
+            O::TypeParameter(ix) => type_params
+                .get(*ix as usize)
+                .ok_or_else(|| Error::TypeParamOOB(*ix, type_params.len()))
+                .cloned()?,
+
+            O::Vector(sig) => T::Vector(Box::new(sig.instantiate(type_params)?)),
+
+            O::Struct(key, params) => T::Struct(Box::new(StructTag {
+                address: key.package,
+                module: ident(&key.module)?,
+                name: ident(&key.name)?,
+                type_params: params
+                    .iter()
+                    .map(|sig| sig.instantiate(type_params))
+                    .collect::<Result<_>>()?,
+            })),
+        })
+    }
+
+    fn read(sig: &SignatureToken, bytecode: &CompiledModule) -> Result<Self> {
+        use OpenSignatureBody as O;
         use SignatureToken as S;
 
         Ok(match sig {
@@ -341,14 +713,14 @@ impl OpenSignature {
             S::U256 => O::U256,
             S::TypeParameter(ix) => O::TypeParameter(*ix),
 
-            S::Vector(sig) => O::Vector(Box::new(OpenSignature::read(sig, bytecode)?)),
+            S::Vector(sig) => O::Vector(Box::new(OpenSignatureBody::read(sig, bytecode)?)),
 
             S::Struct(ix) => O::Struct(StructKey::read(*ix, bytecode), vec![]),
             S::StructInstantiation(ix, params) => O::Struct(
                 StructKey::read(*ix, bytecode),
                 params
                     .iter()
-                    .map(|sig| OpenSignature::read(sig, bytecode))
+                    .map(|sig| OpenSignatureBody::read(sig, bytecode))
                     .collect::<Result<_>>()?,
             ),
         })
@@ -447,11 +819,11 @@ impl ResolutionContext {
     // into storage IDs.
     async fn add_signature<T: PackageStore + ?Sized>(
         &mut self,
-        sig: OpenSignature,
+        sig: OpenSignatureBody,
         store: &T,
         context: &Package,
     ) -> Result<()> {
-        use OpenSignature as O;
+        use OpenSignatureBody as O;
 
         let mut frontier = vec![sig];
         while let Some(sig) = frontier.pop() {
@@ -530,8 +902,11 @@ impl ResolutionContext {
                     ..
                 } = s.as_ref();
 
-                if def.type_params as usize != type_params.len() {
-                    return Err(Error::TypeArityMismatch(def.type_params, type_params.len()));
+                if def.type_params.len() != type_params.len() {
+                    return Err(Error::TypeArityMismatch(
+                        def.type_params.len() as u16,
+                        type_params.len(),
+                    ));
                 }
 
                 // TODO (optimization): This could be made more efficient by only generating layouts
@@ -580,11 +955,11 @@ impl ResolutionContext {
     /// parameters which are substituted when a type parameter is encountered.
     fn resolve_signature(
         &self,
-        sig: &OpenSignature,
+        sig: &OpenSignatureBody,
         param_layouts: &Vec<MoveTypeLayout>,
     ) -> Result<MoveTypeLayout> {
         use MoveTypeLayout as L;
-        use OpenSignature as O;
+        use OpenSignatureBody as O;
 
         Ok(match sig {
             O::Address => L::Address,
@@ -727,8 +1102,11 @@ mod tests {
     use std::{path::PathBuf, str::FromStr, sync::RwLock};
 
     use expect_test::expect;
+    use move_binary_format::file_format::Ability;
     use move_compiler::compiled_unit::{CompiledUnitEnum, NamedCompiledModule};
     use sui_move_build::{BuildConfig, CompiledPackage};
+    use sui_types::base_types::random_object_ref;
+    use sui_types::transaction::{ObjectArg, ProgrammableMoveCall};
 
     use super::*;
 
@@ -1104,6 +1482,167 @@ mod tests {
         assert!(matches!(err, Error::TypeArityMismatch(2, 3)));
     }
 
+    /// Function signatures refer to structs at their defining IDs, and can be instantiated.
+    #[tokio::test]
+    async fn test_function_signature() {
+        let (_, cache) = package_cache([
+            (1, build_package("a0"), a0_types()),
+            (2, build_package("a1"), a1_types()),
+        ]);
+        let resolver = Resolver::new(cache);
+
+        let def = resolver
+            .function_signature(addr("0xa1"), "m", "foo")
+            .await
+            .unwrap();
+
+        assert_eq!(def.visibility, Visibility::Public);
+        assert!(!def.is_entry);
+        assert_eq!(
+            def.type_params,
+            vec![AbilitySet::EMPTY | Ability::Copy | Ability::Drop]
+        );
+
+        let sig = def.instantiate(&[TypeTag::U8]).unwrap();
+        let param = |ref_, t| Signature {
+            ref_,
+            body: type_(t),
+        };
+
+        assert_eq!(
+            sig.parameters,
+            vec![
+                param(None, "u8"),
+                param(None, "u64"),
+                param(Some(Reference::Immutable), "0xa0::m::T1<u8, 0xa1::m::T3>"),
+                param(Some(Reference::Mutable), "vector<0xa0::m::T2>"),
+            ]
+        );
+        assert_eq!(sig.return_, vec![param(None, "0xa0::m::T1<u8, u32>")]);
+
+        let err = def.instantiate(&[]).unwrap_err();
+        assert!(matches!(err, Error::TypeArityMismatch(1, 0)));
+    }
+
+    /// Struct definitions come with their abilities, and fields referring to structs at their
+    /// defining IDs.
+    #[tokio::test]
+    async fn test_struct_definition() {
+        let (_, cache) = package_cache([
+            (1, build_package("a0"), a0_types()),
+            (2, build_package("a1"), a1_types()),
+        ]);
+        let resolver = Resolver::new(cache);
+
+        let def = resolver
+            .struct_definition(addr("0xa1"), "m", "T5")
+            .await
+            .unwrap();
+
+        assert_eq!(def.defining_id, addr("0xa1"));
+        assert_eq!(
+            def.abilities,
+            AbilitySet::EMPTY | Ability::Copy | Ability::Drop
+        );
+        assert_eq!(
+            def.type_params
+                .iter()
+                .map(|p| p.is_phantom)
+                .collect::<Vec<_>>(),
+            vec![true, false]
+        );
+
+        use OpenSignatureBody as O;
+        assert_eq!(
+            def.fields,
+            vec![
+                (
+                    "t".to_string(),
+                    O::Struct(struct_("0xa1", "m", "T4"), vec![])
+                ),
+                (
+                    "u".to_string(),
+                    O::Struct(struct_("0xa0", "m", "T2"), vec![])
+                ),
+                ("p".to_string(), O::TypeParameter(1)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_err_no_function() {
+        let (_, cache) = package_cache([(1, build_package("a0"), a0_types())]);
+        let resolver = Resolver::new(cache);
+
+        let err = resolver
+            .function_signature(addr("0xa0"), "m", "foo")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::FunctionNotFound(_, _, _)));
+    }
+
+    /// Types of pure inputs are inferred from the functions they are passed to, and the commands
+    /// they are used by.
+    #[tokio::test]
+    async fn test_pure_input_layouts() {
+        let (_, cache) = package_cache([
+            (1, build_package("a0"), a0_types()),
+            (2, build_package("a1"), a1_types()),
+        ]);
+        let resolver = Resolver::new(cache);
+
+        let mut tx = ProgrammableTransaction {
+            inputs: vec![
+                CallArg::Pure(bcs::to_bytes(&42u8).unwrap()),
+                CallArg::Pure(bcs::to_bytes(&43u64).unwrap()),
+                CallArg::Pure(bcs::to_bytes(&addr("0x44")).unwrap()),
+                CallArg::Object(ObjectArg::ImmOrOwnedObject(random_object_ref())),
+                CallArg::Pure(vec![]),
+            ],
+            commands: vec![
+                Command::MoveCall(Box::new(ProgrammableMoveCall {
+                    package: addr("0xa1").into(),
+                    module: ident("m").unwrap(),
+                    function: ident("foo").unwrap(),
+                    type_arguments: vec![TypeTag::U8],
+                    arguments: vec![
+                        Argument::Input(0),
+                        Argument::Input(1),
+                        Argument::Input(3),
+                        Argument::Input(3),
+                    ],
+                })),
+                Command::TransferObjects(vec![Argument::Result(0)], Argument::Input(2)),
+            ],
+        };
+
+        let layouts = resolver.pure_input_layouts(&tx).await.unwrap();
+        let layouts: Vec<_> = layouts
+            .iter()
+            .map(|l| l.as_ref().map(|l| format!("{l}")))
+            .collect();
+
+        assert_eq!(
+            layouts,
+            vec![
+                Some("u8".to_string()),
+                Some("u64".to_string()),
+                Some("address".to_string()),
+                None,
+                None,
+            ]
+        );
+
+        // Using the `u8` input as an amount conflicts with its type in the call.
+        tx.commands.push(Command::SplitCoins(
+            Argument::GasCoin,
+            vec![Argument::Input(0)],
+        ));
+
+        let err = resolver.pure_input_layouts(&tx).await.unwrap_err();
+        assert!(matches!(err, Error::InputTypeConflict(0, _, _)));
+    }
+
     /***** Test Helpers ***************************************************************************/
 
     type TypeOriginTable = Vec<StructKey>;
@@ -1123,6 +1662,7 @@ mod tests {
         types.extend([
             struct_("0xa1", "m", "T3"),
             struct_("0xa1", "m", "T4"),
+            struct_("0xa1", "m", "T5"),
             struct_("0xa1", "n", "T1"),
         ]);
 
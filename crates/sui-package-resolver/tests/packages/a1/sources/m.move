@@ -25,4 +25,14 @@ module a::m {
     struct T4 {
         z: u32,
     }
+
+    struct T5<phantom P0, P1: copy + drop> has copy, drop {
+        t: T4,
+        u: T2,
+        p: P1,
+    }
+
+    public fun foo<T: copy + drop>(x: T, y: u64, t: &T1<T, T3>, _v: &mut vector<T2>): T1<T, u32> {
+        T1 { a: t.a, p: x, q: vector[(y as u32)] }
+    }
 }
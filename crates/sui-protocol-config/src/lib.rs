@@ -338,6 +338,10 @@ struct FeatureFlags {
     // If true, recompute has_public_transfer from the type instead of what is stored in the object
     #[serde(skip_serializing_if = "is_false")]
     recompute_has_public_transfer_in_execution: bool,
+
+    // How Narwhal workers compress the batches they create.
+    #[serde(skip_serializing_if = "NarwhalBatchCompression::is_none")]
+    narwhal_batch_compression: NarwhalBatchCompression,
 }
 
 fn is_false(b: &bool) -> bool {
@@ -364,6 +368,23 @@ impl ConsensusTransactionOrdering {
     }
 }
 
+/// Compression algorithm applied to the batches created by Narwhal workers, before they are sent
+/// to other workers and persisted.
+#[derive(Default, Copy, Clone, Serialize, Debug, PartialEq, Eq)]
+pub enum NarwhalBatchCompression {
+    /// Batches are sent and stored uncompressed.
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+}
+
+impl NarwhalBatchCompression {
+    pub fn is_none(&self) -> bool {
+        matches!(self, NarwhalBatchCompression::None)
+    }
+}
+
 /// Constants that change the behavior of the protocol.
 ///
 /// The value of each constant here must be fixed for a given protocol version. To change the value
@@ -1035,6 +1056,10 @@ impl ProtocolConfig {
     pub fn throughput_aware_consensus_submission(&self) -> bool {
         self.feature_flags.throughput_aware_consensus_submission
     }
+
+    pub fn narwhal_batch_compression(&self) -> NarwhalBatchCompression {
+        self.feature_flags.narwhal_batch_compression
+    }
 }
 
 #[cfg(not(msim))]
@@ -1729,6 +1754,9 @@ impl ProtocolConfig {
     pub fn set_enable_effects_v2(&mut self, val: bool) {
         self.feature_flags.enable_effects_v2 = val;
     }
+    pub fn set_narwhal_batch_compression_for_testing(&mut self, val: NarwhalBatchCompression) {
+        self.feature_flags.narwhal_batch_compression = val;
+    }
 }
 
 type OverrideFn = dyn Fn(ProtocolVersion, ProtocolConfig) -> ProtocolConfig + Send;
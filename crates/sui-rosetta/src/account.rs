@@ -14,9 +14,9 @@ use tracing::info;
 use crate::errors::Error;
 use crate::types::{
     AccountBalanceRequest, AccountBalanceResponse, AccountCoinsRequest, AccountCoinsResponse,
-    Amount, Coin, SubAccount, SubAccountType, SubBalance,
+    Amount, Coin, Currency, SubAccount, SubAccountType, SubBalance,
 };
-use crate::{OnlineServerContext, SuiEnv};
+use crate::{OnlineServerContext, SuiEnv, SUI};
 use std::time::Duration;
 
 /// Get an array of all AccountBalances for an AccountIdentifier and the BlockIdentifier
@@ -68,14 +68,15 @@ pub async fn balance(
         }
         Err(Error::RetryExhausted(String::from("retry")))
     } else {
+        // Report the SUI balance unless other currencies are requested
+        let currencies = if request.currencies.is_empty() {
+            vec![SUI.clone()]
+        } else {
+            request.currencies
+        };
         // Get current live balance
         while retry_attempts > 0 {
-            let balances_first = ctx
-                .client
-                .coin_read_api()
-                .get_balance(address, Some(SUI_COIN_TYPE.to_string()))
-                .await?
-                .total_balance as i128;
+            let balances_first = get_balances(&ctx.client, address, &currencies).await?;
 
             // Get current latest checkpoint
             let checkpoint1 = ctx
@@ -101,12 +102,7 @@ pub async fn balance(
             }
 
             // Get live balance again
-            let balances_second = ctx
-                .client
-                .coin_read_api()
-                .get_balance(address, Some(SUI_COIN_TYPE.to_string()))
-                .await?
-                .total_balance as i128;
+            let balances_second = get_balances(&ctx.client, address, &currencies).await?;
 
             // if those two live balances are equal then that is the current balance for checkpoint2
             if balances_first.eq(&balances_second) {
@@ -116,7 +112,7 @@ pub async fn balance(
                 );
                 return Ok(AccountBalanceResponse {
                     block_identifier: ctx.blocks().create_block_identifier(checkpoint2).await?,
-                    balances: vec![Amount::new(balances_first)],
+                    balances: balances_first,
                 });
             } else {
                 // balances are different so we need to try again.
@@ -131,6 +127,23 @@ pub async fn balance(
     }
 }
 
+async fn get_balances(
+    client: &SuiClient,
+    address: SuiAddress,
+    currencies: &[Currency],
+) -> Result<Vec<Amount>, Error> {
+    let mut balances = vec![];
+    for currency in currencies {
+        let balance = client
+            .coin_read_api()
+            .get_balance(address, Some(currency.coin_type()?))
+            .await?
+            .total_balance as i128;
+        balances.push(Amount::new_with_currency(balance, currency.clone()));
+    }
+    Ok(balances)
+}
+
 async fn get_sub_account_balances(
     account_type: SubAccountType,
     client: &SuiClient,
@@ -223,6 +223,24 @@ pub async fn metadata(
             let amount = amounts.iter().sum::<u64>();
             (Some(amount), vec![])
         }
+        InternalOperation::PayCoin {
+            sender,
+            amounts,
+            currency,
+            ..
+        } => {
+            // The coins to pay from are inputs of the transaction, SUI is only needed for gas.
+            let amount = amounts.iter().sum::<u64>();
+            let coins = context
+                .client
+                .coin_read_api()
+                .select_coins(*sender, Some(currency.coin_type()?), amount.into(), vec![])
+                .await?
+                .into_iter()
+                .map(|coin| coin.object_ref())
+                .collect();
+            (Some(0), coins)
+        }
         InternalOperation::Stake { amount, .. } => (*amount, vec![]),
         InternalOperation::WithdrawStake { sender, stake_ids } => {
             let stake_ids = if stake_ids.is_empty() {
@@ -8,7 +8,7 @@ use std::vec;
 
 use anyhow::anyhow;
 use move_core_types::ident_str;
-use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
 use move_core_types::resolver::ModuleResolver;
 use serde::Deserialize;
 use serde::Serialize;
@@ -22,18 +22,21 @@ use sui_sdk::rpc_types::{
     SuiTransactionBlockKind, SuiTransactionBlockResponse,
 };
 use sui_types::base_types::{ObjectID, SequenceNumber, SuiAddress};
+use sui_types::coin::{PAY_MODULE_NAME, PAY_SPLIT_AND_TRANSFER_FUNC_NAME};
 use sui_types::gas_coin::{GasCoin, GAS};
 use sui_types::governance::{ADD_STAKE_FUN_NAME, WITHDRAW_STAKE_FUN_NAME};
 use sui_types::object::Owner;
 use sui_types::sui_system_state::SUI_SYSTEM_MODULE_NAME;
 use sui_types::transaction::TransactionData;
-use sui_types::{SUI_SYSTEM_ADDRESS, SUI_SYSTEM_PACKAGE_ID};
+use sui_types::{
+    parse_sui_type_tag, SUI_FRAMEWORK_PACKAGE_ID, SUI_SYSTEM_ADDRESS, SUI_SYSTEM_PACKAGE_ID,
+};
 
 use crate::types::{
-    AccountIdentifier, Amount, CoinAction, CoinChange, CoinID, CoinIdentifier, InternalOperation,
-    OperationIdentifier, OperationStatus, OperationType,
+    AccountIdentifier, Amount, CoinAction, CoinChange, CoinID, CoinIdentifier, Currency,
+    InternalOperation, OperationIdentifier, OperationStatus, OperationType,
 };
-use crate::Error;
+use crate::{Error, SUI};
 
 #[cfg(test)]
 #[path = "unit_tests/operations_tests.rs"]
@@ -101,6 +104,7 @@ impl Operations {
             .ok_or_else(|| Error::MissingInput("Operation type".into()))?;
         match type_ {
             OperationType::PaySui => self.pay_sui_ops_to_internal(),
+            OperationType::PayCoin => self.pay_coin_ops_to_internal(),
             OperationType::Stake => self.stake_ops_to_internal(),
             OperationType::WithdrawStake => self.withdraw_stake_ops_to_internal(),
             op => Err(Error::UnsupportedOperation(op)),
@@ -135,6 +139,48 @@ impl Operations {
         })
     }
 
+    fn pay_coin_ops_to_internal(self) -> Result<InternalOperation, Error> {
+        let mut recipients = vec![];
+        let mut amounts = vec![];
+        let mut sender = None;
+        let mut currency = None;
+        for op in self {
+            if let (Some(amount), Some(account)) = (op.amount.clone(), op.account.clone()) {
+                if currency.get_or_insert_with(|| amount.currency.clone()) != &amount.currency {
+                    return Err(Error::MalformedOperationError(
+                        "PayCoin operations should all be in the same currency.".into(),
+                    ));
+                }
+                if amount.value.is_negative() {
+                    sender = Some(account.address)
+                } else {
+                    recipients.push(account.address);
+                    let amount = amount.value.abs();
+                    if amount > u64::MAX as i128 {
+                        return Err(Error::InvalidInput(
+                            "Input amount exceed u64::MAX".to_string(),
+                        ));
+                    }
+                    amounts.push(amount as u64)
+                }
+            }
+        }
+        let sender = sender.ok_or_else(|| Error::MissingInput("Sender address".to_string()))?;
+        let currency = currency.ok_or_else(|| Error::MissingInput("Currency".to_string()))?;
+        if currency.metadata.is_none() {
+            return Err(Error::InvalidInput(
+                "PayCoin currency should have the coin type in its metadata, use PaySui for SUI."
+                    .into(),
+            ));
+        }
+        Ok(InternalOperation::PayCoin {
+            sender,
+            recipients,
+            amounts,
+            currency,
+        })
+    }
+
     fn stake_ops_to_internal(self) -> Result<InternalOperation, Error> {
         let mut ops = self
             .0
@@ -217,19 +263,24 @@ impl Operations {
         tx: SuiTransactionBlockKind,
         sender: SuiAddress,
         status: Option<OperationStatus>,
+        currencies: &HashMap<TypeTag, Currency>,
     ) -> Result<Vec<Operation>, Error> {
         Ok(match tx {
             SuiTransactionBlockKind::ProgrammableTransaction(pt) => {
-                Self::parse_programmable_transaction(sender, status, pt)?
+                Self::parse_programmable_transaction(sender, status, pt, currencies)?
             }
             _ => vec![Operation::generic_op(status, sender, tx)],
         })
     }
 
+    /// Parse the operations of a programmable transaction. The currency of PayCoin transactions
+    /// is the one in `currencies` of the coin type they pay with, or the currency of a coin type
+    /// without metadata if it is not there, like when parsing a transaction offline.
     fn parse_programmable_transaction(
         sender: SuiAddress,
         status: Option<OperationStatus>,
         pt: SuiProgrammableTransactionBlock,
+        currencies: &HashMap<TypeTag, Currency>,
     ) -> Result<Vec<Operation>, Error> {
         #[derive(Debug)]
         enum KnownValue {
@@ -278,6 +329,13 @@ impl Operations {
                 .collect::<Option<_>>()?;
             Some(amounts)
         }
+        fn merge_coins(coin: SuiArgument, coins: &[SuiArgument]) -> Option<Vec<KnownValue>> {
+            // Only the input coins of PayCoin transactions are merged
+            std::iter::once(&coin)
+                .chain(coins)
+                .all(|c| matches!(c, SuiArgument::Input(_)))
+                .then(Vec::new)
+        }
         fn transfer_object(
             aggregated_recipients: &mut HashMap<SuiAddress, u64>,
             inputs: &[SuiCallArg],
@@ -337,6 +395,29 @@ impl Operations {
             };
             Ok(validator.map(|v| v.map(|v| (amount, v)))?)
         }
+        // `pay::split_and_transfer<T>` of an input coin, with the amount and recipient as inputs
+        fn pay_coin_call(
+            inputs: &[SuiCallArg],
+            call: &SuiProgrammableMoveCall,
+        ) -> Option<(TypeTag, SuiAddress, u64)> {
+            let SuiProgrammableMoveCall {
+                type_arguments,
+                arguments,
+                ..
+            } = call;
+            let [coin_type] = &type_arguments[..] else {
+                return None;
+            };
+            let [SuiArgument::Input(_), SuiArgument::Input(amount), SuiArgument::Input(recipient)] =
+                arguments[..]
+            else {
+                return None;
+            };
+            let amount =
+                u64::from_str(inputs[amount as usize].pure()?.to_json_value().as_str()?).ok()?;
+            let recipient = inputs[recipient as usize].pure()?.to_sui_address().ok()?;
+            Some((parse_sui_type_tag(coin_type).ok()?, recipient, amount))
+        }
 
         fn unstake_call(
             inputs: &[SuiCallArg],
@@ -361,8 +442,11 @@ impl Operations {
             Ok(id.cloned())
         }
         let SuiProgrammableTransactionBlock { inputs, commands } = &pt;
+        let mut pay_coin_type: Option<TypeTag> = None;
+        let mut merges_coins = false;
         let mut known_results: Vec<Vec<KnownValue>> = vec![];
         let mut aggregated_recipients: HashMap<SuiAddress, u64> = HashMap::new();
+        let mut aggregated_coin_recipients: HashMap<SuiAddress, u64> = HashMap::new();
         let mut needs_generic = false;
         let mut operations = vec![];
         let mut stake_ids = vec![];
@@ -371,6 +455,10 @@ impl Operations {
                 SuiCommand::SplitCoins(coin, amounts) => {
                     split_coins(inputs, &known_results, *coin, amounts)
                 }
+                SuiCommand::MergeCoins(coin, coins) => {
+                    merges_coins = true;
+                    merge_coins(*coin, coins)
+                }
                 SuiCommand::TransferObjects(objs, addr) => transfer_object(
                     &mut aggregated_recipients,
                     inputs,
@@ -393,6 +481,14 @@ impl Operations {
                         vec![]
                     })
                 }
+                SuiCommand::MoveCall(m) if Self::is_pay_coin_call(m) => pay_coin_call(inputs, m)
+                    .filter(|(coin_type, _, _)| {
+                        pay_coin_type.get_or_insert_with(|| coin_type.clone()) == coin_type
+                    })
+                    .map(|(_, recipient, amount)| {
+                        *aggregated_coin_recipients.entry(recipient).or_default() += amount;
+                        vec![]
+                    }),
                 SuiCommand::MoveCall(m) if Self::is_unstake_call(m) => {
                     let stake_id = unstake_call(inputs, m)?;
                     stake_ids.push(stake_id);
@@ -408,7 +504,33 @@ impl Operations {
             }
         }
 
-        if !needs_generic && !aggregated_recipients.is_empty() {
+        // Merging coins only has an effect on the balances in PayCoin transactions, which pay in a
+        // single coin type other than SUI.
+        needs_generic |= merges_coins && aggregated_coin_recipients.is_empty();
+        needs_generic |= !aggregated_coin_recipients.is_empty()
+            && (!aggregated_recipients.is_empty() || pay_coin_type == Some(GAS::type_tag()));
+        let pay_coin_currency = pay_coin_type.filter(|_| !needs_generic).map(|coin_type| {
+            currencies
+                .get(&coin_type)
+                .cloned()
+                .unwrap_or_else(|| Currency::from_coin_type(&coin_type))
+        });
+        if let Some(currency) = pay_coin_currency {
+            let total_paid: u64 = aggregated_coin_recipients.values().copied().sum();
+            operations.extend(
+                aggregated_coin_recipients
+                    .into_iter()
+                    .map(|(recipient, amount)| {
+                        Operation::pay_coin(status, recipient, amount.into(), currency.clone())
+                    }),
+            );
+            operations.push(Operation::pay_coin(
+                status,
+                sender,
+                -(total_paid as i128),
+                currency,
+            ));
+        } else if !needs_generic && !aggregated_recipients.is_empty() {
             let total_paid: u64 = aggregated_recipients.values().copied().sum();
             operations.extend(
                 aggregated_recipients
@@ -449,6 +571,27 @@ impl Operations {
             && tx.function == ADD_STAKE_FUN_NAME.as_str()
     }
 
+    /// The coin types a transaction pays with in PayCoin calls.
+    pub fn pay_coin_types(tx: &SuiTransactionBlockKind) -> Vec<TypeTag> {
+        let SuiTransactionBlockKind::ProgrammableTransaction(pt) = tx else {
+            return vec![];
+        };
+        pt.commands
+            .iter()
+            .filter_map(|command| match command {
+                SuiCommand::MoveCall(m) if Self::is_pay_coin_call(m) => m.type_arguments.first(),
+                _ => None,
+            })
+            .filter_map(|coin_type| parse_sui_type_tag(coin_type).ok())
+            .collect()
+    }
+
+    fn is_pay_coin_call(tx: &SuiProgrammableMoveCall) -> bool {
+        tx.package == SUI_FRAMEWORK_PACKAGE_ID
+            && tx.module == PAY_MODULE_NAME.as_str()
+            && tx.function == PAY_SPLIT_AND_TRANSFER_FUNC_NAME.as_str()
+    }
+
     fn is_unstake_call(tx: &SuiProgrammableMoveCall) -> bool {
         tx.package == SUI_SYSTEM_PACKAGE_ID
             && tx.module == SUI_SYSTEM_MODULE_NAME.as_str()
@@ -460,26 +603,33 @@ impl Operations {
         gas_used: i128,
         balance_changes: &[BalanceChange],
         status: Option<OperationStatus>,
-        balances: HashMap<SuiAddress, i128>,
+        balances: HashMap<(SuiAddress, Currency), i128>,
+        currencies: &HashMap<TypeTag, Currency>,
     ) -> impl Iterator<Item = Operation> {
         let mut balances = balance_changes
             .iter()
             .fold(balances, |mut balances, balance_change| {
                 // Rosetta only care about address owner
                 if let Owner::AddressOwner(owner) = balance_change.owner {
-                    if balance_change.coin_type == GAS::type_tag() {
-                        *balances.entry(owner).or_default() += balance_change.amount;
+                    let currency = if balance_change.coin_type == GAS::type_tag() {
+                        Some(SUI.clone())
+                    } else {
+                        currencies.get(&balance_change.coin_type).cloned()
+                    };
+                    if let Some(currency) = currency {
+                        *balances.entry((owner, currency)).or_default() += balance_change.amount;
                     }
                 }
                 balances
             });
         // separate gas from balances
-        *balances.entry(gas_owner).or_default() -= gas_used;
+        *balances.entry((gas_owner, SUI.clone())).or_default() -= gas_used;
 
-        let balance_change = balances
-            .into_iter()
-            .filter(|(_, amount)| *amount != 0)
-            .map(move |(addr, amount)| Operation::balance_change(status, addr, amount));
+        let balance_change = balances.into_iter().filter(|(_, amount)| *amount != 0).map(
+            move |((addr, currency), amount)| {
+                Operation::balance_change(status, addr, Amount::new_with_currency(amount, currency))
+            },
+        );
 
         let gas = if gas_used != 0 {
             vec![Operation::gas(gas_owner, gas_used)]
@@ -499,13 +649,26 @@ impl TryFrom<SuiTransactionBlockData> for Operations {
             data.transaction().clone(),
             sender,
             None,
+            &HashMap::new(),
         )?))
     }
 }
 
 impl TryFrom<SuiTransactionBlockResponse> for Operations {
     type Error = Error;
+    /// Only SUI balance changes are reported, see [Operations::try_from_response].
     fn try_from(response: SuiTransactionBlockResponse) -> Result<Self, Self::Error> {
+        Self::try_from_response(response, &HashMap::new())
+    }
+}
+
+impl Operations {
+    /// Parse the operations of a transaction, including the balance changes of SUI and of the
+    /// other coin types in `currencies`.
+    pub fn try_from_response(
+        response: SuiTransactionBlockResponse,
+        currencies: &HashMap<TypeTag, Currency>,
+    ) -> Result<Self, Error> {
         let tx = response
             .transaction
             .ok_or_else(|| anyhow!("Response input should not be empty"))?;
@@ -520,7 +683,12 @@ impl TryFrom<SuiTransactionBlockResponse> for Operations {
             - gas_summary.computation_cost as i128;
 
         let status = Some(effect.into_status().into());
-        let ops: Operations = tx.data.try_into()?;
+        let ops = Operations::new(Self::from_transaction(
+            tx.data.transaction().clone(),
+            sender,
+            None,
+            currencies,
+        )?);
         let ops = ops.set_status(status).into_iter();
 
         // We will need to subtract the operation amounts from the actual balance
@@ -532,7 +700,9 @@ impl TryFrom<SuiTransactionBlockResponse> for Operations {
                     if let (Some(acc), Some(amount), Some(OperationStatus::Success)) =
                         (&op.account, &op.amount, &op.status)
                     {
-                        *balances.entry(acc.address).or_default() -= amount.value;
+                        *balances
+                            .entry((acc.address, amount.currency.clone()))
+                            .or_default() -= amount.value;
                     }
                     balances
                 });
@@ -564,8 +734,8 @@ impl TryFrom<SuiTransactionBlockResponse> for Operations {
             }
         }
         let staking_balance = if principal_amounts != 0 {
-            *accounted_balances.entry(sender).or_default() -= principal_amounts;
-            *accounted_balances.entry(sender).or_default() -= reward_amounts;
+            *accounted_balances.entry((sender, SUI.clone())).or_default() -= principal_amounts;
+            *accounted_balances.entry((sender, SUI.clone())).or_default() -= reward_amounts;
             vec![
                 Operation::stake_principle(status, sender, principal_amounts),
                 Operation::stake_reward(status, sender, reward_amounts),
@@ -583,6 +753,7 @@ impl TryFrom<SuiTransactionBlockResponse> for Operations {
                 .ok_or_else(|| anyhow!("Response balance changes should not be empty."))?,
             status,
             accounted_balances,
+            currencies,
         );
 
         Ok(ops
@@ -698,13 +869,30 @@ impl Operation {
         }
     }
 
-    fn balance_change(status: Option<OperationStatus>, addr: SuiAddress, amount: i128) -> Self {
+    fn pay_coin(
+        status: Option<OperationStatus>,
+        address: SuiAddress,
+        amount: i128,
+        currency: Currency,
+    ) -> Self {
+        Operation {
+            operation_identifier: Default::default(),
+            type_: OperationType::PayCoin,
+            status,
+            account: Some(address.into()),
+            amount: Some(Amount::new_with_currency(amount, currency)),
+            coin_change: None,
+            metadata: None,
+        }
+    }
+
+    fn balance_change(status: Option<OperationStatus>, addr: SuiAddress, amount: Amount) -> Self {
         Self {
             operation_identifier: Default::default(),
             type_: OperationType::SuiBalanceChange,
             status,
             account: Some(addr.into()),
-            amount: Some(Amount::new(amount)),
+            amount: Some(amount),
             coin_change: None,
             metadata: None,
         }
@@ -3,15 +3,22 @@
 
 use crate::operations::Operations;
 use crate::types::{
-    Block, BlockHash, BlockIdentifier, BlockResponse, Transaction, TransactionIdentifier,
+    Block, BlockHash, BlockIdentifier, BlockResponse, Currency, CurrencyMetadata, Transaction,
+    TransactionIdentifier,
 };
-use crate::Error;
+use crate::{Error, SUI};
 use async_trait::async_trait;
-use std::sync::Arc;
-use sui_json_rpc_types::SuiTransactionBlockResponseOptions;
+use move_core_types::language_storage::TypeTag;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use sui_json_rpc_types::{
+    SuiTransactionBlockDataAPI, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+};
 use sui_sdk::rpc_types::Checkpoint;
 use sui_sdk::SuiClient;
+use sui_types::gas_coin::GAS;
 use sui_types::messages_checkpoint::CheckpointSequenceNumber;
+use tracing::warn;
 
 #[cfg(test)]
 #[path = "unit_tests/balance_changing_tx_tests.rs"]
@@ -20,13 +27,19 @@ mod balance_changing_tx_tests;
 #[derive(Clone)]
 pub struct OnlineServerContext {
     pub client: SuiClient,
+    pub coin_metadata_cache: CoinMetadataCache,
     block_provider: Arc<dyn BlockProvider + Send + Sync>,
 }
 
 impl OnlineServerContext {
-    pub fn new(client: SuiClient, block_provider: Arc<dyn BlockProvider + Send + Sync>) -> Self {
+    pub fn new(
+        client: SuiClient,
+        coin_metadata_cache: CoinMetadataCache,
+        block_provider: Arc<dyn BlockProvider + Send + Sync>,
+    ) -> Self {
         Self {
             client,
+            coin_metadata_cache,
             block_provider,
         }
     }
@@ -50,9 +63,85 @@ pub trait BlockProvider {
     ) -> Result<BlockIdentifier, Error>;
 }
 
+/// Caches the Rosetta currencies of the coin types read from `CoinMetadata` objects.
+#[derive(Clone)]
+pub struct CoinMetadataCache {
+    client: SuiClient,
+    currencies: Arc<Mutex<HashMap<TypeTag, Currency>>>,
+}
+
+impl CoinMetadataCache {
+    pub fn new(client: SuiClient) -> Self {
+        Self {
+            client,
+            currencies: Default::default(),
+        }
+    }
+
+    pub async fn get_currency(&self, coin_type: &TypeTag) -> Result<Currency, Error> {
+        if coin_type == &GAS::type_tag() {
+            return Ok(SUI.clone());
+        }
+        if let Some(currency) = self.currencies.lock().unwrap().get(coin_type) {
+            return Ok(currency.clone());
+        }
+        let coin_type_str = coin_type.to_canonical_string(/* with_prefix */ true);
+        let metadata = self
+            .client
+            .coin_read_api()
+            .get_coin_metadata(coin_type_str.clone())
+            .await?;
+        let currency = match metadata {
+            Some(metadata) => Currency {
+                symbol: metadata.symbol,
+                decimals: metadata.decimals as u64,
+                metadata: Some(CurrencyMetadata {
+                    coin_type: coin_type_str,
+                }),
+            },
+            None => {
+                // Coins can outlive their metadata object, fall back to the coin type name
+                warn!("Cannot find coin metadata for [{coin_type_str}], assuming 0 decimals");
+                Currency::from_coin_type(coin_type)
+            }
+        };
+        self.currencies
+            .lock()
+            .unwrap()
+            .insert(coin_type.clone(), currency.clone());
+        Ok(currency)
+    }
+
+    /// Parse the operations of a transaction, with the balance changes of all its coin types.
+    pub async fn operations(
+        &self,
+        response: SuiTransactionBlockResponse,
+    ) -> Result<Operations, Error> {
+        let pay_coin_types = response
+            .transaction
+            .iter()
+            .flat_map(|tx| Operations::pay_coin_types(tx.data.transaction()));
+        let coin_types = response
+            .balance_changes
+            .iter()
+            .flatten()
+            .map(|change| change.coin_type.clone())
+            .chain(pay_coin_types);
+        let mut currencies = HashMap::new();
+        for coin_type in coin_types {
+            if !currencies.contains_key(&coin_type) {
+                let currency = self.get_currency(&coin_type).await?;
+                currencies.insert(coin_type, currency);
+            }
+        }
+        Operations::try_from_response(response, &currencies)
+    }
+}
+
 #[derive(Clone)]
 pub struct CheckpointBlockProvider {
     client: SuiClient,
+    coin_metadata_cache: CoinMetadataCache,
 }
 
 #[async_trait]
@@ -103,8 +192,11 @@ impl BlockProvider for CheckpointBlockProvider {
 }
 
 impl CheckpointBlockProvider {
-    pub fn new(client: SuiClient) -> Self {
-        Self { client }
+    pub fn new(client: SuiClient, coin_metadata_cache: CoinMetadataCache) -> Self {
+        Self {
+            client,
+            coin_metadata_cache,
+        }
     }
 
     async fn create_block_response(&self, checkpoint: Checkpoint) -> Result<BlockResponse, Error> {
@@ -127,7 +219,7 @@ impl CheckpointBlockProvider {
             for tx in transaction_responses.into_iter() {
                 transactions.push(Transaction {
                     transaction_identifier: TransactionIdentifier { hash: tx.digest },
-                    operations: Operations::try_from(tx)?,
+                    operations: self.coin_metadata_cache.operations(tx).await?,
                     related_transactions: vec![],
                     metadata: None,
                 })
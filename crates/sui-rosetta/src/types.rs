@@ -7,6 +7,7 @@ use std::str::FromStr;
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use fastcrypto::encoding::Hex;
+use move_core_types::language_storage::TypeTag;
 use serde::de::Error as DeError;
 use serde::{Deserialize, Serializer};
 use serde::{Deserializer, Serialize};
@@ -15,7 +16,9 @@ use strum_macros::EnumIter;
 use strum_macros::EnumString;
 
 use sui_sdk::rpc_types::{SuiExecutionStatus, SuiTransactionBlockKind};
+use sui_sdk::SUI_COIN_TYPE;
 use sui_types::base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress, TransactionDigest};
+use sui_types::coin::{PAY_MODULE_NAME, PAY_SPLIT_AND_TRANSFER_FUNC_NAME};
 use sui_types::crypto::PublicKey as SuiPublicKey;
 use sui_types::crypto::SignatureScheme;
 use sui_types::governance::{ADD_STAKE_FUN_NAME, WITHDRAW_STAKE_FUN_NAME};
@@ -23,7 +26,7 @@ use sui_types::messages_checkpoint::CheckpointDigest;
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::sui_system_state::SUI_SYSTEM_MODULE_NAME;
 use sui_types::transaction::{Argument, CallArg, Command, ObjectArg, TransactionData};
-use sui_types::SUI_SYSTEM_PACKAGE_ID;
+use sui_types::{parse_sui_type_tag, SUI_FRAMEWORK_PACKAGE_ID, SUI_SYSTEM_PACKAGE_ID};
 
 use crate::errors::{Error, ErrorType};
 use crate::operations::Operations;
@@ -95,10 +98,52 @@ impl From<SuiAddress> for AccountIdentifier {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Currency {
     pub symbol: String,
     pub decimals: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<CurrencyMetadata>,
+}
+
+/// Identifies the `Coin<T>` type of non-SUI currencies, SUI has no currency metadata.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CurrencyMetadata {
+    pub coin_type: String,
+}
+
+impl Currency {
+    /// The type argument `T` of the `Coin<T>` objects of this currency. Only SUI may omit the
+    /// currency metadata.
+    pub fn coin_type(&self) -> Result<String, Error> {
+        match &self.metadata {
+            Some(metadata) => Ok(metadata.coin_type.clone()),
+            None if self.symbol == SUI.symbol && self.decimals == SUI.decimals => {
+                Ok(SUI_COIN_TYPE.to_string())
+            }
+            None => Err(Error::InvalidInput(format!(
+                "Currency {} with {} decimals has no coin type in its metadata",
+                self.symbol, self.decimals
+            ))),
+        }
+    }
+
+    /// The currency of a coin type whose `CoinMetadata` is not known, named after the coin type
+    /// and assumed to have no decimals.
+    pub fn from_coin_type(coin_type: &TypeTag) -> Self {
+        let coin_type_str = coin_type.to_canonical_string(/* with_prefix */ true);
+        let symbol = match coin_type {
+            TypeTag::Struct(tag) => tag.name.to_string(),
+            _ => coin_type_str.clone(),
+        };
+        Self {
+            symbol,
+            decimals: 0,
+            metadata: Some(CurrencyMetadata {
+                coin_type: coin_type_str,
+            }),
+        }
+    }
 }
 #[derive(Serialize, Deserialize)]
 pub struct AccountBalanceRequest {
@@ -159,6 +204,13 @@ impl Amount {
             metadata: None,
         }
     }
+    pub fn new_with_currency(value: i128, currency: Currency) -> Self {
+        Self {
+            value,
+            currency,
+            metadata: None,
+        }
+    }
     pub fn new_from_sub_balances(sub_balances: Vec<SubBalance>) -> Self {
         let value = sub_balances.iter().map(|b| b.value).sum();
 
@@ -399,6 +451,7 @@ pub enum OperationType {
     StakePrinciple,
     // sui-rosetta supported operation type
     PaySui,
+    PayCoin,
     Stake,
     WithdrawStake,
     // All other Sui transaction types, readonly
@@ -548,6 +601,7 @@ pub struct ConstructionPreprocessRequest {
 #[derive(Serialize, Deserialize)]
 pub enum PreprocessMetadata {
     PaySui,
+    PayCoin,
     Delegation,
 }
 
@@ -849,6 +903,12 @@ pub enum InternalOperation {
         recipients: Vec<SuiAddress>,
         amounts: Vec<u64>,
     },
+    PayCoin {
+        sender: SuiAddress,
+        recipients: Vec<SuiAddress>,
+        amounts: Vec<u64>,
+        currency: Currency,
+    },
     Stake {
         sender: SuiAddress,
         validator: SuiAddress,
@@ -865,6 +925,7 @@ impl InternalOperation {
     pub fn sender(&self) -> SuiAddress {
         match self {
             InternalOperation::PaySui { sender, .. }
+            | InternalOperation::PayCoin { sender, .. }
             | InternalOperation::Stake { sender, .. }
             | InternalOperation::WithdrawStake { sender, .. } => *sender,
         }
@@ -881,6 +942,40 @@ impl InternalOperation {
                 builder.pay_sui(recipients, amounts)?;
                 builder.finish()
             }
+            Self::PayCoin {
+                recipients,
+                amounts,
+                currency,
+                ..
+            } => {
+                // The coin objects do not tell their type, pay with `pay::split_and_transfer<T>`
+                // so that the coin type is part of the transaction, and checked on execution.
+                let coin_type = parse_sui_type_tag(&currency.coin_type()?)?;
+                let mut builder = ProgrammableTransactionBuilder::new();
+                let mut coins = metadata.objects.into_iter();
+                let coin = coins
+                    .next()
+                    .ok_or_else(|| Error::MissingInput("Coins to pay from".to_string()))?;
+                let coin = builder.obj(ObjectArg::ImmOrOwnedObject(coin))?;
+                let merged = coins
+                    .map(|coin| builder.obj(ObjectArg::ImmOrOwnedObject(coin)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !merged.is_empty() {
+                    builder.command(Command::MergeCoins(coin, merged));
+                }
+                for (recipient, amount) in recipients.into_iter().zip(amounts) {
+                    let amount = builder.pure(amount)?;
+                    let recipient = builder.pure(recipient)?;
+                    builder.command(Command::move_call(
+                        SUI_FRAMEWORK_PACKAGE_ID,
+                        PAY_MODULE_NAME.to_owned(),
+                        PAY_SPLIT_AND_TRANSFER_FUNC_NAME.to_owned(),
+                        vec![coin_type.clone()],
+                        vec![coin, amount, recipient],
+                    ));
+                }
+                builder.finish()
+            }
             InternalOperation::Stake {
                 validator, amount, ..
             } => {
@@ -4,11 +4,16 @@
 use move_core_types::value::MoveTypeLayout;
 use sui_json_rpc_types::SuiCallArg;
 use sui_types::base_types::{ObjectDigest, ObjectID, SequenceNumber, SuiAddress};
+use sui_types::parse_sui_type_tag;
 use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::transaction::{CallArg, TransactionData, TEST_ONLY_GAS_UNIT_FOR_TRANSFER};
 
 use crate::operations::Operations;
-use crate::types::ConstructionMetadata;
+use crate::types::{
+    ConstructionMetadata, Currency, CurrencyMetadata, InternalOperation, OperationType,
+};
+use crate::SUI;
+use sui_sdk::SUI_COIN_TYPE;
 
 #[tokio::test]
 async fn test_operation_data_parsing() -> Result<(), anyhow::Error> {
@@ -50,6 +55,93 @@ async fn test_operation_data_parsing() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_pay_coin_operation_data_parsing() -> Result<(), anyhow::Error> {
+    let gas = (
+        ObjectID::random(),
+        SequenceNumber::new(),
+        ObjectDigest::random(),
+    );
+    let coins = vec![
+        (
+            ObjectID::random(),
+            SequenceNumber::new(),
+            ObjectDigest::random(),
+        ),
+        (
+            ObjectID::random(),
+            SequenceNumber::new(),
+            ObjectDigest::random(),
+        ),
+    ];
+    let sender = SuiAddress::random_for_testing_only();
+    let currency = Currency {
+        symbol: "TEST".to_string(),
+        decimals: 6,
+        metadata: Some(CurrencyMetadata {
+            coin_type: "0x2::test_coin::TEST_COIN".to_string(),
+        }),
+    };
+    let gas_price = 10;
+    let metadata = || ConstructionMetadata {
+        sender,
+        coins: vec![gas],
+        objects: coins.clone(),
+        total_coin_value: 0,
+        gas_price,
+        budget: TEST_ONLY_GAS_UNIT_FOR_TRANSFER * gas_price,
+    };
+    let data = InternalOperation::PayCoin {
+        sender,
+        recipients: vec![SuiAddress::random_for_testing_only()],
+        amounts: vec![10000],
+        currency: currency.clone(),
+    }
+    .try_into_data(metadata())?;
+
+    // Offline, the coin type is known from the transaction, but not its metadata.
+    let ops: Operations = data.clone().try_into()?;
+    assert_eq!(Some(OperationType::PayCoin), ops.type_());
+    let offline_currency = Currency::from_coin_type(&parse_sui_type_tag(&currency.coin_type()?)?);
+    assert_eq!(offline_currency.symbol, "TEST_COIN");
+    assert!(ops
+        .clone()
+        .into_iter()
+        .all(|op| op.amount.map(|amount| amount.currency) == Some(offline_currency.clone())));
+
+    let parsed_data = ops.into_internal()?.try_into_data(metadata())?;
+    assert_eq!(data, parsed_data);
+
+    Ok(())
+}
+
+#[test]
+fn test_currency_coin_type() {
+    assert_eq!(SUI.coin_type().unwrap(), SUI_COIN_TYPE);
+
+    let currency = Currency {
+        symbol: "TEST_COIN".to_string(),
+        decimals: 6,
+        metadata: Some(CurrencyMetadata {
+            coin_type: "0x2::test_coin::TEST_COIN".to_string(),
+        }),
+    };
+    assert_eq!(currency.coin_type().unwrap(), "0x2::test_coin::TEST_COIN");
+
+    // Only SUI may leave out its coin type.
+    let currency = Currency {
+        metadata: None,
+        ..currency
+    };
+    assert!(currency.coin_type().is_err());
+    let currency = Currency {
+        decimals: 0,
+        ..SUI.clone()
+    };
+    assert!(currency.coin_type().is_err());
+}
+
 #[tokio::test]
 async fn test_sui_json() {
     let arg1 = CallArg::Pure(bcs::to_bytes(&1000000u64).unwrap());
@@ -10,7 +10,6 @@ use fastcrypto::encoding::Base64;
 use futures::stream;
 use futures::StreamExt;
 use futures_core::Stream;
-use jsonrpsee::core::client::Subscription;
 
 use crate::error::{Error, SuiRpcResult};
 use crate::RpcClient;
@@ -989,9 +988,13 @@ impl EventApi {
         filter: EventFilter,
     ) -> SuiRpcResult<impl Stream<Item = SuiRpcResult<SuiEvent>>> {
         match &self.api.ws {
-            Some(c) => {
-                let subscription: Subscription<SuiEvent> = c.subscribe_event(filter).await?;
-                Ok(subscription.map(|item| Ok(item?)))
+            Some(ws) => {
+                ws.clone()
+                    .resubscribing(move |client| {
+                        let filter = filter.clone();
+                        async move { client.subscribe_event(filter).await }
+                    })
+                    .await
             }
             _ => Err(Error::Subscription(
                 "Subscription only supported by WebSocket client.".to_string(),
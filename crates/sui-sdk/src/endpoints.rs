@@ -0,0 +1,573 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Failover and load balancing between the fullnodes a [crate::SuiClient] is connected to.
+
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures::{stream, Future, Stream, StreamExt};
+use jsonrpsee::core::client::{BatchResponse, ClientT, Subscription};
+use jsonrpsee::core::params::BatchRequestBuilder;
+use jsonrpsee::core::traits::ToRpcParams;
+use jsonrpsee::core::Error as RpcError;
+use jsonrpsee::http_client::{HeaderMap, HttpClient, HttpClientBuilder};
+use jsonrpsee::rpc_params;
+use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
+use serde::de::DeserializeOwned;
+use serde_json::value::RawValue;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use crate::error::{Error, SuiRpcResult};
+
+/// Weight of the latest request in the moving average of the latency of an endpoint.
+const LATENCY_SMOOTHING: f64 = 0.2;
+/// Method called to check the health of the endpoints.
+const HEALTH_CHECK_METHOD: &str = "sui_getLatestCheckpointSequenceNumber";
+/// Methods that can be sent to another endpoint when an endpoint fails to answer, because calling
+/// them more than once has no other effect. Executing a transaction is idempotent as well, as its
+/// digest identifies it.
+const RETRYABLE_METHOD_PREFIXES: &[&str] = &[
+    "sui_get",
+    "sui_multiGet",
+    "sui_tryGet",
+    "sui_dryRunTransactionBlock",
+    "sui_devInspectTransactionBlock",
+    "sui_executeTransactionBlock",
+    "suix_get",
+    "suix_query",
+    "suix_resolve",
+    "rpc.discover",
+];
+
+pub(crate) fn is_retryable(method: &str) -> bool {
+    RETRYABLE_METHOD_PREFIXES
+        .iter()
+        .any(|prefix| method.starts_with(prefix))
+}
+
+/// Whether an error means that the endpoint failed to answer, rather than the call failed.
+fn is_endpoint_failure(error: &RpcError) -> bool {
+    matches!(
+        error,
+        RpcError::Transport(_) | RpcError::RequestTimeout | RpcError::RestartNeeded(_)
+    )
+}
+
+/// Parameters of a call, serialized once so that the call can be sent to several endpoints.
+#[derive(Clone)]
+struct RawParams(Option<Box<RawValue>>);
+
+impl ToRpcParams for RawParams {
+    fn to_rpc_params(self) -> Result<Option<Box<RawValue>>, RpcError> {
+        Ok(self.0)
+    }
+}
+
+#[derive(Debug, Default)]
+struct EndpointStats {
+    /// Set when the last request or health check failed
+    unhealthy: bool,
+    /// Moving average of the latency of requests, unknown until the first one completes
+    latency: Option<Duration>,
+}
+
+struct HttpEndpoint {
+    url: String,
+    client: HttpClient,
+    stats: Mutex<EndpointStats>,
+}
+
+impl HttpEndpoint {
+    fn record_success(&self, latency: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.unhealthy = false;
+        stats.latency = Some(match stats.latency {
+            Some(average) => {
+                average.mul_f64(1.0 - LATENCY_SMOOTHING) + latency.mul_f64(LATENCY_SMOOTHING)
+            }
+            None => latency,
+        });
+    }
+
+    fn record_failure(&self) {
+        self.stats.lock().unwrap().unhealthy = true;
+    }
+
+    /// Endpoints are preferred when healthy, then when faster. Endpoints with no known latency
+    /// are tried first to measure it.
+    fn preference(&self) -> (bool, Duration) {
+        let stats = self.stats.lock().unwrap();
+        (stats.unhealthy, stats.latency.unwrap_or_default())
+    }
+}
+
+/// HTTP clients of several fullnodes. Requests are sent to the preferred healthy endpoint, and
+/// retryable requests are sent to the next one when an endpoint fails to answer.
+///
+/// Implements [ClientT], so it is used like a single [HttpClient] by the generated API clients.
+pub(crate) struct HttpEndpoints {
+    endpoints: Arc<Vec<HttpEndpoint>>,
+}
+
+impl HttpEndpoints {
+    pub(crate) fn new(
+        urls: &[String],
+        headers: HeaderMap,
+        max_concurrent_requests: usize,
+        request_timeout: Duration,
+    ) -> SuiRpcResult<Self> {
+        if urls.is_empty() {
+            return Err(Error::DataError("No fullnode URL provided".to_string()));
+        }
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                let client = HttpClientBuilder::default()
+                    .max_request_body_size(2 << 30)
+                    .max_concurrent_requests(max_concurrent_requests)
+                    .set_headers(headers.clone())
+                    .request_timeout(request_timeout)
+                    .build(url)?;
+                Ok(HttpEndpoint {
+                    url: url.clone(),
+                    client,
+                    stats: Default::default(),
+                })
+            })
+            .collect::<SuiRpcResult<_>>()?;
+        Ok(Self {
+            endpoints: Arc::new(endpoints),
+        })
+    }
+
+    /// Check the health and latency of every endpoint every `interval`, until the client is
+    /// dropped. Only useful with more than one endpoint.
+    pub(crate) fn spawn_health_checks(&self, interval: Duration) {
+        if self.endpoints.len() < 2 {
+            return;
+        }
+        let endpoints = Arc::downgrade(&self.endpoints);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let Some(endpoints) = Weak::upgrade(&endpoints) else {
+                    return;
+                };
+                futures::future::join_all(endpoints.iter().map(check_health)).await;
+            }
+        });
+    }
+
+    fn by_preference(&self) -> Vec<&HttpEndpoint> {
+        let mut endpoints = self.endpoints.iter().collect::<Vec<_>>();
+        endpoints.sort_by_cached_key(|endpoint| endpoint.preference());
+        endpoints
+    }
+
+    /// Send a call with `call` to the preferred endpoint, and to the next ones while they fail to
+    /// answer if the call is `retryable`.
+    async fn call<'a, T, F, Fut>(
+        &'a self,
+        method: &str,
+        retryable: bool,
+        call: F,
+    ) -> Result<T, RpcError>
+    where
+        F: Fn(&'a HttpClient) -> Fut,
+        Fut: Future<Output = Result<T, RpcError>>,
+    {
+        let mut last_error = None;
+        for endpoint in self.by_preference() {
+            let start = Instant::now();
+            match call(&endpoint.client).await {
+                Err(err) if is_endpoint_failure(&err) => {
+                    endpoint.record_failure();
+                    if !retryable {
+                        return Err(err);
+                    }
+                    warn!(
+                        url = endpoint.url,
+                        method, "Fullnode failed to answer, trying the next one: {err}"
+                    );
+                    last_error = Some(err);
+                }
+                result => {
+                    endpoint.record_success(start.elapsed());
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("There is at least one endpoint"))
+    }
+}
+
+async fn check_health(endpoint: &HttpEndpoint) {
+    let start = Instant::now();
+    match endpoint
+        .client
+        .request::<serde_json::Value, _>(HEALTH_CHECK_METHOD, rpc_params![])
+        .await
+    {
+        Ok(_) => endpoint.record_success(start.elapsed()),
+        Err(err) => {
+            debug!(url = endpoint.url, "Fullnode health check failed: {err}");
+            endpoint.record_failure();
+        }
+    }
+}
+
+impl Debug for HttpEndpoints {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.endpoints.iter().map(|endpoint| &endpoint.url))
+            .finish()
+    }
+}
+
+#[async_trait]
+impl ClientT for HttpEndpoints {
+    async fn notification<Params>(&self, method: &str, params: Params) -> Result<(), RpcError>
+    where
+        Params: ToRpcParams + Send,
+    {
+        let params = RawParams(params.to_rpc_params()?);
+        self.call(method, is_retryable(method), |client| {
+            client.notification(method, params.clone())
+        })
+        .await
+    }
+
+    async fn request<R, Params>(&self, method: &str, params: Params) -> Result<R, RpcError>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        let params = RawParams(params.to_rpc_params()?);
+        self.call(method, is_retryable(method), |client| {
+            client.request(method, params.clone())
+        })
+        .await
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, RpcError>
+    where
+        R: DeserializeOwned + Debug + 'a,
+    {
+        // A batch is sent again only if all of its calls can be.
+        let retryable = batch
+            .clone()
+            .build()?
+            .iter()
+            .all(|(method, _)| is_retryable(method));
+        self.call("batch", retryable, |client| {
+            client.batch_request(batch.clone())
+        })
+        .await
+    }
+}
+
+/// Options of the WebSocket clients, to connect to another endpoint.
+pub(crate) struct WsOptions {
+    pub headers: HeaderMap,
+    pub max_concurrent_requests: usize,
+    pub request_timeout: Duration,
+    pub ping_interval: Option<Duration>,
+}
+
+/// WebSocket client connected to one of several fullnodes, which connects to the next one when
+/// the connection is lost.
+pub(crate) struct WsEndpoints {
+    urls: Vec<String>,
+    options: WsOptions,
+    /// Index in `urls` of the endpoint of the current client
+    current: RwLock<(usize, Arc<WsClient>)>,
+}
+
+impl WsEndpoints {
+    pub(crate) async fn connect(urls: Vec<String>, options: WsOptions) -> SuiRpcResult<Self> {
+        let mut last_error = None;
+        for (index, url) in urls.iter().enumerate() {
+            match Self::connect_to(url, &options).await {
+                Ok(client) => {
+                    return Ok(Self {
+                        current: RwLock::new((index, Arc::new(client))),
+                        urls,
+                        options,
+                    })
+                }
+                Err(err) => {
+                    warn!(url, "Failed to connect to fullnode WebSocket: {err}");
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error
+            .map(Error::from)
+            .unwrap_or_else(|| Error::DataError("No fullnode WebSocket URL provided".to_string())))
+    }
+
+    async fn connect_to(url: &str, options: &WsOptions) -> Result<WsClient, RpcError> {
+        let mut builder = WsClientBuilder::default()
+            .max_request_body_size(2 << 30)
+            .max_concurrent_requests(options.max_concurrent_requests)
+            .set_headers(options.headers.clone())
+            .request_timeout(options.request_timeout);
+        if let Some(duration) = options.ping_interval {
+            builder = builder.ping_interval(duration)
+        }
+        builder.build(url).await
+    }
+
+    /// The current client, reconnected to another endpoint if its connection was lost.
+    pub(crate) async fn client(&self) -> SuiRpcResult<Arc<WsClient>> {
+        let client = self.current.read().await.1.clone();
+        if client.is_connected() {
+            Ok(client)
+        } else {
+            self.reconnect(&client).await
+        }
+    }
+
+    /// Replace `failed`, the client of an endpoint which lost its connection, by a client of the
+    /// next endpoint accepting a connection.
+    async fn reconnect(&self, failed: &Arc<WsClient>) -> SuiRpcResult<Arc<WsClient>> {
+        let mut current = self.current.write().await;
+        if !Arc::ptr_eq(&current.1, failed) && current.1.is_connected() {
+            // Already reconnected by another task
+            return Ok(current.1.clone());
+        }
+        let mut last_error = None;
+        for offset in 1..=self.urls.len() {
+            let index = (current.0 + offset) % self.urls.len();
+            let url = &self.urls[index];
+            match Self::connect_to(url, &self.options).await {
+                Ok(client) => {
+                    warn!(url, "Reconnected to fullnode WebSocket");
+                    *current = (index, Arc::new(client));
+                    return Ok(current.1.clone());
+                }
+                Err(err) => {
+                    warn!(url, "Failed to connect to fullnode WebSocket: {err}");
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error.expect("There is at least one endpoint").into())
+    }
+
+    /// Subscribe with `subscribe`, and subscribe again on another endpoint whenever the
+    /// connection is lost. Items published while reconnecting are missed. The stream ends when
+    /// no endpoint accepts a connection anymore.
+    pub(crate) async fn resubscribing<T, F, Fut>(
+        self: Arc<Self>,
+        subscribe: F,
+    ) -> SuiRpcResult<impl Stream<Item = SuiRpcResult<T>>>
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: Fn(Arc<WsClient>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Subscription<T>, RpcError>> + Send,
+    {
+        let client = self.client().await?;
+        let subscription = subscribe(client.clone()).await?;
+        Ok(stream::unfold(
+            Some((self, subscribe, client, subscription)),
+            |state| async move {
+                let (endpoints, subscribe, mut client, mut subscription) = state?;
+                loop {
+                    match subscription.next().await {
+                        Some(item) => {
+                            let item = item.map_err(Error::from);
+                            return Some((
+                                item,
+                                Some((endpoints, subscribe, client, subscription)),
+                            ));
+                        }
+                        // The connection was lost
+                        None => {
+                            let resubscribed = match endpoints.reconnect(&client).await {
+                                Ok(new_client) => subscribe(new_client.clone())
+                                    .await
+                                    .map(|new_subscription| (new_client, new_subscription))
+                                    .map_err(Error::from),
+                                Err(err) => Err(err),
+                            };
+                            match resubscribed {
+                                Ok((new_client, new_subscription)) => {
+                                    client = new_client;
+                                    subscription = new_subscription;
+                                }
+                                Err(err) => return Some((Err(err), None)),
+                            }
+                        }
+                    }
+                }
+            },
+        ))
+    }
+}
+
+impl Debug for WsEndpoints {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(&self.urls).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_retryable_methods() {
+        assert!(is_retryable("sui_getObject"));
+        assert!(is_retryable("suix_queryEvents"));
+        assert!(is_retryable("sui_executeTransactionBlock"));
+        assert!(!is_retryable("unsafe_paySui"));
+        assert!(!is_retryable("suix_subscribeEvent"));
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_preference() {
+        let urls = [
+            "http://127.0.0.1:9000",
+            "http://127.0.0.1:9001",
+            "http://127.0.0.1:9002",
+        ]
+        .map(String::from);
+        let endpoints =
+            HttpEndpoints::new(&urls, HeaderMap::new(), 1, Duration::from_secs(1)).unwrap();
+        let preferred = |endpoints: &HttpEndpoints| {
+            endpoints
+                .by_preference()
+                .iter()
+                .map(|endpoint| urls.iter().position(|url| url == &endpoint.url).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        endpoints.endpoints[0].record_success(Duration::from_millis(30));
+        endpoints.endpoints[1].record_success(Duration::from_millis(10));
+        endpoints.endpoints[2].record_success(Duration::from_millis(20));
+        assert_eq!(preferred(&endpoints), vec![1, 2, 0]);
+
+        // Failing endpoints are tried last, until they answer again
+        endpoints.endpoints[1].record_failure();
+        assert_eq!(preferred(&endpoints), vec![2, 0, 1]);
+        endpoints.endpoints[1].record_success(Duration::from_millis(10));
+        assert_eq!(preferred(&endpoints), vec![1, 2, 0]);
+
+        // Latency is averaged over requests
+        endpoints.endpoints[1].record_success(Duration::from_millis(210));
+        assert_eq!(preferred(&endpoints), vec![2, 0, 1]);
+    }
+
+    /// Serve every JSON-RPC call with the result "ok" until the test ends, and return the address.
+    async fn spawn_fullnode() -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut request = vec![];
+                    let mut buf = [0u8; 1024];
+                    let body_start = loop {
+                        let read = stream.read(&mut buf).await.unwrap();
+                        request.extend_from_slice(&buf[..read]);
+                        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                            break end + 4;
+                        }
+                    };
+                    let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+                    let content_length: usize = headers
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map(|length| length.trim().parse().unwrap())
+                        .unwrap_or_default();
+                    while request.len() < body_start + content_length {
+                        let read = stream.read(&mut buf).await.unwrap();
+                        request.extend_from_slice(&buf[..read]);
+                    }
+
+                    let answer = |call: &serde_json::Value| serde_json::json!({"jsonrpc": "2.0", "result": "ok", "id": call["id"]});
+                    let body = match serde_json::from_slice(&request[body_start..]).unwrap() {
+                        serde_json::Value::Array(calls) => {
+                            serde_json::Value::Array(calls.iter().map(answer).collect()).to_string()
+                        }
+                        call if call.get("id").is_some() => answer(&call).to_string(),
+                        // Notifications have no answer.
+                        _ => String::new(),
+                    };
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+        address
+    }
+
+    #[tokio::test]
+    async fn test_dead_primary_endpoint() {
+        // Nothing listens on the address of the primary endpoint anymore.
+        let dead = TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let live = spawn_fullnode().await;
+        let urls = [format!("http://{dead}"), format!("http://{live}")];
+        // New endpoints for each call, as the dead one is tried last once it failed.
+        let endpoints =
+            || HttpEndpoints::new(&urls, HeaderMap::new(), 10, Duration::from_secs(5)).unwrap();
+
+        let result: String = endpoints()
+            .request("sui_getChainIdentifier", rpc_params![])
+            .await
+            .unwrap();
+        assert_eq!(result, "ok");
+
+        let notified = endpoints();
+        notified
+            .notification("sui_getChainIdentifier", rpc_params![])
+            .await
+            .unwrap();
+        assert!(notified.endpoints[0].preference().0);
+
+        let mut batch = BatchRequestBuilder::new();
+        batch
+            .insert("sui_getChainIdentifier", rpc_params![])
+            .unwrap();
+        batch.insert(HEALTH_CHECK_METHOD, rpc_params![]).unwrap();
+        let response: BatchResponse<String> = endpoints().batch_request(batch).await.unwrap();
+        assert_eq!(response.num_successful_calls(), 2);
+
+        // Calls that can't be sent twice fail with the endpoint.
+        let mut batch = BatchRequestBuilder::new();
+        batch
+            .insert("sui_getChainIdentifier", rpc_params![])
+            .unwrap();
+        batch.insert("unsafe_paySui", rpc_params![]).unwrap();
+        assert!(endpoints().batch_request::<String>(batch).await.is_err());
+        assert!(endpoints()
+            .request::<String, _>("unsafe_paySui", rpc_params![])
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_no_endpoint() {
+        assert!(HttpEndpoints::new(&[], HeaderMap::new(), 1, Duration::from_secs(1)).is_err());
+    }
+}
@@ -0,0 +1,817 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A pool of gas coins of one address, leased out exclusively to the transactions sent
+//! concurrently by that address.
+//!
+//! Two transactions using the same gas coin at the same version equivocate, which locks the coin
+//! until the end of the epoch. The [GasPool] splits the balance of the address into a number of
+//! gas coins, and leases each of them to one transaction at a time:
+//!
+//! ```rust,no_run
+//! use shared_crypto::intent::Intent;
+//! use sui_json_rpc_types::SuiTransactionBlockResponseOptions;
+//! use sui_keys::keystore::AccountKeystore;
+//! use sui_sdk::gas_pool::{GasPool, GasPoolConfig};
+//! use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
+//! use sui_types::transaction::{Transaction, TransactionData};
+//! # use sui_sdk::SuiClient;
+//! # use sui_keys::keystore::Keystore;
+//! # use sui_types::base_types::SuiAddress;
+//! # use sui_types::transaction::ProgrammableTransaction;
+//! # async fn run(
+//! #     client: SuiClient,
+//! #     keystore: Keystore,
+//! #     signer: &Keystore,
+//! #     address: SuiAddress,
+//! #     pt: ProgrammableTransaction,
+//! # ) -> Result<(), anyhow::Error> {
+//! let pool = GasPool::new(client.clone(), keystore, address, GasPoolConfig::default()).await?;
+//!
+//! let budget = 10_000_000;
+//! let lease = pool.lease(budget).await?;
+//! let gas_price = client.read_api().get_reference_gas_price().await?;
+//! let data =
+//!     TransactionData::new_programmable(address, vec![lease.object_ref()], pt, budget, gas_price);
+//! let signature = signer.sign_secure(&address, &data, Intent::sui_transaction())?;
+//! let response = client
+//!     .quorum_driver_api()
+//!     .execute_transaction_block(
+//!         Transaction::from_data(data, Intent::sui_transaction(), vec![signature]),
+//!         SuiTransactionBlockResponseOptions::new().with_effects(),
+//!         Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+//!     )
+//!     .await?;
+//!
+//! // The effects give the version of the coin after the transaction.
+//! pool.reclaim(lease, &response.effects.expect("effects were requested"))
+//!     .await;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The coins leased out, including the ones merged by a rebalance in flight, are recorded in a
+//! file, so that a pool restarting after a crash does not lease again the coins that a
+//! transaction in flight may have locked.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::Intent;
+use sui_config::Config;
+use sui_json_rpc_types::{
+    SuiObjectDataOptions, SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI,
+    SuiTransactionBlockResponseOptions,
+};
+use sui_keys::keystore::{AccountKeystore, Keystore};
+use sui_types::base_types::{ObjectID, ObjectRef, SuiAddress};
+use sui_types::coin::Coin;
+use sui_types::committee::EpochId;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_types::quorum_driver_types::ExecuteTransactionRequestType;
+use sui_types::transaction::{Transaction, TransactionData};
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+use crate::error::{Error, SuiRpcResult};
+use crate::SuiClient;
+
+/// Maximum number of coins a transaction can pay gas with, and so merge in one go.
+const MAX_GAS_PAYMENT_OBJECTS: usize = 256;
+
+#[derive(Clone, Debug)]
+pub struct GasPoolConfig {
+    /// Number of gas coins the balance of the address is split into
+    pub coin_count: usize,
+    /// Coins with a lower balance are merged back into the pool when rebalancing
+    pub dust_threshold: u64,
+    /// Gas budget of the transactions splitting and merging the coins of the pool
+    pub rebalance_gas_budget: u64,
+    /// How often dust is merged and the coins of the pool rebalanced
+    pub rebalance_interval: Duration,
+    /// File recording the coins leased out, to recover the pool after a crash
+    pub state_path: Option<PathBuf>,
+}
+
+impl Default for GasPoolConfig {
+    fn default() -> Self {
+        Self {
+            coin_count: 100,
+            dust_threshold: 10_000_000,
+            rebalance_gas_budget: 1_000_000_000,
+            rebalance_interval: Duration::from_secs(60),
+            state_path: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct PoolCoin {
+    object_ref: ObjectRef,
+    balance: u64,
+}
+
+impl PoolCoin {
+    fn id(&self) -> ObjectID {
+        self.object_ref.0
+    }
+}
+
+/// The coins leased out by a pool, as persisted to its state file.
+#[derive(Default, Serialize, Deserialize)]
+struct PersistedLeases {
+    epoch: EpochId,
+    leased: Vec<ObjectRef>,
+}
+
+impl Config for PersistedLeases {}
+
+#[derive(Default)]
+struct PoolState {
+    available: BTreeMap<ObjectID, PoolCoin>,
+    leased: BTreeMap<ObjectID, PoolCoin>,
+    /// Coins that were leased out when the pool last stopped. They may be locked by a transaction
+    /// until the end of `epoch`, and are not leased until then.
+    quarantined: BTreeMap<ObjectID, PoolCoin>,
+    epoch: EpochId,
+    /// Set when a lease was returned without effects, so the version of its coin is unknown
+    needs_refresh: bool,
+    /// Coins taken out of the pool by a rebalance in progress, to be merged and split
+    rebalancing: BTreeMap<ObjectID, PoolCoin>,
+    /// Incremented with every snapshot of the leases, to write the state file in order
+    revision: u64,
+}
+
+impl PoolState {
+    /// Take the available coin with the smallest balance covering `budget`.
+    fn take_best_fit(&mut self, budget: u64) -> Option<PoolCoin> {
+        let coin = *self
+            .available
+            .values()
+            .filter(|coin| coin.balance >= budget)
+            .min_by_key(|coin| coin.balance)?;
+        self.available.remove(&coin.id());
+        Some(coin)
+    }
+
+    /// Replace the available coins with the coins owned by the address, except the ones which are
+    /// leased out or quarantined. Versions older than the ones already known are ignored, as the
+    /// coins may have been used since they were listed.
+    fn set_owned_coins(&mut self, owned: Vec<PoolCoin>) {
+        let mut available = BTreeMap::new();
+        for coin in owned {
+            if self.leased.contains_key(&coin.id())
+                || self.quarantined.contains_key(&coin.id())
+                || self.rebalancing.contains_key(&coin.id())
+            {
+                continue;
+            }
+            let coin = match self.available.get(&coin.id()) {
+                Some(known) if known.object_ref.1 > coin.object_ref.1 => *known,
+                _ => coin,
+            };
+            available.insert(coin.id(), coin);
+        }
+        self.available = available;
+        self.needs_refresh = false;
+    }
+
+    fn persisted(&self) -> PersistedLeases {
+        PersistedLeases {
+            epoch: self.epoch,
+            leased: self
+                .leased
+                .values()
+                .chain(self.quarantined.values())
+                .chain(self.rebalancing.values())
+                .map(|coin| coin.object_ref)
+                .collect(),
+        }
+    }
+
+    /// Take a snapshot of the leases, to be written by [GasPoolInner::persist] once the state
+    /// is unlocked.
+    fn snapshot(&mut self) -> (u64, PersistedLeases) {
+        self.revision += 1;
+        (self.revision, self.persisted())
+    }
+}
+
+/// Counts of the coins of a [GasPool].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasPoolStatus {
+    pub available_coins: usize,
+    pub leased_coins: usize,
+    pub quarantined_coins: usize,
+    pub available_balance: u64,
+}
+
+struct GasPoolInner {
+    client: SuiClient,
+    keystore: Keystore,
+    address: SuiAddress,
+    config: GasPoolConfig,
+    state: Mutex<PoolState>,
+    /// Serializes the writes of the state file, and holds the revision of the leases last written
+    state_file: Mutex<u64>,
+    /// Notified whenever a lease is returned
+    coin_returned: Notify,
+    /// Serializes the refreshes and rebalances of the pool
+    maintenance: tokio::sync::Mutex<()>,
+}
+
+/// Pool of gas coins of an address, leased out exclusively to concurrent transactions.
+#[derive(Clone)]
+pub struct GasPool {
+    inner: Arc<GasPoolInner>,
+}
+
+/// Exclusive use of a gas coin of a [GasPool].
+///
+/// Return the lease with [GasPool::reclaim] once the transaction using the coin was executed.
+/// A lease dropped without effects returns the coin to the pool as well, which then fetches its
+/// latest version from the fullnode before leasing it again.
+pub struct GasLease {
+    coin: PoolCoin,
+    pool: Arc<GasPoolInner>,
+    reclaimed: bool,
+}
+
+impl GasLease {
+    pub fn object_ref(&self) -> ObjectRef {
+        self.coin.object_ref
+    }
+
+    pub fn balance(&self) -> u64 {
+        self.coin.balance
+    }
+}
+
+impl Drop for GasLease {
+    fn drop(&mut self) {
+        if !self.reclaimed {
+            self.pool.return_coin(self.coin.id(), None);
+        }
+    }
+}
+
+impl GasPool {
+    /// Create a pool of the SUI coins owned by `address`, splitting them into
+    /// `config.coin_count` gas coins, and start rebalancing it in the background.
+    ///
+    /// When `config.state_path` records coins leased out by a previous pool in the current epoch,
+    /// the coins still at the leased version are quarantined until the next epoch.
+    pub async fn new(
+        client: SuiClient,
+        keystore: Keystore,
+        address: SuiAddress,
+        config: GasPoolConfig,
+    ) -> SuiRpcResult<Self> {
+        if config.coin_count == 0 {
+            return Err(Error::DataError(
+                "A gas pool needs at least one coin".to_string(),
+            ));
+        }
+        let epoch = current_epoch(&client).await?;
+        let previous_leases = match &config.state_path {
+            Some(path) if path.exists() => PersistedLeases::load(path)
+                .map_err(|e| Error::DataError(format!("Cannot load gas pool state: {e}")))?,
+            _ => PersistedLeases::default(),
+        };
+        let inner = Arc::new(GasPoolInner {
+            client,
+            keystore,
+            address,
+            config,
+            state: Default::default(),
+            state_file: Default::default(),
+            coin_returned: Notify::new(),
+            maintenance: Default::default(),
+        });
+
+        let owned = inner.fetch_owned_coins().await?;
+        let snapshot = {
+            let mut state = inner.state.lock().unwrap();
+            state.epoch = epoch;
+            if previous_leases.epoch == epoch {
+                let leased: HashSet<_> = previous_leases.leased.into_iter().collect();
+                for coin in &owned {
+                    if leased.contains(&coin.object_ref) {
+                        state.quarantined.insert(coin.id(), *coin);
+                    }
+                }
+            }
+            if !state.quarantined.is_empty() {
+                warn!(
+                    address = ?inner.address,
+                    "{} gas coins may be locked by transactions sent before a restart, \
+                     they won't be used until the end of epoch {epoch}",
+                    state.quarantined.len()
+                );
+            }
+            state.set_owned_coins(owned);
+            state.snapshot()
+        };
+        inner.persist(snapshot);
+
+        inner.rebalance().await?;
+        let pool = Self { inner };
+        pool.spawn_maintenance();
+        Ok(pool)
+    }
+
+    /// Lease a coin with a balance of at least `budget`, waiting for one to be returned when
+    /// they are all leased out.
+    pub async fn lease(&self, budget: u64) -> SuiRpcResult<GasLease> {
+        loop {
+            // Created before checking the pool, so that coins returned meanwhile are noticed
+            let returned = self.inner.coin_returned.notified();
+            let needs_refresh = {
+                let mut state = self.inner.state.lock().unwrap();
+                if let Some(coin) = state.take_best_fit(budget) {
+                    state.leased.insert(coin.id(), coin);
+                    let snapshot = state.snapshot();
+                    drop(state);
+                    self.inner.persist(snapshot);
+                    return Ok(GasLease {
+                        coin,
+                        pool: self.inner.clone(),
+                        reclaimed: false,
+                    });
+                }
+                if !state.needs_refresh
+                    && state.rebalancing.is_empty()
+                    && !state.leased.values().any(|coin| coin.balance >= budget)
+                {
+                    return Err(Error::InsufficientFund {
+                        address: self.inner.address,
+                        amount: budget as u128,
+                    });
+                }
+                state.needs_refresh
+            };
+            if needs_refresh {
+                self.inner.refresh().await?;
+            } else {
+                returned.await;
+            }
+        }
+    }
+
+    /// Return a lease once the transaction paying gas with its coin was executed, with the
+    /// effects of that transaction. The balance of the coin at the version in the effects is
+    /// fetched from the fullnode, as the transaction may have spent more than its gas from it.
+    pub async fn reclaim(&self, mut lease: GasLease, effects: &SuiTransactionBlockEffects) {
+        let gas_object = effects.gas_object().reference.to_object_ref();
+        let coin = if gas_object.0 == lease.coin.id() {
+            self.inner.fetch_coin(gas_object).await
+        } else {
+            None
+        };
+        lease.reclaimed = true;
+        self.inner.return_coin(lease.coin.id(), coin);
+    }
+
+    /// Merge dust and split the coins of the pool again, if the number of coins differs from
+    /// the configured one or some of them are dust. Done periodically in the background.
+    pub async fn rebalance(&self) -> SuiRpcResult<()> {
+        self.inner.rebalance().await
+    }
+
+    pub fn status(&self) -> GasPoolStatus {
+        let state = self.inner.state.lock().unwrap();
+        GasPoolStatus {
+            available_coins: state.available.len(),
+            leased_coins: state.leased.len(),
+            quarantined_coins: state.quarantined.len(),
+            available_balance: state.available.values().map(|coin| coin.balance).sum(),
+        }
+    }
+
+    fn spawn_maintenance(&self) {
+        let inner = Arc::downgrade(&self.inner);
+        let interval = self.inner.config.rebalance_interval;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately, and the pool was just rebalanced
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let Some(inner) = Weak::upgrade(&inner) else {
+                    return;
+                };
+                if let Err(e) = inner.maintain().await {
+                    warn!(address = ?inner.address, "Gas pool maintenance failed: {e}");
+                }
+            }
+        });
+    }
+}
+
+impl GasPoolInner {
+    /// Put a leased coin back in the pool, at its version after the transaction which used it
+    /// when it is known.
+    fn return_coin(&self, id: ObjectID, coin: Option<PoolCoin>) {
+        let snapshot = {
+            let mut state = self.state.lock().unwrap();
+            state.leased.remove(&id);
+            match coin {
+                Some(coin) if coin.balance > 0 => {
+                    state.available.insert(id, coin);
+                }
+                Some(_) => {}
+                None => state.needs_refresh = true,
+            }
+            state.snapshot()
+        };
+        self.persist(snapshot);
+        self.coin_returned.notify_waiters();
+    }
+
+    /// Write a snapshot of the leases to the state file, unless a more recent one was written
+    /// meanwhile.
+    fn persist(&self, (revision, leases): (u64, PersistedLeases)) {
+        let Some(path) = &self.config.state_path else {
+            return;
+        };
+        let mut written = self.state_file.lock().unwrap();
+        if *written >= revision {
+            return;
+        }
+        // Replace the previous file atomically, so that a crash while writing it doesn't lose
+        // the leases recorded in it.
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let result = leases
+            .save(&tmp_path)
+            .and_then(|()| Ok(std::fs::rename(&tmp_path, path)?));
+        match result {
+            Ok(()) => *written = revision,
+            Err(e) => warn!("Cannot save gas pool state to {}: {e}", path.display()),
+        }
+    }
+
+    /// Fetch the coin at `object_ref`, or `None` when the fullnode doesn't know that version of
+    /// it yet.
+    async fn fetch_coin(&self, object_ref: ObjectRef) -> Option<PoolCoin> {
+        let response = match self
+            .client
+            .read_api()
+            .get_object_with_options(object_ref.0, SuiObjectDataOptions::new().with_bcs())
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                warn!(coin = ?object_ref.0, "Cannot fetch gas coin: {e}");
+                return None;
+            }
+        };
+        if response.object_ref_if_exists() != Some(object_ref) {
+            return None;
+        }
+        let coin = Coin::from_bcs_bytes(response.move_object_bcs()?).ok()?;
+        Some(PoolCoin {
+            object_ref,
+            balance: coin.value(),
+        })
+    }
+
+    async fn fetch_owned_coins(&self) -> SuiRpcResult<Vec<PoolCoin>> {
+        let mut coins = vec![];
+        let mut cursor = None;
+        loop {
+            let page = self
+                .client
+                .coin_read_api()
+                .get_coins(self.address, None, cursor, None)
+                .await?;
+            coins.extend(page.data.iter().map(|coin| PoolCoin {
+                object_ref: coin.object_ref(),
+                balance: coin.balance,
+            }));
+            if !page.has_next_page {
+                return Ok(coins);
+            }
+            cursor = page.next_cursor;
+        }
+    }
+
+    /// Fetch the latest versions of the coins of the pool.
+    async fn refresh(&self) -> SuiRpcResult<()> {
+        let _guard = self.maintenance.lock().await;
+        if !self.state.lock().unwrap().needs_refresh {
+            // Refreshed by another task meanwhile
+            return Ok(());
+        }
+        self.refresh_locked().await
+    }
+
+    async fn maintain(&self) -> SuiRpcResult<()> {
+        let epoch = current_epoch(&self.client).await?;
+        let snapshot = {
+            let mut state = self.state.lock().unwrap();
+            if epoch > state.epoch {
+                if !state.quarantined.is_empty() {
+                    info!(
+                        address = ?self.address,
+                        "Releasing {} quarantined gas coins at epoch {epoch}",
+                        state.quarantined.len()
+                    );
+                    state.quarantined.clear();
+                    state.needs_refresh = true;
+                }
+                state.epoch = epoch;
+                Some(state.snapshot())
+            } else {
+                None
+            }
+        };
+        if let Some(snapshot) = snapshot {
+            self.persist(snapshot);
+        }
+        self.refresh().await?;
+        self.rebalance().await
+    }
+
+    async fn rebalance(&self) -> SuiRpcResult<()> {
+        let _guard = self.maintenance.lock().await;
+        let (coins, split_count, snapshot) = {
+            let mut state = self.state.lock().unwrap();
+            let in_use = state.leased.len() + state.quarantined.len();
+            let target = self.config.coin_count.saturating_sub(in_use).max(1);
+            let Some(coins) =
+                plan_rebalance(&mut state.available, target, self.config.dust_threshold)
+            else {
+                return Ok(());
+            };
+            state.rebalancing = coins.iter().map(|coin| (coin.id(), *coin)).collect();
+            (coins, target - 1, state.snapshot())
+        };
+        // The coins are recorded before the transaction merging them is sent, so that a pool
+        // restarting while it is in flight does not lease them again.
+        self.persist(snapshot);
+
+        let tx = match self.rebalance_transaction(&coins, split_count).await {
+            Ok(tx) => tx,
+            Err(e) => {
+                // Nothing was sent, so the coins are unchanged
+                self.end_rebalance(false);
+                return Err(e);
+            }
+        };
+        let digest = *tx.digest();
+        let result = self
+            .client
+            .quorum_driver_api()
+            .execute_transaction_block(
+                tx,
+                SuiTransactionBlockResponseOptions::new().with_effects(),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await;
+        let result = match result {
+            Ok(response) => {
+                // Coins were merged and split whether the transaction succeeded or not
+                self.end_rebalance(true);
+                match response.effects.as_ref().map(|effects| effects.status()) {
+                    Some(status) if status.is_ok() => {
+                        debug!(
+                            address = ?self.address,
+                            "Rebalanced {} gas coins into {} coins",
+                            coins.len(),
+                            split_count + 1
+                        );
+                        Ok(())
+                    }
+                    status => Err(Error::DataError(format!(
+                        "Gas pool rebalancing transaction {digest} failed: {status:?}"
+                    ))),
+                }
+            }
+            Err(e) => {
+                // The transaction may still be executed and lock the coins, like the ones leased
+                // out when a pool stops.
+                warn!(
+                    address = ?self.address,
+                    "Gas pool rebalancing transaction {digest} may be in flight, {} gas coins \
+                     won't be used until the end of the epoch",
+                    coins.len()
+                );
+                let snapshot = {
+                    let mut state = self.state.lock().unwrap();
+                    let rebalancing = std::mem::take(&mut state.rebalancing);
+                    state.quarantined.extend(rebalancing);
+                    state.snapshot()
+                };
+                self.persist(snapshot);
+                Err(e)
+            }
+        };
+        let refreshed = self.refresh_locked().await;
+        self.coin_returned.notify_waiters();
+        result.and(refreshed)
+    }
+
+    /// Put the coins taken out of the pool by a rebalance back, and fetch their latest versions
+    /// if the rebalancing transaction was executed.
+    fn end_rebalance(&self, executed: bool) {
+        let snapshot = {
+            let mut state = self.state.lock().unwrap();
+            let rebalancing = std::mem::take(&mut state.rebalancing);
+            if executed {
+                state.needs_refresh = true;
+            } else {
+                state.available.extend(rebalancing);
+            }
+            state.snapshot()
+        };
+        self.persist(snapshot);
+        self.coin_returned.notify_waiters();
+    }
+
+    /// Build the transaction paying gas with all `coins`, which merges them, and splitting the
+    /// result into `split_count` new coins plus the gas coin.
+    async fn rebalance_transaction(
+        &self,
+        coins: &[PoolCoin],
+        split_count: usize,
+    ) -> SuiRpcResult<Transaction> {
+        let total: u64 = coins.iter().map(|coin| coin.balance).sum();
+        let budget = self.config.rebalance_gas_budget;
+        let amount = total.saturating_sub(budget) / (split_count as u64 + 1);
+        if amount < self.config.dust_threshold {
+            return Err(Error::InsufficientFund {
+                address: self.address,
+                amount: budget as u128
+                    + self.config.dust_threshold as u128 * (split_count as u128 + 1),
+            });
+        }
+        let gas_price = self
+            .client
+            .governance_api()
+            .get_reference_gas_price()
+            .await?;
+        let pt = {
+            let mut builder = ProgrammableTransactionBuilder::new();
+            builder
+                .pay_sui(vec![self.address; split_count], vec![amount; split_count])
+                .map_err(|e| Error::DataError(e.to_string()))?;
+            builder.finish()
+        };
+        let data = TransactionData::new_programmable(
+            self.address,
+            coins.iter().map(|coin| coin.object_ref).collect(),
+            pt,
+            budget,
+            gas_price,
+        );
+        let signature = self
+            .keystore
+            .sign_secure(&self.address, &data, Intent::sui_transaction())
+            .map_err(|e| Error::DataError(e.to_string()))?;
+        Ok(Transaction::from_data(
+            data,
+            Intent::sui_transaction(),
+            vec![signature],
+        ))
+    }
+
+    /// Refresh while already holding the maintenance lock.
+    async fn refresh_locked(&self) -> SuiRpcResult<()> {
+        let owned = self.fetch_owned_coins().await?;
+        self.state.lock().unwrap().set_owned_coins(owned);
+        self.coin_returned.notify_waiters();
+        Ok(())
+    }
+}
+
+async fn current_epoch(client: &SuiClient) -> SuiRpcResult<EpochId> {
+    Ok(client
+        .governance_api()
+        .get_latest_sui_system_state()
+        .await?
+        .epoch)
+}
+
+/// Take the available coins to merge and split into `target` coins out of the pool, when their
+/// number differs from `target` or some of them are dust. The largest coins are taken first, up to
+/// the number of coins a transaction can pay gas with.
+fn plan_rebalance(
+    available: &mut BTreeMap<ObjectID, PoolCoin>,
+    target: usize,
+    dust_threshold: u64,
+) -> Option<Vec<PoolCoin>> {
+    let has_dust = available.values().any(|coin| coin.balance < dust_threshold);
+    if available.is_empty() || (available.len() == target && !has_dust) {
+        return None;
+    }
+    let mut coins: Vec<_> = available.values().copied().collect();
+    coins.sort_by_key(|coin| std::cmp::Reverse(coin.balance));
+    coins.truncate(MAX_GAS_PAYMENT_OBJECTS);
+    for coin in &coins {
+        available.remove(&coin.id());
+    }
+    Some(coins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sui_types::base_types::{random_object_ref, SequenceNumber};
+
+    fn coin(version: u64, balance: u64) -> PoolCoin {
+        let (id, _, digest) = random_object_ref();
+        PoolCoin {
+            object_ref: (id, SequenceNumber::from_u64(version), digest),
+            balance,
+        }
+    }
+
+    fn pool_state(coins: &[PoolCoin]) -> PoolState {
+        let mut state = PoolState::default();
+        state.set_owned_coins(coins.to_vec());
+        state
+    }
+
+    #[test]
+    fn test_take_best_fit() {
+        let mut state = pool_state(&[coin(1, 100), coin(1, 50), coin(1, 80)]);
+        assert_eq!(state.take_best_fit(60).unwrap().balance, 80);
+        assert_eq!(state.take_best_fit(60).unwrap().balance, 100);
+        assert!(state.take_best_fit(60).is_none());
+        assert_eq!(state.take_best_fit(10).unwrap().balance, 50);
+    }
+
+    #[test]
+    fn test_set_owned_coins() {
+        let mut state = pool_state(&[coin(1, 100), coin(5, 100)]);
+        let leased = state.take_best_fit(100).unwrap();
+        state.leased.insert(leased.id(), leased);
+
+        // Leased coins stay leased, and versions don't go backwards
+        let remaining = *state.available.values().next().unwrap();
+        let stale = PoolCoin {
+            object_ref: (
+                remaining.id(),
+                SequenceNumber::from_u64(0),
+                remaining.object_ref.2,
+            ),
+            ..remaining
+        };
+        state.set_owned_coins(vec![leased, stale, coin(1, 10)]);
+        assert!(!state.available.contains_key(&leased.id()));
+        assert_eq!(state.available[&remaining.id()], remaining);
+        assert_eq!(state.available.len(), 2);
+    }
+
+    #[test]
+    fn test_persisted_leases() {
+        let mut state = pool_state(&[coin(1, 100), coin(1, 100), coin(1, 100), coin(1, 100)]);
+        let leased = state.take_best_fit(100).unwrap();
+        state.leased.insert(leased.id(), leased);
+        let quarantined = state.take_best_fit(100).unwrap();
+        state.quarantined.insert(quarantined.id(), quarantined);
+        let rebalancing = state.take_best_fit(100).unwrap();
+        state.rebalancing.insert(rebalancing.id(), rebalancing);
+
+        // Coins which a transaction in flight may use are all recorded, in successive revisions
+        let (first, _) = state.snapshot();
+        let (revision, leases) = state.snapshot();
+        assert!(revision > first);
+        assert_eq!(
+            leases.leased.into_iter().collect::<HashSet<_>>(),
+            HashSet::from([
+                leased.object_ref,
+                quarantined.object_ref,
+                rebalancing.object_ref
+            ])
+        );
+
+        // Coins being rebalanced are not listed as available again
+        state.set_owned_coins(vec![rebalancing]);
+        assert!(state.available.is_empty());
+    }
+
+    #[test]
+    fn test_plan_rebalance() {
+        let mut available = pool_state(&[coin(1, 100), coin(1, 100)]).available;
+        assert!(plan_rebalance(&mut available, 2, 10).is_none());
+
+        // Too few coins
+        let coins = plan_rebalance(&mut available, 4, 10).unwrap();
+        assert_eq!(coins.len(), 2);
+        assert!(available.is_empty());
+
+        // Dust is merged, with the largest coin first to pay gas with
+        let mut available = pool_state(&[coin(1, 5), coin(1, 100)]).available;
+        let coins = plan_rebalance(&mut available, 2, 10).unwrap();
+        assert_eq!(
+            coins.iter().map(|coin| coin.balance).collect::<Vec<_>>(),
+            vec![100, 5]
+        );
+    }
+}
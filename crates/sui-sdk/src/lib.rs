@@ -77,9 +77,8 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use jsonrpsee::core::client::ClientT;
-use jsonrpsee::http_client::{HeaderMap, HeaderValue, HttpClient, HttpClientBuilder};
+use jsonrpsee::http_client::{HeaderMap, HeaderValue};
 use jsonrpsee::rpc_params;
-use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
 use serde_json::Value;
 
 use move_core_types::language_storage::StructTag;
@@ -97,10 +96,13 @@ pub use sui_types as types;
 use sui_types::base_types::{ObjectID, ObjectInfo, SuiAddress};
 
 use crate::apis::{CoinReadApi, EventApi, GovernanceApi, QuorumDriverApi, ReadApi};
+use crate::endpoints::{HttpEndpoints, WsEndpoints, WsOptions};
 use crate::error::{Error, SuiRpcResult};
 
 pub mod apis;
+mod endpoints;
 pub mod error;
+pub mod gas_pool;
 pub mod json_rpc_error;
 pub mod sui_client_config;
 pub mod wallet_context;
@@ -120,6 +122,11 @@ pub const SUI_TESTNET_URL: &str = "https://fullnode.testnet.sui.io:443";
 /// value of your choice to prevent the inactive WS subscription being
 /// disconnected due to proxy timeout.
 ///
+/// The client can be connected to several fullnodes with `build_with_urls` and `ws_urls`.
+/// Requests are then sent to the healthy fullnode with the lowest latency, and reads and
+/// transaction executions are retried on another fullnode when one fails to answer.
+/// Subscriptions move to another fullnode when their connection is lost.
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -137,8 +144,9 @@ pub const SUI_TESTNET_URL: &str = "https://fullnode.testnet.sui.io:443";
 pub struct SuiClientBuilder {
     request_timeout: Duration,
     max_concurrent_requests: usize,
-    ws_url: Option<String>,
+    ws_urls: Vec<String>,
     ws_ping_interval: Option<Duration>,
+    health_check_interval: Duration,
 }
 
 impl Default for SuiClientBuilder {
@@ -146,8 +154,9 @@ impl Default for SuiClientBuilder {
         Self {
             request_timeout: Duration::from_secs(60),
             max_concurrent_requests: 256,
-            ws_url: None,
+            ws_urls: vec![],
             ws_ping_interval: None,
+            health_check_interval: Duration::from_secs(30),
         }
     }
 }
@@ -167,7 +176,23 @@ impl SuiClientBuilder {
 
     /// Set the WebSocket URL for the Sui network
     pub fn ws_url(mut self, url: impl AsRef<str>) -> Self {
-        self.ws_url = Some(url.as_ref().to_string());
+        self.ws_urls = vec![url.as_ref().to_string()];
+        self
+    }
+
+    /// Set the WebSocket URLs of several fullnodes of the Sui network, in order of preference.
+    /// Subscriptions are moved to the next one when the connection to a fullnode is lost.
+    pub fn ws_urls<S: AsRef<str>>(mut self, urls: impl IntoIterator<Item = S>) -> Self {
+        self.ws_urls = urls
+            .into_iter()
+            .map(|url| url.as_ref().to_string())
+            .collect();
+        self
+    }
+
+    /// Set the interval of the health checks of the fullnodes, when connected to several of them
+    pub fn health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
         self
     }
 
@@ -195,6 +220,33 @@ impl SuiClientBuilder {
     /// }
     /// ```
     pub async fn build(self, http: impl AsRef<str>) -> SuiRpcResult<SuiClient> {
+        self.build_with_urls([http]).await
+    }
+
+    /// Returns a [SuiClient] object connected to several fullnodes of the Sui network.
+    ///
+    /// Requests are sent to the healthy fullnode with the lowest latency. Reads and transaction
+    /// executions are sent to another fullnode when one fails to answer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sui_sdk::SuiClientBuilder;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), anyhow::Error> {
+    ///     let sui = SuiClientBuilder::default()
+    ///         .build_with_urls(["http://127.0.0.1:9000", "http://127.0.0.1:9001"])
+    ///         .await?;
+    ///
+    ///     println!("Sui local version: {:?}", sui.api_version());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn build_with_urls<S: AsRef<str>>(
+        self,
+        http_urls: impl IntoIterator<Item = S>,
+    ) -> SuiRpcResult<SuiClient> {
         let client_version = env!("CARGO_PKG_VERSION");
         let mut headers = HeaderMap::new();
         headers.insert(
@@ -208,30 +260,31 @@ impl SuiClientBuilder {
         );
         headers.insert(CLIENT_SDK_TYPE_HEADER, HeaderValue::from_static("rust"));
 
-        let ws = if let Some(url) = self.ws_url {
-            let mut builder = WsClientBuilder::default()
-                .max_request_body_size(2 << 30)
-                .max_concurrent_requests(self.max_concurrent_requests)
-                .set_headers(headers.clone())
-                .request_timeout(self.request_timeout);
-
-            if let Some(duration) = self.ws_ping_interval {
-                builder = builder.ping_interval(duration)
-            }
-
-            Some(builder.build(url).await?)
-        } else {
+        let ws = if self.ws_urls.is_empty() {
             None
+        } else {
+            let options = WsOptions {
+                headers: headers.clone(),
+                max_concurrent_requests: self.max_concurrent_requests,
+                request_timeout: self.request_timeout,
+                ping_interval: self.ws_ping_interval,
+            };
+            Some(Arc::new(WsEndpoints::connect(self.ws_urls, options).await?))
         };
 
-        let http = HttpClientBuilder::default()
-            .max_request_body_size(2 << 30)
-            .max_concurrent_requests(self.max_concurrent_requests)
-            .set_headers(headers.clone())
-            .request_timeout(self.request_timeout)
-            .build(http)?;
+        let http_urls = http_urls
+            .into_iter()
+            .map(|url| url.as_ref().to_string())
+            .collect::<Vec<_>>();
+        let http = HttpEndpoints::new(
+            &http_urls,
+            headers,
+            self.max_concurrent_requests,
+            self.request_timeout,
+        )?;
+        http.spawn_health_checks(self.health_check_interval);
 
-        let info = Self::get_server_info(&http, &ws).await?;
+        let info = Self::get_server_info(&http, ws.as_deref()).await?;
 
         let rpc = RpcClient { http, ws, info };
         let api = Arc::new(rpc);
@@ -328,8 +381,8 @@ impl SuiClientBuilder {
     ///
     /// Fails with an error if it cannot call the RPC discover.
     async fn get_server_info(
-        http: &HttpClient,
-        ws: &Option<WsClient>,
+        http: &HttpEndpoints,
+        ws: Option<&WsEndpoints>,
     ) -> Result<ServerInfo, Error> {
         let rpc_spec: Value = http.request("rpc.discover", rpc_params![]).await?;
         let version = rpc_spec
@@ -341,7 +394,11 @@ impl SuiClientBuilder {
         let rpc_methods = Self::parse_methods(&rpc_spec)?;
 
         let subscriptions = if let Some(ws) = ws {
-            let rpc_spec: Value = ws.request("rpc.discover", rpc_params![]).await?;
+            let rpc_spec: Value = ws
+                .client()
+                .await?
+                .request("rpc.discover", rpc_params![])
+                .await?;
             Self::parse_methods(&rpc_spec)?
         } else {
             Vec::new()
@@ -417,8 +474,8 @@ pub struct SuiClient {
 }
 
 pub(crate) struct RpcClient {
-    http: HttpClient,
-    ws: Option<WsClient>,
+    http: HttpEndpoints,
+    ws: Option<Arc<WsEndpoints>>,
     info: ServerInfo,
 }
 
@@ -5,6 +5,7 @@
 #[cfg(test)]
 mod tests;
 
+pub mod p2p;
 pub mod reader;
 pub mod uploader;
 mod writer;
@@ -0,0 +1,234 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Transfer of formal state snapshots between peers over state sync.
+//!
+//! Every object file of a snapshot is served as one chunk, exactly as it is stored on disk. The
+//! accumulator of a chunk is computed from the reference file of the same bucket and partition,
+//! and its metadata is the BCS encoded [`FileMetadata`] of the object file, which tells the
+//! receiving side how to decompress it.
+
+use crate::reader::{LiveObjectIter, ObjectRefIter, StateSnapshotReaderV1};
+use crate::{FileMetadata, FileType};
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use fastcrypto::hash::{HashFunction, MultisetHash, Sha3_256};
+use object_store::path::Path;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use sui_core::authority::authority_store_tables::{AuthorityPerpetualTables, LiveObject};
+use sui_core::authority::AuthorityStore;
+use sui_core::db_checkpoint_handler::SUCCESS_MARKER;
+use sui_network::state_sync::{
+    StateSnapshotChunkInfo, StateSnapshotManifest, StateSnapshotSink, StateSnapshotStore,
+};
+use sui_types::accumulator::Accumulator;
+use sui_types::committee::EpochId;
+use tracing::info;
+
+/// Serves the latest complete state snapshot found in a local snapshot directory, i.e. one
+/// written by the state snapshot uploader to an object store of type `File`.
+pub struct LocalStateSnapshotStore {
+    root: PathBuf,
+    // Computing the accumulators of a snapshot requires reading all of its reference files, so
+    // this is only done once per epoch. The lock is held while they are computed, so concurrent
+    // requests wait for that instead of repeating it.
+    cache: Mutex<Option<Arc<LocalSnapshot>>>,
+}
+
+struct LocalSnapshot {
+    manifest: StateSnapshotManifest,
+    object_files: Vec<FileMetadata>,
+}
+
+impl LocalStateSnapshotStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            cache: Mutex::new(None),
+        }
+    }
+
+    fn epoch_dir(epoch: EpochId) -> Path {
+        Path::from(format!("epoch_{epoch}"))
+    }
+
+    /// The latest epoch whose snapshot was completely written.
+    fn latest_epoch(&self) -> Result<Option<EpochId>> {
+        let mut latest = None;
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let Some(epoch) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_prefix("epoch_"))
+                .and_then(|epoch| epoch.parse::<EpochId>().ok())
+            else {
+                continue;
+            };
+            if entry.path().join(SUCCESS_MARKER).exists() {
+                latest = latest.max(Some(epoch));
+            }
+        }
+        Ok(latest)
+    }
+
+    fn snapshot(&self, epoch: EpochId) -> Result<Option<Arc<LocalSnapshot>>> {
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(snapshot) = cache.as_ref() {
+            if snapshot.manifest.epoch == epoch {
+                return Ok(Some(snapshot.clone()));
+            }
+        }
+        let epoch_path = self.root.join(format!("epoch_{epoch}"));
+        if !epoch_path.join(SUCCESS_MARKER).exists() {
+            return Ok(None);
+        }
+
+        let manifest = StateSnapshotReaderV1::read_manifest(epoch_path.join("MANIFEST"))?;
+        if manifest.epoch() != epoch {
+            return Err(anyhow!(
+                "Manifest in {epoch_path:?} is not for epoch: {epoch}"
+            ));
+        }
+        let mut ref_files = BTreeMap::new();
+        let mut object_files = vec![];
+        for file_metadata in manifest.file_metadata() {
+            match file_metadata.file_type {
+                FileType::Object => object_files.push(file_metadata.clone()),
+                FileType::Reference => {
+                    ref_files.insert(
+                        (file_metadata.bucket_num, file_metadata.part_num),
+                        file_metadata,
+                    );
+                }
+            }
+        }
+
+        let mut chunks = Vec::with_capacity(object_files.len());
+        for file_metadata in &object_files {
+            let ref_file = ref_files
+                .get(&(file_metadata.bucket_num, file_metadata.part_num))
+                .with_context(|| {
+                    format!(
+                        "No ref file found for bucket: {}, part: {}",
+                        file_metadata.bucket_num, file_metadata.part_num
+                    )
+                })?;
+            let mut accumulator = Accumulator::default();
+            accumulator.insert_all(
+                ObjectRefIter::new(ref_file, self.root.clone(), Self::epoch_dir(epoch))?
+                    .map(|object_ref| object_ref.2),
+            );
+            let size =
+                fs::metadata(file_metadata.local_file_path(&self.root, &Self::epoch_dir(epoch))?)?
+                    .len();
+            chunks.push(StateSnapshotChunkInfo {
+                accumulator,
+                metadata: bcs::to_bytes(file_metadata)?,
+                size,
+            });
+        }
+        info!(
+            "Serving state snapshot of epoch {epoch} to peers in {} chunks",
+            chunks.len()
+        );
+
+        let snapshot = Arc::new(LocalSnapshot {
+            manifest: StateSnapshotManifest { epoch, chunks },
+            object_files,
+        });
+        *cache = Some(snapshot.clone());
+        Ok(Some(snapshot))
+    }
+}
+
+impl StateSnapshotStore for LocalStateSnapshotStore {
+    fn manifest(&self) -> Result<Option<StateSnapshotManifest>> {
+        let Some(epoch) = self.latest_epoch()? else {
+            return Ok(None);
+        };
+        Ok(self
+            .snapshot(epoch)?
+            .map(|snapshot| snapshot.manifest.clone()))
+    }
+
+    fn chunk(
+        &self,
+        epoch: EpochId,
+        index: usize,
+        offset: u64,
+        length: u64,
+    ) -> Result<Option<Vec<u8>>> {
+        let Some(snapshot) = self.snapshot(epoch)? else {
+            return Ok(None);
+        };
+        let Some(file_metadata) = snapshot.object_files.get(index) else {
+            return Ok(None);
+        };
+        let file_path = file_metadata.local_file_path(&self.root, &Self::epoch_dir(epoch))?;
+        let mut file = fs::File::open(file_path)?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut bytes = vec![];
+        file.take(length).read_to_end(&mut bytes)?;
+        Ok(Some(bytes))
+    }
+}
+
+/// Inserts the live objects of a state snapshot downloaded from peers into the perpetual tables.
+pub struct PerpetualTablesSnapshotSink {
+    perpetual_db: Arc<AuthorityPerpetualTables>,
+    indirect_objects_threshold: usize,
+}
+
+impl PerpetualTablesSnapshotSink {
+    pub fn new(
+        perpetual_db: Arc<AuthorityPerpetualTables>,
+        indirect_objects_threshold: usize,
+    ) -> Self {
+        Self {
+            perpetual_db,
+            indirect_objects_threshold,
+        }
+    }
+}
+
+impl StateSnapshotSink for PerpetualTablesSnapshotSink {
+    type Chunk = (Vec<LiveObject>, [u8; 32]);
+
+    fn decode(
+        &self,
+        info: &StateSnapshotChunkInfo,
+        bytes: Vec<u8>,
+    ) -> Result<(Self::Chunk, Accumulator)> {
+        let file_metadata: FileMetadata = bcs::from_bytes(&info.metadata)?;
+        if file_metadata.file_type != FileType::Object {
+            return Err(anyhow!(
+                "Unexpected file type: {:?}",
+                file_metadata.file_type
+            ));
+        }
+        let objects: Vec<_> = LiveObjectIter::new(&file_metadata, Bytes::from(bytes))?.collect();
+        let mut accumulator = Accumulator::default();
+        let mut hasher = Sha3_256::default();
+        for object in &objects {
+            let digest = object.object_reference().2;
+            accumulator.insert(digest);
+            hasher.update(digest.inner());
+        }
+        Ok(((objects, hasher.finalize().digest), accumulator))
+    }
+
+    fn insert(&self, (objects, sha3_digest): Self::Chunk) -> Result<()> {
+        AuthorityStore::bulk_insert_live_objects(
+            &self.perpetual_db,
+            objects.into_iter(),
+            self.indirect_objects_threshold,
+            &sha3_digest,
+        )?;
+        Ok(())
+    }
+}
@@ -490,7 +490,7 @@ impl StateSnapshotReaderV1 {
         Path::from(format!("epoch_{}", self.epoch))
     }
 
-    fn read_manifest(path: PathBuf) -> anyhow::Result<Manifest> {
+    pub(crate) fn read_manifest(path: PathBuf) -> anyhow::Result<Manifest> {
         let manifest_file = File::open(path)?;
         let manifest_file_size = manifest_file.metadata()?.len() as usize;
         let mut manifest_reader = BufReader::new(manifest_file);
@@ -0,0 +1,560 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Verification requests for packages that are not listed in the service's config. Developers
+//! submit a git repository and revision, or a tarball of sources, along with the package ID they
+//! expect it to verify against. Requests are queued and verified one at a time. Job statuses and
+//! the sources of verified packages are persisted to the storage directory so that they survive
+//! restarts.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail};
+use axum::body::Bytes;
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::response::IntoResponse;
+use axum::Json;
+use flate2::read::GzDecoder;
+use hyper::StatusCode;
+use move_core_types::account_address::AccountAddress;
+use move_symbol_pool::Symbol;
+use serde::{Deserialize, Serialize};
+use sui_sdk::types::base_types::ObjectID;
+use tar::{Archive, EntryType};
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+use crate::{
+    validate_git_source, verify_package, AddressLookup, AppState, CloneCommand, ErrorResponse,
+    Network, NetworkLookup, RequestsConfig, SourceInfo, SourceLookup,
+};
+
+const JOBS_DIR: &str = "jobs";
+const SOURCES_DIR: &str = "sources";
+const UPLOADS_DIR: &str = "uploads";
+const WORK_DIR: &str = "work";
+
+/// Limits on the uploaded sources once decompressed, so that a small upload can't fill the disk.
+const MAX_EXTRACTED_BYTES: u64 = 64 << 20;
+const MAX_EXTRACTED_ENTRIES: usize = 10_000;
+
+pub type JobId = u64;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum JobSource {
+    /// Sources at `revision` (a branch, tag or commit) of a git repository.
+    Git {
+        repository: String,
+        revision: String,
+    },
+    /// Sources uploaded as a gzipped tarball.
+    Tarball,
+}
+
+impl JobSource {
+    /// Reject sources that can't be fetched safely.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            JobSource::Git {
+                repository,
+                revision,
+            } => validate_git_source(repository, revision),
+            JobSource::Tarball => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Verified,
+    Failed { error: String },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Job {
+    pub id: JobId,
+    pub network: Network,
+    pub package_id: ObjectID,
+    pub source: JobSource,
+    /// Path of the package, relative to the root of the repository or tarball.
+    pub path: String,
+    #[serde(flatten)]
+    pub status: JobStatus,
+}
+
+/// Body of a request to verify sources from a git repository.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GitVerificationRequest {
+    #[serde(default)]
+    pub network: Network,
+    pub package_id: ObjectID,
+    pub repository: String,
+    pub revision: String,
+    #[serde(default)]
+    pub path: String,
+}
+
+/// Query parameters of a request to verify sources from a tarball, which is the request's body.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TarballVerificationRequest {
+    #[serde(default)]
+    pub network: Network,
+    pub package_id: ObjectID,
+    #[serde(default)]
+    pub path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SubmitResponse {
+    pub job_id: JobId,
+}
+
+pub struct JobQueue {
+    storage_dir: PathBuf,
+    max_job_duration: Duration,
+    jobs: RwLock<BTreeMap<JobId, Job>>,
+    sender: mpsc::Sender<JobId>,
+}
+
+impl JobQueue {
+    /// Load the jobs persisted under the storage directory. Jobs that had not finished when the
+    /// service stopped are marked as failed. The returned receiver should be passed to
+    /// [`run_jobs`].
+    pub fn new(config: &RequestsConfig) -> anyhow::Result<(Arc<Self>, mpsc::Receiver<JobId>)> {
+        let storage_dir = config.storage_dir.clone();
+        fs::create_dir_all(storage_dir.join(JOBS_DIR))?;
+        fs::create_dir_all(storage_dir.join(UPLOADS_DIR))?;
+
+        let mut jobs = BTreeMap::new();
+        for entry in fs::read_dir(storage_dir.join(JOBS_DIR))? {
+            let path = entry?.path();
+            // Skip the temporary files of jobs that were being persisted.
+            if path.extension().map_or(true, |ext| ext != "json") {
+                continue;
+            }
+            let mut job: Job = serde_json::from_slice(&fs::read(path)?)?;
+            if matches!(job.status, JobStatus::Queued | JobStatus::Running) {
+                job.status = JobStatus::Failed {
+                    error: "Service restarted before verification finished".to_string(),
+                };
+                persist_job(&storage_dir, &job)?;
+            }
+            jobs.insert(job.id, job);
+        }
+
+        let (sender, receiver) = mpsc::channel(config.max_queued_jobs);
+        let queue = Self {
+            storage_dir,
+            max_job_duration: Duration::from_secs(config.max_job_duration_secs),
+            jobs: RwLock::new(jobs),
+            sender,
+        };
+
+        Ok((Arc::new(queue), receiver))
+    }
+
+    /// Queue a job to verify the package at `path` in `source` against `package_id`. `tarball`
+    /// holds the uploaded sources for [`JobSource::Tarball`] jobs.
+    pub fn submit(
+        &self,
+        network: Network,
+        package_id: ObjectID,
+        source: JobSource,
+        path: String,
+        tarball: Option<Bytes>,
+    ) -> anyhow::Result<JobId> {
+        let mut jobs = self.jobs.write().unwrap();
+        let id = jobs.keys().next_back().map_or(0, |id| id + 1);
+        let permit = self
+            .sender
+            .try_reserve()
+            .map_err(|_| anyhow!("Too many verification requests queued, try again later"))?;
+
+        if let Some(tarball) = tarball {
+            fs::write(self.upload_path(id), tarball)?;
+        }
+
+        let job = Job {
+            id,
+            network,
+            package_id,
+            source,
+            path,
+            status: JobStatus::Queued,
+        };
+
+        persist_job(&self.storage_dir, &job)?;
+        jobs.insert(id, job);
+        permit.send(id);
+        Ok(id)
+    }
+
+    pub fn job(&self, id: JobId) -> Option<Job> {
+        self.jobs.read().unwrap().get(&id).cloned()
+    }
+
+    fn set_status(&self, id: JobId, status: JobStatus) -> anyhow::Result<()> {
+        let mut jobs = self.jobs.write().unwrap();
+        let job = jobs
+            .get_mut(&id)
+            .ok_or_else(|| anyhow!("Unknown job {id}"))?;
+        job.status = status;
+        persist_job(&self.storage_dir, job)
+    }
+
+    /// Fetch, build and verify the sources for `job`, returning the persisted sources if they
+    /// match the package on-chain. Jobs taking longer than the configured duration are failed,
+    /// and the git commands they were running are killed.
+    async fn verify(&self, job: &Job) -> anyhow::Result<AddressLookup> {
+        let work_dir = self.storage_dir.join(WORK_DIR).join(job.id.to_string());
+        if work_dir.exists() {
+            fs::remove_dir_all(&work_dir)?;
+        }
+        fs::create_dir_all(&work_dir)?;
+
+        let result = tokio::time::timeout(self.max_job_duration, self.verify_in(job, &work_dir))
+            .await
+            .unwrap_or_else(|_| {
+                Err(anyhow!(
+                    "Verification did not finish within {}s",
+                    self.max_job_duration.as_secs()
+                ))
+            });
+
+        // Clean up regardless of the outcome: verified sources have been persisted separately.
+        if let Err(e) = fs::remove_dir_all(&work_dir) {
+            error!("Failed to clean up {}: {e}", work_dir.display());
+        }
+        if matches!(job.source, JobSource::Tarball) {
+            let _ = fs::remove_file(self.upload_path(job.id));
+        }
+
+        result
+    }
+
+    async fn verify_in(&self, job: &Job, work_dir: &Path) -> anyhow::Result<AddressLookup> {
+        match &job.source {
+            JobSource::Git {
+                repository,
+                revision,
+            } => {
+                CloneCommand::at_revision(repository, revision, work_dir)?
+                    .run()
+                    .await?
+            }
+            JobSource::Tarball => extract_tarball(&self.upload_path(job.id), work_dir)?,
+        }
+
+        let (network, lookup) = verify_package(&job.network, work_dir.join(&job.path)).await?;
+        let address = AccountAddress::from(job.package_id);
+        let Some(sources) = lookup.get(&address) else {
+            let published_at: Vec<_> = lookup.keys().map(|a| a.to_hex_literal()).collect();
+            bail!(
+                "Package is published at {}, not {}",
+                published_at.join(", "),
+                job.package_id,
+            );
+        };
+
+        let sources = persist_sources(&self.storage_dir, &network, address, sources)?;
+        Ok(AddressLookup::from([(address, sources)]))
+    }
+
+    fn upload_path(&self, id: JobId) -> PathBuf {
+        self.storage_dir
+            .join(UPLOADS_DIR)
+            .join(format!("{id}.tar.gz"))
+    }
+}
+
+/// Verify queued jobs one at a time, adding the sources of packages that verify to `app_state`.
+pub async fn run_jobs(
+    queue: Arc<JobQueue>,
+    app_state: Arc<RwLock<AppState>>,
+    mut receiver: mpsc::Receiver<JobId>,
+) {
+    while let Some(id) = receiver.recv().await {
+        let Some(job) = queue.job(id) else {
+            continue;
+        };
+
+        if let Err(e) = queue.set_status(id, JobStatus::Running) {
+            error!("Failed to update status of job {id}: {e}");
+        }
+
+        info!("verifying job {id}: {} on {}", job.package_id, job.network);
+        let status = match queue.verify(&job).await {
+            Ok(sources) => {
+                let mut app_state = app_state.write().unwrap();
+                app_state
+                    .sources
+                    .entry(job.network.clone())
+                    .or_default()
+                    .extend(sources);
+                JobStatus::Verified
+            }
+            Err(e) => JobStatus::Failed {
+                error: format!("{e:#}"),
+            },
+        };
+
+        info!("job {id} finished: {status:?}");
+        if let Err(e) = queue.set_status(id, status) {
+            error!("Failed to update status of job {id}: {e}");
+        }
+    }
+}
+
+/// Load the sources of packages verified by previous jobs.
+pub fn load_verified_sources(storage_dir: &Path) -> anyhow::Result<NetworkLookup> {
+    let mut lookup = NetworkLookup::new();
+    let sources_dir = storage_dir.join(SOURCES_DIR);
+    if !sources_dir.exists() {
+        return Ok(lookup);
+    }
+
+    for network_dir in fs::read_dir(sources_dir)? {
+        let network_dir = network_dir?.path();
+        let network: Network = file_name(&network_dir)?.parse()?;
+        let address_lookup = lookup.entry(network).or_default();
+
+        for address_dir in fs::read_dir(network_dir)? {
+            let address_dir = address_dir?.path();
+            let address = AccountAddress::from_hex_literal(file_name(&address_dir)?)?;
+            let source_lookup = address_lookup.entry(address).or_default();
+
+            for module in fs::read_dir(address_dir)? {
+                let path = module?.path();
+                let name = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .ok_or_else(|| anyhow!("Invalid source file {}", path.display()))?;
+                let source = Some(fs::read_to_string(&path)?);
+                source_lookup.insert(Symbol::from(name), SourceInfo { path, source });
+            }
+        }
+    }
+
+    Ok(lookup)
+}
+
+pub(crate) async fn verify_git_route(
+    State(app_state): State<Arc<RwLock<AppState>>>,
+    Json(GitVerificationRequest {
+        network,
+        package_id,
+        repository,
+        revision,
+        path,
+    }): Json<GitVerificationRequest>,
+) -> impl IntoResponse {
+    let source = JobSource::Git {
+        repository,
+        revision,
+    };
+    submit(&app_state, network, package_id, source, path, None)
+}
+
+pub(crate) async fn verify_tarball_route(
+    State(app_state): State<Arc<RwLock<AppState>>>,
+    Query(TarballVerificationRequest {
+        network,
+        package_id,
+        path,
+    }): Query<TarballVerificationRequest>,
+    tarball: Bytes,
+) -> impl IntoResponse {
+    submit(
+        &app_state,
+        network,
+        package_id,
+        JobSource::Tarball,
+        path,
+        Some(tarball),
+    )
+}
+
+pub(crate) async fn job_route(
+    State(app_state): State<Arc<RwLock<AppState>>>,
+    UrlPath(id): UrlPath<JobId>,
+) -> impl IntoResponse {
+    let jobs = app_state.read().unwrap().jobs.clone();
+    let Some(jobs) = jobs else {
+        return not_accepting_requests();
+    };
+
+    match jobs.job(id) {
+        Some(job) => (StatusCode::OK, Json(job).into_response()),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("No verification job with ID {id}"),
+            })
+            .into_response(),
+        ),
+    }
+}
+
+fn submit(
+    app_state: &RwLock<AppState>,
+    network: Network,
+    package_id: ObjectID,
+    source: JobSource,
+    path: String,
+    tarball: Option<Bytes>,
+) -> (StatusCode, axum::response::Response) {
+    let jobs = app_state.read().unwrap().jobs.clone();
+    let Some(jobs) = jobs else {
+        return not_accepting_requests();
+    };
+
+    if let Err(e) = source.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            })
+            .into_response(),
+        );
+    }
+
+    // Packages must be inside the checked out repository or the extracted tarball.
+    if !Path::new(&path)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        let error = format!("Invalid package path {path}");
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse { error }).into_response(),
+        );
+    }
+
+    match jobs.submit(network, package_id, source, path, tarball) {
+        Ok(job_id) => (
+            StatusCode::ACCEPTED,
+            Json(SubmitResponse { job_id }).into_response(),
+        ),
+        Err(e) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ErrorResponse {
+                error: e.to_string(),
+            })
+            .into_response(),
+        ),
+    }
+}
+
+fn not_accepting_requests() -> (StatusCode, axum::response::Response) {
+    (
+        StatusCode::NOT_FOUND,
+        Json(ErrorResponse {
+            error: "This service does not accept verification requests".to_string(),
+        })
+        .into_response(),
+    )
+}
+
+fn persist_job(storage_dir: &Path, job: &Job) -> anyhow::Result<()> {
+    let path = storage_dir.join(JOBS_DIR).join(format!("{}.json", job.id));
+    // Replace the previous status atomically, so that a crash doesn't leave a truncated job.
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_vec_pretty(job)?)?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+/// Copy verified sources into the storage directory, so they can be served after the job's
+/// working directory is cleaned up, and loaded again on restart.
+fn persist_sources(
+    storage_dir: &Path,
+    network: &Network,
+    address: AccountAddress,
+    sources: &SourceLookup,
+) -> anyhow::Result<SourceLookup> {
+    let dir = storage_dir
+        .join(SOURCES_DIR)
+        .join(network.to_string())
+        .join(address.to_hex_literal());
+    fs::create_dir_all(&dir)?;
+
+    let mut persisted = SourceLookup::new();
+    for (name, SourceInfo { source, .. }) in sources {
+        let source = source
+            .clone()
+            .ok_or_else(|| anyhow!("Missing source for module {name}"))?;
+        let path = dir.join(format!("{name}.move"));
+        fs::write(&path, &source)?;
+        persisted.insert(
+            *name,
+            SourceInfo {
+                path,
+                source: Some(source),
+            },
+        );
+    }
+
+    Ok(persisted)
+}
+
+/// Extract the gzipped tarball of uploaded sources into `dest`. Only regular files and
+/// directories within `dest` are extracted: entries with absolute or `..` paths and links are
+/// rejected, and so are tarballs over `MAX_EXTRACTED_BYTES` or `MAX_EXTRACTED_ENTRIES`.
+fn extract_tarball(tarball: &Path, dest: &Path) -> anyhow::Result<()> {
+    // Also bounds the headers, which the sizes of the entries don't account for.
+    let decoder = GzDecoder::new(fs::File::open(tarball)?).take(MAX_EXTRACTED_BYTES);
+    let mut archive = Archive::new(decoder);
+    let entries = archive
+        .entries()
+        .map_err(|e| anyhow!("Error extracting uploaded sources: {e}"))?;
+    let mut extracted_bytes = 0;
+    for (i, entry) in entries.enumerate() {
+        if i >= MAX_EXTRACTED_ENTRIES {
+            bail!("Uploaded sources have more than {MAX_EXTRACTED_ENTRIES} entries");
+        }
+        let mut entry = entry.map_err(|e| anyhow!("Error extracting uploaded sources: {e}"))?;
+        extracted_bytes += entry.size();
+        if extracted_bytes > MAX_EXTRACTED_BYTES {
+            bail!("Uploaded sources are larger than {MAX_EXTRACTED_BYTES} bytes once extracted");
+        }
+        let path = entry.path()?.into_owned();
+        if !path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            bail!("Invalid path {} in uploaded sources", path.display());
+        }
+
+        let target = dest.join(&path);
+        match entry.header().entry_type() {
+            EntryType::Directory => fs::create_dir_all(&target)?,
+            EntryType::Regular => {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                entry.unpack(&target)?;
+            }
+            EntryType::XGlobalHeader => {}
+            entry_type => bail!(
+                "Unsupported entry {} of type {entry_type:?} in uploaded sources",
+                path.display()
+            ),
+        }
+    }
+    Ok(())
+}
+
+fn file_name(path: &Path) -> anyhow::Result<&str> {
+    path.file_name()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow!("Invalid path {}", path.display()))
+}
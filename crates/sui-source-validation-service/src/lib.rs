@@ -6,15 +6,17 @@ use std::collections::BTreeMap;
 use std::fmt;
 use std::net::TcpListener;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
-use std::{ffi::OsString, fs, path::Path, process::Command};
+use std::{ffi::OsString, fs, path::Path};
+use tokio::process::Command;
 use tokio::sync::oneshot::Sender;
 
 use anyhow::{anyhow, bail};
 use axum::extract::{Query, State};
 use axum::response::{IntoResponse, Response};
-use axum::routing::{get, IntoMakeService};
+use axum::routing::{get, post, IntoMakeService};
 use axum::Extension;
 use axum::{Json, Router, Server};
 use hyper::http::{HeaderName, HeaderValue, Method};
@@ -41,6 +43,10 @@ use sui_sdk::types::base_types::ObjectID;
 use sui_sdk::SuiClientBuilder;
 use sui_source_validation::{BytecodeSourceVerifier, SourceMode};
 
+use crate::jobs::JobQueue;
+
+pub mod jobs;
+
 pub const HOST_PORT_ENV: &str = "HOST_PORT";
 pub const SUI_SOURCE_VALIDATION_VERSION_HEADER: &str = "x-sui-source-validation-version";
 pub const SUI_SOURCE_VALIDATION_VERSION: &str = "0.1";
@@ -70,6 +76,22 @@ pub fn host_port() -> String {
 #[derive(Clone, Deserialize, Debug)]
 pub struct Config {
     pub packages: Vec<PackageSource>,
+    /// Accept verification requests for packages not listed in `packages` through the API.
+    #[serde(default)]
+    pub requests: Option<RequestsConfig>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct RequestsConfig {
+    /// Directory that job statuses, uploaded sources, and the sources of verified packages are
+    /// persisted to.
+    pub storage_dir: PathBuf,
+    /// Number of requests that can wait to be verified before new requests are turned away.
+    #[serde(default = "default_max_queued_jobs")]
+    pub max_queued_jobs: usize,
+    /// Time a request has to fetch, build and verify its sources before it is failed.
+    #[serde(default = "default_max_job_duration_secs")]
+    pub max_job_duration_secs: u64,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -108,7 +130,7 @@ pub struct SourceInfo {
     pub source: Option<String>,
 }
 
-#[derive(Eq, PartialEq, Clone, Default, Deserialize, Debug, Ord, PartialOrd)]
+#[derive(Eq, PartialEq, Clone, Default, Serialize, Deserialize, Debug, Ord, PartialOrd)]
 #[serde(rename_all = "lowercase")]
 pub enum Network {
     #[default]
@@ -137,6 +159,20 @@ impl fmt::Display for Network {
     }
 }
 
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "mainnet" => Network::Mainnet,
+            "testnet" => Network::Testnet,
+            "devnet" => Network::Devnet,
+            "localnet" => Network::Localnet,
+            _ => bail!("Unknown network {s}"),
+        })
+    }
+}
+
 /// Map module name to verified source info.
 pub type SourceLookup = BTreeMap<Symbol, SourceInfo>;
 /// Map addresses to module names and sources.
@@ -159,7 +195,9 @@ pub async fn verify_package(
         print_diags_to_stderr: false,
         lint: false,
     };
-    let compiled_package = build_config.build(package_path.as_ref().to_path_buf())?;
+    // Building is blocking, and run apart so that callers can time out while it is running.
+    let path = package_path.as_ref().to_path_buf();
+    let compiled_package = tokio::task::spawn_blocking(move || build_config.build(path)).await??;
 
     let network_url = match network {
         Network::Mainnet => MAINNET_URL,
@@ -201,6 +239,14 @@ pub async fn verify_package(
     Ok((network.clone(), address_map))
 }
 
+fn default_max_queued_jobs() -> usize {
+    64
+}
+
+fn default_max_job_duration_secs() -> u64 {
+    600
+}
+
 pub fn parse_config(config_path: impl AsRef<Path>) -> anyhow::Result<Config> {
     let contents = fs::read_to_string(config_path)?;
     Ok(toml::from_str(&contents)?)
@@ -217,6 +263,34 @@ pub fn repo_name_from_url(url: &str) -> anyhow::Result<String> {
     Ok(repo_name.to_string())
 }
 
+/// Check that a repository and revision submitted by a user are safe to pass to git: the
+/// repository must be fetched over HTTPS, and the revision must be a commit hash or a valid ref
+/// name, so that neither can be taken for an option or a local path.
+pub fn validate_git_source(repo_url: &str, revision: &str) -> anyhow::Result<()> {
+    let url =
+        Url::parse(repo_url).map_err(|e| anyhow!("Invalid repository URL {repo_url}: {e}"))?;
+    if url.scheme() != "https" || url.host_str().is_none() || repo_url.starts_with('-') {
+        bail!("Invalid repository URL {repo_url}: only https:// repositories are supported");
+    }
+
+    let valid_revision = !revision.is_empty()
+        && revision.len() <= 255
+        && revision
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '/' | '-'))
+        && !revision.starts_with('-')
+        && !revision.ends_with(".lock")
+        && revision
+            .split('/')
+            .all(|component| !component.is_empty() && !component.starts_with('.'))
+        && !revision.contains("..")
+        && !revision.ends_with('.');
+    if !valid_revision {
+        bail!("Invalid revision {revision}: expected a commit hash, branch or tag name");
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 /// Represents a sequence of git commands to clone a repository and sparsely checkout Move packages within.
 pub struct CloneCommand {
@@ -277,15 +351,50 @@ impl CloneCommand {
         })
     }
 
+    /// Fetch a single `revision` (a branch, tag or commit) of the repository at `repo_url`, and
+    /// check it out at `dest`. Both are checked with [`validate_git_source`].
+    pub fn at_revision(
+        repo_url: &str,
+        revision: &str,
+        dest: &Path,
+    ) -> anyhow::Result<CloneCommand> {
+        validate_git_source(repo_url, revision)?;
+        let dest = dest.as_os_str().to_os_string();
+        let args = vec![
+            vec!["init".into(), "--quiet".into(), dest.clone()],
+            vec![
+                "-C".into(),
+                dest.clone(),
+                "fetch".into(),
+                "--depth=1".into(),
+                "--".into(),
+                repo_url.into(),
+                revision.into(),
+            ],
+            vec!["-C".into(), dest, "checkout".into(), "FETCH_HEAD".into()],
+        ];
+
+        Ok(Self {
+            args,
+            repo_url: repo_url.to_string(),
+        })
+    }
+
+    /// Run the git commands. They are killed if the returned future is dropped before they exit.
     pub async fn run(&self) -> anyhow::Result<()> {
         for args in &self.args {
-            let result = Command::new("git").args(args).output().map_err(|_| {
-                anyhow!(
-                    "Error cloning {} with command `git {:#?}`",
-                    self.repo_url,
-                    args
-                )
-            })?;
+            let result = Command::new("git")
+                .args(args)
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|_| {
+                    anyhow!(
+                        "Error cloning {} with command `git {:#?}`",
+                        self.repo_url,
+                        args
+                    )
+                })?;
             if !result.status.success() {
                 bail!(
                     "Nonzero exit status when cloning {} with command `git {:#?}`. \
@@ -456,6 +565,8 @@ pub async fn watch_for_upgrades(
 pub struct AppState {
     pub sources: NetworkLookup,
     pub metrics: Option<SourceServiceMetrics>,
+    /// Verification requests, if the service accepts them.
+    pub jobs: Option<Arc<JobQueue>>,
 }
 
 pub fn serve(
@@ -464,11 +575,14 @@ pub fn serve(
     let app = Router::new()
         .route("/api", get(api_route))
         .route("/api/list", get(list_route))
+        .route("/api/verify", post(jobs::verify_git_route))
+        .route("/api/verify/tarball", post(jobs::verify_tarball_route))
+        .route("/api/verify/:id", get(jobs::job_route))
         .layer(
             ServiceBuilder::new()
                 .layer(
                     tower_http::cors::CorsLayer::new()
-                        .allow_methods([Method::GET])
+                        .allow_methods([Method::GET, Method::POST])
                         .allow_origin(tower_http::cors::Any),
                 )
                 .layer(middleware::from_fn(check_version_header)),
@@ -9,6 +9,7 @@ use clap::Parser;
 
 use telemetry_subscribers::TelemetryConfig;
 
+use sui_source_validation_service::jobs::{load_verified_sources, run_jobs, JobQueue};
 use sui_source_validation_service::{
     host_port, initialize, parse_config, serve, start_prometheus_server, watch_for_upgrades,
     AppState, DirectorySource, Network, PackageSource, RepositorySource, SourceServiceMetrics,
@@ -40,9 +41,17 @@ pub async fn main() -> anyhow::Result<()> {
     let package_config = parse_config(args.config_path)?;
     let tmp_dir = tempfile::tempdir()?;
     let start = tokio::time::Instant::now();
-    let sources = initialize(&package_config, tmp_dir.path()).await?;
+    let mut sources = initialize(&package_config, tmp_dir.path()).await?;
     info!("verification complete in {:?}", start.elapsed());
 
+    let mut jobs = None;
+    if let Some(requests) = &package_config.requests {
+        for (network, lookup) in load_verified_sources(&requests.storage_dir)? {
+            sources.entry(network).or_default().extend(lookup);
+        }
+        jobs = Some(JobQueue::new(requests)?);
+    }
+
     let metrics_listener = std::net::TcpListener::bind(METRICS_HOST_PORT)?;
     let registry_service = start_prometheus_server(metrics_listener);
     let prometheus_registry = registry_service.default_registry();
@@ -51,8 +60,17 @@ pub async fn main() -> anyhow::Result<()> {
     let app_state = Arc::new(RwLock::new(AppState {
         sources,
         metrics: Some(metrics),
+        jobs: jobs.as_ref().map(|(queue, _)| queue.clone()),
     }));
     let mut threads = vec![];
+    if let Some((queue, receiver)) = jobs {
+        let app_state_copy = app_state.clone();
+        let runner = tokio::spawn(async move {
+            run_jobs(queue, app_state_copy, receiver).await;
+            Ok(())
+        });
+        threads.push(runner);
+    }
     let networks_to_watch = vec![
         Network::Mainnet,
         Network::Testnet,
@@ -8,6 +8,7 @@ use std::io::Read;
 use std::os::unix::fs::FileExt;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use sui::client_commands::{SuiClientCommandResult, SuiClientCommands};
 use sui_json_rpc_types::{SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI};
 use sui_move_build::{BuildConfig, SuiPackageHooks};
@@ -18,15 +19,21 @@ use sui_sdk::types::base_types::ObjectID;
 use sui_sdk::types::object::Owner;
 use sui_sdk::types::transaction::TEST_ONLY_GAS_UNIT_FOR_PUBLISH;
 use sui_sdk::wallet_context::WalletContext;
+use tar::{Builder, EntryType, Header};
 use tokio::sync::oneshot;
 
+use axum::body::Bytes;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use move_core_types::account_address::AccountAddress;
 use move_symbol_pool::Symbol;
+use sui_source_validation_service::jobs::{run_jobs, Job, JobQueue, JobSource, JobStatus};
 use sui_source_validation_service::{
-    host_port, initialize, serve, start_prometheus_server, verify_packages, watch_for_upgrades,
-    AddressLookup, AppState, CloneCommand, Config, DirectorySource, ErrorResponse, Network,
-    NetworkLookup, Package, PackageSource, RepositorySource, SourceInfo, SourceLookup,
-    SourceResponse, SourceServiceMetrics, METRICS_HOST_PORT, SUI_SOURCE_VALIDATION_VERSION_HEADER,
+    host_port, initialize, serve, start_prometheus_server, validate_git_source, verify_packages,
+    watch_for_upgrades, AddressLookup, AppState, CloneCommand, Config, DirectorySource,
+    ErrorResponse, Network, NetworkLookup, Package, PackageSource, RepositorySource,
+    RequestsConfig, SourceInfo, SourceLookup, SourceResponse, SourceServiceMetrics,
+    METRICS_HOST_PORT, SUI_SOURCE_VALIDATION_VERSION_HEADER,
 };
 use test_cluster::TestClusterBuilder;
 
@@ -86,6 +93,7 @@ async fn test_end_to_end() -> anyhow::Result<()> {
             }],
             network: Some(Network::Localnet),
         })],
+        requests: None,
     };
     // Start watching for upgrades.
     let mut sources = NetworkLookup::new();
@@ -93,6 +101,7 @@ async fn test_end_to_end() -> anyhow::Result<()> {
     let app_state = Arc::new(RwLock::new(AppState {
         sources,
         metrics: None,
+        jobs: None,
     }));
     let app_state_ref = app_state.clone();
     let (tx, rx) = oneshot::channel();
@@ -135,6 +144,7 @@ async fn test_end_to_end() -> anyhow::Result<()> {
             }],
             network: Some(Network::Localnet),
         })],
+        requests: None,
     };
 
     let fixtures = tempfile::tempdir()?;
@@ -262,7 +272,10 @@ fn copy_with_published_at_manifest(
 
 #[tokio::test]
 async fn test_api_route() -> anyhow::Result<()> {
-    let config = Config { packages: vec![] };
+    let config = Config {
+        packages: vec![],
+        requests: None,
+    };
     let tmp_dir = tempfile::tempdir()?;
     initialize(&config, tmp_dir.path()).await?;
 
@@ -296,6 +309,7 @@ async fn test_api_route() -> anyhow::Result<()> {
     let app_state = Arc::new(RwLock::new(AppState {
         sources,
         metrics: None,
+        jobs: None,
     }));
     tokio::spawn(serve(app_state).expect("Cannot start service."));
 
@@ -425,6 +439,7 @@ fn test_parse_package_config() -> anyhow::Result<()> {
                     },
                 ),
             ],
+            requests: None,
         }"#]];
     expect.assert_eq(&format!("{:#?}", config));
     Ok(())
@@ -482,3 +497,288 @@ fn test_clone_command() -> anyhow::Result<()> {
     expect.assert_eq(&format!("{:#?}", command));
     Ok(())
 }
+
+#[test]
+fn test_clone_command_at_revision() -> anyhow::Result<()> {
+    let command = CloneCommand::at_revision(
+        "https://github.com/user/repo",
+        "abc123",
+        PathBuf::from("/foo/0").as_path(),
+    )?;
+    let expect = expect![
+        r#"CloneCommand {
+    args: [
+        [
+            "init",
+            "--quiet",
+            "/foo/0",
+        ],
+        [
+            "-C",
+            "/foo/0",
+            "fetch",
+            "--depth=1",
+            "--",
+            "https://github.com/user/repo",
+            "abc123",
+        ],
+        [
+            "-C",
+            "/foo/0",
+            "checkout",
+            "FETCH_HEAD",
+        ],
+    ],
+    repo_url: "https://github.com/user/repo",
+}"#
+    ];
+    expect.assert_eq(&format!("{:#?}", command));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_job_queue() -> anyhow::Result<()> {
+    let storage = tempfile::tempdir()?;
+    let config = RequestsConfig {
+        storage_dir: storage.path().to_path_buf(),
+        max_queued_jobs: 1,
+        max_job_duration_secs: 600,
+    };
+
+    let (queue, _receiver) = JobQueue::new(&config)?;
+    let source = JobSource::Git {
+        repository: "https://github.com/user/repo".into(),
+        revision: "main".into(),
+    };
+    let package_id = ObjectID::from_hex_literal("0x1234")?;
+    let id = queue.submit(
+        Network::Localnet,
+        package_id,
+        source.clone(),
+        "a".into(),
+        None,
+    )?;
+    let Some(Job { status, .. }) = queue.job(id) else {
+        panic!("Job {id} not found");
+    };
+    assert_eq!(status, JobStatus::Queued);
+
+    // The queue only has room for one job.
+    assert!(queue
+        .submit(Network::Localnet, package_id, source, "b".into(), None)
+        .is_err());
+
+    // Jobs that had not finished are failed when the service restarts.
+    drop(queue);
+    let (queue, _receiver) = JobQueue::new(&config)?;
+    let Some(Job { status, path, .. }) = queue.job(id) else {
+        panic!("Job {id} not found after restart");
+    };
+    assert_eq!(path, "a");
+    assert!(matches!(status, JobStatus::Failed { .. }));
+    Ok(())
+}
+
+#[test]
+fn test_validate_git_source() {
+    for (repository, revision) in [
+        ("https://github.com/user/repo", "main"),
+        ("https://github.com/user/repo", "releases/v1.2.0"),
+        (
+            "https://github.com/user/repo",
+            "9fceb02d0ae598e95dc970b74767f19372d61af8",
+        ),
+    ] {
+        assert!(
+            validate_git_source(repository, revision).is_ok(),
+            "{repository} at {revision} should be accepted"
+        );
+    }
+
+    for (repository, revision) in [
+        ("--upload-pack=touch /tmp/pwned", "main"),
+        ("file:///home/user/repo", "main"),
+        ("/home/user/repo", "main"),
+        ("ssh://git@github.com/user/repo", "main"),
+        ("ext::sh -c touch% /tmp/pwned", "main"),
+        (
+            "https://github.com/user/repo",
+            "--upload-pack=touch /tmp/pwned",
+        ),
+        ("https://github.com/user/repo", "-main"),
+        ("https://github.com/user/repo", "main/../../etc"),
+        ("https://github.com/user/repo", "main branch"),
+        ("https://github.com/user/repo", ""),
+    ] {
+        assert!(
+            validate_git_source(repository, revision).is_err(),
+            "{repository} at {revision} should be rejected"
+        );
+    }
+}
+
+/// A gzipped tarball holding a single entry. Its path and link name are written to the header
+/// as is, bypassing the checks `tar::Builder` makes when creating archives.
+fn raw_tarball(path: &str, entry_type: EntryType, link_name: &str, contents: &[u8]) -> Bytes {
+    let mut header = Header::new_gnu();
+    header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+    header.as_old_mut().linkname[..link_name.len()].copy_from_slice(link_name.as_bytes());
+    header.set_entry_type(entry_type);
+    header.set_size(contents.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+
+    let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    builder.append(&header, contents).unwrap();
+    Bytes::from(builder.into_inner().unwrap().finish().unwrap())
+}
+
+/// A gzipped tarball of `count` files of `size` zeros.
+fn tarball_bomb(count: usize, size: u64) -> Bytes {
+    let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for i in 0..count {
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Regular);
+        header.set_size(size);
+        header.set_mode(0o644);
+        builder
+            .append_data(
+                &mut header,
+                format!("sources/{i}.move"),
+                std::io::repeat(0).take(size),
+            )
+            .unwrap();
+    }
+    Bytes::from(builder.into_inner().unwrap().finish().unwrap())
+}
+
+#[tokio::test]
+async fn test_malicious_jobs() -> anyhow::Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let config = RequestsConfig {
+        storage_dir: tmp_dir.path().join("storage"),
+        max_queued_jobs: 10,
+        max_job_duration_secs: 600,
+    };
+    let (queue, receiver) = JobQueue::new(&config)?;
+    let app_state = Arc::new(RwLock::new(AppState {
+        sources: NetworkLookup::new(),
+        metrics: None,
+        jobs: Some(queue.clone()),
+    }));
+    tokio::spawn(run_jobs(queue.clone(), app_state, receiver));
+
+    let package_id = ObjectID::from_hex_literal("0x1234")?;
+    let pwned = tmp_dir.path().join("pwned");
+    let mut job_ids = vec![];
+
+    // Sources are rejected when the API receives them, but jobs that are already queued are
+    // checked again before running git.
+    for (repository, revision) in [
+        (format!("--upload-pack=touch {}", pwned.display()), "main"),
+        (
+            "https://github.com/user/repo".to_string(),
+            "--upload-pack=touch /tmp/pwned",
+        ),
+        (format!("file://{}", tmp_dir.path().display()), "main"),
+    ] {
+        let source = JobSource::Git {
+            repository,
+            revision: revision.to_string(),
+        };
+        assert!(source.validate().is_err());
+        job_ids.push(queue.submit(Network::Localnet, package_id, source, "".into(), None)?);
+    }
+
+    // Uploaded tarballs can't write outside of the job's working directory, whose parent is the
+    // storage directory's `work` directory.
+    let escaped = tmp_dir.path().join("escaped.move");
+    for tarball in [
+        raw_tarball(
+            "../../../escaped.move",
+            EntryType::Regular,
+            "",
+            b"module escaped {}",
+        ),
+        raw_tarball(
+            escaped.to_str().unwrap(),
+            EntryType::Regular,
+            "",
+            b"module escaped {}",
+        ),
+        raw_tarball("sources", EntryType::Symlink, "/", b""),
+        raw_tarball("sources", EntryType::Link, "/etc/passwd", b""),
+        // Sources that compress well can't fill the disk once extracted.
+        tarball_bomb(1, 65 << 20),
+        tarball_bomb(10_001, 0),
+    ] {
+        job_ids.push(queue.submit(
+            Network::Localnet,
+            package_id,
+            JobSource::Tarball,
+            "".into(),
+            Some(tarball),
+        )?);
+    }
+
+    for id in job_ids {
+        let status = loop {
+            let Some(Job { status, .. }) = queue.job(id) else {
+                panic!("Job {id} not found");
+            };
+            if !matches!(status, JobStatus::Queued | JobStatus::Running) {
+                break status;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        };
+        assert!(
+            matches!(status, JobStatus::Failed { .. }),
+            "Job {id} should fail, got {status:?}"
+        );
+    }
+
+    assert!(!pwned.exists());
+    assert!(!escaped.exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_job_timeout() -> anyhow::Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let config = RequestsConfig {
+        storage_dir: tmp_dir.path().join("storage"),
+        max_queued_jobs: 1,
+        max_job_duration_secs: 0,
+    };
+    let (queue, receiver) = JobQueue::new(&config)?;
+    let app_state = Arc::new(RwLock::new(AppState {
+        sources: NetworkLookup::new(),
+        metrics: None,
+        jobs: Some(queue.clone()),
+    }));
+    tokio::spawn(run_jobs(queue.clone(), app_state, receiver));
+
+    let package_id = ObjectID::from_hex_literal("0x1234")?;
+    let tarball = raw_tarball("sources/a.move", EntryType::Regular, "", b"module a::a {}");
+    let id = queue.submit(
+        Network::Localnet,
+        package_id,
+        JobSource::Tarball,
+        "".into(),
+        Some(tarball),
+    )?;
+    let status = loop {
+        let Some(Job { status, .. }) = queue.job(id) else {
+            panic!("Job {id} not found");
+        };
+        if !matches!(status, JobStatus::Queued | JobStatus::Running) {
+            break status;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    };
+    assert!(
+        matches!(&status, JobStatus::Failed { error } if error.contains("did not finish")),
+        "Job {id} should time out, got {status:?}"
+    );
+    Ok(())
+}
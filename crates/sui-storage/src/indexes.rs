@@ -17,7 +17,6 @@ use prometheus::{register_int_counter_with_registry, IntCounter, Registry};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::BTreeMap;
 use sui_types::execution::DynamicallyLoadedObjectMetadata;
-use tokio::sync::OwnedMutexGuard;
 
 use crate::mutex_table::MutexTable;
 use crate::sharded_lru::ShardedLruCache;
@@ -31,6 +30,7 @@ use sui_types::dynamic_field::{self, DynamicFieldInfo};
 use sui_types::effects::TransactionEvents;
 use sui_types::error::{SuiError, SuiResult, UserInputError};
 use sui_types::inner_temporary_store::TxCoins;
+use sui_types::messages_checkpoint::CheckpointSequenceNumber;
 use sui_types::object::{Object, Owner};
 use sui_types::parse_sui_struct_tag;
 use tokio::task::spawn_blocking;
@@ -45,6 +45,10 @@ use typed_store_derive::DBMapUtils;
 type OwnerIndexKey = (SuiAddress, ObjectID);
 type CoinIndexKey = (SuiAddress, String, ObjectID);
 type DynamicFieldKey = (ObjectID, ObjectID);
+/// Package, module, struct name and type parameters of an object's type, followed by its ID.
+type ObjectTypeKey = (ObjectID, String, String, String, ObjectID);
+type BalanceHistoryKey = (SuiAddress, String, TxSequenceNumber);
+type BalanceCheckpointKey = (SuiAddress, String, CheckpointSequenceNumber);
 type EventId = (TxSequenceNumber, usize);
 type EventIndex = (TransactionEventsDigest, TransactionDigest, u64);
 type AllBalance = HashMap<TypeTag, TotalBalance>;
@@ -52,11 +56,12 @@ type AllBalance = HashMap<TypeTag, TotalBalance>;
 pub const MAX_TX_RANGE_SIZE: u64 = 4096;
 
 pub const MAX_GET_OWNED_OBJECT_SIZE: usize = 256;
+const INDEX_REBUILD_CLEAR_BATCH_SIZE: usize = 10_000;
 const ENV_VAR_COIN_INDEX_BLOCK_CACHE_SIZE_MB: &str = "COIN_INDEX_BLOCK_CACHE_MB";
 const ENV_VAR_DISABLE_INDEX_CACHE: &str = "DISABLE_INDEX_CACHE";
 const ENV_VAR_INVALIDATE_INSTEAD_OF_UPDATE: &str = "INVALIDATE_INSTEAD_OF_UPDATE";
 
-#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TotalBalance {
     pub balance: i128,
     pub num_coins: i64,
@@ -68,6 +73,62 @@ pub struct ObjectIndexChanges {
     pub deleted_dynamic_fields: Vec<DynamicFieldKey>,
     pub new_owners: Vec<(OwnerIndexKey, ObjectInfo)>,
     pub new_dynamic_fields: Vec<(DynamicFieldKey, DynamicFieldInfo)>,
+    pub deleted_object_types: Vec<ObjectTypeKey>,
+    pub new_object_types: Vec<(ObjectTypeKey, ObjectInfo)>,
+}
+
+/// The owners of the coins a transaction changes.
+fn coin_owners(object_index_changes: &ObjectIndexChanges) -> HashSet<SuiAddress> {
+    object_index_changes
+        .deleted_owners
+        .iter()
+        .map(|(owner, _)| *owner)
+        .chain(
+            object_index_changes
+                .new_owners
+                .iter()
+                .map(|((owner, _), _)| *owner),
+        )
+        .collect()
+}
+
+/// The key of an object in the `objects_by_type` index.
+pub fn object_type_key(type_: &StructTag, object_id: ObjectID) -> ObjectTypeKey {
+    (
+        type_.address.into(),
+        type_.module.to_string(),
+        type_.name.to_string(),
+        type_params_key(&type_.type_params),
+        object_id,
+    )
+}
+
+fn type_params_key(type_params: &[TypeTag]) -> String {
+    type_params
+        .iter()
+        .map(|t| t.to_canonical_string(/* with_prefix */ true))
+        .join(",")
+}
+
+/// The balance of an address in a coin type before and after a transaction that changed it.
+///
+/// The change is the one JSON-RPC reports in the `balanceChanges` of the transaction for the
+/// address, as both are computed from the coins the transaction took as input and wrote. Unlike
+/// `balanceChanges`, transactions that only change the number of coins of the address, like
+/// merging coins, are recorded too, with an amount of zero.
+#[derive(Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Debug)]
+pub struct BalanceChangeInfo {
+    pub digest: TransactionDigest,
+    pub previous_balance: TotalBalance,
+    pub balance: TotalBalance,
+}
+
+impl BalanceChangeInfo {
+    /// Amount received by the address in the transaction, negative if it spent more than it
+    /// received.
+    pub fn amount(&self) -> i128 {
+        self.balance.balance - self.previous_balance.balance
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Debug)]
@@ -89,6 +150,50 @@ impl CoinInfo {
     }
 }
 
+/// A set of index tables that can be rebuilt offline, see `IndexStoreTables::reset_index_group`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum IndexGroup {
+    /// `owner_index`
+    Owner,
+    /// `coin_index`
+    Coin,
+    /// `dynamic_field_index`
+    DynamicField,
+    /// `objects_by_type`
+    ObjectsByType,
+    /// The transaction and event indexes, which are keyed by transaction sequence numbers and so
+    /// have to be rebuilt together. The balance history and the versions of loaded child objects,
+    /// which can't be rebuilt, are cleared.
+    Transactions,
+}
+
+impl IndexGroup {
+    pub const ALL: [IndexGroup; 5] = [
+        IndexGroup::Owner,
+        IndexGroup::Coin,
+        IndexGroup::DynamicField,
+        IndexGroup::ObjectsByType,
+        IndexGroup::Transactions,
+    ];
+
+    /// Whether the group indexes the live object set, as opposed to the transaction history.
+    pub fn is_object_index(&self) -> bool {
+        !matches!(self, IndexGroup::Transactions)
+    }
+}
+
+/// The data of an executed transaction that the transaction and event indexes are built from.
+pub struct IndexedTransaction {
+    pub sequence: TxSequenceNumber,
+    pub digest: TransactionDigest,
+    pub sender: SuiAddress,
+    pub input_objects: Vec<ObjectID>,
+    pub mutated_objects: Vec<(ObjectRef, Owner)>,
+    pub move_functions: Vec<(ObjectID, Identifier, Identifier)>,
+    pub events: TransactionEvents,
+    pub timestamp_ms: u64,
+}
+
 pub struct IndexStoreMetrics {
     balance_lookup_from_db: IntCounter,
     balance_lookup_from_total: IntCounter,
@@ -135,7 +240,6 @@ pub struct IndexStoreCaches {
 
 #[derive(Default)]
 pub struct IndexStoreCacheUpdates {
-    _locks: Vec<OwnedMutexGuard<()>>,
     per_coin_type_balance_changes: Vec<((SuiAddress, TypeTag), SuiResult<TotalBalance>)>,
     all_balance_changes: Vec<(SuiAddress, SuiResult<Arc<AllBalance>>)>,
 }
@@ -198,6 +302,40 @@ pub struct IndexStoreTables {
     #[default_options_override_fn = "dynamic_field_index_table_default_config"]
     dynamic_field_index: DBMap<DynamicFieldKey, DynamicFieldInfo>,
 
+    /// This is an index of currently existing move objects, indexed by the composite key of the
+    /// package, module, struct name and type parameters of their type, and their object ID.
+    /// This allows listing all the objects of a package, module or struct type with a prefix scan.
+    /// Only maintained when enabled, see `IndexStore::new`.
+    #[default_options_override_fn = "index_table_default_config"]
+    objects_by_type: DBMap<ObjectTypeKey, ObjectInfo>,
+
+    /// Set once `objects_by_type` has been built from the live object set, after it was enabled
+    /// on a node that already had state.
+    objects_by_type_backfilled: DBMap<(), bool>,
+
+    /// This is a log of the changes to the balance of each address in each coin type, indexed by
+    /// the composite key of the address, the coin type and the sequence number of the transaction
+    /// making the change. Only maintained when enabled, see `IndexStore::new`.
+    #[default_options_override_fn = "index_table_default_config"]
+    balance_history: DBMap<BalanceHistoryKey, BalanceChangeInfo>,
+
+    /// Sequence number of the first transaction indexed since `balance_history` was enabled. The
+    /// history is complete from that transaction on.
+    balance_history_start: DBMap<(), TxSequenceNumber>,
+
+    /// The changes to balances made by transactions whose checkpoint is not in
+    /// `balance_checkpoints` yet, as the change to the total balance of each address and coin
+    /// type.
+    unindexed_balance_changes: DBMap<TransactionDigest, Vec<(SuiAddress, String, TotalBalance)>>,
+
+    /// The balance of each address in each coin type at the end of the checkpoints that changed
+    /// it, indexed by the composite key of the address, the coin type and the checkpoint.
+    #[default_options_override_fn = "index_table_default_config"]
+    balance_checkpoints: DBMap<BalanceCheckpointKey, TotalBalance>,
+
+    /// The first and last checkpoints in `balance_checkpoints`.
+    balance_checkpoints_range: DBMap<(), (CheckpointSequenceNumber, CheckpointSequenceNumber)>,
+
     /// This is an index of all the versions of loaded child objects
     loaded_child_object_versions: DBMap<TransactionDigest, Vec<(ObjectID, SequenceNumber)>>,
 
@@ -213,6 +351,10 @@ pub struct IndexStoreTables {
     event_by_sender: DBMap<(SuiAddress, EventId), EventIndex>,
     #[default_options_override_fn = "index_table_default_config"]
     event_by_time: DBMap<(u64, EventId), EventIndex>,
+
+    /// The chunks of work already done by an interrupted rebuild of each index group, see
+    /// `IndexStoreTables::reset_index_group`.
+    index_rebuild_progress: DBMap<(IndexGroup, u64), ()>,
 }
 
 impl IndexStoreTables {
@@ -223,6 +365,338 @@ impl IndexStoreTables {
     pub fn coin_index(&self) -> &DBMap<CoinIndexKey, CoinInfo> {
         &self.coin_index
     }
+
+    /// Whether a rebuild of `group` was started and not finished.
+    pub fn is_rebuild_in_progress(&self, group: IndexGroup) -> SuiResult<bool> {
+        Ok(self
+            .index_rebuild_progress
+            .iter_with_bounds(Some((group, 0)), Some((group, u64::MAX)))
+            .next()
+            .is_some())
+    }
+
+    /// Whether `chunk` of the work of rebuilding `group` is done.
+    pub fn is_rebuild_chunk_done(&self, group: IndexGroup, chunk: u64) -> SuiResult<bool> {
+        Ok(self.index_rebuild_progress.contains_key(&(group, chunk))?)
+    }
+
+    /// Clear the tables of `group` and any progress made rebuilding them, to start rebuilding
+    /// them. The rebuild is split into chunks, each recorded as done with the last of its rows by
+    /// `rebuild_objects` or `rebuild_transactions`, so that an interrupted rebuild can skip them.
+    /// Rows are keyed deterministically, so a chunk that was partially written is simply written
+    /// again. Nothing else must write to the tables until `finish_rebuild` is called.
+    pub fn reset_index_group(&self, group: IndexGroup) -> SuiResult {
+        self.clear_rebuild_progress(group)?;
+        match group {
+            IndexGroup::Owner => clear_table(&self.owner_index)?,
+            IndexGroup::Coin => clear_table(&self.coin_index)?,
+            IndexGroup::DynamicField => clear_table(&self.dynamic_field_index)?,
+            IndexGroup::ObjectsByType => {
+                self.objects_by_type_backfilled.remove(&())?;
+                clear_table(&self.objects_by_type)?;
+            }
+            IndexGroup::Transactions => {
+                clear_table(&self.transaction_order)?;
+                clear_table(&self.transactions_seq)?;
+                clear_table(&self.transactions_from_addr)?;
+                clear_table(&self.transactions_to_addr)?;
+                clear_table(&self.transactions_by_input_object_id)?;
+                clear_table(&self.transactions_by_mutated_object_id)?;
+                clear_table(&self.transactions_by_move_function)?;
+                clear_table(&self.event_order)?;
+                clear_table(&self.event_by_move_module)?;
+                clear_table(&self.event_by_move_event)?;
+                clear_table(&self.event_by_event_module)?;
+                clear_table(&self.event_by_sender)?;
+                clear_table(&self.event_by_time)?;
+                // The balance history is keyed by the old sequence numbers. It starts over from
+                // the next indexed transaction if it is enabled.
+                self.clear_balance_history()?;
+                // The versions of the child objects loaded by transactions are only known when
+                // executing them.
+                clear_table(&self.loaded_child_object_versions)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Index live `objects` into the object index `groups`, along with the dynamic field info of
+    /// those which are dynamic fields. If `done_chunk` is set, it is recorded as done in `groups`.
+    pub fn rebuild_objects(
+        &self,
+        groups: &[IndexGroup],
+        objects: &[(Object, Option<DynamicFieldInfo>)],
+        done_chunk: Option<u64>,
+    ) -> SuiResult {
+        let mut batch = self.owner_index.batch();
+        for group in groups.iter().filter(|group| group.is_object_index()) {
+            match group {
+                IndexGroup::Owner => batch.insert_batch(
+                    &self.owner_index,
+                    objects.iter().filter_map(|(object, _)| {
+                        let Owner::AddressOwner(owner) = object.owner else {
+                            return None;
+                        };
+                        Some((
+                            (owner, object.id()),
+                            ObjectInfo::new(&object.compute_object_reference(), object),
+                        ))
+                    }),
+                )?,
+                IndexGroup::Coin => batch.insert_batch(
+                    &self.coin_index,
+                    objects.iter().filter_map(|(object, _)| {
+                        let Owner::AddressOwner(owner) = object.owner else {
+                            return None;
+                        };
+                        let coin_type = object.coin_type_maybe()?;
+                        Some((
+                            (owner, coin_type.to_string(), object.id()),
+                            CoinInfo::from_object(object)?,
+                        ))
+                    }),
+                )?,
+                IndexGroup::DynamicField => batch.insert_batch(
+                    &self.dynamic_field_index,
+                    objects.iter().filter_map(|(object, info)| {
+                        let Owner::ObjectOwner(parent) = object.owner else {
+                            return None;
+                        };
+                        Some(((ObjectID::from(parent), object.id()), info.clone()?))
+                    }),
+                )?,
+                IndexGroup::ObjectsByType => batch.insert_batch(
+                    &self.objects_by_type,
+                    objects.iter().filter_map(|(object, _)| {
+                        Some((
+                            object_type_key(&object.struct_tag()?, object.id()),
+                            ObjectInfo::new(&object.compute_object_reference(), object),
+                        ))
+                    }),
+                )?,
+                IndexGroup::Transactions => unreachable!("not an object index"),
+            };
+            if let Some(chunk) = done_chunk {
+                batch.insert_batch(&self.index_rebuild_progress, [((*group, chunk), ())])?;
+            }
+        }
+        batch.write()?;
+        Ok(())
+    }
+
+    /// Index `transactions` into the transaction and event indexes. If `done_chunk` is set, it is
+    /// recorded as done.
+    pub fn rebuild_transactions(
+        &self,
+        transactions: &[IndexedTransaction],
+        done_chunk: Option<u64>,
+    ) -> SuiResult {
+        let mut batch = self.transaction_order.batch();
+        for transaction in transactions {
+            self.insert_transaction(
+                &mut batch,
+                transaction.sequence,
+                &transaction.digest,
+                transaction.sender,
+                transaction.input_objects.iter().copied(),
+                transaction.mutated_objects.iter().cloned(),
+                transaction.move_functions.iter().cloned(),
+            )?;
+            self.insert_events(
+                &mut batch,
+                transaction.sequence,
+                &transaction.digest,
+                &transaction.events,
+                transaction.timestamp_ms,
+            )?;
+        }
+        if let Some(chunk) = done_chunk {
+            batch.insert_batch(
+                &self.index_rebuild_progress,
+                [((IndexGroup::Transactions, chunk), ())],
+            )?;
+        }
+        batch.write()?;
+        Ok(())
+    }
+
+    /// Complete the rebuild of `group`, once all of its chunks are done.
+    pub fn finish_rebuild(&self, group: IndexGroup) -> SuiResult {
+        if group == IndexGroup::ObjectsByType {
+            self.objects_by_type_backfilled.insert(&(), &true)?;
+        }
+        self.clear_rebuild_progress(group)
+    }
+
+    fn clear_balance_history(&self) -> SuiResult {
+        self.balance_history_start.remove(&())?;
+        clear_table(&self.balance_history)?;
+        self.balance_checkpoints_range.remove(&())?;
+        clear_table(&self.balance_checkpoints)?;
+        clear_table(&self.unindexed_balance_changes)
+    }
+
+    fn clear_rebuild_progress(&self, group: IndexGroup) -> SuiResult {
+        let mut batch = self.index_rebuild_progress.batch();
+        batch.delete_batch(
+            &self.index_rebuild_progress,
+            self.index_rebuild_progress
+                .iter_with_bounds(Some((group, 0)), Some((group, u64::MAX)))
+                .map(|(key, _)| key),
+        )?;
+        batch.write()?;
+        Ok(())
+    }
+
+    fn insert_transaction(
+        &self,
+        batch: &mut DBBatch,
+        sequence: TxSequenceNumber,
+        digest: &TransactionDigest,
+        sender: SuiAddress,
+        active_inputs: impl Iterator<Item = ObjectID>,
+        mutated_objects: impl Iterator<Item = (ObjectRef, Owner)> + Clone,
+        move_functions: impl Iterator<Item = (ObjectID, Identifier, Identifier)>,
+    ) -> SuiResult {
+        batch.insert_batch(
+            &self.transaction_order,
+            std::iter::once((sequence, *digest)),
+        )?;
+
+        batch.insert_batch(&self.transactions_seq, std::iter::once((*digest, sequence)))?;
+
+        batch.insert_batch(
+            &self.transactions_from_addr,
+            std::iter::once(((sender, sequence), *digest)),
+        )?;
+
+        batch.insert_batch(
+            &self.transactions_by_input_object_id,
+            active_inputs.map(|id| ((id, sequence), *digest)),
+        )?;
+
+        batch.insert_batch(
+            &self.transactions_by_mutated_object_id,
+            mutated_objects
+                .clone()
+                .map(|(obj_ref, _)| ((obj_ref.0, sequence), *digest)),
+        )?;
+
+        batch.insert_batch(
+            &self.transactions_by_move_function,
+            move_functions.map(|(obj_id, module, function)| {
+                (
+                    (obj_id, module.to_string(), function.to_string(), sequence),
+                    *digest,
+                )
+            }),
+        )?;
+
+        batch.insert_batch(
+            &self.transactions_to_addr,
+            mutated_objects.filter_map(|(_, owner)| {
+                owner
+                    .get_address_owner_address()
+                    .ok()
+                    .map(|addr| ((addr, sequence), digest))
+            }),
+        )?;
+        Ok(())
+    }
+
+    fn insert_events(
+        &self,
+        batch: &mut DBBatch,
+        sequence: TxSequenceNumber,
+        digest: &TransactionDigest,
+        events: &TransactionEvents,
+        timestamp_ms: u64,
+    ) -> SuiResult {
+        let event_digest = events.digest();
+        batch.insert_batch(
+            &self.event_order,
+            events
+                .data
+                .iter()
+                .enumerate()
+                .map(|(i, _)| ((sequence, i), (event_digest, *digest, timestamp_ms))),
+        )?;
+        batch.insert_batch(
+            &self.event_by_move_module,
+            events
+                .data
+                .iter()
+                .enumerate()
+                .map(|(i, e)| {
+                    (
+                        i,
+                        ModuleId::new(e.package_id.into(), e.transaction_module.clone()),
+                    )
+                })
+                .map(|(i, m)| ((m, (sequence, i)), (event_digest, *digest, timestamp_ms))),
+        )?;
+        batch.insert_batch(
+            &self.event_by_sender,
+            events.data.iter().enumerate().map(|(i, e)| {
+                (
+                    (e.sender, (sequence, i)),
+                    (event_digest, *digest, timestamp_ms),
+                )
+            }),
+        )?;
+        batch.insert_batch(
+            &self.event_by_move_event,
+            events.data.iter().enumerate().map(|(i, e)| {
+                (
+                    (e.type_.clone(), (sequence, i)),
+                    (event_digest, *digest, timestamp_ms),
+                )
+            }),
+        )?;
+
+        batch.insert_batch(
+            &self.event_by_time,
+            events.data.iter().enumerate().map(|(i, _)| {
+                (
+                    (timestamp_ms, (sequence, i)),
+                    (event_digest, *digest, timestamp_ms),
+                )
+            }),
+        )?;
+
+        batch.insert_batch(
+            &self.event_by_event_module,
+            events.data.iter().enumerate().map(|(i, e)| {
+                (
+                    (
+                        ModuleId::new(e.type_.address, e.type_.module.clone()),
+                        (sequence, i),
+                    ),
+                    (event_digest, *digest, timestamp_ms),
+                )
+            }),
+        )?;
+        Ok(())
+    }
+}
+
+/// Delete all the rows of `table` one by one. Unlike range deletes, point deletes are visible right
+/// away to the reads of the index tables, which ignore range deletions.
+fn clear_table<K, V>(table: &DBMap<K, V>) -> SuiResult
+where
+    K: Serialize + DeserializeOwned,
+    V: Serialize + DeserializeOwned,
+{
+    let keys = table
+        .unbounded_iter()
+        .map(|(key, _)| key)
+        .chunks(INDEX_REBUILD_CLEAR_BATCH_SIZE);
+    for chunk in &keys {
+        let mut batch = table.batch();
+        batch.delete_batch(table, chunk)?;
+        batch.write()?;
+    }
+    Ok(())
 }
 
 pub struct IndexStore {
@@ -231,6 +705,8 @@ pub struct IndexStore {
     caches: IndexStoreCaches,
     metrics: Arc<IndexStoreMetrics>,
     max_type_length: u64,
+    index_objects_by_type: bool,
+    index_balance_history: bool,
 }
 
 // These functions are used to initialize the DB tables
@@ -276,9 +752,45 @@ fn coin_index_table_default_config() -> DBOptions {
 }
 
 impl IndexStore {
-    pub fn new(path: PathBuf, registry: &Registry, max_type_length: Option<u64>) -> Self {
+    pub fn new(
+        path: PathBuf,
+        registry: &Registry,
+        max_type_length: Option<u64>,
+        index_objects_by_type: bool,
+        index_balance_history: bool,
+    ) -> Self {
         let tables =
             IndexStoreTables::open_tables_read_write(path, MetricConf::default(), None, None);
+        Self::new_with_tables(
+            tables,
+            registry,
+            max_type_length,
+            index_objects_by_type,
+            index_balance_history,
+        )
+    }
+
+    fn new_with_tables(
+        tables: IndexStoreTables,
+        registry: &Registry,
+        max_type_length: Option<u64>,
+        index_objects_by_type: bool,
+        index_balance_history: bool,
+    ) -> Self {
+        if !index_objects_by_type {
+            // The index goes stale as soon as it stops being maintained, so it has to be rebuilt
+            // from scratch if it is enabled again.
+            tables
+                .reset_index_group(IndexGroup::ObjectsByType)
+                .expect("Failed to clear objects_by_type index");
+        }
+        if !index_balance_history {
+            // A history with gaps in it can't tell the balance at a given point in time, so it
+            // restarts from scratch if it is enabled again.
+            tables
+                .clear_balance_history()
+                .expect("Failed to clear balance_history index");
+        }
         let metrics = IndexStoreMetrics::new(registry);
         let caches = IndexStoreCaches {
             per_coin_type_balance: ShardedLruCache::new(1_000_000, 1000),
@@ -291,15 +803,28 @@ impl IndexStore {
             .skip_to_last()
             .next()
             .map(|(seq, _)| seq + 1)
-            .unwrap_or(0)
-            .into();
+            .unwrap_or(0);
+        if index_balance_history
+            && tables
+                .balance_history_start
+                .get(&())
+                .expect("Failed to read balance_history index")
+                .is_none()
+        {
+            tables
+                .balance_history_start
+                .insert(&(), &next_sequence_number)
+                .expect("Failed to initialize balance_history index");
+        }
 
         Self {
             tables,
-            next_sequence_number,
+            next_sequence_number: next_sequence_number.into(),
             caches,
             metrics: Arc::new(metrics),
             max_type_length: max_type_length.unwrap_or(128),
+            index_objects_by_type,
+            index_balance_history,
         }
     }
 
@@ -307,7 +832,9 @@ impl IndexStore {
         &self.tables
     }
 
-    pub async fn index_coin(
+    /// Index the changes a transaction made to coins. The locks on the owners of the changed coins
+    /// must be held until the returned cache updates are applied.
+    pub fn index_coin(
         &self,
         digest: &TransactionDigest,
         batch: &mut DBBatch,
@@ -320,21 +847,6 @@ impl IndexStore {
         if tx_coins.is_none() {
             return Ok(IndexStoreCacheUpdates::default());
         }
-        // Acquire locks on changed coin owners
-        let mut addresses: HashSet<SuiAddress> = HashSet::new();
-        addresses.extend(
-            object_index_changes
-                .deleted_owners
-                .iter()
-                .map(|(owner, _)| *owner),
-        );
-        addresses.extend(
-            object_index_changes
-                .new_owners
-                .iter()
-                .map(|((owner, _), _)| *owner),
-        );
-        let _locks = self.caches.locks.acquire_locks(addresses.into_iter()).await;
         let mut balance_changes: HashMap<SuiAddress, HashMap<TypeTag, TotalBalance>> =
             HashMap::new();
         // Index coin info
@@ -442,7 +954,6 @@ impl IndexStore {
             })
             .collect();
         let cache_updates = IndexStoreCacheUpdates {
-            _locks,
             per_coin_type_balance_changes,
             all_balance_changes,
         };
@@ -462,60 +973,44 @@ impl IndexStore {
         tx_coins: Option<TxCoins>,
         loaded_child_objects: &BTreeMap<ObjectID, DynamicallyLoadedObjectMetadata>,
     ) -> SuiResult<u64> {
+        // Lock the owners of the changed coins until their balances are updated in the tables and
+        // the caches. The sequence number is taken once the locks are held, so that the changes
+        // to the balance of an address are indexed in the order of their sequence numbers.
+        let _locks = if tx_coins.is_some() {
+            Some(
+                self.caches
+                    .locks
+                    .acquire_locks(coin_owners(&object_index_changes).into_iter())
+                    .await,
+            )
+        } else {
+            None
+        };
         let sequence = self.next_sequence_number.fetch_add(1, Ordering::SeqCst);
         let mut batch = self.tables.transactions_from_addr.batch();
 
-        batch.insert_batch(
-            &self.tables.transaction_order,
-            std::iter::once((sequence, *digest)),
-        )?;
-
-        batch.insert_batch(
-            &self.tables.transactions_seq,
-            std::iter::once((*digest, sequence)),
-        )?;
-
-        batch.insert_batch(
-            &self.tables.transactions_from_addr,
-            std::iter::once(((sender, sequence), *digest)),
-        )?;
-
-        batch.insert_batch(
-            &self.tables.transactions_by_input_object_id,
-            active_inputs.map(|id| ((id, sequence), *digest)),
-        )?;
-
-        batch.insert_batch(
-            &self.tables.transactions_by_mutated_object_id,
-            mutated_objects
-                .clone()
-                .map(|(obj_ref, _)| ((obj_ref.0, sequence), *digest)),
-        )?;
-
-        batch.insert_batch(
-            &self.tables.transactions_by_move_function,
-            move_functions.map(|(obj_id, module, function)| {
-                (
-                    (obj_id, module.to_string(), function.to_string(), sequence),
-                    *digest,
-                )
-            }),
-        )?;
-
-        batch.insert_batch(
-            &self.tables.transactions_to_addr,
-            mutated_objects.filter_map(|(_, owner)| {
-                owner
-                    .get_address_owner_address()
-                    .ok()
-                    .map(|addr| ((addr, sequence), digest))
-            }),
+        self.tables.insert_transaction(
+            &mut batch,
+            sequence,
+            digest,
+            sender,
+            active_inputs,
+            mutated_objects,
+            move_functions,
         )?;
 
         // Coin Index
-        let cache_updates = self
-            .index_coin(digest, &mut batch, &object_index_changes, tx_coins)
-            .await?;
+        let cache_updates = self.index_coin(digest, &mut batch, &object_index_changes, tx_coins)?;
+
+        // Balance history
+        if self.index_balance_history {
+            self.index_balance_history(
+                &mut batch,
+                sequence,
+                digest,
+                &cache_updates.per_coin_type_balance_changes,
+            )?;
+        }
 
         // Owner index
         batch.delete_batch(
@@ -537,71 +1032,21 @@ impl IndexStore {
             object_index_changes.new_dynamic_fields.into_iter(),
         )?;
 
-        // events
-        let event_digest = events.digest();
-        batch.insert_batch(
-            &self.tables.event_order,
-            events
-                .data
-                .iter()
-                .enumerate()
-                .map(|(i, _)| ((sequence, i), (event_digest, *digest, timestamp_ms))),
-        )?;
-        batch.insert_batch(
-            &self.tables.event_by_move_module,
-            events
-                .data
-                .iter()
-                .enumerate()
-                .map(|(i, e)| {
-                    (
-                        i,
-                        ModuleId::new(e.package_id.into(), e.transaction_module.clone()),
-                    )
-                })
-                .map(|(i, m)| ((m, (sequence, i)), (event_digest, *digest, timestamp_ms))),
-        )?;
-        batch.insert_batch(
-            &self.tables.event_by_sender,
-            events.data.iter().enumerate().map(|(i, e)| {
-                (
-                    (e.sender, (sequence, i)),
-                    (event_digest, *digest, timestamp_ms),
-                )
-            }),
-        )?;
-        batch.insert_batch(
-            &self.tables.event_by_move_event,
-            events.data.iter().enumerate().map(|(i, e)| {
-                (
-                    (e.type_.clone(), (sequence, i)),
-                    (event_digest, *digest, timestamp_ms),
-                )
-            }),
-        )?;
-
-        batch.insert_batch(
-            &self.tables.event_by_time,
-            events.data.iter().enumerate().map(|(i, _)| {
-                (
-                    (timestamp_ms, (sequence, i)),
-                    (event_digest, *digest, timestamp_ms),
-                )
-            }),
-        )?;
+        // Objects by type index
+        if self.index_objects_by_type {
+            batch.delete_batch(
+                &self.tables.objects_by_type,
+                object_index_changes.deleted_object_types.into_iter(),
+            )?;
+            batch.insert_batch(
+                &self.tables.objects_by_type,
+                object_index_changes.new_object_types.into_iter(),
+            )?;
+        }
 
-        batch.insert_batch(
-            &self.tables.event_by_event_module,
-            events.data.iter().enumerate().map(|(i, e)| {
-                (
-                    (
-                        ModuleId::new(e.type_.address, e.type_.module.clone()),
-                        (sequence, i),
-                    ),
-                    (event_digest, *digest, timestamp_ms),
-                )
-            }),
-        )?;
+        // events
+        self.tables
+            .insert_events(&mut batch, sequence, digest, events, timestamp_ms)?;
 
         // Loaded child objects table
         let loaded_child_objects: Vec<_> = loaded_child_objects
@@ -633,17 +1078,165 @@ impl IndexStore {
 
         batch.write()?;
 
-        if !invalidate_caches {
-            // We cannot update the cache before updating the db or else on failing to write to db
-            // we will update the cache (when we retry to index this transaction again we would have
-            // updated the cache twice). However, this only means cache is eventually consistent with
-            // the db (within a very short delay)
-            self.update_per_coin_type_cache(cache_updates.per_coin_type_balance_changes)
-                .await?;
-            self.update_all_balance_cache(cache_updates.all_balance_changes)
-                .await?;
-        }
-        Ok(sequence)
+        if !invalidate_caches {
+            // We cannot update the cache before updating the db or else on failing to write to db
+            // we will update the cache (when we retry to index this transaction again we would have
+            // updated the cache twice). However, this only means cache is eventually consistent with
+            // the db (within a very short delay)
+            self.update_per_coin_type_cache(cache_updates.per_coin_type_balance_changes)
+                .await?;
+            self.update_all_balance_cache(cache_updates.all_balance_changes)
+                .await?;
+        }
+        Ok(sequence)
+    }
+
+    /// Record the balance changes made by a transaction, given as the change to the total balance
+    /// of each address and coin type. The previous balances are read from the coin index, which
+    /// `batch` hasn't updated yet. The locks on the addresses must be held until the batch is
+    /// written, so that no other transaction changes them in between.
+    fn index_balance_history(
+        &self,
+        batch: &mut DBBatch,
+        sequence: TxSequenceNumber,
+        digest: &TransactionDigest,
+        balance_changes: &[((SuiAddress, TypeTag), SuiResult<TotalBalance>)],
+    ) -> SuiResult {
+        let mut entries = vec![];
+        let mut changes = vec![];
+        for ((owner, coin_type), change) in balance_changes {
+            let change = change.clone()?;
+            if change == TotalBalance::default() {
+                continue;
+            }
+            changes.push((*owner, coin_type.to_string(), change));
+            let previous_balance = Self::get_balance_from_db(
+                self.metrics.clone(),
+                self.tables.coin_index.clone(),
+                *owner,
+                coin_type.clone(),
+            )?;
+            entries.push((
+                (*owner, coin_type.to_string(), sequence),
+                BalanceChangeInfo {
+                    digest: *digest,
+                    previous_balance,
+                    balance: TotalBalance {
+                        balance: previous_balance.balance + change.balance,
+                        num_coins: previous_balance.num_coins + change.num_coins,
+                    },
+                },
+            ));
+        }
+        batch.insert_batch(&self.tables.balance_history, entries)?;
+        if !changes.is_empty() {
+            batch.insert_batch(
+                &self.tables.unindexed_balance_changes,
+                std::iter::once((*digest, changes)),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Record the balances at the end of `checkpoint` of the addresses whose balance changed in
+    /// it, given the transactions of the checkpoint. Checkpoints must be indexed in order, once
+    /// their transactions are indexed. Indexing the last checkpoint again adds the changes of its
+    /// transactions that were indexed since.
+    pub fn index_checkpoint_balances(
+        &self,
+        checkpoint: CheckpointSequenceNumber,
+        tx_digests: &[TransactionDigest],
+    ) -> SuiResult {
+        if !self.index_balance_history {
+            return Ok(());
+        }
+        let range = self.tables.balance_checkpoints_range.get(&())?;
+        if range.is_some_and(|(_, last)| checkpoint < last) {
+            return Ok(());
+        }
+
+        let mut changes: BTreeMap<(SuiAddress, String), TotalBalance> = BTreeMap::new();
+        let mut indexed = vec![];
+        for (digest, tx_changes) in tx_digests.iter().zip(
+            self.tables
+                .unindexed_balance_changes
+                .multi_get(tx_digests)?,
+        ) {
+            let Some(tx_changes) = tx_changes else {
+                continue;
+            };
+            indexed.push(*digest);
+            for (owner, coin_type, change) in tx_changes {
+                let total = changes.entry((owner, coin_type)).or_default();
+                total.balance += change.balance;
+                total.num_coins += change.num_coins;
+            }
+        }
+
+        let mut balances = vec![];
+        for ((owner, coin_type), change) in changes {
+            let previous = match self.balance_at_checkpoint(owner, &coin_type, checkpoint)? {
+                Some(balance) => balance,
+                // The balance before the first change indexed, which is recorded in the history
+                // along with the change.
+                None => self
+                    .first_balance_change(owner, &coin_type)?
+                    .map(|change| change.previous_balance)
+                    .unwrap_or_default(),
+            };
+            balances.push((
+                (owner, coin_type, checkpoint),
+                TotalBalance {
+                    balance: previous.balance + change.balance,
+                    num_coins: previous.num_coins + change.num_coins,
+                },
+            ));
+        }
+
+        let first = range.map_or(checkpoint, |(first, _)| first);
+        let mut batch = self.tables.balance_checkpoints.batch();
+        batch.insert_batch(&self.tables.balance_checkpoints, balances)?;
+        batch.delete_batch(&self.tables.unindexed_balance_changes, indexed)?;
+        batch.insert_batch(
+            &self.tables.balance_checkpoints_range,
+            std::iter::once(((), (first, checkpoint))),
+        )?;
+        batch.write()?;
+        Ok(())
+    }
+
+    /// The balance of `owner` in `coin_type` at the end of the last checkpoint up to `checkpoint`
+    /// that changed it, if any.
+    fn balance_at_checkpoint(
+        &self,
+        owner: SuiAddress,
+        coin_type: &str,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> SuiResult<Option<TotalBalance>> {
+        Ok(self
+            .tables
+            .balance_checkpoints
+            .unbounded_iter()
+            .skip_prior_to(&(owner, coin_type.to_owned(), checkpoint))?
+            .reverse()
+            .next()
+            .filter(|((address, type_, _), _)| *address == owner && type_ == coin_type)
+            .map(|(_, balance)| balance))
+    }
+
+    fn first_balance_change(
+        &self,
+        owner: SuiAddress,
+        coin_type: &str,
+    ) -> SuiResult<Option<BalanceChangeInfo>> {
+        Ok(self
+            .tables
+            .balance_history
+            .unbounded_iter()
+            .skip_to(&(owner, coin_type.to_owned(), TxSequenceNumber::MIN))?
+            .next()
+            .filter(|((address, type_, _), _)| *address == owner && type_ == coin_type)
+            .map(|(_, change)| change))
     }
 
     pub fn next_sequence_number(&self) -> TxSequenceNumber {
@@ -1297,6 +1890,202 @@ impl IndexStore {
             .map(|(_, object_info)| object_info))
     }
 
+    pub fn objects_by_type_index_enabled(&self) -> bool {
+        self.index_objects_by_type
+    }
+
+    /// Whether the `objects_by_type` index is enabled but hasn't been built from the live object
+    /// set yet, which `sui-tool db-tool rebuild-indexes` does.
+    pub fn objects_by_type_needs_backfill(&self) -> SuiResult<bool> {
+        Ok(self.index_objects_by_type
+            && !self
+                .tables
+                .objects_by_type_backfilled
+                .get(&())?
+                .unwrap_or(false))
+    }
+
+    /// Iterate over the currently existing objects whose type matches `type_filter`, ordered by
+    /// type and then by object ID. `type_filter` must be a `Package`, `MoveModule` or `StructType`
+    /// filter. `cursor` is the type and ID of the last object returned by a previous call, which
+    /// is not returned again.
+    pub fn get_objects_by_type_iterator(
+        &self,
+        type_filter: &SuiObjectDataFilter,
+        cursor: Option<(StructTag, ObjectID)>,
+    ) -> SuiResult<impl Iterator<Item = ObjectInfo> + '_> {
+        if !self.index_objects_by_type {
+            return Err(SuiError::UnsupportedFeatureError {
+                error: "The objects_by_type index is not enabled on this node".to_string(),
+            });
+        }
+        if self.objects_by_type_needs_backfill()? {
+            return Err(SuiError::UnsupportedFeatureError {
+                error: "The objects_by_type index of this node is not built yet".to_string(),
+            });
+        }
+        let (package, module, name, type_params) = match type_filter {
+            SuiObjectDataFilter::Package(package) => (*package, None, None, None),
+            SuiObjectDataFilter::MoveModule { package, module } => {
+                (*package, Some(module.to_string()), None, None)
+            }
+            SuiObjectDataFilter::StructType(tag) => (
+                tag.address.into(),
+                Some(tag.module.to_string()),
+                Some(tag.name.to_string()),
+                // Types without type parameters match all instantiations of a generic type.
+                (!tag.type_params.is_empty()).then(|| type_params_key(&tag.type_params)),
+            ),
+            _ => {
+                return Err(UserInputError::Unsupported(
+                    "Objects can only be listed by package, module or struct type".to_string(),
+                )
+                .into())
+            }
+        };
+        let cursor = cursor.map(|(type_, object_id)| object_type_key(&type_, object_id));
+        let lower_bound = cursor.clone().unwrap_or_else(|| {
+            (
+                package,
+                module.clone().unwrap_or_default(),
+                name.clone().unwrap_or_default(),
+                type_params.clone().unwrap_or_default(),
+                ObjectID::ZERO,
+            )
+        });
+        Ok(self
+            .tables
+            .objects_by_type
+            .unbounded_iter()
+            .skip_to(&lower_bound)?
+            .skip_while(move |(key, _)| Some(key) == cursor.as_ref())
+            .take_while(move |((p, m, n, t, _), _)| {
+                *p == package
+                    && module.as_ref().map_or(true, |module| module == m)
+                    && name.as_ref().map_or(true, |name| name == n)
+                    && type_params
+                        .as_ref()
+                        .map_or(true, |type_params| type_params == t)
+            })
+            .map(|(_, object_info)| object_info))
+    }
+
+    pub fn balance_history_index_enabled(&self) -> bool {
+        self.index_balance_history
+    }
+
+    /// The changes to the balance of `owner` in `coin_type`, ordered by the sequence number of the
+    /// transactions making them. `cursor` is the sequence number of the last change returned by a
+    /// previous call, which is not returned again.
+    pub fn get_balance_history(
+        &self,
+        owner: SuiAddress,
+        coin_type: &TypeTag,
+        cursor: Option<TxSequenceNumber>,
+        limit: usize,
+        reverse: bool,
+    ) -> SuiResult<Vec<(TxSequenceNumber, BalanceChangeInfo)>> {
+        if !self.index_balance_history {
+            return Err(SuiError::UnsupportedFeatureError {
+                error: "The balance_history index is not enabled on this node".to_string(),
+            });
+        }
+        let coin_type = coin_type.to_string();
+        let in_history = |((address, type_, _), _): &(BalanceHistoryKey, BalanceChangeInfo)| {
+            *address == owner && *type_ == coin_type
+        };
+        // skip the cursor itself if it is Some
+        let is_cursor = |((_, _, sequence), _): &(BalanceHistoryKey, BalanceChangeInfo)| {
+            Some(*sequence) == cursor
+        };
+        let entry =
+            |((_, _, sequence), info): (BalanceHistoryKey, BalanceChangeInfo)| (sequence, info);
+        Ok(if reverse {
+            self.tables
+                .balance_history
+                .unbounded_iter()
+                .skip_prior_to(&(
+                    owner,
+                    coin_type.clone(),
+                    cursor.unwrap_or(TxSequenceNumber::MAX),
+                ))?
+                .reverse()
+                .skip_while(is_cursor)
+                .take_while(in_history)
+                .take(limit)
+                .map(entry)
+                .collect()
+        } else {
+            self.tables
+                .balance_history
+                .unbounded_iter()
+                .skip_to(&(
+                    owner,
+                    coin_type.clone(),
+                    cursor.unwrap_or(TxSequenceNumber::MIN),
+                ))?
+                .skip_while(is_cursor)
+                .take_while(in_history)
+                .take(limit)
+                .map(entry)
+                .collect()
+        })
+    }
+
+    /// The digest of the first transaction indexed since the balance history was enabled, if any.
+    /// The history holds all the balance changes made from that transaction on.
+    pub fn get_balance_history_start(&self) -> SuiResult<Option<TransactionDigest>> {
+        let Some(start) = self.tables.balance_history_start.get(&())? else {
+            return Ok(None);
+        };
+        Ok(self
+            .tables
+            .transaction_order
+            .unbounded_iter()
+            .skip_to(&start)?
+            .next()
+            .map(|(_, digest)| digest))
+    }
+
+    /// The balance of `owner` in `coin_type` at the end of `checkpoint`, or None if the balances
+    /// at that checkpoint are not indexed: it is before the balance history was enabled, or its
+    /// balances have not been indexed yet. Transactions executed before the history was enabled
+    /// but checkpointed after are not accounted for in the first checkpoints indexed.
+    pub fn get_balance_at_checkpoint(
+        &self,
+        owner: SuiAddress,
+        coin_type: &TypeTag,
+        checkpoint: CheckpointSequenceNumber,
+    ) -> SuiResult<Option<TotalBalance>> {
+        if !self.index_balance_history {
+            return Err(SuiError::UnsupportedFeatureError {
+                error: "The balance_history index is not enabled on this node".to_string(),
+            });
+        }
+        let Some((first, last)) = self.tables.balance_checkpoints_range.get(&())? else {
+            return Ok(None);
+        };
+        if checkpoint < first || checkpoint > last {
+            return Ok(None);
+        }
+        let type_ = coin_type.to_string();
+        if let Some(balance) = self.balance_at_checkpoint(owner, &type_, checkpoint)? {
+            return Ok(Some(balance));
+        }
+        // No change up to `checkpoint`, so the balance is the one before the first change, or
+        // the current one if there was no change at all.
+        match self.first_balance_change(owner, &type_)? {
+            Some(change) => Ok(Some(change.previous_balance)),
+            None => Self::get_balance_from_db(
+                self.metrics.clone(),
+                self.tables.coin_index.clone(),
+                owner,
+                coin_type.clone(),
+            )
+            .map(Some),
+        }
+    }
+
     pub fn insert_genesis_objects(&self, object_index_changes: ObjectIndexChanges) -> SuiResult {
         let mut batch = self.tables.owner_index.batch();
         batch.insert_batch(
@@ -1307,6 +2096,13 @@ impl IndexStore {
             &self.tables.dynamic_field_index,
             object_index_changes.new_dynamic_fields.into_iter(),
         )?;
+        if self.index_objects_by_type {
+            batch.insert_batch(
+                &self.tables.objects_by_type,
+                object_index_changes.new_object_types.into_iter(),
+            )?;
+            batch.insert_batch(&self.tables.objects_by_type_backfilled, [((), true)])?;
+        }
         batch.write()?;
         Ok(())
     }
@@ -1583,19 +2379,27 @@ impl IndexStore {
 
 #[cfg(test)]
 mod tests {
-    use crate::indexes::ObjectIndexChanges;
+    use crate::indexes::{
+        object_type_key, IndexGroup, IndexedTransaction, ObjectIndexChanges, TotalBalance,
+    };
     use crate::IndexStore;
     use move_core_types::account_address::AccountAddress;
+    use move_core_types::language_storage::TypeTag;
     use prometheus::Registry;
     use std::collections::BTreeMap;
     use std::env::temp_dir;
     use std::sync::Arc;
-    use sui_types::base_types::{ObjectInfo, ObjectType, SuiAddress};
+    use sui_json_rpc_types::SuiObjectDataFilter;
+    use sui_types::base_types::{ObjectID, ObjectInfo, ObjectType, SuiAddress};
+    use sui_types::coin::Coin;
     use sui_types::digests::TransactionDigest;
     use sui_types::effects::TransactionEvents;
+    use sui_types::error::SuiResult;
     use sui_types::gas_coin::GAS;
     use sui_types::object;
-    use sui_types::object::Owner;
+    use sui_types::object::{MoveObject, Owner, OBJECT_START_VERSION};
+    use sui_types::parse_sui_struct_tag;
+    use typed_store::traits::Map;
 
     #[tokio::test]
     async fn test_index_cache() -> anyhow::Result<()> {
@@ -1606,7 +2410,8 @@ mod tests {
         // and verified from both db and cache.
         // This tests make sure we are invalidating entries in the cache and always reading latest
         // balance.
-        let index_store = IndexStore::new(temp_dir(), &Registry::default(), Some(128));
+        let index_store =
+            IndexStore::new(temp_dir(), &Registry::default(), Some(128), false, false);
         let address: SuiAddress = AccountAddress::random().into();
         let mut written_objects = BTreeMap::new();
         let mut object_map = BTreeMap::new();
@@ -1633,6 +2438,8 @@ mod tests {
             deleted_dynamic_fields: vec![],
             new_owners: new_objects,
             new_dynamic_fields: vec![],
+            deleted_object_types: vec![],
+            new_object_types: vec![],
         };
 
         let tx_coins = (object_map.clone(), written_objects.clone());
@@ -1679,6 +2486,8 @@ mod tests {
             deleted_dynamic_fields: vec![],
             new_owners: vec![],
             new_dynamic_fields: vec![],
+            deleted_object_types: vec![],
+            new_object_types: vec![],
         };
         let tx_coins = (object_map, written_objects);
         index_store
@@ -1723,4 +2532,564 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_objects_by_type_index() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let index_store = IndexStore::new(
+            dir.path().to_path_buf(),
+            &Registry::default(),
+            None,
+            true,
+            false,
+        );
+        assert!(index_store.objects_by_type_needs_backfill()?);
+
+        let address: SuiAddress = AccountAddress::random().into();
+        let foo = parse_sui_struct_tag("0x42::foo::FOO")?;
+        let mut objects = vec![];
+        for _ in 0..3 {
+            objects.push(object::Object::new_gas_with_balance_and_owner_for_testing(
+                100, address,
+            ));
+        }
+        for _ in 0..2 {
+            let coin = MoveObject::new_coin(
+                Coin::type_(TypeTag::Struct(Box::new(foo.clone()))).into(),
+                OBJECT_START_VERSION,
+                ObjectID::random(),
+                100,
+            );
+            objects.push(object::Object::new_move(
+                coin,
+                Owner::AddressOwner(address),
+                TransactionDigest::genesis(),
+            ));
+        }
+        assert!(index_store
+            .get_objects_by_type_iterator(&SuiObjectDataFilter::gas_coin(), None)
+            .is_err());
+        build_objects_by_type(&index_store, &objects)?;
+        assert!(!index_store.objects_by_type_needs_backfill()?);
+
+        let count = |filter: SuiObjectDataFilter| -> anyhow::Result<usize> {
+            Ok(index_store
+                .get_objects_by_type_iterator(&filter, None)?
+                .count())
+        };
+        let coin = parse_sui_struct_tag("0x2::coin::Coin")?;
+        assert_eq!(count(SuiObjectDataFilter::StructType(coin.clone()))?, 5);
+        assert_eq!(count(SuiObjectDataFilter::gas_coin())?, 3);
+        assert_eq!(count(SuiObjectDataFilter::Package(coin.address.into()))?, 5);
+        assert_eq!(
+            count(SuiObjectDataFilter::MoveModule {
+                package: coin.address.into(),
+                module: coin.module.clone(),
+            })?,
+            5
+        );
+        assert_eq!(count(SuiObjectDataFilter::Package(foo.address.into()))?, 0);
+        assert!(index_store
+            .get_objects_by_type_iterator(&SuiObjectDataFilter::AddressOwner(address), None)
+            .is_err());
+
+        // Paginate through all coins.
+        let filter = SuiObjectDataFilter::StructType(coin);
+        let first_page: Vec<_> = index_store
+            .get_objects_by_type_iterator(&filter, None)?
+            .take(2)
+            .collect();
+        let last = first_page.last().unwrap();
+        let ObjectType::Struct(last_type) = &last.type_ else {
+            panic!("coins have a struct type");
+        };
+        let second_page: Vec<_> = index_store
+            .get_objects_by_type_iterator(
+                &filter,
+                Some((last_type.clone().into(), last.object_id)),
+            )?
+            .collect();
+        let mut ids: Vec<_> = first_page
+            .iter()
+            .chain(&second_page)
+            .map(|info| info.object_id)
+            .collect();
+        ids.sort();
+        let mut expected: Vec<_> = objects.iter().map(|o| o.id()).collect();
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        // Deleted objects are removed from the index.
+        let deleted = &objects[0];
+        let object_index_changes = ObjectIndexChanges {
+            deleted_owners: vec![],
+            deleted_dynamic_fields: vec![],
+            new_owners: vec![],
+            new_dynamic_fields: vec![],
+            deleted_object_types: vec![object_type_key(
+                &deleted.type_().unwrap().clone().into(),
+                deleted.id(),
+            )],
+            new_object_types: vec![],
+        };
+        index_store
+            .index_tx(
+                address,
+                vec![].into_iter(),
+                vec![].into_iter(),
+                vec![].into_iter(),
+                &TransactionEvents { data: vec![] },
+                object_index_changes,
+                &TransactionDigest::random(),
+                1234,
+                None,
+                &BTreeMap::new(),
+            )
+            .await?;
+        assert_eq!(count(SuiObjectDataFilter::gas_coin())?, 2);
+
+        Ok(())
+    }
+
+    /// Build the objects_by_type index from `objects`, as `sui-tool db-tool rebuild-indexes` does.
+    fn build_objects_by_type(index_store: &IndexStore, objects: &[object::Object]) -> SuiResult {
+        let tables = index_store.tables();
+        let objects: Vec<_> = objects.iter().map(|o| (o.clone(), None)).collect();
+        tables.reset_index_group(IndexGroup::ObjectsByType)?;
+        tables.rebuild_objects(&[IndexGroup::ObjectsByType], &objects, None)?;
+        tables.finish_rebuild(IndexGroup::ObjectsByType)
+    }
+
+    #[tokio::test]
+    async fn test_objects_by_type_index_reenabled() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let index_store = IndexStore::new(
+            dir.path().to_path_buf(),
+            &Registry::default(),
+            None,
+            true,
+            false,
+        );
+        let address: SuiAddress = AccountAddress::random().into();
+        let objects: Vec<_> = (0..3)
+            .map(|_| object::Object::new_gas_with_balance_and_owner_for_testing(100, address))
+            .collect();
+        build_objects_by_type(&index_store, &objects)?;
+
+        // Disable the index, and delete an object while it isn't maintained.
+        let index_store = IndexStore::new_with_tables(
+            index_store.tables,
+            &Registry::default(),
+            None,
+            false,
+            false,
+        );
+        assert_eq!(
+            index_store.tables.objects_by_type.unbounded_iter().count(),
+            0
+        );
+        let deleted = objects[0].id();
+
+        // Re-enable it. It serves queries again once it is rebuilt from the live objects.
+        let index_store = IndexStore::new_with_tables(
+            index_store.tables,
+            &Registry::default(),
+            None,
+            true,
+            false,
+        );
+        assert!(index_store.objects_by_type_needs_backfill()?);
+        build_objects_by_type(&index_store, &objects[1..])?;
+        let ids: Vec<_> = index_store
+            .get_objects_by_type_iterator(&SuiObjectDataFilter::gas_coin(), None)?
+            .map(|info| info.object_id)
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert!(!ids.contains(&deleted));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_balance_history_index() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let index_store = IndexStore::new(
+            dir.path().to_path_buf(),
+            &Registry::default(),
+            None,
+            false,
+            true,
+        );
+        assert_eq!(index_store.get_balance_history_start()?, None);
+        let address: SuiAddress = AccountAddress::random().into();
+
+        // Receive 10 coins of 100, then spend 3 of them.
+        let mut input_coins = BTreeMap::new();
+        let mut written_coins = BTreeMap::new();
+        let mut new_owners = vec![];
+        for _ in 0..10 {
+            let object = object::Object::new_gas_with_balance_and_owner_for_testing(100, address);
+            new_owners.push((
+                (address, object.id()),
+                ObjectInfo::new(&object.compute_object_reference(), &object),
+            ));
+            input_coins.insert(object.id(), Arc::new(object.clone()));
+            written_coins.insert(object.id(), object);
+        }
+        let deleted_owners: Vec<_> = input_coins
+            .keys()
+            .take(3)
+            .map(|id| (address, *id))
+            .collect();
+        let changes = [
+            (new_owners, vec![], (BTreeMap::new(), written_coins)),
+            (vec![], deleted_owners, (input_coins, BTreeMap::new())),
+        ];
+
+        let mut digests = vec![];
+        for (new_owners, deleted_owners, tx_coins) in changes {
+            let digest = TransactionDigest::random();
+            let object_index_changes = ObjectIndexChanges {
+                deleted_owners,
+                deleted_dynamic_fields: vec![],
+                new_owners,
+                new_dynamic_fields: vec![],
+                deleted_object_types: vec![],
+                new_object_types: vec![],
+            };
+            index_store
+                .index_tx(
+                    address,
+                    vec![].into_iter(),
+                    vec![].into_iter(),
+                    vec![].into_iter(),
+                    &TransactionEvents { data: vec![] },
+                    object_index_changes,
+                    &digest,
+                    1234,
+                    Some(tx_coins),
+                    &BTreeMap::new(),
+                )
+                .await?;
+            digests.push(digest);
+        }
+        assert_eq!(index_store.get_balance_history_start()?, Some(digests[0]));
+
+        let history =
+            index_store.get_balance_history(address, &GAS::type_tag(), None, 10, false)?;
+        assert_eq!(history.len(), 2);
+        let (first, second) = (history[0].1, history[1].1);
+        assert_eq!(first.digest, digests[0]);
+        assert_eq!(first.amount(), 1000);
+        assert_eq!(first.previous_balance, TotalBalance::default());
+        assert_eq!(first.balance.num_coins, 10);
+        assert_eq!(second.digest, digests[1]);
+        assert_eq!(second.amount(), -300);
+        assert_eq!(second.balance.balance, 700);
+        assert_eq!(second.balance.num_coins, 7);
+
+        // Paginate in both directions.
+        let page = index_store.get_balance_history(address, &GAS::type_tag(), None, 1, true)?;
+        assert_eq!(page, vec![history[1]]);
+        let page =
+            index_store.get_balance_history(address, &GAS::type_tag(), Some(page[0].0), 1, true)?;
+        assert_eq!(page, vec![history[0]]);
+        let page = index_store.get_balance_history(
+            address,
+            &GAS::type_tag(),
+            Some(history[0].0),
+            10,
+            false,
+        )?;
+        assert_eq!(page, vec![history[1]]);
+
+        // Other addresses and coin types have no history.
+        let other: SuiAddress = AccountAddress::random().into();
+        assert!(index_store
+            .get_balance_history(other, &GAS::type_tag(), None, 10, false)?
+            .is_empty());
+        let foo = TypeTag::Struct(Box::new(parse_sui_struct_tag("0x42::foo::FOO")?));
+        assert!(index_store
+            .get_balance_history(address, &foo, None, 10, false)?
+            .is_empty());
+
+        // Receive another coin in a transaction indexed last but checkpointed with the first one.
+        let object = object::Object::new_gas_with_balance_and_owner_for_testing(50, address);
+        let object_index_changes = ObjectIndexChanges {
+            deleted_owners: vec![],
+            deleted_dynamic_fields: vec![],
+            new_owners: vec![(
+                (address, object.id()),
+                ObjectInfo::new(&object.compute_object_reference(), &object),
+            )],
+            new_dynamic_fields: vec![],
+            deleted_object_types: vec![],
+            new_object_types: vec![],
+        };
+        let digest = TransactionDigest::random();
+        index_store
+            .index_tx(
+                address,
+                vec![].into_iter(),
+                vec![].into_iter(),
+                vec![].into_iter(),
+                &TransactionEvents { data: vec![] },
+                object_index_changes,
+                &digest,
+                1234,
+                Some((BTreeMap::new(), BTreeMap::from([(object.id(), object)]))),
+                &BTreeMap::new(),
+            )
+            .await?;
+        let balance_at = |checkpoint| {
+            index_store.get_balance_at_checkpoint(address, &GAS::type_tag(), checkpoint)
+        };
+        assert_eq!(balance_at(5)?, None);
+
+        index_store.index_checkpoint_balances(5, &[digests[0], digest])?;
+        index_store.index_checkpoint_balances(6, &[])?;
+        index_store.index_checkpoint_balances(7, &[digests[1]])?;
+        // Indexing a checkpoint again is a no-op.
+        index_store.index_checkpoint_balances(5, &[digests[0], digest])?;
+        assert_eq!(balance_at(4)?, None);
+        assert_eq!(
+            balance_at(5)?,
+            Some(TotalBalance {
+                balance: 1050,
+                num_coins: 11
+            })
+        );
+        assert_eq!(balance_at(6)?, balance_at(5)?);
+        assert_eq!(
+            balance_at(7)?,
+            Some(TotalBalance {
+                balance: 750,
+                num_coins: 8
+            })
+        );
+        assert_eq!(balance_at(8)?, None);
+        assert_eq!(
+            index_store.get_balance_at_checkpoint(other, &GAS::type_tag(), 6)?,
+            Some(TotalBalance::default())
+        );
+
+        // Disabling the history clears it.
+        let index_store = IndexStore::new_with_tables(
+            index_store.tables,
+            &Registry::default(),
+            None,
+            false,
+            false,
+        );
+        assert_eq!(
+            index_store.tables.balance_history.unbounded_iter().count(),
+            0
+        );
+        assert_eq!(
+            index_store
+                .tables
+                .balance_checkpoints
+                .unbounded_iter()
+                .count(),
+            0
+        );
+        assert_eq!(
+            index_store
+                .tables
+                .unindexed_balance_changes
+                .unbounded_iter()
+                .count(),
+            0
+        );
+
+        // Once enabled again, it starts over from the balance in the coin index.
+        let index_store = IndexStore::new_with_tables(
+            index_store.tables,
+            &Registry::default(),
+            None,
+            false,
+            true,
+        );
+        let object = object::Object::new_gas_with_balance_and_owner_for_testing(25, address);
+        let object_index_changes = ObjectIndexChanges {
+            deleted_owners: vec![],
+            deleted_dynamic_fields: vec![],
+            new_owners: vec![(
+                (address, object.id()),
+                ObjectInfo::new(&object.compute_object_reference(), &object),
+            )],
+            new_dynamic_fields: vec![],
+            deleted_object_types: vec![],
+            new_object_types: vec![],
+        };
+        index_store
+            .index_tx(
+                address,
+                vec![].into_iter(),
+                vec![].into_iter(),
+                vec![].into_iter(),
+                &TransactionEvents { data: vec![] },
+                object_index_changes,
+                &TransactionDigest::random(),
+                1234,
+                Some((BTreeMap::new(), BTreeMap::from([(object.id(), object)]))),
+                &BTreeMap::new(),
+            )
+            .await?;
+        let history =
+            index_store.get_balance_history(address, &GAS::type_tag(), None, 10, false)?;
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            history[0].1.previous_balance,
+            TotalBalance {
+                balance: 750,
+                num_coins: 8
+            }
+        );
+        assert_eq!(history[0].1.amount(), 25);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_balance_history_concurrent_indexing() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let index_store = Arc::new(IndexStore::new(
+            dir.path().to_path_buf(),
+            &Registry::default(),
+            None,
+            false,
+            true,
+        ));
+        let address: SuiAddress = AccountAddress::random().into();
+
+        // Many transactions sending a coin to the same address are indexed at the same time.
+        let num_txs = 50;
+        let tasks: Vec<_> = (0..num_txs)
+            .map(|_| {
+                let index_store = index_store.clone();
+                tokio::spawn(async move {
+                    let object =
+                        object::Object::new_gas_with_balance_and_owner_for_testing(100, address);
+                    let object_index_changes = ObjectIndexChanges {
+                        deleted_owners: vec![],
+                        deleted_dynamic_fields: vec![],
+                        new_owners: vec![(
+                            (address, object.id()),
+                            ObjectInfo::new(&object.compute_object_reference(), &object),
+                        )],
+                        new_dynamic_fields: vec![],
+                        deleted_object_types: vec![],
+                        new_object_types: vec![],
+                    };
+                    let written_coins = BTreeMap::from([(object.id(), object)]);
+                    index_store
+                        .index_tx(
+                            address,
+                            vec![].into_iter(),
+                            vec![].into_iter(),
+                            vec![].into_iter(),
+                            &TransactionEvents { data: vec![] },
+                            object_index_changes,
+                            &TransactionDigest::random(),
+                            1234,
+                            Some((BTreeMap::new(), written_coins)),
+                            &BTreeMap::new(),
+                        )
+                        .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await??;
+        }
+
+        // Each change starts from the balance the previous one left.
+        let history =
+            index_store.get_balance_history(address, &GAS::type_tag(), None, num_txs, false)?;
+        assert_eq!(history.len(), num_txs);
+        let mut balance = TotalBalance::default();
+        for (_, change) in &history {
+            assert_eq!(change.previous_balance, balance);
+            assert_eq!(change.amount(), 100);
+            balance = change.balance;
+        }
+        assert_eq!(balance.balance, 100 * num_txs as i128);
+        assert_eq!(balance.num_coins, num_txs as i64);
+        assert_eq!(
+            index_store.get_balance(address, GAS::type_tag()).await?,
+            balance
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_index_rebuild() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let index_store = IndexStore::new(
+            dir.path().to_path_buf(),
+            &Registry::default(),
+            None,
+            false,
+            false,
+        );
+        let tables = index_store.tables();
+
+        let address: SuiAddress = AccountAddress::random().into();
+        let objects: Vec<_> = (0..3)
+            .map(|_| {
+                (
+                    object::Object::new_gas_with_balance_and_owner_for_testing(100, address),
+                    None,
+                )
+            })
+            .collect();
+        for group in [IndexGroup::Owner, IndexGroup::Coin] {
+            tables.reset_index_group(group)?;
+            assert!(!tables.is_rebuild_in_progress(group)?);
+        }
+        tables.rebuild_objects(&[IndexGroup::Owner, IndexGroup::Coin], &objects, Some(7))?;
+        assert!(tables.is_rebuild_chunk_done(IndexGroup::Owner, 7)?);
+        assert!(!tables.is_rebuild_chunk_done(IndexGroup::Owner, 8)?);
+        assert!(tables.is_rebuild_in_progress(IndexGroup::Coin)?);
+        assert_eq!(tables.owner_index().unbounded_iter().count(), 3);
+        assert_eq!(tables.coin_index().unbounded_iter().count(), 3);
+
+        // Resetting a group clears its rows and progress, and leaves the other groups alone.
+        tables.reset_index_group(IndexGroup::Owner)?;
+        assert!(!tables.is_rebuild_in_progress(IndexGroup::Owner)?);
+        assert_eq!(tables.owner_index().unbounded_iter().count(), 0);
+        assert_eq!(tables.coin_index().unbounded_iter().count(), 3);
+        tables.finish_rebuild(IndexGroup::Coin)?;
+        assert!(!tables.is_rebuild_in_progress(IndexGroup::Coin)?);
+
+        let digest = TransactionDigest::random();
+        tables
+            .loaded_child_object_versions
+            .insert(&digest, &vec![(objects[0].0.id(), OBJECT_START_VERSION)])?;
+        tables.reset_index_group(IndexGroup::Transactions)?;
+        assert!(tables.loaded_child_object_versions.is_empty());
+        tables.rebuild_transactions(
+            &[IndexedTransaction {
+                sequence: 42,
+                digest,
+                sender: address,
+                input_objects: vec![objects[0].0.id()],
+                mutated_objects: vec![(
+                    objects[0].0.compute_object_reference(),
+                    Owner::AddressOwner(address),
+                )],
+                move_functions: vec![],
+                events: TransactionEvents::default(),
+                timestamp_ms: 0,
+            }],
+            Some(0),
+        )?;
+        assert_eq!(index_store.get_transaction_seq(&digest)?, Some(42));
+        assert_eq!(
+            index_store.get_transactions_from_addr(address, None, None, false)?,
+            vec![digest]
+        );
+        Ok(())
+    }
 }
@@ -139,6 +139,8 @@ impl ValidatorConfigBuilder {
             consensus_config: Some(consensus_config),
             enable_event_processing: false,
             enable_index_processing: default_enable_index_processing(),
+            enable_objects_by_type_index: false,
+            enable_balance_history_index: false,
             genesis: sui_config::node::Genesis::new(genesis),
             grpc_load_shed: None,
             grpc_concurrency_limit: Some(DEFAULT_GRPC_CONCURRENCY_LIMIT),
@@ -157,12 +159,15 @@ impl ValidatorConfigBuilder {
             name_service_registry_id: None,
             name_service_reverse_registry_id: None,
             transaction_deny_config: Default::default(),
+            transaction_deny_config_path: None,
             certificate_deny_config: Default::default(),
             state_debug_dump_config: Default::default(),
             state_archive_write_config: StateArchiveConfig::default(),
             state_archive_read_config: vec![],
             state_snapshot_write_config: StateSnapshotConfig::default(),
             indexer_max_subscriptions: Default::default(),
+            json_rpc_max_batch_size: Default::default(),
+            json_rpc_rate_limit_config: Default::default(),
             transaction_kv_store_read_config: Default::default(),
             transaction_kv_store_write_config: None,
             enable_experimental_rest_api: true,
@@ -375,6 +380,8 @@ impl FullnodeConfigBuilder {
             consensus_config: None,
             enable_event_processing: true, // This is unused.
             enable_index_processing: default_enable_index_processing(),
+            enable_objects_by_type_index: false,
+            enable_balance_history_index: false,
             genesis: self.genesis.unwrap_or(sui_config::node::Genesis::new(
                 network_config.genesis.clone(),
             )),
@@ -396,12 +403,15 @@ impl FullnodeConfigBuilder {
             name_service_registry_id: None,
             name_service_reverse_registry_id: None,
             transaction_deny_config: Default::default(),
+            transaction_deny_config_path: None,
             certificate_deny_config: Default::default(),
             state_debug_dump_config: Default::default(),
             state_archive_write_config: StateArchiveConfig::default(),
             state_archive_read_config: vec![],
             state_snapshot_write_config: StateSnapshotConfig::default(),
             indexer_max_subscriptions: Default::default(),
+            json_rpc_max_batch_size: Default::default(),
+            json_rpc_rate_limit_config: Default::default(),
             transaction_kv_store_read_config: Default::default(),
             transaction_kv_store_write_config: Default::default(),
             enable_experimental_rest_api: true,
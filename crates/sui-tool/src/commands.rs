@@ -3,9 +3,10 @@
 
 use crate::{
     db_tool::{execute_db_tool_command, print_db_all_tables, DbToolCommand},
-    download_db_snapshot, download_formal_snapshot, get_object, get_transaction_block,
-    make_clients, restore_from_db_checkpoint, state_sync_from_archive, verify_archive,
-    verify_archive_by_checksum, ConciseObjectOutput, GroupedObjectOutput, VerboseObjectOutput,
+    download_db_snapshot, download_formal_snapshot, download_p2p_snapshot, get_object,
+    get_transaction_block, make_clients, restore_from_db_checkpoint, state_sync_from_archive,
+    verify_archive, verify_archive_by_checksum, ConciseObjectOutput, GroupedObjectOutput,
+    VerboseObjectOutput,
 };
 use anyhow::{anyhow, Result};
 use std::env;
@@ -16,7 +17,7 @@ use sui_protocol_config::Chain;
 use sui_replay::{execute_replay_command, ReplayToolCommand};
 use telemetry_subscribers::TracingHandle;
 
-use sui_types::{base_types::*, object::Owner};
+use sui_types::{base_types::*, multiaddr::Multiaddr, object::Owner};
 
 use clap::*;
 use fastcrypto::encoding::Encoding;
@@ -260,6 +261,24 @@ pub enum ToolCommand {
         verbose: bool,
     },
 
+    /// Restore the state of a node from the latest end of epoch state snapshot served by its
+    /// peers over state sync, for networks without object storage to download snapshots from.
+    #[clap(name = "download-p2p-snapshot")]
+    DownloadP2pSnapshot {
+        #[clap(long = "genesis")]
+        genesis: PathBuf,
+        #[clap(long = "path", default_value = "/tmp")]
+        path: PathBuf,
+        /// P2P address of a node serving state snapshots, e.g. "/dns/localhost/udp/8084".
+        /// Can be specified multiple times.
+        #[clap(long = "peer", required = true)]
+        peers: Vec<Multiaddr>,
+        /// Number of parallel downloads to perform. Defaults to a reasonable
+        /// value based on number of available logical cores.
+        #[clap(long = "num-parallel-downloads")]
+        num_parallel_downloads: Option<usize>,
+    },
+
     #[clap(name = "replay")]
     Replay {
         #[arg(long = "rpc")]
@@ -632,6 +651,19 @@ impl ToolCommand {
                     .await?;
                 }
             }
+            ToolCommand::DownloadP2pSnapshot {
+                genesis,
+                path,
+                peers,
+                num_parallel_downloads,
+            } => {
+                let num_parallel_downloads = num_parallel_downloads.unwrap_or_else(|| {
+                    num_cpus::get()
+                        .checked_sub(1)
+                        .expect("Failed to get number of CPUs")
+                });
+                download_p2p_snapshot(&path, &genesis, peers, num_parallel_downloads).await?;
+            }
             ToolCommand::Replay {
                 rpc_url,
                 safety_checks,
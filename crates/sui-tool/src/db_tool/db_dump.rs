@@ -7,7 +7,7 @@ use comfy_table::{Cell, ContentArrangement, Row, Table};
 use prometheus::Registry;
 use rocksdb::MultiThreaded;
 use std::collections::{BTreeMap, HashMap};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str;
 use std::sync::Arc;
 use strum_macros::EnumString;
@@ -25,6 +25,7 @@ use sui_storage::mutex_table::RwLockTable;
 use sui_storage::IndexStoreTables;
 use sui_types::base_types::{EpochId, ObjectID};
 use tracing::info;
+use typed_store::rocks::export::ExportSummary;
 use typed_store::rocks::{default_db_options, MetricConf};
 use typed_store::traits::{Map, TableSummary};
 
@@ -257,6 +258,79 @@ pub async fn prune_checkpoints(db_path: PathBuf) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub fn export_table(
+    store_name: StoreName,
+    epoch: Option<EpochId>,
+    db_path: PathBuf,
+    table_name: &str,
+    output: &Path,
+    lower_bound: Option<Vec<u8>>,
+    upper_bound: Option<Vec<u8>>,
+) -> anyhow::Result<ExportSummary> {
+    match store_name {
+        StoreName::Validator => {
+            let epoch_tables = AuthorityEpochTables::describe_tables();
+            if epoch_tables.contains_key(table_name) {
+                let epoch = epoch.ok_or_else(|| anyhow!("--epoch is required"))?;
+                AuthorityEpochTables::open_readonly(epoch, &db_path).export(
+                    table_name,
+                    output,
+                    lower_bound,
+                    upper_bound,
+                )
+            } else {
+                AuthorityPerpetualTables::open_readonly(&db_path).export(
+                    table_name,
+                    output,
+                    lower_bound,
+                    upper_bound,
+                )
+            }
+        }
+        StoreName::Index => {
+            IndexStoreTables::get_read_only_handle(db_path, None, None, MetricConf::default())
+                .export(table_name, output, lower_bound, upper_bound)
+        }
+        StoreName::Epoch => {
+            CommitteeStoreTables::get_read_only_handle(db_path, None, None, MetricConf::default())
+                .export(table_name, output, lower_bound, upper_bound)
+        }
+    }
+    .map_err(|err| anyhow!(err.to_string()))
+}
+
+pub fn import_table(
+    store_name: StoreName,
+    epoch: Option<EpochId>,
+    db_path: PathBuf,
+    table_name: &str,
+    input: &Path,
+    batch_size: usize,
+) -> anyhow::Result<ExportSummary> {
+    match store_name {
+        StoreName::Validator => {
+            let epoch_tables = AuthorityEpochTables::describe_tables();
+            if epoch_tables.contains_key(table_name) {
+                let epoch = epoch.ok_or_else(|| anyhow!("--epoch is required"))?;
+                AuthorityEpochTables::open(epoch, &db_path, None)
+                    .import_table(table_name, input, batch_size)
+            } else {
+                AuthorityPerpetualTables::open(&db_path, None)
+                    .import_table(table_name, input, batch_size)
+            }
+        }
+        StoreName::Index => {
+            IndexStoreTables::open_tables_read_write(db_path, MetricConf::default(), None, None)
+                .import_table(table_name, input, batch_size)
+        }
+        StoreName::Epoch => {
+            CommitteeStoreTables::open_tables_read_write(db_path, MetricConf::default(), None, None)
+                .import_table(table_name, input, batch_size)
+        }
+    }
+    .map_err(|err| anyhow!(err.to_string()))
+}
+
 // TODO: condense this using macro or trait dyn skills
 pub fn dump_table(
     store_name: StoreName,
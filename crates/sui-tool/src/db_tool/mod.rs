@@ -3,7 +3,10 @@
 
 use self::db_dump::{dump_table, duplicate_objects_summary, list_tables, table_summary, StoreName};
 use self::index_search::{search_index, SearchRange};
-use crate::db_tool::db_dump::{compact, print_table_metadata, prune_checkpoints, prune_objects};
+use self::rebuild_indexes::{rebuild_indexes, IndexName};
+use crate::db_tool::db_dump::{
+    compact, export_table, import_table, print_table_metadata, prune_checkpoints, prune_objects,
+};
 use anyhow::{anyhow, bail};
 use clap::Parser;
 use narwhal_storage::NodeStorage;
@@ -20,6 +23,7 @@ use sui_types::sui_system_state::{get_sui_system_state, SuiSystemStateTrait};
 use typed_store::rocks::MetricConf;
 pub mod db_dump;
 mod index_search;
+mod rebuild_indexes;
 
 #[derive(Parser)]
 #[command(rename_all = "kebab-case")]
@@ -43,6 +47,9 @@ pub enum DbToolCommand {
     Compact,
     PruneObjects,
     PruneCheckpoints,
+    Export(ExportOptions),
+    Import(ImportOptions),
+    RebuildIndexes(RebuildIndexesOptions),
 }
 
 #[derive(Parser)]
@@ -160,6 +167,67 @@ pub struct RewindCheckpointExecutionOptions {
     checkpoint_sequence_number: u64,
 }
 
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub struct ExportOptions {
+    /// The type of store to export from
+    #[arg(long = "store", short = 's', value_enum)]
+    store_name: StoreName,
+    /// The name of the table to export
+    #[arg(long = "table-name", short = 't')]
+    table_name: String,
+    /// The file to write the export to
+    #[arg(long = "output", short = 'o')]
+    output: PathBuf,
+    /// Hex encoded inclusive lower bound of the serialized keys to export. Keys are serialized
+    /// big-endian, so e.g. an object ID selects the objects table entries from that object on.
+    #[arg(long = "start")]
+    start: Option<String>,
+    /// Hex encoded exclusive upper bound of the serialized keys to export
+    #[arg(long = "end")]
+    end: Option<String>,
+    /// The epoch to use when loading AuthorityEpochTables.
+    #[arg(long = "epoch", short = 'e')]
+    epoch: Option<EpochId>,
+}
+
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub struct ImportOptions {
+    /// The type of store to import into
+    #[arg(long = "store", short = 's', value_enum)]
+    store_name: StoreName,
+    /// The name of the table to import into. Must be the table the export was taken from.
+    #[arg(long = "table-name", short = 't')]
+    table_name: String,
+    /// The export file to import
+    #[arg(long = "input", short = 'i')]
+    input: PathBuf,
+    /// Number of entries written per write batch
+    #[arg(long, default_value_t = 10_000)]
+    batch_size: usize,
+    /// The epoch to use when loading AuthorityEpochTables.
+    #[arg(long = "epoch", short = 'e')]
+    epoch: Option<EpochId>,
+}
+
+#[derive(Parser)]
+#[command(rename_all = "kebab-case")]
+pub struct RebuildIndexesOptions {
+    /// Path to the genesis blob of the network
+    #[arg(long)]
+    genesis: PathBuf,
+    /// An index to rebuild. Can be repeated. Defaults to all of them
+    #[arg(long = "index", value_enum)]
+    indexes: Vec<IndexName>,
+    /// Number of chunks of work processed in parallel
+    #[arg(long, default_value_t = num_cpus::get())]
+    parallelism: usize,
+    /// Discard the progress of an interrupted rebuild and start over
+    #[arg(long)]
+    restart: bool,
+}
+
 pub async fn execute_db_tool_command(db_path: PathBuf, cmd: DbToolCommand) -> anyhow::Result<()> {
     match cmd {
         DbToolCommand::ListTables => print_db_all_tables(db_path),
@@ -192,6 +260,46 @@ pub async fn execute_db_tool_command(db_path: PathBuf, cmd: DbToolCommand) -> an
         DbToolCommand::Compact => compact(db_path),
         DbToolCommand::PruneObjects => prune_objects(db_path).await,
         DbToolCommand::PruneCheckpoints => prune_checkpoints(db_path).await,
+        DbToolCommand::Export(d) => {
+            let summary = export_table(
+                d.store_name,
+                d.epoch,
+                db_path,
+                &d.table_name,
+                &d.output,
+                d.start.map(|k| parse_key_bound(&k)).transpose()?,
+                d.end.map(|k| parse_key_bound(&k)).transpose()?,
+            )?;
+            println!(
+                "Exported {} entries of {} to {:?}, checksum {}",
+                summary.num_entries,
+                d.table_name,
+                d.output,
+                hex::encode(summary.checksum)
+            );
+            Ok(())
+        }
+        DbToolCommand::Import(d) => {
+            let summary = import_table(
+                d.store_name,
+                d.epoch,
+                db_path,
+                &d.table_name,
+                &d.input,
+                d.batch_size,
+            )?;
+            println!(
+                "Imported {} entries into {} from {:?}, checksum {}",
+                summary.num_entries,
+                d.table_name,
+                d.input,
+                hex::encode(summary.checksum)
+            );
+            Ok(())
+        }
+        DbToolCommand::RebuildIndexes(d) => {
+            rebuild_indexes(db_path, &d.genesis, d.indexes, d.parallelism, d.restart).await
+        }
         DbToolCommand::IndexSearchKeyRange(rg) => {
             let res = search_index(
                 db_path,
@@ -219,6 +327,11 @@ pub async fn execute_db_tool_command(db_path: PathBuf, cmd: DbToolCommand) -> an
     }
 }
 
+fn parse_key_bound(key: &str) -> anyhow::Result<Vec<u8>> {
+    hex::decode(key.strip_prefix("0x").unwrap_or(key))
+        .map_err(|e| anyhow!("Invalid hex encoded key bound {key}: {e}"))
+}
+
 pub fn print_db_all_tables(db_path: PathBuf) -> anyhow::Result<()> {
     list_tables(db_path)?.iter().for_each(|t| println!("{}", t));
     Ok(())
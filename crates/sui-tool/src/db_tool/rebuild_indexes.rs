@@ -0,0 +1,94 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use clap::ValueEnum;
+use prometheus::Registry;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use sui_config::genesis::Genesis;
+use sui_core::authority::authority_store_tables::AuthorityPerpetualTables;
+use sui_core::authority::AuthorityStore;
+use sui_core::checkpoints::CheckpointStore;
+use sui_core::epoch::committee_store::CommitteeStore;
+use sui_core::rebuild_indexes::rebuild_indexes as rebuild_index_groups;
+use sui_storage::indexes::IndexGroup;
+use sui_storage::IndexStoreTables;
+use tracing::info;
+use typed_store::rocks::MetricConf;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum IndexName {
+    Owner,
+    Coin,
+    DynamicField,
+    ObjectsByType,
+    /// The transaction and event indexes
+    Transactions,
+}
+
+impl From<IndexName> for IndexGroup {
+    fn from(name: IndexName) -> Self {
+        match name {
+            IndexName::Owner => IndexGroup::Owner,
+            IndexName::Coin => IndexGroup::Coin,
+            IndexName::DynamicField => IndexGroup::DynamicField,
+            IndexName::ObjectsByType => IndexGroup::ObjectsByType,
+            IndexName::Transactions => IndexGroup::Transactions,
+        }
+    }
+}
+
+/// Regenerate the secondary indexes of the node whose DB is at `db_path` from its object and
+/// checkpoint stores. The node must be stopped.
+pub async fn rebuild_indexes(
+    db_path: PathBuf,
+    genesis: &Path,
+    indexes: Vec<IndexName>,
+    parallelism: usize,
+    restart: bool,
+) -> anyhow::Result<()> {
+    let mut groups: Vec<IndexGroup> = if indexes.is_empty() {
+        IndexGroup::ALL.to_vec()
+    } else {
+        indexes.into_iter().map(IndexGroup::from).collect()
+    };
+    groups.sort();
+    groups.dedup();
+
+    let genesis = Genesis::load(genesis)?;
+    let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&db_path.join("store"), None));
+    let committee_store = Arc::new(CommitteeStore::new(
+        db_path.join("epochs"),
+        &genesis.committee()?,
+        None,
+    ));
+    let store = AuthorityStore::open(
+        perpetual_db,
+        &genesis,
+        &committee_store,
+        usize::MAX,
+        false,
+        &Registry::default(),
+    )
+    .await?;
+    let checkpoint_store = CheckpointStore::new(&db_path.join("checkpoints"));
+    let index_tables = Arc::new(IndexStoreTables::open_tables_read_write(
+        db_path.join("indexes"),
+        MetricConf::default(),
+        None,
+        None,
+    ));
+
+    info!("Rebuilding {groups:?} indexes in {:?}", db_path.display());
+    rebuild_index_groups(
+        store,
+        checkpoint_store,
+        index_tables,
+        &groups,
+        parallelism,
+        restart,
+    )
+    .await?;
+    println!("Rebuilt and verified {groups:?} indexes");
+    Ok(())
+}
@@ -18,10 +18,12 @@ use std::{fs, io};
 use sui_config::{genesis::Genesis, NodeConfig};
 use sui_core::authority_client::{AuthorityAPI, NetworkAuthorityClient};
 use sui_network::default_mysten_network_config;
+use sui_network::state_sync::{sync_checkpoint_summaries_from_peers, StateSnapshotDownloader};
 use sui_protocol_config::Chain;
 use sui_sdk::SuiClientBuilder;
 use sui_types::accumulator::Accumulator;
-use sui_types::crypto::AuthorityPublicKeyBytes;
+use sui_types::crypto::{get_key_pair, AuthorityPublicKeyBytes, KeypairTraits, NetworkKeyPair};
+use sui_types::digests::ChainIdentifier;
 use sui_types::multiaddr::Multiaddr;
 use sui_types::object::ObjectFormatOptions;
 use sui_types::{base_types::*, object::Owner};
@@ -39,12 +41,14 @@ use prometheus::Registry;
 use sui_archival::reader::{ArchiveReader, ArchiveReaderMetrics};
 use sui_archival::{verify_archive_with_checksums, verify_archive_with_genesis_config};
 use sui_config::node::ArchiveReaderConfig;
+use sui_config::p2p::StateSyncConfig;
 use sui_core::authority::authority_store_tables::AuthorityPerpetualTables;
 use sui_core::authority::AuthorityStore;
 use sui_core::checkpoints::CheckpointStore;
 use sui_core::db_checkpoint_handler::SUCCESS_MARKER;
 use sui_core::epoch::committee_store::CommitteeStore;
 use sui_core::storage::RocksDbStore;
+use sui_snapshot::p2p::PerpetualTablesSnapshotSink;
 use sui_snapshot::reader::StateSnapshotReaderV1;
 use sui_snapshot::setup_db_state;
 use sui_storage::object_store::util::{copy_file, get_path};
@@ -62,6 +66,9 @@ use typed_store::rocks::MetricConf;
 pub mod commands;
 pub mod db_tool;
 
+// Snapshot chunks can take a while to transfer, being up to 128MB each.
+const P2P_SNAPSHOT_REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
 // This functions requires at least one of genesis or fullnode_rpc to be `Some`.
 async fn make_clients(
     genesis: Option<PathBuf>,
@@ -958,6 +965,124 @@ pub async fn download_formal_snapshot(
     Ok(())
 }
 
+pub async fn download_p2p_snapshot(
+    path: &Path,
+    genesis: &Path,
+    peers: Vec<Multiaddr>,
+    num_parallel_downloads: usize,
+) -> Result<(), anyhow::Error> {
+    let path = path.join("staging").to_path_buf();
+    if path.exists() {
+        fs::remove_dir_all(path.clone())?;
+    }
+    let perpetual_db = Arc::new(AuthorityPerpetualTables::open(&path.join("store"), None));
+    let genesis = Genesis::load(genesis)?;
+    let genesis_committee = genesis.committee()?;
+    let committee_store = Arc::new(CommitteeStore::new(
+        path.join("epochs"),
+        &genesis_committee,
+        None,
+    ));
+    let checkpoint_store = Arc::new(CheckpointStore::open_tables_read_write(
+        path.join("checkpoints"),
+        MetricConf::default(),
+        None,
+        None,
+    ));
+    let store = AuthorityStore::open(
+        perpetual_db.clone(),
+        &genesis,
+        &committee_store,
+        usize::MAX,
+        false,
+        &Registry::default(),
+    )
+    .await?;
+    let state_sync_store =
+        RocksDbStore::new(store, committee_store.clone(), checkpoint_store.clone());
+    checkpoint_store.insert_checkpoint_contents(genesis.checkpoint_contents().clone())?;
+    checkpoint_store.insert_verified_checkpoint(&genesis.checkpoint())?;
+    checkpoint_store.update_highest_synced_checkpoint(&genesis.checkpoint())?;
+
+    let chain_identifier = ChainIdentifier::from(*genesis.checkpoint().digest());
+    let mut anemo_config = anemo::Config::default();
+    // Snapshot chunks are whole object files, which are larger than the default max frame size.
+    anemo_config.max_frame_size = Some(1 << 30);
+    let network = anemo::Network::bind("0.0.0.0:0")
+        .server_name(&format!("sui-{chain_identifier}"))
+        .private_key(get_key_pair::<NetworkKeyPair>().1.private().0.to_bytes())
+        .config(anemo_config)
+        .start(anemo::Router::new())?;
+    let mut peer_ids = vec![];
+    for peer in peers {
+        let address = peer
+            .to_anemo_address()
+            .map_err(|e| anyhow!("Invalid peer address {peer}: {e}"))?;
+        peer_ids.push(network.connect(address).await?);
+    }
+
+    let downloader = StateSnapshotDownloader::new(
+        network.clone(),
+        peer_ids.clone(),
+        P2P_SNAPSHOT_REQUEST_TIMEOUT,
+        num_parallel_downloads,
+    );
+    let epoch = downloader
+        .latest_epoch()
+        .await
+        .ok_or_else(|| anyhow!("None of the peers serve a state snapshot"))?;
+    eprintln!("Beginning p2p snapshot restore to end of epoch {}", epoch);
+
+    // The summaries up to the end of the epoch are needed to verify the snapshot against the root
+    // state digest committed to by its last checkpoint.
+    let highest_verified_checkpoint = sync_checkpoint_summaries_from_peers(
+        &network,
+        state_sync_store,
+        &peer_ids,
+        &StateSyncConfig::default(),
+    )
+    .await?;
+    let last_checkpoint = checkpoint_store
+        .get_epoch_last_checkpoint(epoch)?
+        .ok_or_else(|| {
+            anyhow!(
+                "Failed to sync the last checkpoint of epoch {}, highest verified checkpoint is {}",
+                epoch,
+                highest_verified_checkpoint.sequence_number
+            )
+        })?;
+
+    let sink = PerpetualTablesSnapshotSink::new(perpetual_db.clone(), usize::MAX);
+    let root_accumulator = downloader.download(&last_checkpoint, &sink).await?;
+    eprintln!("P2p snapshot state verification completed successfully!");
+
+    // Checkpoints past the end of the epoch stay verified, and their contents will be synced by
+    // the node once it starts.
+    checkpoint_store.update_highest_synced_checkpoint(&last_checkpoint)?;
+    checkpoint_store.update_highest_executed_checkpoint(&last_checkpoint)?;
+    checkpoint_store.update_highest_pruned_checkpoint(&last_checkpoint)?;
+    setup_db_state(
+        epoch,
+        root_accumulator,
+        perpetual_db,
+        checkpoint_store,
+        committee_store,
+    )
+    .await?;
+
+    let new_path = path.parent().unwrap().join("live");
+    if new_path.exists() {
+        fs::remove_dir_all(new_path.clone())?;
+    }
+    fs::rename(&path, &new_path)?;
+    info!(
+        "Successfully restored state from p2p snapshot at end of epoch {}",
+        epoch
+    );
+
+    Ok(())
+}
+
 pub async fn download_db_snapshot(
     path: &Path,
     epoch: u64,
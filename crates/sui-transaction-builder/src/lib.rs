@@ -34,6 +34,8 @@ use sui_types::transaction::{
 };
 use sui_types::{coin, fp_ensure, SUI_FRAMEWORK_PACKAGE_ID, SUI_SYSTEM_PACKAGE_ID};
 
+pub mod sponsor;
+
 #[async_trait]
 pub trait DataReader {
     async fn get_owned_objects(
@@ -615,6 +617,49 @@ impl TransactionBuilder {
         )
     }
 
+    /// Build a transaction that runs `kind` on behalf of `sender`, with gas paid for by `sponsor`.
+    /// If `gas` is not provided, a coin owned by the sponsor with at least `gas_budget` value, and
+    /// not otherwise used by the transaction, is selected.
+    pub async fn sponsor_transaction(
+        &self,
+        sender: SuiAddress,
+        kind: TransactionKind,
+        sponsor: SuiAddress,
+        gas: Option<ObjectID>,
+        gas_budget: u64,
+    ) -> anyhow::Result<TransactionData> {
+        ensure!(
+            sender != sponsor,
+            "Sponsor must be different from the sender of the transaction"
+        );
+        ensure!(
+            matches!(kind, TransactionKind::ProgrammableTransaction(_)),
+            "Only programmable transactions can be sponsored"
+        );
+
+        let inputs = kind
+            .input_objects()?
+            .iter()
+            .flat_map(|obj| match obj {
+                InputObjectKind::ImmOrOwnedMoveObject((id, _, _)) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        let gas_price = self.0.get_reference_gas_price().await?;
+        let gas = self
+            .select_gas(sponsor, gas, gas_budget, inputs, gas_price)
+            .await?;
+
+        Ok(TransactionData::new_with_gas_coins_allow_sponsor(
+            kind,
+            sender,
+            vec![gas],
+            gas_budget,
+            gas_price,
+            sponsor,
+        ))
+    }
+
     pub async fn batch_transaction(
         &self,
         signer: SuiAddress,
@@ -0,0 +1,279 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for sponsored transactions, where the gas for a transaction built by its sender is
+//! paid for by a different address (the sponsor).
+//!
+//! The flow is:
+//! 1. The sender builds the [`TransactionKind`] they want to run and wraps it in a
+//!    [`SponsorRequest`].
+//! 2. The sponsor checks the request against their [`SponsorPolicy`], picks gas coins to pay for
+//!    it (see [`crate::TransactionBuilder::sponsor_transaction`]), and signs the resulting
+//!    [`TransactionData`].
+//! 3. The sender signs the same [`TransactionData`], and submits it with both signatures.
+
+use std::collections::BTreeSet;
+
+use anyhow::{bail, ensure};
+use serde::{Deserialize, Serialize};
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::transaction::{
+    Argument, Command, InputObjectKind, TransactionData, TransactionDataAPI, TransactionKind,
+};
+
+/// What the sender of a sponsored transaction sends to their sponsor: the transaction they want
+/// to run, without any gas payment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SponsorRequest {
+    pub sender: SuiAddress,
+    pub kind: TransactionKind,
+    pub gas_budget: u64,
+}
+
+/// Limits on what a sponsor is willing to pay for. The default policy accepts any programmable
+/// transaction that does not touch the sponsor's gas coin, or publish or upgrade packages.
+#[derive(Debug, Clone, Default)]
+pub struct SponsorPolicy {
+    /// The largest gas budget the sponsor will accept.
+    pub max_gas_budget: Option<u64>,
+    /// Whether commands may use the gas coin as an argument (e.g. to split or transfer it), which
+    /// would spend the sponsor's funds on more than just gas.
+    pub allow_gas_coin_use: bool,
+    /// Whether the transaction may publish or upgrade packages.
+    pub allow_publish: bool,
+    /// If set, the only packages that `MoveCall` commands may call into.
+    pub allowed_packages: Option<BTreeSet<ObjectID>>,
+}
+
+impl SponsorRequest {
+    pub fn new(sender: SuiAddress, kind: TransactionKind, gas_budget: u64) -> Self {
+        Self {
+            sender,
+            kind,
+            gas_budget,
+        }
+    }
+
+    /// The request for sponsoring `data`, dropping its gas payment.
+    pub fn from_data(data: &TransactionData) -> Self {
+        Self::new(data.sender(), data.kind().clone(), data.gas_budget())
+    }
+}
+
+impl SponsorPolicy {
+    /// Check that the sponsor is willing to pay for `request`.
+    pub fn check_request(&self, request: &SponsorRequest) -> anyhow::Result<()> {
+        self.check_budget(request.gas_budget)?;
+        self.check_kind(&request.kind)
+    }
+
+    /// Check that the sponsor is willing to sign `data`, which must be paid for by `sponsor`.
+    pub fn check_transaction(
+        &self,
+        data: &TransactionData,
+        sponsor: SuiAddress,
+    ) -> anyhow::Result<()> {
+        ensure!(
+            data.gas_owner() == sponsor,
+            "Gas is owned by {}, not the sponsor {sponsor}",
+            data.gas_owner(),
+        );
+        ensure!(
+            data.sender() != sponsor,
+            "Sender and sponsor are the same address: {sponsor}",
+        );
+
+        self.check_budget(data.gas_budget())?;
+        self.check_kind(data.kind())?;
+
+        // The sponsor's gas coins can only be used to pay for gas.
+        let gas: BTreeSet<_> = data.gas().iter().map(|(id, _, _)| *id).collect();
+        for input in data.kind().input_objects()? {
+            if let InputObjectKind::ImmOrOwnedMoveObject((id, _, _)) = input {
+                ensure!(
+                    !gas.contains(&id),
+                    "Gas payment object {id} is also an input to the transaction",
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_budget(&self, budget: u64) -> anyhow::Result<()> {
+        if let Some(max) = self.max_gas_budget {
+            ensure!(
+                budget <= max,
+                "Gas budget {budget} exceeds the sponsor's maximum of {max}",
+            );
+        }
+        Ok(())
+    }
+
+    fn check_kind(&self, kind: &TransactionKind) -> anyhow::Result<()> {
+        let TransactionKind::ProgrammableTransaction(pt) = kind else {
+            bail!("Only programmable transactions can be sponsored");
+        };
+
+        for (ix, command) in pt.commands.iter().enumerate() {
+            match command {
+                Command::MoveCall(call) => {
+                    if let Some(allowed) = &self.allowed_packages {
+                        ensure!(
+                            allowed.contains(&call.package),
+                            "Command {ix} calls {}::{}::{}, which is not in an allowed package",
+                            call.package,
+                            call.module,
+                            call.function,
+                        );
+                    }
+                }
+
+                Command::Publish(_, _) | Command::Upgrade(_, _, _, _) => {
+                    ensure!(
+                        self.allow_publish,
+                        "Command {ix} publishes or upgrades a package",
+                    );
+                }
+
+                Command::TransferObjects(_, _)
+                | Command::SplitCoins(_, _)
+                | Command::MergeCoins(_, _)
+                | Command::MakeMoveVec(_, _) => {}
+            }
+
+            let uses_gas_coin = command_arguments(command).contains(&Argument::GasCoin);
+            ensure!(
+                self.allow_gas_coin_use || !uses_gas_coin,
+                "Command {ix} uses the sponsor's gas coin",
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// All the arguments that `command` reads from.
+fn command_arguments(command: &Command) -> Vec<Argument> {
+    match command {
+        Command::MoveCall(call) => call.arguments.clone(),
+        Command::TransferObjects(objs, recipient) => {
+            objs.iter().copied().chain(Some(*recipient)).collect()
+        }
+        Command::SplitCoins(coin, amounts) => Some(*coin)
+            .into_iter()
+            .chain(amounts.iter().copied())
+            .collect(),
+        Command::MergeCoins(target, sources) => Some(*target)
+            .into_iter()
+            .chain(sources.iter().copied())
+            .collect(),
+        Command::MakeMoveVec(_, elems) => elems.clone(),
+        Command::Publish(_, _) => vec![],
+        Command::Upgrade(_, _, _, ticket) => vec![*ticket],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_types::base_types::random_object_ref;
+    use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+
+    use super::*;
+
+    fn sponsored(kind: TransactionKind, gas_budget: u64) -> (TransactionData, SuiAddress) {
+        let sponsor = SuiAddress::random_for_testing_only();
+        let data = TransactionData::new_with_gas_coins_allow_sponsor(
+            kind,
+            SuiAddress::random_for_testing_only(),
+            vec![random_object_ref()],
+            gas_budget,
+            1000,
+            sponsor,
+        );
+        (data, sponsor)
+    }
+
+    fn transfer_kind() -> TransactionKind {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder
+            .transfer_object(SuiAddress::random_for_testing_only(), random_object_ref())
+            .unwrap();
+        TransactionKind::programmable(builder.finish())
+    }
+
+    #[test]
+    fn test_accepts_plain_transaction() {
+        let (data, sponsor) = sponsored(transfer_kind(), 10_000_000);
+        SponsorPolicy::default()
+            .check_transaction(&data, sponsor)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rejects_gas_coin_use() {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder
+            .pay_sui(vec![SuiAddress::random_for_testing_only()], vec![100])
+            .unwrap();
+        let (data, sponsor) =
+            sponsored(TransactionKind::programmable(builder.finish()), 10_000_000);
+
+        let err = SponsorPolicy::default()
+            .check_transaction(&data, sponsor)
+            .unwrap_err();
+        assert!(err.to_string().contains("gas coin"), "{err}");
+
+        let policy = SponsorPolicy {
+            allow_gas_coin_use: true,
+            ..Default::default()
+        };
+        policy.check_transaction(&data, sponsor).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_large_budget() {
+        let request = SponsorRequest::new(
+            SuiAddress::random_for_testing_only(),
+            transfer_kind(),
+            10_000_000,
+        );
+
+        let policy = SponsorPolicy {
+            max_gas_budget: Some(1_000_000),
+            ..Default::default()
+        };
+        assert!(policy.check_request(&request).is_err());
+    }
+
+    #[test]
+    fn test_rejects_disallowed_package() {
+        let mut builder = ProgrammableTransactionBuilder::new();
+        builder.command(Command::move_call(
+            ObjectID::random(),
+            "m".parse().unwrap(),
+            "f".parse().unwrap(),
+            vec![],
+            vec![],
+        ));
+        let request = SponsorRequest::new(
+            SuiAddress::random_for_testing_only(),
+            TransactionKind::programmable(builder.finish()),
+            10_000_000,
+        );
+
+        let policy = SponsorPolicy {
+            allowed_packages: Some(BTreeSet::from([ObjectID::random()])),
+            ..Default::default()
+        };
+        assert!(policy.check_request(&request).is_err());
+    }
+
+    #[test]
+    fn test_rejects_wrong_sponsor() {
+        let (data, _) = sponsored(transfer_kind(), 10_000_000);
+        assert!(SponsorPolicy::default()
+            .check_transaction(&data, SuiAddress::random_for_testing_only())
+            .is_err());
+    }
+}
@@ -3,6 +3,7 @@
 
 use core::fmt;
 use std::{
+    collections::BTreeSet,
     fmt::{Debug, Display, Formatter, Write},
     path::PathBuf,
     sync::Arc,
@@ -45,6 +46,7 @@ use sui_replay::ReplayToolCommand;
 use sui_sdk::sui_client_config::{SuiClientConfig, SuiEnv};
 use sui_sdk::wallet_context::WalletContext;
 use sui_sdk::SuiClient;
+use sui_transaction_builder::sponsor::{SponsorPolicy, SponsorRequest};
 use sui_types::{
     base_types::{ObjectID, SequenceNumber, SuiAddress},
     crypto::SignatureScheme,
@@ -57,7 +59,9 @@ use sui_types::{
     object::Owner,
     parse_sui_type_tag,
     signature::GenericSignature,
-    transaction::{SenderSignedData, Transaction, TransactionData, TransactionDataAPI},
+    transaction::{
+        SenderSignedData, Transaction, TransactionData, TransactionDataAPI, TransactionKind,
+    },
 };
 
 use tabled::{
@@ -441,6 +445,79 @@ pub enum SuiClientCommands {
         serialize_signed_transaction: bool,
     },
 
+    /// Combine the sender's and sponsor's signatures for a sponsored transaction, and execute it.
+    /// The sender's signature is produced using the keystore if it is not provided.
+    #[clap(name = "sponsor-combine")]
+    SponsorCombine {
+        /// BCS serialized transaction data bytes, as produced by `sponsor-sign`, as base-64
+        /// encoded string.
+        #[clap(long)]
+        tx_bytes: String,
+
+        /// The sponsor's Base64 encoded signature `flag || signature || pubkey`.
+        #[clap(long)]
+        sponsor_signature: String,
+
+        /// The sender's Base64 encoded signature `flag || signature || pubkey`.
+        #[clap(long)]
+        sender_signature: Option<String>,
+
+        /// Instead of executing the transaction, serialize the bcs bytes of the signed transaction data
+        /// (SenderSignedData) using base64 encoding, and print out the string.
+        #[clap(long, required = false)]
+        serialize_signed_transaction: bool,
+    },
+
+    /// Turn a transaction into a request for another address to sponsor its gas. The output is
+    /// passed to `sponsor-sign` by the sponsor.
+    #[clap(name = "sponsor-request")]
+    #[clap(group(ArgGroup::new("tx").required(true).args(&["tx_bytes", "tx_kind"])))]
+    SponsorRequest {
+        /// BCS serialized transaction data bytes (e.g. from --serialize-unsigned-transaction), as
+        /// base-64 encoded string. Its gas payment is discarded.
+        #[clap(long)]
+        tx_bytes: Option<String>,
+
+        /// BCS serialized transaction kind bytes, as base-64 encoded string. The transaction is
+        /// sent from the active address.
+        #[clap(long)]
+        tx_kind: Option<String>,
+
+        /// Gas budget for the transaction, required with --tx-kind.
+        #[clap(long)]
+        gas_budget: Option<u64>,
+    },
+
+    /// Sponsor a transaction requested with `sponsor-request`, paying for its gas from the active
+    /// address. The request is checked against the given limits before signing.
+    #[clap(name = "sponsor-sign")]
+    SponsorSign {
+        /// The sponsor request, as produced by `sponsor-request`.
+        #[clap(long)]
+        request: String,
+
+        /// ID of the gas object for gas payment, in 20 bytes Hex string
+        /// If not provided, a gas object with at least the requested gas budget will be selected
+        #[clap(long)]
+        gas: Option<ObjectID>,
+
+        /// Refuse to sponsor transactions with a larger gas budget than this.
+        #[clap(long)]
+        max_gas_budget: Option<u64>,
+
+        /// Only sponsor transactions whose Move calls are to these packages.
+        #[clap(long, num_args(1..))]
+        allowed_packages: Option<Vec<ObjectID>>,
+
+        /// Allow the transaction to use the sponsor's gas coin in its commands.
+        #[clap(long)]
+        allow_gas_coin_use: bool,
+
+        /// Allow the transaction to publish or upgrade packages.
+        #[clap(long)]
+        allow_publish: bool,
+    },
+
     /// Switch active address and network(e.g., devnet, local rpc server)
     #[clap(name = "switch")]
     Switch {
@@ -1212,6 +1289,116 @@ impl SuiClientCommands {
                     SplitCoin
                 )
             }
+            SuiClientCommands::SponsorRequest {
+                tx_bytes,
+                tx_kind,
+                gas_budget,
+            } => {
+                let request = match (tx_bytes, tx_kind) {
+                    (Some(tx_bytes), None) => {
+                        let data: TransactionData = bcs::from_bytes(&decode_base64(&tx_bytes)?)?;
+                        let mut request = SponsorRequest::from_data(&data);
+                        if let Some(gas_budget) = gas_budget {
+                            request.gas_budget = gas_budget;
+                        }
+                        request
+                    }
+                    (None, Some(tx_kind)) => {
+                        let kind: TransactionKind = bcs::from_bytes(&decode_base64(&tx_kind)?)?;
+                        let gas_budget = gas_budget.ok_or_else(|| {
+                            anyhow!("--gas-budget is required when requesting sponsorship for a transaction kind")
+                        })?;
+                        SponsorRequest::new(context.active_address()?, kind, gas_budget)
+                    }
+                    _ => {
+                        return Err(anyhow!(
+                            "Exactly one of `tx-bytes` and `tx-kind` must be present for sponsor-request command."
+                        ));
+                    }
+                };
+
+                SuiClientCommandResult::SponsorRequest(request)
+            }
+
+            SuiClientCommands::SponsorSign {
+                request,
+                gas,
+                max_gas_budget,
+                allowed_packages,
+                allow_gas_coin_use,
+                allow_publish,
+            } => {
+                let request: SponsorRequest = bcs::from_bytes(&decode_base64(&request)?)?;
+                let policy = SponsorPolicy {
+                    max_gas_budget,
+                    allow_gas_coin_use,
+                    allow_publish,
+                    allowed_packages: allowed_packages.map(BTreeSet::from_iter),
+                };
+                policy.check_request(&request)?;
+
+                let sponsor = context.active_address()?;
+                let client = context.get_client().await?;
+                let data = client
+                    .transaction_builder()
+                    .sponsor_transaction(
+                        request.sender,
+                        request.kind,
+                        sponsor,
+                        gas,
+                        request.gas_budget,
+                    )
+                    .await?;
+                policy.check_transaction(&data, sponsor)?;
+
+                let signature = context.config.keystore.sign_secure(
+                    &sponsor,
+                    &data,
+                    Intent::sui_transaction(),
+                )?;
+
+                SuiClientCommandResult::SponsorSign(SponsoredTransactionOutput {
+                    tx_bytes: Base64::encode(bcs::to_bytes(&data)?),
+                    sponsor_signature: Base64::encode(signature.as_ref()),
+                })
+            }
+
+            SuiClientCommands::SponsorCombine {
+                tx_bytes,
+                sponsor_signature,
+                sender_signature,
+                serialize_signed_transaction,
+            } => {
+                let data: TransactionData = bcs::from_bytes(&decode_base64(&tx_bytes)?)?;
+                ensure!(
+                    data.gas_owner() != data.sender(),
+                    "Transaction is not sponsored: its gas is paid for by its sender"
+                );
+
+                let sender_signature = match sender_signature {
+                    Some(sig) => decode_signature(&sig)?,
+                    None => context
+                        .config
+                        .keystore
+                        .sign_secure(&data.sender(), &data, Intent::sui_transaction())?
+                        .into(),
+                };
+
+                let sponsor_signature = decode_signature(&sponsor_signature)?;
+                let transaction = Transaction::from_generic_sig_data(
+                    data,
+                    Intent::sui_transaction(),
+                    vec![sender_signature, sponsor_signature],
+                );
+
+                if serialize_signed_transaction {
+                    SuiClientCommandResult::SerializedSignedTransaction(transaction.into_data())
+                } else {
+                    let response = context.execute_transaction_may_fail(transaction).await?;
+                    SuiClientCommandResult::ExecuteSignedTx(response)
+                }
+            }
+
             SuiClientCommands::MergeCoin {
                 primary_coin,
                 coin_to_merge,
@@ -1623,6 +1810,17 @@ impl Display for SuiClientCommandResult {
                     fastcrypto::encoding::Base64::encode(bcs::to_bytes(sender_signed_tx).unwrap())
                 )?;
             }
+            SuiClientCommandResult::SponsorRequest(request) => {
+                writeln!(
+                    writer,
+                    "{}",
+                    Base64::encode(bcs::to_bytes(request).unwrap())
+                )?;
+            }
+            SuiClientCommandResult::SponsorSign(output) => {
+                writeln!(writer, "Transaction: {}", output.tx_bytes)?;
+                writeln!(writer, "Sponsor signature: {}", output.sponsor_signature)?;
+            }
             SuiClientCommandResult::Transfer(response) => {
                 write!(writer, "{}", write_transaction_response(response)?)?;
             }
@@ -1757,6 +1955,15 @@ async fn construct_move_call_transaction(
         .await
 }
 
+fn decode_base64(s: &str) -> Result<Vec<u8>, anyhow::Error> {
+    Base64::decode(s).map_err(|e| anyhow!("Invalid Base64 string: {e}"))
+}
+
+/// Parse a Base64 encoded signature `flag || signature || pubkey`.
+fn decode_signature(s: &str) -> Result<GenericSignature, anyhow::Error> {
+    GenericSignature::from_bytes(&decode_base64(s)?).map_err(|e| anyhow!(e))
+}
+
 fn convert_number_to_string(value: Value) -> Value {
     match value {
         Value::Number(n) => Value::String(n.to_string()),
@@ -1912,6 +2119,13 @@ pub struct AddressesOutput {
     pub addresses: Vec<SuiAddress>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SponsoredTransactionOutput {
+    pub tx_bytes: String,
+    pub sponsor_signature: String,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DynamicFieldOutput {
@@ -2054,6 +2268,8 @@ pub enum SuiClientCommandResult {
     SerializedSignedTransaction(SenderSignedData),
     SerializedUnsignedTransaction(TransactionData),
     SplitCoin(SuiTransactionBlockResponse),
+    SponsorRequest(SponsorRequest),
+    SponsorSign(SponsoredTransactionOutput),
     Switch(SwitchResponse),
     SyncClientState,
     TransactionBlock(SuiTransactionBlockResponse),
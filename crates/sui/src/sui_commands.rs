@@ -10,6 +10,7 @@ use crate::validator_commands::SuiValidatorCommand;
 use anyhow::{anyhow, bail};
 use clap::*;
 use fastcrypto::traits::KeyPair;
+use move_binary_format::CompiledModule;
 use move_package::BuildConfig;
 use rand::rngs::OsRng;
 use std::io::{stderr, stdout, Write};
@@ -25,6 +26,7 @@ use sui_config::{
 use sui_config::{
     SUI_BENCHMARK_GENESIS_GAS_KEYSTORE_FILENAME, SUI_GENESIS_FILENAME, SUI_KEYSTORE_FILENAME,
 };
+use sui_json_rpc_types::{SuiObjectDataOptions, SuiRawData};
 use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
 use sui_move::{self, execute_move_command};
 use sui_move_build::SuiPackageHooks;
@@ -35,6 +37,7 @@ use sui_swarm_config::genesis_config::{GenesisConfig, DEFAULT_NUMBER_OF_AUTHORIT
 use sui_swarm_config::network_config::NetworkConfig;
 use sui_swarm_config::network_config_builder::ConfigBuilder;
 use sui_swarm_config::node_config_builder::FullnodeConfigBuilder;
+use sui_types::base_types::ObjectID;
 use sui_types::crypto::{SignatureScheme, SuiKeyPair};
 use tracing::info;
 
@@ -314,6 +317,21 @@ impl SuiCommand {
                 }
                 Ok(())
             }
+            SuiCommand::Move {
+                package_path,
+                build_config,
+                cmd: sui_move::Command::CheckUpgrade(check_upgrade),
+            } if check_upgrade.package_id.is_some() => {
+                let package_id = check_upgrade.package_id.expect("Package ID is set");
+                let config = check_upgrade
+                    .client_config
+                    .clone()
+                    .unwrap_or(sui_config_dir()?.join(SUI_CLIENT_CONFIG));
+                prompt_if_no_config(&config, false).await?;
+                let context = WalletContext::new(&config, None, None).await?;
+                let previous_modules = fetch_package_modules(&context, package_id).await?;
+                check_upgrade.execute_with_previous(package_path, build_config, &previous_modules)
+            }
             SuiCommand::Move {
                 package_path,
                 build_config,
@@ -324,6 +342,31 @@ impl SuiCommand {
     }
 }
 
+/// Fetches and deserializes the modules of the on-chain package `package_id`.
+async fn fetch_package_modules(
+    context: &WalletContext,
+    package_id: ObjectID,
+) -> Result<Vec<CompiledModule>, anyhow::Error> {
+    let data = context
+        .get_client()
+        .await?
+        .read_api()
+        .get_object_with_options(package_id, SuiObjectDataOptions::new().with_bcs())
+        .await?
+        .into_object()?;
+    let Some(SuiRawData::Package(package)) = data.bcs else {
+        bail!("Object {package_id} is not a package");
+    };
+    package
+        .module_map
+        .iter()
+        .map(|(name, bytes)| {
+            CompiledModule::deserialize_with_defaults(bytes)
+                .map_err(|e| anyhow!("Failed to deserialize module {name} of {package_id}: {e}"))
+        })
+        .collect()
+}
+
 async fn genesis(
     from_config: Option<PathBuf>,
     write_config: Option<PathBuf>,
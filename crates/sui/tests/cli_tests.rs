@@ -8,12 +8,14 @@ use std::str::FromStr;
 use std::{fmt::Write, fs::read_dir, path::PathBuf, str, thread, time::Duration};
 
 use expect_test::expect;
+use fastcrypto::encoding::{Base64, Encoding};
 use move_package::BuildConfig as MoveBuildConfig;
 use serde_json::json;
 use sui_test_transaction_builder::batch_make_transfer_transactions;
 use sui_types::object::Owner;
+use sui_types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use sui_types::transaction::{
-    TEST_ONLY_GAS_UNIT_FOR_GENERIC, TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS,
+    TransactionKind, TEST_ONLY_GAS_UNIT_FOR_GENERIC, TEST_ONLY_GAS_UNIT_FOR_OBJECT_BASICS,
     TEST_ONLY_GAS_UNIT_FOR_PUBLISH, TEST_ONLY_GAS_UNIT_FOR_SPLIT_COIN,
     TEST_ONLY_GAS_UNIT_FOR_TRANSFER,
 };
@@ -2283,6 +2285,91 @@ async fn test_serialize_tx() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+#[sim_test]
+async fn test_sponsored_transaction() -> Result<(), anyhow::Error> {
+    let mut test_cluster = TestClusterBuilder::new().build().await;
+    let rgp = test_cluster.get_reference_gas_price().await;
+    let sender = test_cluster.get_address_0();
+    let sponsor = test_cluster.get_address_1();
+    let recipient = SuiAddress::random_for_testing_only();
+    let context = &mut test_cluster.wallet;
+
+    // The sender transfers one of their objects, without paying for gas.
+    let object = context
+        .get_one_gas_object_owned_by_address(sender)
+        .await?
+        .unwrap();
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_object(recipient, object)?;
+    let kind = TransactionKind::programmable(builder.finish());
+    let gas_budget = rgp * TEST_ONLY_GAS_UNIT_FOR_TRANSFER;
+
+    context.config.active_address = Some(sender);
+    let SuiClientCommandResult::SponsorRequest(request) = SuiClientCommands::SponsorRequest {
+        tx_bytes: None,
+        tx_kind: Some(Base64::encode(bcs::to_bytes(&kind)?)),
+        gas_budget: Some(gas_budget),
+    }
+    .execute(context)
+    .await?
+    else {
+        panic!("Expected a sponsor request");
+    };
+    assert_eq!(request.sender, sender);
+    let request = Base64::encode(bcs::to_bytes(&request)?);
+
+    // The sponsor refuses to pay more than they are willing to.
+    context.config.active_address = Some(sponsor);
+    let res = SuiClientCommands::SponsorSign {
+        request: request.clone(),
+        gas: None,
+        max_gas_budget: Some(gas_budget - 1),
+        allowed_packages: None,
+        allow_gas_coin_use: false,
+        allow_publish: false,
+    }
+    .execute(context)
+    .await;
+    assert!(res.is_err());
+
+    let SuiClientCommandResult::SponsorSign(output) = SuiClientCommands::SponsorSign {
+        request,
+        gas: None,
+        max_gas_budget: Some(gas_budget),
+        allowed_packages: None,
+        allow_gas_coin_use: false,
+        allow_publish: false,
+    }
+    .execute(context)
+    .await?
+    else {
+        panic!("Expected a sponsored transaction");
+    };
+
+    // The sender co-signs and executes the transaction.
+    context.config.active_address = Some(sender);
+    let SuiClientCommandResult::ExecuteSignedTx(response) = SuiClientCommands::SponsorCombine {
+        tx_bytes: output.tx_bytes,
+        sponsor_signature: output.sponsor_signature,
+        sender_signature: None,
+        serialize_signed_transaction: false,
+    }
+    .execute(context)
+    .await?
+    else {
+        panic!("Expected an executed transaction");
+    };
+
+    let effects = response.effects.unwrap();
+    assert!(effects.status().is_ok());
+    assert_eq!(
+        effects.gas_object().owner,
+        Owner::AddressOwner(sponsor),
+        "Gas should be paid by the sponsor"
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_stake_with_none_amount() -> Result<(), anyhow::Error> {
     let mut test_cluster = TestClusterBuilder::new().build().await;
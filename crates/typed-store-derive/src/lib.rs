@@ -502,6 +502,19 @@ pub fn derive_dbmap_utils_general(input: TokenStream) -> TokenStream {
                 ) -> #secondary_db_map_struct_name #generics {
                 #secondary_db_map_struct_name::open_tables_read_only(primary_path, with_secondary_path, metric_conf, global_db_options_override)
             }
+
+            /// Import an export file produced by `TypedStoreDebug::export_table` into the given table
+            pub fn import_table(&self, table_name: &str, path: &std::path::Path, batch_size: usize) -> eyre::Result<typed_store::rocks::export::ExportSummary> {
+                Ok(match table_name {
+                    #(
+                        stringify!(#field_names) => {
+                            self.#field_names.import_from_file(path, batch_size)?
+                        }
+                    )*
+
+                    _ => eyre::bail!("No such table name: {}", table_name),
+                })
+            }
         }
 
 
@@ -595,6 +608,22 @@ pub fn derive_dbmap_utils_general(input: TokenStream) -> TokenStream {
                 })
             }
 
+            /// Export the entries of a table to a file
+            /// Tables must be opened in read only mode using `open_tables_read_only`
+            pub fn export(&self, table_name: &str, path: &std::path::Path, lower_bound: Option<Vec<u8>>,
+                upper_bound: Option<Vec<u8>>) -> eyre::Result<typed_store::rocks::export::ExportSummary> {
+                Ok(match table_name {
+                    #(
+                        stringify!(#field_names) => {
+                            typed_store::traits::Map::try_catch_up_with_primary(&self.#field_names)?;
+                            self.#field_names.export_to_file(path, lower_bound, upper_bound)?
+                        }
+                    )*
+
+                    _ => eyre::bail!("No such table name: {}", table_name),
+                })
+            }
+
             pub fn describe_tables() -> std::collections::BTreeMap<String, (String, String)> {
                 vec![#(
                     (stringify!(#field_names).to_owned(), (stringify!(#key_names).to_owned(), stringify!(#value_names).to_owned())),
@@ -632,6 +661,16 @@ pub fn derive_dbmap_utils_general(input: TokenStream) -> TokenStream {
                     self.table_summary(table_name.as_str())
                 }
 
+                fn export_table(
+                    &self,
+                    table_name: String,
+                    path: &std::path::Path,
+                    lower_bound: Option<Vec<u8>>,
+                    upper_bound: Option<Vec<u8>>,
+                ) -> eyre::Result<typed_store::rocks::export::ExportSummary> {
+                    self.export(table_name.as_str(), path, lower_bound, upper_bound)
+                }
+
 
         }
 
@@ -963,6 +1002,27 @@ pub fn derive_sallydb_general(input: TokenStream) -> TokenStream {
                 })
             }
 
+            /// Export the entries of a table to a file
+            /// Tables must be opened in read only mode using `open_tables_read_only`
+            pub fn export(&self, table_name: &str, path: &std::path::Path, lower_bound: Option<Vec<u8>>,
+                upper_bound: Option<Vec<u8>>) -> eyre::Result<typed_store::rocks::export::ExportSummary> {
+                Ok(match table_name {
+                    #(
+                        stringify!(#field_names) => {
+                            match &self.#field_names {
+                                SallyColumn::RocksDB((db_map, typed_store::sally::SallyConfig { mode: typed_store::sally::SallyRunMode::FallbackToDB })) => {
+                                    typed_store::traits::Map::try_catch_up_with_primary(db_map)?;
+                                    db_map.export_to_file(path, lower_bound, upper_bound)?
+                                }
+                                _ => unimplemented!(),
+                            }
+                        }
+                    )*
+
+                    _ => eyre::bail!("No such table name: {}", table_name),
+                })
+            }
+
             pub fn describe_tables() -> std::collections::BTreeMap<String, (String, String)> {
                 vec![#(
                     (stringify!(#field_names).to_owned(), (stringify!(#key_names).to_owned(), stringify!(#value_names).to_owned())),
@@ -1000,6 +1060,16 @@ pub fn derive_sallydb_general(input: TokenStream) -> TokenStream {
                     self.table_summary(table_name.as_str())
                 }
 
+                fn export_table(
+                    &self,
+                    table_name: String,
+                    path: &std::path::Path,
+                    lower_bound: Option<Vec<u8>>,
+                    upper_bound: Option<Vec<u8>>,
+                ) -> eyre::Result<typed_store::rocks::export::ExportSummary> {
+                    self.export(table_name.as_str(), path, lower_bound, upper_bound)
+                }
+
         }
 
     })
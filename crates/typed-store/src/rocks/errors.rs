@@ -23,6 +23,8 @@ pub enum TypedStoreError {
     MetricsReporting,
     #[error("Transaction should be retried")]
     RetryableTransactionError,
+    #[error("table export error: {0}")]
+    ExportError(String),
 }
 
 #[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug, Error)]
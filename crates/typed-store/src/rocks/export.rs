@@ -0,0 +1,313 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Portable export and import of `DBMap` tables.
+//!
+//! An export file starts with `EXPORT_FILE_MAGIC`, followed by a zstd stream of length-prefixed
+//! BCS `ExportRecord`s: a header describing the table, one record per key-value pair in key
+//! order, and a trailer with the number of entries and a Blake2b256 checksum over the entries.
+//! Keys and values are copied as they are stored, so an export can be imported losslessly into a
+//! table of the same type on another machine, or with another version of RocksDB.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use fastcrypto::encoding::{Encoding, Hex};
+use fastcrypto::hash::{Blake2b256, HashFunction};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::info;
+
+use super::{DBMap, TypedStoreError};
+
+pub const EXPORT_FILE_MAGIC: [u8; 8] = *b"TSEXPRT1";
+
+const EXPORT_COMPRESSION_LEVEL: i32 = 3;
+
+/// Describes the table an export file was taken from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExportHeader {
+    pub table_name: String,
+    pub key_type: String,
+    pub value_type: String,
+    /// Inclusive lower bound of the serialized keys of the export, if any.
+    pub lower_bound: Option<Vec<u8>>,
+    /// Exclusive upper bound of the serialized keys of the export, if any.
+    pub upper_bound: Option<Vec<u8>>,
+}
+
+/// Number of entries of an export file, and the checksum over them.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    pub num_entries: u64,
+    pub checksum: [u8; 32],
+}
+
+#[derive(Serialize, Deserialize)]
+enum ExportRecord {
+    Header(ExportHeader),
+    Entry { key: Vec<u8>, value: Vec<u8> },
+    Trailer(ExportSummary),
+}
+
+fn export_error(e: impl std::fmt::Display) -> TypedStoreError {
+    TypedStoreError::ExportError(e.to_string())
+}
+
+/// Streams the entries of a table into an export file.
+pub struct TableExportWriter<W: Write> {
+    encoder: zstd::Encoder<'static, W>,
+    hasher: Blake2b256,
+    num_entries: u64,
+}
+
+impl<W: Write> TableExportWriter<W> {
+    pub fn new(mut writer: W, header: ExportHeader) -> Result<Self, TypedStoreError> {
+        writer.write_all(&EXPORT_FILE_MAGIC).map_err(export_error)?;
+        let encoder = zstd::Encoder::new(writer, EXPORT_COMPRESSION_LEVEL).map_err(export_error)?;
+        let mut export_writer = Self {
+            encoder,
+            hasher: Blake2b256::default(),
+            num_entries: 0,
+        };
+        export_writer.write_record(&ExportRecord::Header(header))?;
+        Ok(export_writer)
+    }
+
+    fn write_record(&mut self, record: &ExportRecord) -> Result<Vec<u8>, TypedStoreError> {
+        let bytes = bcs::to_bytes(record)?;
+        self.encoder
+            .write_all(&(bytes.len() as u32).to_be_bytes())
+            .and_then(|_| self.encoder.write_all(&bytes))
+            .map_err(export_error)?;
+        Ok(bytes)
+    }
+
+    /// Appends a serialized key-value pair. Entries must be appended in key order.
+    pub fn append(&mut self, key: &[u8], value: &[u8]) -> Result<(), TypedStoreError> {
+        let bytes = self.write_record(&ExportRecord::Entry {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        })?;
+        self.hasher.update(&bytes);
+        self.num_entries += 1;
+        Ok(())
+    }
+
+    /// Writes the trailer and flushes the export.
+    pub fn finish(mut self) -> Result<ExportSummary, TypedStoreError> {
+        let summary = ExportSummary {
+            num_entries: self.num_entries,
+            checksum: std::mem::take(&mut self.hasher).finalize().digest,
+        };
+        self.write_record(&ExportRecord::Trailer(summary.clone()))?;
+        let mut writer = self.encoder.finish().map_err(export_error)?;
+        writer.flush().map_err(export_error)?;
+        Ok(summary)
+    }
+}
+
+/// Reads the entries of an export file, verifying the checksum once all entries are read.
+pub struct TableExportReader<R: Read> {
+    decoder: zstd::Decoder<'static, BufReader<R>>,
+    header: ExportHeader,
+    hasher: Blake2b256,
+    num_entries: u64,
+    summary: Option<ExportSummary>,
+}
+
+impl TableExportReader<File> {
+    pub fn open(path: &Path) -> Result<Self, TypedStoreError> {
+        Self::new(File::open(path).map_err(export_error)?)
+    }
+}
+
+impl<R: Read> TableExportReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, TypedStoreError> {
+        let mut magic = [0u8; EXPORT_FILE_MAGIC.len()];
+        reader.read_exact(&mut magic).map_err(export_error)?;
+        if magic != EXPORT_FILE_MAGIC {
+            return Err(export_error("not a typed-store export file"));
+        }
+        let mut decoder = zstd::Decoder::new(reader).map_err(export_error)?;
+        let header = match read_record(&mut decoder)? {
+            ExportRecord::Header(header) => header,
+            _ => return Err(export_error("export file does not start with a header")),
+        };
+        Ok(Self {
+            decoder,
+            header,
+            hasher: Blake2b256::default(),
+            num_entries: 0,
+            summary: None,
+        })
+    }
+
+    pub fn header(&self) -> &ExportHeader {
+        &self.header
+    }
+
+    /// Returns the verified summary of the export, once all of its entries were read.
+    pub fn summary(&self) -> Option<&ExportSummary> {
+        self.summary.as_ref()
+    }
+
+    fn next_entry(&mut self) -> Result<Option<(Vec<u8>, Vec<u8>)>, TypedStoreError> {
+        if self.summary.is_some() {
+            return Ok(None);
+        }
+        let bytes = read_record_bytes(&mut self.decoder)?;
+        match bcs::from_bytes(&bytes)? {
+            ExportRecord::Entry { key, value } => {
+                self.hasher.update(&bytes);
+                self.num_entries += 1;
+                Ok(Some((key, value)))
+            }
+            ExportRecord::Trailer(summary) => {
+                let hasher = std::mem::take(&mut self.hasher);
+                let actual = ExportSummary {
+                    num_entries: self.num_entries,
+                    checksum: hasher.finalize().digest,
+                };
+                if actual != summary {
+                    return Err(export_error(format!(
+                        "export file is corrupted: expected {} entries with checksum {}, found {} entries with checksum {}",
+                        summary.num_entries,
+                        Hex::encode(summary.checksum),
+                        actual.num_entries,
+                        Hex::encode(actual.checksum),
+                    )));
+                }
+                self.summary = Some(summary);
+                Ok(None)
+            }
+            ExportRecord::Header(_) => Err(export_error("unexpected header in export file")),
+        }
+    }
+}
+
+impl<R: Read> Iterator for TableExportReader<R> {
+    type Item = Result<(Vec<u8>, Vec<u8>), TypedStoreError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().transpose()
+    }
+}
+
+fn read_record_bytes(reader: &mut impl Read) -> Result<Vec<u8>, TypedStoreError> {
+    let mut len = [0u8; 4];
+    reader
+        .read_exact(&mut len)
+        .map_err(|e| export_error(format!("export file is truncated: {e}")))?;
+    // Read into a growable buffer, as the length comes from the file and a corrupted one must not
+    // make us allocate up to 4GiB upfront.
+    let len = u32::from_be_bytes(len) as u64;
+    let mut bytes = vec![];
+    reader
+        .take(len)
+        .read_to_end(&mut bytes)
+        .map_err(|e| export_error(format!("export file is truncated: {e}")))?;
+    if bytes.len() as u64 != len {
+        return Err(export_error(format!(
+            "export file is truncated: expected a record of {len} bytes, found {}",
+            bytes.len()
+        )));
+    }
+    Ok(bytes)
+}
+
+fn read_record(reader: &mut impl Read) -> Result<ExportRecord, TypedStoreError> {
+    Ok(bcs::from_bytes(&read_record_bytes(reader)?)?)
+}
+
+impl<K, V: Serialize + DeserializeOwned> DBMap<K, V> {
+    /// Exports the entries of this table whose serialized keys are within
+    /// `[lower_bound, upper_bound)` to a compressed and checksummed file at `path`.
+    pub fn export_to_file(
+        &self,
+        path: &Path,
+        lower_bound: Option<Vec<u8>>,
+        upper_bound: Option<Vec<u8>>,
+    ) -> Result<ExportSummary, TypedStoreError> {
+        let header = ExportHeader {
+            table_name: self.cf.clone(),
+            key_type: std::any::type_name::<K>().to_string(),
+            value_type: std::any::type_name::<V>().to_string(),
+            lower_bound: lower_bound.clone(),
+            upper_bound: upper_bound.clone(),
+        };
+        let file = BufWriter::new(File::create(path).map_err(export_error)?);
+        let mut writer = TableExportWriter::new(file, header)?;
+
+        let mut readopts = self.opts.readopts();
+        if let Some(lower_bound) = lower_bound {
+            readopts.set_iterate_lower_bound(lower_bound);
+        }
+        if let Some(upper_bound) = upper_bound {
+            readopts.set_iterate_upper_bound(upper_bound);
+        }
+        let mut db_iter = self.rocksdb.raw_iterator_cf(&self.cf(), readopts);
+        db_iter.seek_to_first();
+        while db_iter.valid() {
+            let key = db_iter.key().expect("Valid iterator failed to get key");
+            let value = db_iter.value().expect("Valid iterator failed to get value");
+            writer.append(key, value)?;
+            db_iter.next();
+        }
+        db_iter.status()?;
+        let summary = writer.finish()?;
+        info!(
+            "Exported {} entries of table {} to {}",
+            summary.num_entries,
+            self.cf,
+            path.display()
+        );
+        Ok(summary)
+    }
+
+    /// Imports an export file taken from a table of the same type, `batch_size` entries per
+    /// write batch. The whole file is verified before anything is written, so a corrupted export
+    /// leaves the table untouched.
+    pub fn import_from_file(
+        &self,
+        path: &Path,
+        batch_size: usize,
+    ) -> Result<ExportSummary, TypedStoreError> {
+        let mut reader = TableExportReader::open(path)?;
+        let header = reader.header().clone();
+        if header.table_name != self.cf {
+            return Err(export_error(format!(
+                "export of table {} can't be imported into table {}",
+                header.table_name, self.cf
+            )));
+        }
+        for entry in reader.by_ref() {
+            let (_, value) = entry?;
+            bcs::from_bytes::<V>(&value)?;
+        }
+        let summary = reader
+            .summary()
+            .cloned()
+            .expect("Summary is set after reading all entries");
+
+        let mut batch = self.batch();
+        let mut pending = 0;
+        for entry in TableExportReader::open(path)? {
+            let (key, value) = entry?;
+            batch.batch.put_cf(&self.cf(), key, value);
+            pending += 1;
+            if pending >= batch_size {
+                std::mem::replace(&mut batch, self.batch()).write()?;
+                pending = 0;
+            }
+        }
+        batch.write()?;
+        info!(
+            "Imported {} entries into table {} from {}",
+            summary.num_entries,
+            self.cf,
+            path.display()
+        );
+        Ok(summary)
+    }
+}
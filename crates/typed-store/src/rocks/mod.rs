@@ -1,6 +1,7 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 pub mod errors;
+pub mod export;
 pub(crate) mod iter;
 pub(crate) mod keys;
 pub(crate) mod safe_iter;
@@ -1118,6 +1118,81 @@ async fn open_as_secondary_test() {
     assert_eq!(secondary_db.get(&0).unwrap(), Some("10".to_string()));
 }
 
+#[tokio::test]
+async fn test_export_import() {
+    let source = open_map::<_, u32, String>(temp_dir(), Some("table"), false);
+    source
+        .multi_insert((0..100).map(|i| (i, i.to_string())))
+        .expect("Failed to multi-insert");
+    let export_path = temp_dir().join("table.export");
+    let summary = source
+        .export_to_file(
+            &export_path,
+            Some(be_fix_int_ser(&10u32).unwrap()),
+            Some(be_fix_int_ser(&20u32).unwrap()),
+        )
+        .expect("Failed to export table");
+    assert_eq!(summary.num_entries, 10);
+
+    let target = open_map::<_, u32, String>(temp_dir(), Some("table"), false);
+    let imported = target
+        .import_from_file(&export_path, 3)
+        .expect("Failed to import table");
+    assert_eq!(imported, summary);
+    let entries: Vec<_> = target.unbounded_iter().collect();
+    assert_eq!(
+        entries,
+        (10..20).map(|i| (i, i.to_string())).collect::<Vec<_>>()
+    );
+
+    // Tables of another name or value type are rejected without writing anything.
+    let other = open_map::<_, u32, String>(temp_dir(), Some("other"), false);
+    assert!(other.import_from_file(&export_path, 3).is_err());
+    let wrong_type = open_map::<_, u32, u64>(temp_dir(), Some("table"), false);
+    assert!(wrong_type.import_from_file(&export_path, 3).is_err());
+    assert!(wrong_type.is_empty());
+}
+
+#[tokio::test]
+async fn test_import_corrupted_export() {
+    let export_path = temp_dir().join("table.export");
+    let header = export::ExportHeader {
+        table_name: "table".to_string(),
+        key_type: "u32".to_string(),
+        value_type: "String".to_string(),
+        lower_bound: None,
+        upper_bound: None,
+    };
+    let mut writer = export::TableExportWriter::new(
+        std::fs::File::create(&export_path).unwrap(),
+        header.clone(),
+    )
+    .unwrap();
+    for i in 0..100u32 {
+        writer
+            .append(
+                &be_fix_int_ser(&i).unwrap(),
+                &bcs::to_bytes(&i.to_string()).unwrap(),
+            )
+            .unwrap();
+    }
+    writer.finish().unwrap();
+    // Drop the end of the file, as an interrupted copy would.
+    let bytes = std::fs::read(&export_path).unwrap();
+    std::fs::write(&export_path, &bytes[..bytes.len() - 16]).unwrap();
+
+    let reader = export::TableExportReader::open(&export_path).unwrap();
+    assert_eq!(reader.header(), &header);
+    assert!(reader.collect::<Result<Vec<_>, _>>().is_err());
+
+    let db = open_map::<_, u32, String>(temp_dir(), Some("table"), false);
+    assert!(matches!(
+        db.import_from_file(&export_path, 10),
+        Err(TypedStoreError::ExportError(_))
+    ));
+    assert!(db.is_empty());
+}
+
 #[derive(Serialize, Deserialize, Copy, Clone)]
 struct ObjectWithRefCount {
     value: i64,
@@ -1,9 +1,11 @@
 // Copyright (c) Mysten Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
+use crate::rocks::export::ExportSummary;
 use crate::TypedStoreError;
 use async_trait::async_trait;
 use serde::{de::DeserializeOwned, Serialize};
 use std::ops::RangeBounds;
+use std::path::Path;
 use std::{borrow::Borrow, collections::BTreeMap, error::Error};
 
 pub trait Map<'a, K, V>
@@ -207,4 +209,14 @@ pub trait TypedStoreDebug {
 
     /// Return table summary of the input table
     fn table_summary(&self, table_name: String) -> eyre::Result<TableSummary>;
+
+    /// Export the entries of a table whose serialized keys are within `[lower_bound, upper_bound)`
+    /// to a compressed and checksummed file, which can be imported with `DBMap::import_from_file`
+    fn export_table(
+        &self,
+        table_name: String,
+        path: &Path,
+        lower_bound: Option<Vec<u8>>,
+        upper_bound: Option<Vec<u8>>,
+    ) -> eyre::Result<ExportSummary>;
 }
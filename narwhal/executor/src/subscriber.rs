@@ -367,6 +367,10 @@ impl Subscriber {
                 }
             };
             for (digest, batch) in batches {
+                // Workers decompress the batches they return, but consensus output must only
+                // contain uncompressed batches, so make sure of it. Compressed batches that do
+                // not decompress are rejected when they are deserialized.
+                let batch = batch.decompress();
                 Self::record_fetched_batch_metrics(inner, &batch, &digest);
                 fetched_batches.insert(digest, batch);
             }
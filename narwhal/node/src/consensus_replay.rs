@@ -0,0 +1,365 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline replay of consensus over the certificates persisted by a node. Certificates are read
+//! from the `CertificateStore` and fed to a fresh `Bullshark` instance, and the sub-dags it commits
+//! are compared against the commits recorded in the node's `ConsensusStore`. This makes it possible
+//! to check changes to the consensus logic against real validator data without running a network.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use config::Committee;
+use primary::consensus::{
+    Bullshark, ConsensusError, ConsensusMetrics, ConsensusState, LeaderSchedule, LeaderSwapTable,
+};
+use prometheus::Registry;
+use serde::Serialize;
+use storage::{ConsensusStore, NodeStorage};
+use sui_protocol_config::ProtocolConfig;
+use types::{
+    Certificate, CommittedSubDag, ConsensusCommit, ConsensusCommitV2, Round, SequenceNumber,
+};
+
+/// The outcome of replaying consensus over a store.
+#[derive(Debug, Default, Serialize)]
+pub struct ReplayReport {
+    /// The number of certificates fed to consensus.
+    pub certificates: usize,
+    /// The number of commits recorded in the store.
+    pub recorded_commits: usize,
+    /// The number of commits produced by the replay.
+    pub replayed_commits: usize,
+    /// Differences between the recorded and the replayed commits, by sub-dag index.
+    pub mismatches: Vec<Mismatch>,
+}
+
+/// A property of a commit that differs between the recorded and the replayed sequence.
+#[derive(Debug, Serialize)]
+pub struct Mismatch {
+    pub sub_dag_index: SequenceNumber,
+    pub field: &'static str,
+    pub recorded: String,
+    pub replayed: String,
+}
+
+impl ReplayReport {
+    /// Whether the replay reproduced the recorded commits exactly.
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Feed the certificates of `store` to Bullshark in the order the node's consensus processed
+/// them, and compare the sub-dags it commits with the ones recorded in `store`.
+///
+/// Bullshark commits a leader as soon as it sees enough support for it, so the order certificates
+/// arrive in matters: a certificate that reached the node late, after certificates of later rounds,
+/// can turn a direct commit into an indirect one. Consensus records the order it processes
+/// certificates in, and the replay follows it. Stores written before that order was recorded fall
+/// back to (round, origin) order, in which case a mismatch is not by itself evidence of a
+/// consensus bug.
+///
+/// Bullshark persists its commits as it goes, so they are written to `scratch`, which must be an
+/// empty store distinct from the one in `store`. The committee, protocol config, gc depth and
+/// schedule length must match the ones the node ran with for the epoch of the store.
+///
+/// Replayed commits past the last recorded one are counted but not reported as mismatches, since
+/// the node may have stored certificates that consensus had not processed yet when it stopped.
+pub fn replay_consensus(
+    store: &NodeStorage,
+    scratch: Arc<ConsensusStore>,
+    committee: &Committee,
+    protocol_config: &ProtocolConfig,
+    gc_depth: Round,
+    num_sub_dags_per_schedule: u64,
+) -> Result<ReplayReport, ConsensusError> {
+    let recorded: BTreeMap<SequenceNumber, ConsensusCommit> = store
+        .consensus_store
+        .read_committed_sub_dags_from(&0)?
+        .into_iter()
+        .map(|commit| (commit.sub_dag_index(), commit))
+        .collect();
+
+    let metrics = Arc::new(ConsensusMetrics::new(&Registry::new()));
+    let mut state = ConsensusState::new(metrics.clone(), gc_depth);
+    let mut bullshark = Bullshark::new(
+        committee.clone(),
+        scratch,
+        protocol_config.clone(),
+        metrics,
+        num_sub_dags_per_schedule,
+        LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
+    );
+
+    let mut report = ReplayReport {
+        recorded_commits: recorded.len(),
+        ..Default::default()
+    };
+
+    let mut process = |certificate: Certificate| -> Result<(), ConsensusError> {
+        report.certificates += 1;
+        let (_, sub_dags) = bullshark.process_certificate(&mut state, certificate)?;
+        for sub_dag in sub_dags {
+            report.replayed_commits += 1;
+            if let Some(commit) = recorded.get(&sub_dag.sub_dag_index) {
+                compare(commit, &sub_dag, &mut report.mismatches);
+            }
+        }
+        Ok(())
+    };
+
+    let order = store.consensus_store.read_certificate_order();
+    if !order.is_empty() {
+        for digest in order {
+            if let Some(certificate) = store.certificate_store.read(digest)? {
+                process(certificate)?;
+            }
+        }
+    } else {
+        // Read one round at a time, as the store can hold a whole epoch of certificates.
+        let rounds = store.certificate_store.origins_after_round(0)?;
+        for (round, origins) in rounds {
+            for origin in origins {
+                if let Some(certificate) = store.certificate_store.read_by_index(origin, round)? {
+                    process(certificate)?;
+                }
+            }
+        }
+    }
+
+    // Recorded commits that the replay never reached.
+    for index in recorded.keys() {
+        if *index > report.replayed_commits as SequenceNumber {
+            report.mismatches.push(Mismatch {
+                sub_dag_index: *index,
+                field: "commit",
+                recorded: "committed".to_string(),
+                replayed: "not committed".to_string(),
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+fn compare(recorded: &ConsensusCommit, sub_dag: &CommittedSubDag, mismatches: &mut Vec<Mismatch>) {
+    let replayed = ConsensusCommit::V2(ConsensusCommitV2::from_sub_dag(sub_dag));
+    let mut check = |field, recorded: String, replayed: String| {
+        if recorded != replayed {
+            mismatches.push(Mismatch {
+                sub_dag_index: sub_dag.sub_dag_index,
+                field,
+                recorded,
+                replayed,
+            });
+        }
+    };
+
+    check(
+        "leader",
+        format!("{:?}", recorded.leader()),
+        format!("{:?}", replayed.leader()),
+    );
+    check(
+        "leader_round",
+        recorded.leader_round().to_string(),
+        replayed.leader_round().to_string(),
+    );
+    check(
+        "certificates",
+        format!("{:?}", recorded.certificates()),
+        format!("{:?}", replayed.certificates()),
+    );
+
+    let scores = |commit: &ConsensusCommit| {
+        let scores = commit.reputation_score();
+        let by_authority: BTreeMap<_, _> = scores.scores_per_authority.into_iter().collect();
+        format!(
+            "{by_authority:?} (final of schedule: {})",
+            scores.final_of_schedule
+        )
+    };
+    check("reputation_score", scores(recorded), scores(&replayed));
+
+    // Commits written before timestamps were recorded have none to compare against.
+    let recorded_timestamp = recorded.commit_timestamp();
+    if recorded_timestamp != 0 {
+        check(
+            "commit_timestamp",
+            recorded_timestamp.to_string(),
+            replayed.commit_timestamp().to_string(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastcrypto::hash::Hash;
+    use std::collections::{BTreeSet, HashMap, VecDeque};
+    use test_utils::{
+        latest_protocol_version, make_optimal_certificates, mock_certificate, temp_dir,
+        CommitteeFixture,
+    };
+    use types::ReputationScores;
+
+    const GC_DEPTH: Round = 50;
+    const NUM_SUB_DAGS_PER_SCHEDULE: u64 = 5;
+
+    /// Feed `certificates` to consensus the way a node would, with consensus writing to the node's
+    /// store, and optionally recording the order they were processed in.
+    fn record(
+        store: &NodeStorage,
+        committee: &Committee,
+        protocol_config: &ProtocolConfig,
+        certificates: impl IntoIterator<Item = Certificate>,
+        record_order: bool,
+    ) -> Vec<CommittedSubDag> {
+        let metrics = Arc::new(ConsensusMetrics::new(&Registry::new()));
+        let mut state = ConsensusState::new(metrics.clone(), GC_DEPTH);
+        let mut bullshark = Bullshark::new(
+            committee.clone(),
+            store.consensus_store.clone(),
+            protocol_config.clone(),
+            metrics,
+            NUM_SUB_DAGS_PER_SCHEDULE,
+            LeaderSchedule::new(committee.clone(), LeaderSwapTable::default()),
+        );
+        let mut committed = vec![];
+        for (index, certificate) in certificates.into_iter().enumerate() {
+            if record_order {
+                store
+                    .consensus_store
+                    .write_certificate_order(index as SequenceNumber, certificate.digest())
+                    .unwrap();
+            }
+            let (_, sub_dags) = bullshark
+                .process_certificate(&mut state, certificate)
+                .unwrap();
+            committed.extend(sub_dags);
+        }
+        committed
+    }
+
+    fn replay(
+        store: &NodeStorage,
+        committee: &Committee,
+        protocol_config: &ProtocolConfig,
+    ) -> ReplayReport {
+        replay_consensus(
+            store,
+            Arc::new(ConsensusStore::new_for_tests()),
+            committee,
+            protocol_config,
+            GC_DEPTH,
+            NUM_SUB_DAGS_PER_SCHEDULE,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_replay_consensus() {
+        let fixture = CommitteeFixture::builder().build();
+        let committee = fixture.committee();
+        let protocol_config = latest_protocol_version();
+        let store = NodeStorage::reopen(temp_dir(), None);
+
+        let genesis: BTreeSet<_> = Certificate::genesis(&protocol_config, &committee)
+            .iter()
+            .map(|c| c.digest())
+            .collect();
+        let ids: Vec<_> = fixture.authorities().map(|a| a.id()).collect();
+        let (certificates, _) =
+            make_optimal_certificates(&committee, &protocol_config, 1..=30, &genesis, &ids);
+        store
+            .certificate_store
+            .write_all(certificates.iter().cloned())
+            .unwrap();
+
+        // Without a recorded order, the replay falls back to (round, origin) order.
+        let committed = record(&store, &committee, &protocol_config, certificates, false);
+        // Enough commits to go through a schedule change.
+        assert!(committed.len() as u64 > NUM_SUB_DAGS_PER_SCHEDULE);
+
+        let report = replay(&store, &committee, &protocol_config);
+        assert!(report.is_consistent(), "{:?}", report.mismatches);
+        assert_eq!(report.certificates, 30 * ids.len());
+        assert_eq!(report.recorded_commits, committed.len());
+        assert_eq!(report.replayed_commits, committed.len());
+
+        // Tamper with the scores recorded for the first commit.
+        let first = &committed[0];
+        let tampered = CommittedSubDag::new(
+            first.certificates.clone(),
+            first.leader.clone(),
+            first.sub_dag_index,
+            ReputationScores::default(),
+            None,
+        );
+        store
+            .consensus_store
+            .write_consensus_state(&HashMap::new(), &tampered)
+            .unwrap();
+
+        let report = replay(&store, &committee, &protocol_config);
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].sub_dag_index, first.sub_dag_index);
+        assert_eq!(report.mismatches[0].field, "reputation_score");
+    }
+
+    #[test]
+    fn test_replay_consensus_late_certificate() {
+        let fixture = CommitteeFixture::builder().build();
+        let committee = fixture.committee();
+        let protocol_config = latest_protocol_version();
+        let store = NodeStorage::reopen(temp_dir(), None);
+
+        let genesis: BTreeSet<_> = Certificate::genesis(&protocol_config, &committee)
+            .iter()
+            .map(|c| c.digest())
+            .collect();
+        let ids: Vec<_> = fixture.authorities().map(|a| a.id()).collect();
+
+        // Rounds 1 to 3 are complete, but no certificate of round 4 refers to the last authority's
+        // certificate of round 3, which reaches the node only after round 6.
+        let (mut certificates, parents) =
+            make_optimal_certificates(&committee, &protocol_config, 1..=3, &genesis, &ids);
+        let late = certificates.pop_back().unwrap();
+        assert_eq!(late.round(), 3);
+        let parents: BTreeSet<_> = parents
+            .into_iter()
+            .filter(|digest| *digest != late.digest())
+            .collect();
+
+        let mut round_4 = VecDeque::new();
+        for id in &ids {
+            let (_, certificate) =
+                mock_certificate(&committee, &protocol_config, *id, 4, parents.clone());
+            round_4.push_back(certificate);
+        }
+        let parents = round_4.iter().map(|c| c.digest()).collect();
+        certificates.extend(round_4);
+
+        let (rest, _) =
+            make_optimal_certificates(&committee, &protocol_config, 5..=30, &parents, &ids);
+        let mut rest: Vec<_> = rest.into_iter().collect();
+        let late_position = rest.iter().position(|c| c.round() == 7).unwrap();
+        rest.insert(late_position, late);
+        certificates.extend(rest);
+
+        store
+            .certificate_store
+            .write_all(certificates.iter().cloned())
+            .unwrap();
+        let committed = record(&store, &committee, &protocol_config, certificates, true);
+        assert!(committed.len() as u64 > NUM_SUB_DAGS_PER_SCHEDULE);
+
+        // The replay follows the recorded order rather than the (round, origin) one.
+        let report = replay(&store, &committee, &protocol_config);
+        assert!(report.is_consistent(), "{:?}", report.mismatches);
+        assert_eq!(report.certificates, 30 * ids.len());
+        assert_eq!(report.recorded_commits, committed.len());
+        assert_eq!(report.replayed_commits, committed.len());
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright (c) Mysten Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Export of the certificate DAG persisted by a node, for offline inspection. Reads certificates
+//! from the `CertificateStore` and commits from the `ConsensusStore` of a (copied) node database,
+//! and reconstructs the leader schedule, including swaps made according to reputation scores.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use config::{AuthorityIdentifier, Committee};
+use fastcrypto::hash::Hash;
+use primary::consensus::LeaderSwapTable;
+use serde::Serialize;
+use storage::{NodeStorage, StoreResult};
+use sui_protocol_config::ProtocolConfig;
+use types::{Certificate, CertificateDigest, ConsensusCommit, HeaderAPI, Round, SequenceNumber};
+
+/// The part of the DAG between two rounds (inclusive), along with the commits and leader
+/// elections that happened in that range.
+#[derive(Debug, Serialize)]
+pub struct DagExport {
+    pub from_round: Round,
+    pub to_round: Round,
+    pub vertices: Vec<Vertex>,
+    pub leaders: Vec<LeaderElection>,
+    pub commits: Vec<Commit>,
+}
+
+/// A certificate in the DAG, identified by its round and origin.
+#[derive(Debug, Serialize)]
+pub struct Vertex {
+    pub round: Round,
+    pub origin: AuthorityIdentifier,
+    pub digest: String,
+    /// Parents in the previous round, by origin. Parents that are no longer in the store (because
+    /// they were garbage collected) are omitted.
+    pub parents: Vec<AuthorityIdentifier>,
+    /// Index of the sub-dag this certificate was committed in, if any.
+    pub sub_dag_index: Option<SequenceNumber>,
+}
+
+/// The leader of an even round, according to the leader schedule in effect at that round.
+#[derive(Debug, Serialize)]
+pub struct LeaderElection {
+    pub round: Round,
+    /// The leader according to the committee's stake-weighted election.
+    pub elected: AuthorityIdentifier,
+    /// The leader after applying the leader swap table, which replaces leaders with low
+    /// reputation scores. Equal to `elected` if no swap happened.
+    pub leader: AuthorityIdentifier,
+    /// Index of the sub-dag this leader's certificate committed, if any.
+    pub sub_dag_index: Option<SequenceNumber>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Commit {
+    pub sub_dag_index: SequenceNumber,
+    pub leader_round: Round,
+    pub leader: String,
+    pub num_certificates: usize,
+    pub commit_timestamp: u64,
+    /// Whether this commit's reputation scores were used to build a new leader swap table.
+    pub final_of_schedule: bool,
+}
+
+/// Read the DAG between `from_round` and `to_round` (inclusive) from `store`. The committee must
+/// be the one for the epoch the store was written in, for leader elections to be accurate.
+pub fn export_dag(
+    store: &NodeStorage,
+    committee: &Committee,
+    protocol_config: &ProtocolConfig,
+    from_round: Round,
+    to_round: Round,
+) -> StoreResult<DagExport> {
+    let certificates: Vec<Certificate> = store
+        .certificate_store
+        .after_round(from_round)?
+        .into_iter()
+        .take_while(|c| c.round() <= to_round)
+        .collect();
+
+    // Origins of all known certificates, including parents of the first round in range.
+    let mut origins: HashMap<CertificateDigest, AuthorityIdentifier> = certificates
+        .iter()
+        .map(|c| (c.digest(), c.origin()))
+        .collect();
+    for certificate in &certificates {
+        if certificate.round() != from_round {
+            continue;
+        }
+        for parent in certificate.header().parents() {
+            if let Some(parent) = store.certificate_store.read(*parent)? {
+                origins.insert(parent.digest(), parent.origin());
+            }
+        }
+    }
+
+    // Replay all commits to track the leader swap tables that were in effect, and find the
+    // sub-dags of the certificates in range.
+    let all_commits = store.consensus_store.read_committed_sub_dags_from(&0)?;
+    let mut swap_tables = BTreeMap::from([(0, LeaderSwapTable::default())]);
+    let mut committed_in = HashMap::new();
+    let mut commits = vec![];
+    for commit in &all_commits {
+        let scores = commit.reputation_score();
+        if scores.final_of_schedule {
+            // Bullshark installs the new table once the commit is processed, so it applies to
+            // leaders of later rounds.
+            swap_tables.insert(
+                commit.leader_round() + 1,
+                LeaderSwapTable::new(
+                    committee,
+                    commit.leader_round(),
+                    &scores,
+                    protocol_config.consensus_bad_nodes_stake_threshold(),
+                ),
+            );
+        }
+
+        if !(from_round..=to_round).contains(&commit.leader_round()) {
+            continue;
+        }
+
+        for digest in commit.certificates() {
+            committed_in.insert(digest, commit.sub_dag_index());
+        }
+        commits.push(commit_info(commit));
+    }
+
+    let vertices = certificates
+        .iter()
+        .map(|c| Vertex {
+            round: c.round(),
+            origin: c.origin(),
+            digest: format!("{:?}", c.digest()),
+            parents: c
+                .header()
+                .parents()
+                .iter()
+                .filter_map(|p| origins.get(p).copied())
+                .collect(),
+            sub_dag_index: committed_in.get(&c.digest()).copied(),
+        })
+        .collect();
+
+    let committed_leaders: HashMap<Round, SequenceNumber> = commits
+        .iter()
+        .map(|c| (c.leader_round, c.sub_dag_index))
+        .collect();
+    // Leaders are elected on even rounds, starting from round 2.
+    let first_leader_round = (from_round + from_round % 2).max(2);
+    let leaders = (first_leader_round..=to_round)
+        .step_by(2)
+        .map(|round| {
+            let elected = committee.leader(round).id();
+            let (_, table) = swap_tables
+                .range(..=round)
+                .next_back()
+                .expect("There is always a table in effect from round 0");
+            let leader = table.swap(&elected, round).map_or(elected, |a| a.id());
+            LeaderElection {
+                round,
+                elected,
+                leader,
+                sub_dag_index: committed_leaders.get(&round).copied(),
+            }
+        })
+        .collect();
+
+    Ok(DagExport {
+        from_round,
+        to_round,
+        vertices,
+        leaders,
+        commits,
+    })
+}
+
+impl DagExport {
+    /// Render the DAG in Graphviz DOT format. Certificates of the same round share a rank, edges
+    /// point from a certificate to its parents, committed certificates are shaded, and the
+    /// certificates of committed leaders are highlighted.
+    pub fn to_dot(&self) -> String {
+        let mut leaders: HashMap<(Round, AuthorityIdentifier), &LeaderElection> = HashMap::new();
+        for election in &self.leaders {
+            leaders.insert((election.round, election.leader), election);
+        }
+
+        let mut by_round: BTreeMap<Round, Vec<&Vertex>> = BTreeMap::new();
+        for vertex in &self.vertices {
+            by_round.entry(vertex.round).or_default().push(vertex);
+        }
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph dag {{").unwrap();
+        writeln!(dot, "  rankdir=BT;").unwrap();
+        writeln!(dot, "  node [shape=box, style=filled, fillcolor=white];").unwrap();
+
+        for (round, vertices) in &by_round {
+            writeln!(dot, "  {{ rank=same;").unwrap();
+            for v in vertices {
+                let mut label = format!("r{} a{}", v.round, v.origin);
+                let mut color = "white";
+                if let Some(index) = v.sub_dag_index {
+                    write!(label, "\\nsub-dag {index}").unwrap();
+                    color = "lightblue";
+                }
+                if let Some(election) = leaders.get(&(*round, v.origin)) {
+                    if election.leader != election.elected {
+                        write!(label, "\\nleader (swapped from a{})", election.elected).unwrap();
+                    } else {
+                        write!(label, "\\nleader").unwrap();
+                    }
+                    if election.sub_dag_index.is_some() {
+                        color = "gold";
+                    }
+                }
+                writeln!(
+                    dot,
+                    "    {} [label=\"{label}\", fillcolor={color}];",
+                    vertex_id(v.round, v.origin)
+                )
+                .unwrap();
+            }
+            writeln!(dot, "  }}").unwrap();
+        }
+
+        for v in &self.vertices {
+            // Edges to parents outside of the exported range would add vertices without labels.
+            if v.round == self.from_round {
+                continue;
+            }
+            for parent in &v.parents {
+                writeln!(
+                    dot,
+                    "  {} -> {};",
+                    vertex_id(v.round, v.origin),
+                    vertex_id(v.round - 1, *parent)
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+fn commit_info(commit: &ConsensusCommit) -> Commit {
+    Commit {
+        sub_dag_index: commit.sub_dag_index(),
+        leader_round: commit.leader_round(),
+        leader: format!("{:?}", commit.leader()),
+        num_certificates: commit.certificates().len(),
+        commit_timestamp: commit.commit_timestamp(),
+        final_of_schedule: commit.reputation_score().final_of_schedule,
+    }
+}
+
+fn vertex_id(round: Round, origin: AuthorityIdentifier) -> String {
+    format!("r{round}_a{origin}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use test_utils::{
+        latest_protocol_version, make_optimal_certificates, temp_dir, CommitteeFixture,
+    };
+    use types::{CommittedSubDag, ReputationScores};
+
+    #[test]
+    fn test_export_dag() {
+        let fixture = CommitteeFixture::builder().build();
+        let committee = fixture.committee();
+        let protocol_config = latest_protocol_version();
+        let store = NodeStorage::reopen(temp_dir(), None);
+
+        let genesis: BTreeSet<_> = Certificate::genesis(&protocol_config, &committee)
+            .iter()
+            .map(|c| c.digest())
+            .collect();
+        let ids: Vec<_> = fixture.authorities().map(|a| a.id()).collect();
+        let (certificates, _) =
+            make_optimal_certificates(&committee, &protocol_config, 1..=4, &genesis, &ids);
+        let certificates: Vec<_> = certificates.into_iter().collect();
+        store
+            .certificate_store
+            .write_all(certificates.clone())
+            .unwrap();
+
+        // Commit the round 2 leader, along with everything in round 1.
+        let leader = committee.leader(2).id();
+        let leader_certificate = certificates
+            .iter()
+            .find(|c| c.round() == 2 && c.origin() == leader)
+            .unwrap()
+            .clone();
+        let mut committed: Vec<_> = certificates
+            .iter()
+            .filter(|c| c.round() == 1)
+            .cloned()
+            .collect();
+        committed.push(leader_certificate.clone());
+        let sub_dag = CommittedSubDag::new(
+            committed,
+            leader_certificate,
+            1,
+            ReputationScores::new(&committee),
+            None,
+        );
+        store
+            .consensus_store
+            .write_consensus_state(&HashMap::new(), &sub_dag)
+            .unwrap();
+
+        let export = export_dag(&store, &committee, &protocol_config, 2, 4).unwrap();
+
+        // Rounds 2 to 4 of a fully connected DAG.
+        assert_eq!(export.vertices.len(), 3 * ids.len());
+        for vertex in &export.vertices {
+            assert_eq!(vertex.parents.len(), ids.len());
+            let expected = (vertex.round == 2 && vertex.origin == leader).then_some(1);
+            assert_eq!(vertex.sub_dag_index, expected);
+        }
+
+        assert_eq!(export.commits.len(), 1);
+        assert_eq!(export.commits[0].leader_round, 2);
+        assert_eq!(export.commits[0].num_certificates, ids.len() + 1);
+
+        let rounds: Vec<_> = export.leaders.iter().map(|l| l.round).collect();
+        assert_eq!(rounds, vec![2, 4]);
+        assert_eq!(export.leaders[0].leader, leader);
+        assert_eq!(export.leaders[0].sub_dag_index, Some(1));
+        assert_eq!(export.leaders[1].sub_dag_index, None);
+
+        let dot = export.to_dot();
+        assert!(dot.starts_with("digraph dag {"));
+        assert!(dot.contains(&format!(
+            "{} [label=\"r2 a{leader}\\nsub-dag 1\\nleader\", fillcolor=gold];",
+            vertex_id(2, leader)
+        )));
+        assert!(dot.contains(&format!(
+            "{} -> {};",
+            vertex_id(3, ids[0]),
+            vertex_id(2, leader)
+        )));
+
+        // Round 2 parents are outside of the range, so they have no edges.
+        assert!(!dot.contains(&vertex_id(1, ids[0])));
+    }
+}
@@ -13,9 +13,9 @@ use serde_reflection::{Registry, Result, Samples, Tracer, TracerConfig};
 use std::{fs::File, io::Write};
 use test_utils::latest_protocol_version;
 use types::{
-    Batch, BatchDigest, Certificate, CertificateDigest, Header, HeaderDigest, HeaderV1Builder,
-    MetadataV1, VersionedMetadata, WorkerOthersBatchMessage, WorkerOwnBatchMessage,
-    WorkerSynchronizeMessage,
+    Batch, BatchCompression, BatchDigest, Certificate, CertificateDigest, Header, HeaderDigest,
+    HeaderV1Builder, MetadataV1, VersionedMetadata, WorkerOthersBatchMessage,
+    WorkerOwnBatchMessage, WorkerSynchronizeMessage,
 };
 
 #[allow(clippy::mutable_key_type)]
@@ -136,6 +136,12 @@ fn get_registry() -> Result<Registry> {
     tracer.trace_value(&mut samples, &others_batch)?;
     tracer.trace_value(&mut samples, &sync)?;
 
+    // Compressed batches only deserialize when their payload decompresses.
+    let compressed_batch = Batch::new(vec![vec![0u8; 8]], &latest_protocol_version())
+        .compress(BatchCompression::Zstd)
+        .unwrap();
+    tracer.trace_value(&mut samples, &compressed_batch)?;
+
     // 2. Trace the main entry point(s) + every enum separately.
     tracer.trace_type::<Batch>(&samples)?;
     tracer.trace_type::<BatchDigest>(&samples)?;
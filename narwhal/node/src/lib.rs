@@ -7,6 +7,8 @@ use futures::stream::FuturesUnordered;
 pub use storage::{CertificateStoreCacheMetrics, NodeStorage};
 use thiserror::Error;
 
+pub mod consensus_replay;
+pub mod dag_export;
 pub mod execution_state;
 pub mod metrics;
 pub mod primary_node;
@@ -8,13 +8,15 @@
     rust_2021_compatibility
 )]
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use config::{ChainIdentifier, Committee, Import, Parameters, WorkerCache, WorkerId};
 use crypto::{KeyPair, NetworkKeyPair};
 use eyre::Context;
 use fastcrypto::traits::KeyPair as _;
 use mysten_metrics::RegistryService;
 use narwhal_node as node;
+use narwhal_node::consensus_replay::replay_consensus;
+use narwhal_node::dag_export::export_dag;
 use narwhal_node::primary_node::PrimaryNode;
 use narwhal_node::worker_node::WorkerNode;
 use network::client::NetworkClient;
@@ -38,6 +40,7 @@ use tracing::subscriber::set_global_default;
 use tracing::{info, warn};
 #[cfg(feature = "benchmark")]
 use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use types::Round;
 use worker::TrivialTransactionValidator;
 
 #[derive(Parser)]
@@ -98,6 +101,57 @@ enum Commands {
         #[command(subcommand)]
         subcommand: NodeType,
     },
+    /// Export the certificate DAG of a round range from a node's store, along with commits and
+    /// leader elections. Meant to be run against a copy of the store of a stopped node.
+    ExportDag {
+        /// The file containing committee information for the epoch of the store
+        #[arg(long)]
+        committee: String,
+        /// The path of the data store
+        #[arg(long)]
+        store: PathBuf,
+        /// The first round to export
+        #[arg(long)]
+        from_round: Round,
+        /// The last round to export
+        #[arg(long)]
+        to_round: Round,
+        /// The protocol version the store was written with, defaults to the latest
+        #[arg(long)]
+        protocol_version: Option<u64>,
+        /// The output format
+        #[arg(long, value_enum, default_value_t = DagFormat::Json)]
+        format: DagFormat,
+        /// The file to write the export to, defaults to stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Replay consensus over the certificates in a node's store, and check that it produces the
+    /// commits recorded in the store. Meant to be run against a copy of the store of a stopped node.
+    /// Certificates that reached the node late can make the replayed commits differ without a bug.
+    ReplayConsensus {
+        /// The file containing committee information for the epoch of the store
+        #[arg(long)]
+        committee: String,
+        /// The path of the data store
+        #[arg(long)]
+        store: PathBuf,
+        /// The protocol version the store was written with, defaults to the latest
+        #[arg(long)]
+        protocol_version: Option<u64>,
+        /// The file containing the node parameters, for the gc depth
+        #[arg(long)]
+        parameters: Option<String>,
+        /// The number of committed sub dags after which the leader schedule changes
+        #[arg(long, default_value_t = PrimaryNode::CONSENSUS_SCHEDULE_CHANGE_SUB_DAGS)]
+        num_sub_dags_per_schedule: u64,
+    },
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum DagFormat {
+    Json,
+    Dot,
 }
 
 #[derive(Subcommand)]
@@ -213,6 +267,80 @@ async fn main() -> Result<(), eyre::Report> {
             )
             .await?
         }
+        Commands::ExportDag {
+            committee,
+            store,
+            from_round,
+            to_round,
+            protocol_version,
+            format,
+            output,
+        } => {
+            let mut committee =
+                Committee::import(committee).context("Failed to load the committee information")?;
+            committee.load();
+
+            let protocol_version = protocol_version.map_or(ProtocolVersion::max(), Into::into);
+            let protocol_config = ProtocolConfig::get_for_version(protocol_version, Chain::Unknown);
+
+            let store = NodeStorage::reopen(store, None);
+            let export = export_dag(&store, &committee, &protocol_config, *from_round, *to_round)
+                .context("Failed to read the DAG from the store")?;
+
+            let contents = match format {
+                DagFormat::Json => serde_json::to_string_pretty(&export)?,
+                DagFormat::Dot => export.to_dot(),
+            };
+            match output {
+                Some(path) => std::fs::write(path, contents)
+                    .with_context(|| format!("Failed to write to {}", path.display()))?,
+                None => println!("{contents}"),
+            }
+        }
+        Commands::ReplayConsensus {
+            committee,
+            store,
+            protocol_version,
+            parameters,
+            num_sub_dags_per_schedule,
+        } => {
+            let mut committee =
+                Committee::import(committee).context("Failed to load the committee information")?;
+            committee.load();
+
+            let protocol_version = protocol_version.map_or(ProtocolVersion::max(), Into::into);
+            let protocol_config = ProtocolConfig::get_for_version(protocol_version, Chain::Unknown);
+
+            let parameters = match parameters {
+                Some(filename) => {
+                    Parameters::import(filename).context("Failed to load the node's parameters")?
+                }
+                None => Parameters::default(),
+            };
+
+            // Consensus writes its commits as it goes, so keep them away from the store under test.
+            let scratch_dir = tempfile::tempdir().context("Failed to create a scratch store")?;
+            let scratch = NodeStorage::reopen(scratch_dir.path(), None);
+
+            let store = NodeStorage::reopen(store, None);
+            let report = replay_consensus(
+                &store,
+                scratch.consensus_store,
+                &committee,
+                &protocol_config,
+                parameters.gc_depth,
+                *num_sub_dags_per_schedule,
+            )
+            .context("Failed to replay consensus")?;
+
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            if !report.is_consistent() {
+                eyre::bail!(
+                    "Replay diverged from the recorded commits in {} places",
+                    report.mismatches.len()
+                );
+            }
+        }
     }
 
     Ok(())
@@ -384,6 +384,10 @@ pub struct PrimaryNode {
 }
 
 impl PrimaryNode {
+    /// The number of committed sub dags after which consensus changes its leader schedule.
+    pub const CONSENSUS_SCHEDULE_CHANGE_SUB_DAGS: u64 =
+        PrimaryNodeInner::CONSENSUS_SCHEDULE_CHANGE_SUB_DAGS;
+
     pub fn new(parameters: Parameters, registry_service: RegistryService) -> PrimaryNode {
         let inner = PrimaryNodeInner {
             parameters,
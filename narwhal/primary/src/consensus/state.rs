@@ -317,6 +317,11 @@ pub struct Consensus {
 
     /// Inner state
     state: ConsensusState,
+
+    /// Records the order certificates are processed in.
+    store: Arc<ConsensusStore>,
+    /// The position to record the next processed certificate at.
+    next_certificate_order_index: SequenceNumber,
 }
 
 impl Consensus {
@@ -352,6 +357,8 @@ impl Consensus {
             );
         }
 
+        let next_certificate_order_index = store.next_certificate_order_index();
+
         let state = ConsensusState::new_from_store(
             metrics.clone(),
             last_committed_round,
@@ -375,6 +382,8 @@ impl Consensus {
             protocol,
             metrics,
             state,
+            store,
+            next_certificate_order_index,
         };
 
         spawn_logged_monitored_task!(s.run(), "Consensus", INFO)
@@ -410,6 +419,10 @@ impl Consensus {
                         }
                     }
 
+                    // Record the order certificates reach consensus in, so commits can be replayed offline.
+                    self.store.write_certificate_order(self.next_certificate_order_index, certificate.digest())?;
+                    self.next_certificate_order_index += 1;
+
                     // Process the certificate using the selected consensus protocol.
                     let (_, committed_sub_dags) = self.protocol.process_certificate(&mut self.state, certificate)?;
 
@@ -13,23 +13,30 @@ pub(crate) const NUM_SUB_DAGS_PER_SCHEDULE: u64 = 100;
 pub fn make_consensus_store(store_path: &std::path::Path) -> Arc<ConsensusStore> {
     const LAST_COMMITTED_CF: &str = "last_committed";
     const COMMITTED_SUB_DAG_CF: &str = "committed_sub_dag";
+    const CERTIFICATE_ORDER_CF: &str = "certificate_order";
 
     let rocksdb = rocks::open_cf(
         store_path,
         None,
         MetricConf::default(),
-        &[LAST_COMMITTED_CF, COMMITTED_SUB_DAG_CF],
+        &[
+            LAST_COMMITTED_CF,
+            COMMITTED_SUB_DAG_CF,
+            CERTIFICATE_ORDER_CF,
+        ],
     )
     .expect("Failed to create database");
 
-    let (last_committed_map, committed_sub_dag_map) = reopen!(&rocksdb,
+    let (last_committed_map, committed_sub_dag_map, certificate_order_map) = reopen!(&rocksdb,
         LAST_COMMITTED_CF;<AuthorityIdentifier, Round>,
-        COMMITTED_SUB_DAG_CF;<SequenceNumber, ConsensusCommit>
+        COMMITTED_SUB_DAG_CF;<SequenceNumber, ConsensusCommit>,
+        CERTIFICATE_ORDER_CF;<SequenceNumber, CertificateDigest>
     );
 
     Arc::new(ConsensusStore::new(
         last_committed_map,
         committed_sub_dag_map,
+        certificate_order_map,
     ))
 }
 
@@ -7,7 +7,9 @@ use std::collections::HashMap;
 use store::rocks::{open_cf, DBMap, MetricConf, ReadWriteOptions};
 use store::{reopen, Map, TypedStoreError};
 use tracing::debug;
-use types::{CommittedSubDag, ConsensusCommit, ConsensusCommitV2, Round, SequenceNumber};
+use types::{
+    CertificateDigest, CommittedSubDag, ConsensusCommit, ConsensusCommitV2, Round, SequenceNumber,
+};
 
 /// The persistent storage of the sequencer.
 pub struct ConsensusStore {
@@ -15,6 +17,8 @@ pub struct ConsensusStore {
     last_committed: DBMap<AuthorityIdentifier, Round>,
     /// The global consensus sequence
     committed_sub_dags_by_index_v2: DBMap<SequenceNumber, ConsensusCommit>,
+    /// The certificates in the order consensus processed them, for offline replay.
+    certificate_order: DBMap<SequenceNumber, CertificateDigest>,
 }
 
 impl ConsensusStore {
@@ -22,10 +26,12 @@ impl ConsensusStore {
     pub fn new(
         last_committed: DBMap<AuthorityIdentifier, Round>,
         committed_sub_dags_map: DBMap<SequenceNumber, ConsensusCommit>,
+        certificate_order: DBMap<SequenceNumber, CertificateDigest>,
     ) -> Self {
         Self {
             last_committed,
             committed_sub_dags_by_index_v2: committed_sub_dags_map,
+            certificate_order,
         }
     }
 
@@ -37,17 +43,23 @@ impl ConsensusStore {
             &[
                 NodeStorage::LAST_COMMITTED_CF,
                 NodeStorage::COMMITTED_SUB_DAG_INDEX_CF,
+                NodeStorage::CERTIFICATE_ORDER_CF,
             ],
         )
         .expect("Cannot open database");
-        let (last_committed_map, committed_sub_dag_map) = reopen!(&rocksdb, NodeStorage::LAST_COMMITTED_CF;<AuthorityIdentifier, Round>, NodeStorage::COMMITTED_SUB_DAG_INDEX_CF;<SequenceNumber, ConsensusCommit>);
-        Self::new(last_committed_map, committed_sub_dag_map)
+        let (last_committed_map, committed_sub_dag_map, certificate_order_map) = reopen!(&rocksdb, NodeStorage::LAST_COMMITTED_CF;<AuthorityIdentifier, Round>, NodeStorage::COMMITTED_SUB_DAG_INDEX_CF;<SequenceNumber, ConsensusCommit>, NodeStorage::CERTIFICATE_ORDER_CF;<SequenceNumber, CertificateDigest>);
+        Self::new(
+            last_committed_map,
+            committed_sub_dag_map,
+            certificate_order_map,
+        )
     }
 
     /// Clear the store.
     pub fn clear(&self) -> StoreResult<()> {
         self.last_committed.unsafe_clear()?;
         self.committed_sub_dags_by_index_v2.unsafe_clear()?;
+        self.certificate_order.unsafe_clear()?;
         Ok(())
     }
 
@@ -68,6 +80,34 @@ impl ConsensusStore {
         write_batch.write()
     }
 
+    /// Record that consensus processed the certificate `digest` at position `index`.
+    pub fn write_certificate_order(
+        &self,
+        index: SequenceNumber,
+        digest: CertificateDigest,
+    ) -> Result<(), TypedStoreError> {
+        self.certificate_order.insert(&index, &digest)
+    }
+
+    /// The position to record the next processed certificate at.
+    pub fn next_certificate_order_index(&self) -> SequenceNumber {
+        self.certificate_order
+            .unbounded_iter()
+            .skip_to_last()
+            .next()
+            .map(|(index, _)| index + 1)
+            .unwrap_or_default()
+    }
+
+    /// Load the digests of the certificates consensus processed, in the order it processed them.
+    /// Certificates that consensus received again after a restart appear more than once.
+    pub fn read_certificate_order(&self) -> Vec<CertificateDigest> {
+        self.certificate_order
+            .unbounded_iter()
+            .map(|(_, digest)| digest)
+            .collect()
+    }
+
     /// Load the last committed round of each validator.
     pub fn read_last_committed(&self) -> HashMap<AuthorityIdentifier, Round> {
         self.last_committed.unbounded_iter().collect()
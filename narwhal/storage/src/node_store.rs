@@ -46,6 +46,7 @@ impl NodeStorage {
     pub(crate) const LAST_COMMITTED_CF: &'static str = "last_committed";
     pub(crate) const SUB_DAG_INDEX_CF: &'static str = "sub_dag";
     pub(crate) const COMMITTED_SUB_DAG_INDEX_CF: &'static str = "committed_sub_dag";
+    pub(crate) const CERTIFICATE_ORDER_CF: &'static str = "certificate_order";
 
     // 100 nodes * 60 rounds (assuming 1 round/sec this will hold data for about the last 1 minute
     // which should be more than enough for advancing the protocol and also help other nodes)
@@ -83,7 +84,8 @@ impl NodeStorage {
             ),
             (Self::LAST_COMMITTED_CF, cf_options.clone()),
             (Self::SUB_DAG_INDEX_CF, cf_options.clone()),
-            (Self::COMMITTED_SUB_DAG_INDEX_CF, cf_options),
+            (Self::COMMITTED_SUB_DAG_INDEX_CF, cf_options.clone()),
+            (Self::CERTIFICATE_ORDER_CF, cf_options),
         ];
         let rocksdb = open_cf_opts(
             store_path,
@@ -106,6 +108,7 @@ impl NodeStorage {
             // This can be removed when DBMap supports removing tables.
             _sub_dag_index_map,
             committed_sub_dag_map,
+            certificate_order_map,
         ) = reopen!(&rocksdb,
             Self::LAST_PROPOSED_CF;<ProposerKey, Header>,
             Self::VOTES_CF;<AuthorityIdentifier, VoteInfo>,
@@ -116,7 +119,8 @@ impl NodeStorage {
             Self::BATCHES_CF;<BatchDigest, Batch>,
             Self::LAST_COMMITTED_CF;<AuthorityIdentifier, Round>,
             Self::SUB_DAG_INDEX_CF;<SequenceNumber, CommittedSubDagShell>,
-            Self::COMMITTED_SUB_DAG_INDEX_CF;<SequenceNumber, ConsensusCommit>
+            Self::COMMITTED_SUB_DAG_INDEX_CF;<SequenceNumber, ConsensusCommit>,
+            Self::CERTIFICATE_ORDER_CF;<SequenceNumber, CertificateDigest>
         );
 
         let proposer_store = ProposerStore::new(last_proposed_map);
@@ -137,6 +141,7 @@ impl NodeStorage {
         let consensus_store = Arc::new(ConsensusStore::new(
             last_committed_map,
             committed_sub_dag_map,
+            certificate_order_map,
         ));
 
         Self {
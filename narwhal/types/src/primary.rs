@@ -26,6 +26,7 @@ use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use std::{
+    borrow::Cow,
     collections::{BTreeMap, BTreeSet, VecDeque},
     fmt,
 };
@@ -33,7 +34,7 @@ use std::{
     collections::{HashMap, HashSet},
     time::{Duration, SystemTime},
 };
-use sui_protocol_config::ProtocolConfig;
+use sui_protocol_config::{NarwhalBatchCompression, ProtocolConfig};
 use tracing::warn;
 
 /// The round number.
@@ -179,6 +180,7 @@ impl MetadataAPI for MetadataV1 {
 pub enum Batch {
     V1(BatchV1),
     V2(BatchV2),
+    V3(BatchV3),
 }
 
 impl Batch {
@@ -186,12 +188,41 @@ impl Batch {
         Self::V2(BatchV2::new(transactions, protocol_config))
     }
 
+    /// The size of the batch's transactions, whether it is compressed or not.
     pub fn size(&self) -> usize {
         match self {
             Batch::V1(data) => data.size(),
             Batch::V2(data) => data.size(),
+            Batch::V3(data) => data.size(),
         }
     }
+
+    /// Compress the transactions of the batch. Batches that are already compressed, or use the
+    /// legacy format, are returned unchanged.
+    pub fn compress(self, compression: BatchCompression) -> anyhow::Result<Self> {
+        let Batch::V2(batch) = self else {
+            return Ok(self);
+        };
+        let payload = compression.compress_transactions(&batch.transactions)?;
+        Ok(Batch::V3(BatchV3 {
+            compression,
+            transactions: batch.transactions,
+            versioned_metadata: batch.versioned_metadata,
+            payload: OnceCell::with_value(payload),
+            digest: OnceCell::new(),
+        }))
+    }
+
+    /// The uncompressed form of the batch. Batches that are not compressed are returned unchanged.
+    pub fn decompress(self) -> Self {
+        let Batch::V3(batch) = self else {
+            return self;
+        };
+        Batch::V2(BatchV2 {
+            transactions: batch.transactions,
+            versioned_metadata: batch.versioned_metadata,
+        })
+    }
 }
 
 impl Hash<{ crypto::DIGEST_LENGTH }> for Batch {
@@ -201,6 +232,7 @@ impl Hash<{ crypto::DIGEST_LENGTH }> for Batch {
         match self {
             Batch::V1(data) => data.digest(),
             Batch::V2(data) => data.digest(),
+            Batch::V3(data) => data.digest(),
         }
     }
 }
@@ -318,12 +350,192 @@ impl BatchV2 {
     }
 }
 
+/// The largest size a compressed batch may expand to, so that peers cannot make us allocate
+/// arbitrary amounts of memory with a small payload.
+pub const MAX_DECOMPRESSED_BATCH_SIZE: usize = 32 << 20;
+
+// Batches are compressed on their way out of the batch maker, so favour speed over ratio.
+const ZSTD_COMPRESSION_LEVEL: i32 = 1;
+
+/// The algorithm used to compress the transactions of a `BatchV3`.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Arbitrary)]
+pub enum BatchCompression {
+    Zstd,
+    Lz4,
+}
+
+impl BatchCompression {
+    /// The compression workers should apply to the batches they create, if any.
+    pub fn from_protocol_config(protocol_config: &ProtocolConfig) -> Option<Self> {
+        match protocol_config.narwhal_batch_compression() {
+            NarwhalBatchCompression::None => None,
+            NarwhalBatchCompression::Zstd => Some(Self::Zstd),
+            NarwhalBatchCompression::Lz4 => Some(Self::Lz4),
+        }
+    }
+
+    fn compress_transactions(&self, transactions: &[Transaction]) -> anyhow::Result<Vec<u8>> {
+        let bytes = bcs::to_bytes(transactions)?;
+        Ok(self.compress(&bytes)?)
+    }
+
+    fn decompress_transactions(&self, payload: &[u8]) -> anyhow::Result<Vec<Transaction>> {
+        let bytes = self.decompress(payload)?;
+        Ok(bcs::from_bytes(&bytes)?)
+    }
+
+    fn compress(&self, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Zstd => zstd::bulk::compress(bytes, ZSTD_COMPRESSION_LEVEL),
+            // Prepend the uncompressed size, which lz4 needs to decompress.
+            Self::Lz4 => lz4::block::compress(bytes, None, true),
+        }
+    }
+
+    fn decompress(&self, payload: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self {
+            Self::Zstd => Ok(zstd::bulk::decompress(
+                payload,
+                MAX_DECOMPRESSED_BATCH_SIZE,
+            )?),
+            Self::Lz4 => {
+                let size = payload
+                    .get(..4)
+                    .map(|size| u32::from_le_bytes(size.try_into().unwrap()) as usize)
+                    .ok_or_else(|| anyhow::anyhow!("Compressed batch is missing its size"))?;
+                anyhow::ensure!(
+                    size <= MAX_DECOMPRESSED_BATCH_SIZE,
+                    "Compressed batch expands to {size} bytes, more than the maximum of \
+                    {MAX_DECOMPRESSED_BATCH_SIZE}"
+                );
+                Ok(lz4::block::decompress(payload, None)?)
+            }
+        }
+    }
+}
+
+/// A batch whose transactions are compressed, to save bandwidth between workers and space in the
+/// batch store. It has the same digest as its uncompressed form.
+///
+/// Only the compressed payload is serialized. The transactions are decompressed when the batch is
+/// deserialized, so a payload that does not decompress fails to deserialize, and a batch read
+/// from the network or the batch store can be used like an uncompressed one.
+#[derive(Clone, Debug, Arbitrary)]
+pub struct BatchV3 {
+    compression: BatchCompression,
+    transactions: Vec<Transaction>,
+    // This field is not included as part of the batch digest
+    versioned_metadata: VersionedMetadata,
+    // The BCS-serialized transactions of the batch, compressed. Kept from when the batch was
+    // compressed or received, so that it is not compressed again every time it is serialized.
+    #[proptest(value = "OnceCell::new()")]
+    payload: OnceCell<Vec<u8>>,
+    #[proptest(value = "OnceCell::new()")]
+    digest: OnceCell<BatchDigest>,
+}
+
+/// The serialized form of `BatchV3`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "BatchV3")]
+struct SerializedBatchV3<'a> {
+    compression: BatchCompression,
+    payload: Cow<'a, [u8]>,
+    versioned_metadata: Cow<'a, VersionedMetadata>,
+}
+
+impl Serialize for BatchV3 {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let payload = self
+            .payload
+            .get_or_try_init(|| self.compression.compress_transactions(&self.transactions))
+            .map_err(serde::ser::Error::custom)?;
+        SerializedBatchV3 {
+            compression: self.compression,
+            payload: Cow::Borrowed(payload),
+            versioned_metadata: Cow::Borrowed(&self.versioned_metadata),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BatchV3 {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let batch = SerializedBatchV3::deserialize(deserializer)?;
+        let transactions = batch
+            .compression
+            .decompress_transactions(&batch.payload)
+            .map_err(serde::de::Error::custom)?;
+        Ok(Self {
+            compression: batch.compression,
+            transactions,
+            versioned_metadata: batch.versioned_metadata.into_owned(),
+            payload: OnceCell::with_value(batch.payload.into_owned()),
+            digest: OnceCell::new(),
+        })
+    }
+}
+
+impl PartialEq for BatchV3 {
+    fn eq(&self, other: &Self) -> bool {
+        self.compression == other.compression
+            && self.transactions == other.transactions
+            && self.versioned_metadata == other.versioned_metadata
+    }
+}
+
+impl Eq for BatchV3 {}
+
+impl BatchV3 {
+    /// The size of the batch's transactions once decompressed.
+    pub fn size(&self) -> usize {
+        self.transactions.iter().map(|t| t.len()).sum()
+    }
+
+    /// The size of the batch's compressed payload, if it is known without compressing it again.
+    pub fn compressed_size(&self) -> Option<usize> {
+        self.payload.get().map(|payload| payload.len())
+    }
+}
+
+impl BatchAPI for BatchV3 {
+    fn transactions(&self) -> &Vec<Transaction> {
+        &self.transactions
+    }
+
+    fn transactions_mut(&mut self) -> &mut Vec<Transaction> {
+        // The transactions may change, so drop what was derived from them.
+        self.payload.take();
+        self.digest.take();
+        &mut self.transactions
+    }
+
+    fn into_transactions(self) -> Vec<Transaction> {
+        self.transactions
+    }
+
+    fn metadata(&self) -> &Metadata {
+        unimplemented!("BatchV3 does not have a Metadata field");
+    }
+
+    fn metadata_mut(&mut self) -> &mut Metadata {
+        unimplemented!("BatchV3 does not have a Metadata field");
+    }
+
+    fn versioned_metadata(&self) -> &VersionedMetadata {
+        &self.versioned_metadata
+    }
+
+    fn versioned_metadata_mut(&mut self) -> &mut VersionedMetadata {
+        &mut self.versioned_metadata
+    }
+}
+
 // TODO: Remove once we have removed BatchV1 from the codebase.
 pub fn validate_batch_version(
     batch: &Batch,
     protocol_config: &ProtocolConfig,
 ) -> anyhow::Result<()> {
-    // We will only accept BatchV2 from the network.
+    // We will only accept BatchV2 from the network, or BatchV3 if batch compression is enabled.
     match batch {
         Batch::V1(_) => {
             Err(anyhow::anyhow!(format!(
@@ -334,6 +546,14 @@ pub fn validate_batch_version(
         Batch::V2(_) => {
             Ok(())
         }
+        Batch::V3(_) => {
+            anyhow::ensure!(
+                BatchCompression::from_protocol_config(protocol_config).is_some(),
+                "Received a compressed batch but network is at {:?} and batch compression is not enabled",
+                protocol_config.version
+            );
+            Ok(())
+        }
     }
 }
 
@@ -413,6 +633,18 @@ impl Hash<{ crypto::DIGEST_LENGTH }> for BatchV2 {
     }
 }
 
+impl Hash<{ crypto::DIGEST_LENGTH }> for BatchV3 {
+    type TypedDigest = BatchDigest;
+
+    fn digest(&self) -> Self::TypedDigest {
+        *self.digest.get_or_init(|| {
+            BatchDigest::new(
+                crypto::DefaultHashFunction::digest_iterator(self.transactions.iter()).into(),
+            )
+        })
+    }
+}
+
 // Messages generated internally by Narwhal that are included in headers for sequencing.
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, Deserialize, MallocSizeOf, Serialize)]
@@ -2173,11 +2405,62 @@ impl From<&Vote> for VoteInfo {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Batch, BatchAPI, BatchV2, MetadataAPI, MetadataV1, Timestamp, VersionedMetadata};
+    use crate::{
+        validate_batch_version, Batch, BatchAPI, BatchCompression, BatchV2, MetadataAPI,
+        MetadataV1, Timestamp, VersionedMetadata,
+    };
+    use fastcrypto::hash::Hash;
     use std::time::Duration;
+    use sui_protocol_config::NarwhalBatchCompression;
     use test_utils::latest_protocol_version;
     use tokio::time::sleep;
 
+    #[test]
+    fn test_batch_compression() {
+        let mut protocol_config = latest_protocol_version();
+        let batch = Batch::new(vec![vec![1; 1000], vec![2; 1000]], &protocol_config);
+
+        for compression in [BatchCompression::Zstd, BatchCompression::Lz4] {
+            let compressed = batch.clone().compress(compression).unwrap();
+            assert!(matches!(compressed, Batch::V3(_)));
+            let Batch::V3(compressed_batch) = &compressed else {
+                unreachable!()
+            };
+            assert!(compressed_batch.compressed_size().unwrap() < batch.size());
+            assert_eq!(compressed.size(), batch.size());
+            assert_eq!(compressed.transactions(), batch.transactions());
+            assert_eq!(compressed.digest(), batch.digest());
+
+            // The transactions are decompressed when the batch is deserialized.
+            let bytes = bcs::to_bytes(&compressed).unwrap();
+            assert!(bytes.len() < bcs::to_bytes(&batch).unwrap().len());
+            let deserialized: Batch = bcs::from_bytes(&bytes).unwrap();
+            assert_eq!(deserialized, compressed);
+            assert_eq!(deserialized.digest(), batch.digest());
+            assert_eq!(deserialized.decompress(), batch);
+        }
+
+        // Compressed batches are only accepted once the protocol enables compression.
+        let compressed = batch.compress(BatchCompression::Lz4).unwrap();
+        assert!(validate_batch_version(&compressed, &protocol_config).is_err());
+        protocol_config.set_narwhal_batch_compression_for_testing(NarwhalBatchCompression::Zstd);
+        assert!(validate_batch_version(&compressed, &protocol_config).is_ok());
+
+        // Corrupted payloads fail to deserialize. The payload starts after the variant, the
+        // compression and the payload's length, which all fit in a byte here.
+        let mut bytes = bcs::to_bytes(&compressed).unwrap();
+        bytes[3..19].iter_mut().for_each(|b| *b = !*b);
+        assert!(bcs::from_bytes::<Batch>(&bytes).is_err());
+
+        // Modifying the transactions of a compressed batch updates its digest and payload.
+        let mut modified = compressed;
+        modified.transactions_mut().push(vec![3; 1000]);
+        let expected = Batch::new(modified.transactions().clone(), &protocol_config);
+        assert_eq!(modified.digest(), expected.digest());
+        let deserialized: Batch = bcs::from_bytes(&bcs::to_bytes(&modified).unwrap()).unwrap();
+        assert_eq!(deserialized.transactions(), expected.transactions());
+    }
+
     #[tokio::test]
     async fn test_elapsed() {
         let batch = Batch::new(vec![], &latest_protocol_version());
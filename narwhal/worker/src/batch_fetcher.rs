@@ -61,10 +61,24 @@ impl BatchFetcher {
 
     /// Bulk fetches payload from local storage and remote workers.
     /// This function performs infinite retries and blocks until all batches are available.
+    /// Compressed batches are returned decompressed.
     pub async fn fetch(
         &self,
         digests: HashSet<BatchDigest>,
         known_workers: HashSet<NetworkPublicKey>,
+    ) -> HashMap<BatchDigest, Batch> {
+        self.fetch_stored(digests, known_workers)
+            .await
+            .into_iter()
+            .map(|(digest, batch)| (digest, batch.decompress()))
+            .collect()
+    }
+
+    /// Fetches batches in the form they are stored in, which may be compressed.
+    async fn fetch_stored(
+        &self,
+        digests: HashSet<BatchDigest>,
+        known_workers: HashSet<NetworkPublicKey>,
     ) -> HashMap<BatchDigest, Batch> {
         debug!(
             "Attempting to fetch {} digests from {} workers",
@@ -346,9 +360,10 @@ mod tests {
     use itertools::Itertools;
     use rand::rngs::StdRng;
     use std::collections::HashMap;
+    use sui_protocol_config::NarwhalBatchCompression;
     use test_utils::latest_protocol_version;
     use tokio::time::timeout;
-    use types::BatchV1;
+    use types::{BatchCompression, BatchV1};
 
     // TODO: Remove once we have removed BatchV1 from the codebase.
     // Case #1: Receive BatchV1 but network is upgraded past v11 so we fail because we expect BatchV2
@@ -480,6 +495,53 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    pub async fn test_fetcher_with_compressed_batches() {
+        let mut network = TestRequestBatchesNetwork::new();
+        let batch_store = test_utils::create_batch_store();
+        let mut protocol_config = latest_protocol_version();
+        protocol_config.set_narwhal_batch_compression_for_testing(NarwhalBatchCompression::Zstd);
+        let batch1 = Batch::new(vec![vec![1; 100]], &protocol_config);
+        let batch2 = Batch::new(vec![vec![2; 100]], &protocol_config);
+        let compressed1 = batch1.clone().compress(BatchCompression::Zstd).unwrap();
+        let compressed2 = batch2.clone().compress(BatchCompression::Lz4).unwrap();
+        assert_eq!(compressed1.digest(), batch1.digest());
+        assert_eq!(compressed2.digest(), batch2.digest());
+
+        // One batch is stored locally, the other one is fetched from remote workers.
+        batch_store.insert(&batch1.digest(), &compressed1).unwrap();
+        network.put(&[1, 2], compressed2.clone());
+        let (digests, known_workers) = (
+            HashSet::from_iter(vec![batch1.digest(), batch2.digest()]),
+            HashSet::from_iter(test_pks(&[1, 2])),
+        );
+        let fetcher = BatchFetcher {
+            name: test_pk(0),
+            network: Arc::new(network.clone()),
+            batch_store: batch_store.clone(),
+            metrics: Arc::new(WorkerMetrics::default()),
+            protocol_config,
+        };
+
+        // Batches are returned decompressed.
+        let mut fetched_batches = fetcher.fetch(digests, known_workers).await;
+        for batch in fetched_batches.values_mut() {
+            batch.versioned_metadata_mut().set_received_at(0);
+        }
+        let mut expected_batches = HashMap::from_iter(vec![
+            (batch1.digest(), batch1.clone()),
+            (batch2.digest(), batch2.clone()),
+        ]);
+        for batch in expected_batches.values_mut() {
+            batch.versioned_metadata_mut().set_received_at(0);
+        }
+        assert_eq!(fetched_batches, expected_batches);
+
+        // The remote batch is stored as received.
+        let stored = batch_store.get(&batch2.digest()).unwrap().unwrap();
+        assert!(matches!(stored, Batch::V3(_)));
+    }
+
     #[tokio::test]
     pub async fn test_fetcher_locally_with_remaining() {
         // Limit is set to two batches in test request_batches(). Request 3 batches
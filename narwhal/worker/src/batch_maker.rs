@@ -20,8 +20,8 @@ use tokio::{
 };
 use tracing::{error, warn};
 use types::{
-    error::DagError, now, Batch, BatchAPI, BatchDigest, ConditionalBroadcastReceiver, MetadataAPI,
-    Transaction, TxResponse, WorkerOwnBatchMessage,
+    error::DagError, now, Batch, BatchAPI, BatchCompression, BatchDigest,
+    ConditionalBroadcastReceiver, MetadataAPI, Transaction, TxResponse, WorkerOwnBatchMessage,
 };
 
 #[cfg(feature = "trace_transaction")]
@@ -234,6 +234,33 @@ impl BatchMaker {
             .with_label_values(&[reason])
             .observe(size as f64);
 
+        // Batches have the same digest whether they are compressed or not, so compute it before
+        // compressing.
+        let digest = batch.digest();
+        if let Some(compression) = BatchCompression::from_protocol_config(&self.protocol_config) {
+            batch = batch
+                .compress(compression)
+                .expect("Compressing a batch in memory should not fail");
+
+            let algorithm = format!("{compression:?}");
+            let compressed_size = match &batch {
+                Batch::V3(compressed) => compressed.compressed_size().unwrap_or(size),
+                _ => size,
+            };
+            self.node_metrics
+                .created_batch_compression_ratio
+                .with_label_values(&[&algorithm])
+                .observe(compressed_size as f64 / size.max(1) as f64);
+            self.node_metrics
+                .created_batch_compression_bytes
+                .with_label_values(&[&algorithm, "uncompressed"])
+                .inc_by(size as u64);
+            self.node_metrics
+                .created_batch_compression_bytes
+                .with_label_values(&[&algorithm, "compressed"])
+                .inc_by(compressed_size as u64);
+        }
+
         // Send the batch through the deliver channel for further processing.
         let (notify_done, broadcasted_to_quorum) = tokio::sync::oneshot::channel();
         if self
@@ -250,7 +277,7 @@ impl BatchMaker {
 
         tracing::debug!(
             "Batch {:?} took {} seconds to create due to {}",
-            batch.digest(),
+            digest,
             batch_creation_duration,
             reason
         );
@@ -287,8 +314,6 @@ impl BatchMaker {
             }
 
             // Now save it to disk
-            let digest = batch.digest();
-
             if let Err(e) = store.insert(&digest, &batch) {
                 error!("Store failed with error: {:?}", e);
                 return;
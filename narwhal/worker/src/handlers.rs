@@ -43,6 +43,12 @@ impl<V: TransactionValidator> WorkerToWorker for WorkerReceiverHandler<V> {
         request: anemo::Request<WorkerBatchMessage>,
     ) -> Result<anemo::Response<()>, anemo::rpc::Status> {
         let message = request.into_body();
+        validate_batch_version(&message.batch, &self.protocol_config).map_err(|err| {
+            anemo::rpc::Status::new_with_message(
+                StatusCode::BadRequest,
+                format!("Invalid batch: {err}"),
+            )
+        })?;
         if let Err(err) = self
             .validator
             .validate_batch(&message.batch, &self.protocol_config)
@@ -55,7 +61,7 @@ impl<V: TransactionValidator> WorkerToWorker for WorkerReceiverHandler<V> {
         }
         let digest = message.batch.digest();
 
-        let mut batch = message.batch.clone();
+        let mut batch = message.batch;
 
         // Set received_at timestamp for remote batch.
         batch.versioned_metadata_mut().set_received_at(now());
@@ -73,6 +73,10 @@ pub struct WorkerMetrics {
     pub worker_remote_fetch_latency: Histogram,
     /// The number of pending remote calls to request_batches
     pub pending_remote_request_batches: IntGauge,
+    /// Ratio of the compressed to the uncompressed size of created batches
+    pub created_batch_compression_ratio: HistogramVec,
+    /// Total size in bytes of created batches, before and after compression
+    pub created_batch_compression_bytes: IntCounterVec,
 }
 
 impl WorkerMetrics {
@@ -149,6 +153,21 @@ impl WorkerMetrics {
                 registry
             )
             .unwrap(),
+            created_batch_compression_ratio: register_histogram_vec_with_registry!(
+                "created_batch_compression_ratio",
+                "Ratio of the compressed to the uncompressed size of created batches",
+                &["algorithm"],
+                vec![0.05, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1],
+                registry
+            )
+            .unwrap(),
+            created_batch_compression_bytes: register_int_counter_vec_with_registry!(
+                "created_batch_compression_bytes",
+                "Total size in bytes of created batches, before and after compression",
+                &["algorithm", "form"],
+                registry
+            )
+            .unwrap(),
         }
     }
 }
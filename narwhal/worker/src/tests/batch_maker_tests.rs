@@ -5,6 +5,7 @@ use super::*;
 
 use crate::NUM_SHUTDOWN_RECEIVERS;
 use prometheus::Registry;
+use sui_protocol_config::NarwhalBatchCompression;
 use test_utils::{create_batch_store, latest_protocol_version, transaction};
 use types::MockWorkerToPrimary;
 use types::PreSubscribedBroadcastSender;
@@ -69,6 +70,73 @@ async fn make_batch() {
     assert!(store.get(&expected_batch.digest()).unwrap().is_some());
 }
 
+#[tokio::test]
+async fn make_compressed_batch() {
+    let client = create_network_client();
+    let store = create_batch_store();
+    let mut tx_shutdown = PreSubscribedBroadcastSender::new(NUM_SHUTDOWN_RECEIVERS);
+    let (tx_batch_maker, rx_batch_maker) = test_utils::test_channel!(1);
+    let (tx_quorum_waiter, mut rx_quorum_waiter) = test_utils::test_channel!(1);
+    let node_metrics = Arc::new(WorkerMetrics::new(&Registry::new()));
+
+    // Mock the primary client to always succeed.
+    let mut mock_server = MockWorkerToPrimary::new();
+    mock_server
+        .expect_report_own_batch()
+        .returning(|_| Ok(anemo::Response::new(())));
+    client.set_worker_to_primary_local_handler(Arc::new(mock_server));
+
+    let mut protocol_config = latest_protocol_version();
+    protocol_config.set_narwhal_batch_compression_for_testing(NarwhalBatchCompression::Lz4);
+
+    // Spawn a `BatchMaker` instance.
+    let id = 0;
+    let _batch_maker_handle = BatchMaker::spawn(
+        id,
+        /* max_batch_size */ 200,
+        /* max_batch_delay */
+        Duration::from_millis(1_000_000), // Ensure the timer is not triggered.
+        tx_shutdown.subscribe(),
+        rx_batch_maker,
+        tx_quorum_waiter,
+        node_metrics.clone(),
+        client,
+        store.clone(),
+        protocol_config.clone(),
+    );
+
+    // Send enough transactions to seal a batch.
+    let tx = transaction();
+    let (s0, r0) = tokio::sync::oneshot::channel();
+    let (s1, r1) = tokio::sync::oneshot::channel();
+    tx_batch_maker.send((tx.clone(), s0)).await.unwrap();
+    tx_batch_maker.send((tx.clone(), s1)).await.unwrap();
+
+    // The batch is broadcast compressed, with the digest of its transactions.
+    let expected_batch = Batch::new(vec![tx.clone(), tx.clone()], &protocol_config);
+    let (batch, resp) = rx_quorum_waiter.recv().await.unwrap();
+    assert!(matches!(batch, Batch::V3(_)));
+    assert_eq!(batch.digest(), expected_batch.digest());
+    assert_eq!(batch.transactions(), expected_batch.transactions());
+
+    // Eventually deliver message
+    assert!(resp.send(()).is_ok());
+    assert_eq!(r0.await.unwrap(), expected_batch.digest());
+    assert!(r1.await.is_ok());
+
+    // The batch is stored compressed.
+    let stored = store.get(&expected_batch.digest()).unwrap().unwrap();
+    assert!(matches!(stored, Batch::V3(_)));
+
+    assert_eq!(
+        node_metrics
+            .created_batch_compression_bytes
+            .with_label_values(&["Lz4", "uncompressed"])
+            .get(),
+        expected_batch.size() as u64
+    );
+}
+
 #[tokio::test]
 async fn batch_timeout() {
     let client = create_network_client();
@@ -659,7 +659,8 @@ mod checked {
         Ok(())
     }
 
-    fn check_module_compatibility(
+    /// Checks that `new_module` is a valid upgrade of `cur_module` under `policy`.
+    pub fn check_module_compatibility(
         policy: &UpgradePolicy,
         cur_module: &normalized::Module,
         new_module: &normalized::Module,